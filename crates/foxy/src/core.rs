@@ -2,13 +2,35 @@ use foxy_renderer::error::RendererError;
 use foxy_utils::thread::error::ThreadError;
 use thiserror::Error;
 
+pub mod accessibility;
+pub mod adaptive_quality;
+pub mod assets;
 pub mod builder;
+pub mod camera_controller;
+pub mod curve;
+pub mod deep_link;
 pub mod foxy_state;
 pub mod event;
 pub mod foxy_loop;
+pub mod gizmo;
+pub mod hitch;
+pub mod inspector;
 pub mod input;
+pub mod localization;
 pub mod message;
+pub mod plugin;
+pub mod profiling;
+pub mod reflect;
+pub mod run_condition;
 pub mod runnable;
+pub mod single_instance;
+pub mod stepping;
+pub mod streaming;
+pub mod telemetry;
+pub mod text;
+pub mod timings;
+pub mod title_bar;
+pub mod ui_navigation;
 
 pub type FoxyResult<T> = Result<T, FoxyError>;
 
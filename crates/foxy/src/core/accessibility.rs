@@ -0,0 +1,49 @@
+use foxy_renderer::renderer::render_data::ColorFilter;
+
+/// Player-facing accessibility settings, typically set from an options menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Accessibility {
+  /// Multiplies the size of the UI on top of the [`UiScaling`](super::builder::UiScaling) policy.
+  pub ui_scale: f32,
+  /// Applied to every frame unless the app sets its own in its render data.
+  pub color_filter: ColorFilter,
+  /// Asks for camera shake, flashes, bobbing, parallax and long tweens to be toned down or skipped. The engine only
+  /// carries the preference; apps decide what it means for their motion.
+  pub reduced_motion: bool,
+}
+
+impl Default for Accessibility {
+  fn default() -> Self {
+    Self {
+      ui_scale: 1.0,
+      color_filter: ColorFilter::None,
+      reduced_motion: false,
+    }
+  }
+}
+
+impl Accessibility {
+  pub fn with_ui_scale(mut self, ui_scale: f32) -> Self {
+    self.ui_scale = ui_scale;
+    self
+  }
+
+  pub fn with_color_filter(mut self, color_filter: ColorFilter) -> Self {
+    self.color_filter = color_filter;
+    self
+  }
+
+  pub fn with_reduced_motion(mut self, reduced_motion: bool) -> Self {
+    self.reduced_motion = reduced_motion;
+    self
+  }
+
+  /// Scales the duration or amplitude of optional motion: 0 when motion is reduced and 1 otherwise.
+  pub fn motion_scale(&self) -> f32 {
+    if self.reduced_motion {
+      0.0
+    } else {
+      1.0
+    }
+  }
+}
@@ -0,0 +1,243 @@
+use std::time::{Duration, Instant};
+
+use foxy_renderer::renderer::{profiler::FrameStats, render_data::RenderData};
+use tracing::debug;
+
+/// The settings [`AdaptiveQuality`] trades for GPU time.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QualitySettings {
+  /// See [`Renderer::set_render_scale`](foxy_renderer::renderer::Renderer::set_render_scale).
+  pub render_scale: f32,
+  /// The size of a side of the app's shadow maps, in texels. The engine draws no shadows of its own, so it's up to the
+  /// app to read it from [`AdaptiveQuality::settings`] when drawing them.
+  pub shadow_resolution: u32,
+  /// Whether the passes given to [`AdaptiveQuality::with_post_effects`] run.
+  pub post_effects: bool,
+}
+
+impl Default for QualitySettings {
+  fn default() -> Self {
+    Self {
+      render_scale: 1.0,
+      shadow_resolution: 2048,
+      post_effects: true,
+    }
+  }
+}
+
+/// Lowers the quality of frames while the GPU can't keep up with a target frame rate, and raises it again once the GPU
+/// has time to spare, within bounds set by the app.
+///
+/// While the GPU time of frames is over the target, the render scale is lowered a step at a time first, then the
+/// shadow resolution is halved, and the post effects are turned off last. Quality comes back in the reverse order once
+/// frames take less than the headroom of the target. Each change waits out a cooldown, so the GPU time can settle
+/// before the next.
+///
+/// Off until enabled. Needs GPU timestamp queries, see [`FrameStats::gpu_time`], and holds its settings on GPUs without
+/// them. A render scale the app sets in its own [`RenderData`] wins over the controller's for that frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdaptiveQuality {
+  enabled: bool,
+  target_frame_time: Duration,
+  /// The share of the target frame time frames have to stay under before quality is raised.
+  headroom: f32,
+  min: QualitySettings,
+  max: QualitySettings,
+  render_scale_step: f32,
+  cooldown: Duration,
+  post_effect_passes: Vec<&'static str>,
+  settings: QualitySettings,
+  /// The GPU time of recent frames in seconds, smoothed so single slow frames don't lower the quality.
+  gpu_time: Option<f32>,
+  last_change: Option<Instant>,
+  /// Whether the settings were reset to the upper bounds since the last frame, after being turned off.
+  restore: bool,
+}
+
+impl Default for AdaptiveQuality {
+  fn default() -> Self {
+    let max = QualitySettings::default();
+    Self {
+      enabled: false,
+      target_frame_time: Duration::from_secs(1) / 60,
+      headroom: 0.85,
+      min: QualitySettings {
+        render_scale: 0.5,
+        shadow_resolution: 512,
+        post_effects: false,
+      },
+      max,
+      render_scale_step: 0.05,
+      cooldown: Duration::from_secs(1),
+      post_effect_passes: Vec::new(),
+      settings: max,
+      gpu_time: None,
+      last_change: None,
+      restore: false,
+    }
+  }
+}
+
+impl AdaptiveQuality {
+  /// How much of each frame's GPU time goes into the smoothed one.
+  const SMOOTHING: f32 = 0.1;
+
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_enabled(mut self, enabled: bool) -> Self {
+    self.set_enabled(enabled);
+    self
+  }
+
+  pub fn with_target_frame_rate(mut self, frame_rate: u32) -> Self {
+    self.set_target_frame_rate(frame_rate);
+    self
+  }
+
+  /// The share of the target frame time frames have to stay under before quality is raised, e.g. 0.85 so that quality
+  /// isn't raised only to be lowered again.
+  pub fn with_headroom(mut self, headroom: f32) -> Self {
+    self.headroom = headroom.clamp(0.0, 1.0);
+    self
+  }
+
+  /// The lowest and highest settings the controller may pick. It starts at `max`.
+  pub fn with_bounds(mut self, min: QualitySettings, max: QualitySettings) -> Self {
+    self.min = min;
+    self.max = max;
+    self.settings = max;
+    self
+  }
+
+  /// How much the render scale changes at a time.
+  pub fn with_render_scale_step(mut self, step: f32) -> Self {
+    self.render_scale_step = step;
+    self
+  }
+
+  /// How long to wait after a change before making another.
+  pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+    self.cooldown = cooldown;
+    self
+  }
+
+  /// The custom passes, by [`Pass::name`](foxy_renderer::renderer::render_pass::Pass::name), that are skipped while
+  /// post effects are off, e.g. bloom or depth of field.
+  pub fn with_post_effects(mut self, passes: impl IntoIterator<Item = &'static str>) -> Self {
+    self.post_effect_passes = passes.into_iter().collect();
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+
+  /// Turning the controller off puts every setting back at its upper bound.
+  pub fn set_enabled(&mut self, enabled: bool) {
+    if self.enabled && !enabled {
+      self.settings = self.max;
+      self.gpu_time = None;
+      self.last_change = None;
+      self.restore = true;
+    }
+    self.enabled = enabled;
+  }
+
+  pub fn target_frame_time(&self) -> Duration {
+    self.target_frame_time
+  }
+
+  pub fn set_target_frame_rate(&mut self, frame_rate: u32) {
+    self.target_frame_time = Duration::from_secs(1) / frame_rate.max(1);
+  }
+
+  /// The settings picked for the current frame.
+  pub fn settings(&self) -> &QualitySettings {
+    &self.settings
+  }
+
+  /// The smoothed GPU time the settings were picked by, if any frame has been measured since the controller was
+  /// enabled.
+  pub fn gpu_time(&self) -> Option<Duration> {
+    self.gpu_time.map(Duration::from_secs_f32)
+  }
+
+  /// Picks the settings for the next frame from the GPU time of a recent one.
+  pub(crate) fn update(&mut self, frame_stats: &FrameStats) {
+    if !self.enabled || frame_stats.passes.is_empty() {
+      return;
+    }
+
+    let frame_gpu_time = frame_stats.gpu_time().as_secs_f32();
+    let gpu_time = match self.gpu_time {
+      Some(gpu_time) => gpu_time + (frame_gpu_time - gpu_time) * Self::SMOOTHING,
+      None => frame_gpu_time,
+    };
+    self.gpu_time = Some(gpu_time);
+
+    if self
+      .last_change
+      .is_some_and(|last_change| last_change.elapsed() < self.cooldown)
+    {
+      return;
+    }
+    let target = self.target_frame_time.as_secs_f32();
+    let changed = if gpu_time > target {
+      self.lower()
+    } else if gpu_time < target * self.headroom {
+      self.raise()
+    } else {
+      false
+    };
+    if changed {
+      self.last_change = Some(Instant::now());
+      debug!(
+        "Adaptive quality at {:.2} ms of GPU time for a {:.2} ms target: {:?}",
+        gpu_time * 1000.0,
+        target * 1000.0,
+        self.settings,
+      );
+    }
+  }
+
+  /// Hands the settings the renderer applies itself to the frame.
+  pub(crate) fn apply(&mut self, render_data: &mut RenderData) {
+    if !self.enabled && !std::mem::take(&mut self.restore) {
+      return;
+    }
+    render_data.render_scale = Some(self.settings.render_scale);
+    if !self.settings.post_effects {
+      render_data.disabled_passes.extend(&self.post_effect_passes);
+    }
+  }
+
+  fn lower(&mut self) -> bool {
+    let (min, settings) = (&self.min, &mut self.settings);
+    if settings.render_scale > min.render_scale {
+      settings.render_scale = (settings.render_scale - self.render_scale_step).max(min.render_scale);
+    } else if settings.shadow_resolution > min.shadow_resolution {
+      settings.shadow_resolution = (settings.shadow_resolution / 2).max(min.shadow_resolution);
+    } else if settings.post_effects && !min.post_effects {
+      settings.post_effects = false;
+    } else {
+      return false;
+    }
+    true
+  }
+
+  fn raise(&mut self) -> bool {
+    let (max, settings) = (&self.max, &mut self.settings);
+    if !settings.post_effects && max.post_effects {
+      settings.post_effects = true;
+    } else if settings.shadow_resolution < max.shadow_resolution {
+      settings.shadow_resolution = settings.shadow_resolution.saturating_mul(2).min(max.shadow_resolution);
+    } else if settings.render_scale < max.render_scale {
+      settings.render_scale = (settings.render_scale + self.render_scale_step).min(max.render_scale);
+    } else {
+      return false;
+    }
+    true
+  }
+}
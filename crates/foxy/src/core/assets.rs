@@ -0,0 +1,406 @@
+use std::{
+  any::Any,
+  collections::{hash_map, HashMap, HashSet, VecDeque},
+  fmt,
+  fs,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use foxy_utils::baked::{manifest::AssetManifest, mesh::BakedModel, sound::BakedSound, texture::BakedTexture};
+use tracing::*;
+
+use super::{FoxyError, FoxyResult};
+
+/// A loaded asset, shared with everything using it. See [`Assets::get`] for getting it as its concrete type.
+pub type Asset = Arc<dyn Any + Send + Sync>;
+
+/// What a loader learns about the asset it's loading, and tells the [`Assets`] about it.
+#[derive(Debug)]
+pub struct LoadContext<'a> {
+  path: &'a Path,
+  dependencies: Vec<PathBuf>,
+}
+
+impl LoadContext<'_> {
+  /// The path of the asset, relative to the asset directory.
+  pub fn path(&self) -> &Path {
+    self.path
+  }
+
+  /// Records that the asset references `path`, relative to the asset directory, e.g. a scene using a mesh or a
+  /// material using a texture. Dependencies are loaded along with the asset by [`Assets::preload`].
+  pub fn add_dependency(&mut self, path: impl Into<PathBuf>) {
+    self.dependencies.push(path.into());
+  }
+}
+
+/// Turns the bytes of asset files into assets.
+pub trait AssetLoader: Send + Sync + 'static {
+  /// The file extensions the loader handles, without the dot.
+  fn extensions(&self) -> &[&str];
+
+  fn load(&self, bytes: Vec<u8>, context: &mut LoadContext) -> Result<Asset, String>;
+}
+
+/// Loads one of the formats baked by `foxy_asset_baker`.
+struct BakedLoader {
+  extensions: &'static [&'static str],
+  decode: fn(&[u8]) -> Result<Asset, String>,
+}
+
+impl AssetLoader for BakedLoader {
+  fn extensions(&self) -> &[&str] {
+    self.extensions
+  }
+
+  fn load(&self, bytes: Vec<u8>, _context: &mut LoadContext) -> Result<Asset, String> {
+    (self.decode)(&bytes)
+  }
+}
+
+const SPIRV_MAGIC: u32 = 0x07230203;
+
+fn baked_loaders() -> Vec<Arc<dyn AssetLoader>> {
+  vec![
+    Arc::new(BakedLoader {
+      extensions: &[BakedTexture::EXTENSION],
+      decode: |bytes| Ok(Arc::new(BakedTexture::from_bytes(bytes).map_err(|error| error.to_string())?)),
+    }),
+    Arc::new(BakedLoader {
+      extensions: &[BakedModel::EXTENSION],
+      decode: |bytes| Ok(Arc::new(BakedModel::from_bytes(bytes).map_err(|error| error.to_string())?)),
+    }),
+    Arc::new(BakedLoader {
+      extensions: &[BakedSound::EXTENSION],
+      decode: |bytes| Ok(Arc::new(BakedSound::from_bytes(bytes).map_err(|error| error.to_string())?)),
+    }),
+    Arc::new(BakedLoader {
+      extensions: &["spv"],
+      decode: |bytes| {
+        let words: Vec<u32> = bytes
+          .chunks_exact(4)
+          .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+          .collect();
+        if bytes.len() % 4 != 0 || words.first() != Some(&SPIRV_MAGIC) {
+          return Err("not SPIR-V".into());
+        }
+        Ok(Arc::new(words))
+      },
+    }),
+  ]
+}
+
+struct Entry {
+  asset: Asset,
+  dependencies: Vec<PathBuf>,
+}
+
+/// The artifacts baked from the source assets.
+struct Baked {
+  dir: PathBuf,
+  manifest: AssetManifest,
+}
+
+/// Where assets are read from and how, shareable with loading threads.
+#[derive(Clone)]
+pub(crate) struct AssetSource {
+  asset_dir: PathBuf,
+  loaders: Vec<Arc<dyn AssetLoader>>,
+  baked: Option<Arc<Baked>>,
+}
+
+impl AssetSource {
+  /// Reads and loads the asset at `path`, or the artifact baked from it if there is one, returning it with its
+  /// dependencies.
+  pub(crate) fn read(&self, path: &Path) -> FoxyResult<(Asset, Vec<PathBuf>)> {
+    let (file, bytes) = match self.read_baked(path) {
+      Some(baked) => baked,
+      None => (path.to_path_buf(), fs::read(self.asset_dir.join(path))?),
+    };
+    let mut context = LoadContext {
+      path,
+      dependencies: Vec::new(),
+    };
+    let asset = match self.loader(&file) {
+      Some(loader) => loader
+        .load(bytes, &mut context)
+        .map_err(|error| FoxyError::Error(format!("failed to load {}: {error}", path.display())))?,
+      None => Arc::new(bytes),
+    };
+
+    Ok((asset, context.dependencies))
+  }
+
+  /// The path and bytes of the artifact baked from `path`, if there is one and it can be read.
+  fn read_baked(&self, path: &Path) -> Option<(PathBuf, Vec<u8>)> {
+    let baked = self.baked.as_ref()?;
+    let artifact = baked.manifest.artifact(path)?;
+    match fs::read(baked.dir.join(artifact)) {
+      Ok(bytes) => Some((artifact.to_path_buf(), bytes)),
+      Err(error) => {
+        warn!(
+          "failed to read {}, loading {} instead: {error}",
+          artifact.display(),
+          path.display()
+        );
+        None
+      }
+    }
+  }
+
+  fn loader(&self, path: &Path) -> Option<&Arc<dyn AssetLoader>> {
+    let extension = path.extension()?.to_str()?;
+    self
+      .loaders
+      .iter()
+      .find(|loader| loader.extensions().iter().any(|handled| handled.eq_ignore_ascii_case(extension)))
+  }
+}
+
+/// Loads assets from the asset directory and keeps track of which assets reference which, so that a scene can be
+/// loaded and unloaded along with everything it uses.
+///
+/// Files without a loader for their extension are loaded as their raw bytes, as a `Vec<u8>`. The formats baked by
+/// `foxy_asset_baker` load as `foxy_utils::baked` types, and SPIR-V as a `Vec<u32>`.
+pub struct Assets {
+  source: AssetSource,
+  entries: HashMap<PathBuf, Entry>,
+  /// The assets preloaded with their dependencies, which keep those dependencies loaded.
+  roots: HashSet<PathBuf>,
+}
+
+impl fmt::Debug for Assets {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Assets")
+      .field("asset_dir", &self.source.asset_dir)
+      .field("baked_dir", &self.source.baked.as_ref().map(|baked| &baked.dir))
+      .field("loaded", &self.entries.len())
+      .field("roots", &self.roots)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Default for Assets {
+  fn default() -> Self {
+    let asset_dir = std::env::current_exe()
+      .ok()
+      .and_then(|exe| exe.parent().map(Path::to_path_buf))
+      .unwrap_or_default()
+      .join(Self::ASSET_DIR);
+
+    Self::new(asset_dir)
+  }
+}
+
+impl Assets {
+  pub const ASSET_DIR: &'static str = "assets";
+
+  pub fn new(asset_dir: impl Into<PathBuf>) -> Self {
+    Self {
+      source: AssetSource {
+        asset_dir: asset_dir.into(),
+        loaders: baked_loaders(),
+        baked: None,
+      },
+      entries: HashMap::new(),
+      roots: HashSet::new(),
+    }
+  }
+
+  /// Adds a loader, which takes over its extensions from any loader added before.
+  pub fn with_loader(mut self, loader: impl AssetLoader) -> Self {
+    self.source.loaders.insert(0, Arc::new(loader));
+    self
+  }
+
+  /// Loads the artifacts `foxy_asset_baker` baked into `baked_dir` in place of their source assets, as listed by its
+  /// manifest. Assets stay known by their source paths. Without a manifest, the source assets are loaded.
+  pub fn with_baked_dir(mut self, baked_dir: impl Into<PathBuf>) -> Self {
+    let dir = baked_dir.into();
+    match AssetManifest::read(&dir) {
+      Ok(manifest) => self.source.baked = Some(Arc::new(Baked { dir, manifest })),
+      Err(error) => warn!("failed to read the baked asset manifest in {}: {error}", dir.display()),
+    }
+    self
+  }
+
+  pub fn asset_dir(&self) -> &Path {
+    &self.source.asset_dir
+  }
+
+  pub fn is_loaded(&self, path: impl AsRef<Path>) -> bool {
+    self.entries.contains_key(path.as_ref())
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// The asset at `path`, if it's loaded and is a `T`.
+  pub fn get<T: Any + Send + Sync>(&self, path: impl AsRef<Path>) -> Option<Arc<T>> {
+    let entry = self.entries.get(path.as_ref())?;
+    entry.asset.clone().downcast().ok()
+  }
+
+  /// The assets that the asset at `path` references, as recorded when it was loaded.
+  pub fn dependencies(&self, path: impl AsRef<Path>) -> &[PathBuf] {
+    self
+      .entries
+      .get(path.as_ref())
+      .map_or(&[], |entry| entry.dependencies.as_slice())
+  }
+
+  /// Every loaded asset that `path` references, directly or not, including itself.
+  pub fn closure(&self, path: impl AsRef<Path>) -> HashSet<PathBuf> {
+    let mut closure = HashSet::new();
+    let mut pending = vec![path.as_ref().to_path_buf()];
+    while let Some(path) = pending.pop() {
+      if closure.insert(path.clone()) {
+        pending.extend(self.dependencies(&path).iter().cloned());
+      }
+    }
+
+    closure
+  }
+
+  /// Loads the asset at `path`, or returns it if it's already loaded. Its dependencies are recorded but not loaded.
+  pub fn load(&mut self, path: impl AsRef<Path>) -> FoxyResult<Asset> {
+    let path = path.as_ref();
+    if let Some(entry) = self.entries.get(path) {
+      return Ok(entry.asset.clone());
+    }
+
+    let (asset, dependencies) = self.source.read(path)?;
+    self.insert(path.to_path_buf(), asset.clone(), dependencies);
+
+    Ok(asset)
+  }
+
+  /// Loads `scene` and everything it references, one asset per step of the returned iterator, so that a loading
+  /// screen can show the progress. The scene keeps its dependencies loaded until it's passed to
+  /// [`Assets::unload`].
+  pub fn preload(&mut self, scene: impl AsRef<Path>) -> Preload<'_> {
+    let scene = scene.as_ref().to_path_buf();
+    self.add_root(scene.clone());
+
+    Preload {
+      assets: self,
+      pending: VecDeque::from([scene]),
+      visited: HashSet::new(),
+      loaded: 0,
+    }
+  }
+
+  /// Unloads `scene` and the assets only it references, directly or not. Assets also referenced by another preloaded
+  /// scene stay loaded. Returns the unloaded assets.
+  pub fn unload(&mut self, scene: impl AsRef<Path>) -> Vec<PathBuf> {
+    let scene = scene.as_ref();
+    self.roots.remove(scene);
+
+    let kept: HashSet<PathBuf> = self.roots.iter().flat_map(|root| self.closure(root)).collect();
+    let unloaded: Vec<PathBuf> = self
+      .closure(scene)
+      .into_iter()
+      .filter(|path| !kept.contains(path) && self.entries.contains_key(path))
+      .collect();
+
+    for path in &unloaded {
+      self.entries.remove(path);
+      debug!("unloaded {}", path.display());
+    }
+
+    unloaded
+  }
+
+  pub(crate) fn source(&self) -> &AssetSource {
+    &self.source
+  }
+
+  pub(crate) fn loaded_paths(&self) -> HashSet<PathBuf> {
+    self.entries.keys().cloned().collect()
+  }
+
+  /// Adds an asset loaded elsewhere, unless it's already loaded.
+  pub(crate) fn insert(&mut self, path: PathBuf, asset: Asset, dependencies: Vec<PathBuf>) {
+    if let hash_map::Entry::Vacant(slot) = self.entries.entry(path) {
+      debug!("loaded {}", slot.key().display());
+      slot.insert(Entry { asset, dependencies });
+    }
+  }
+
+  /// Keeps `scene` and its dependencies loaded until it's unloaded.
+  pub(crate) fn add_root(&mut self, scene: PathBuf) {
+    self.roots.insert(scene);
+  }
+}
+
+/// How far along a [`Preload`] is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreloadProgress {
+  /// The asset just loaded.
+  pub path: PathBuf,
+  pub loaded: usize,
+  /// The number of assets known to be needed so far. Grows as loaded assets reveal their dependencies.
+  pub total: usize,
+}
+
+impl PreloadProgress {
+  /// How much of the known total is loaded, from 0 to 1.
+  pub fn fraction(&self) -> f32 {
+    self.loaded as f32 / self.total.max(1) as f32
+  }
+}
+
+/// Loads a scene and its dependencies, breadth first. Each step loads one asset, or stops at the first that fails.
+pub struct Preload<'a> {
+  assets: &'a mut Assets,
+  pending: VecDeque<PathBuf>,
+  visited: HashSet<PathBuf>,
+  loaded: usize,
+}
+
+impl Preload<'_> {
+  /// Runs the preload to the end.
+  pub fn finish(self) -> FoxyResult<()> {
+    for progress in self {
+      progress?;
+    }
+    Ok(())
+  }
+}
+
+impl Iterator for Preload<'_> {
+  type Item = FoxyResult<PreloadProgress>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let path = loop {
+      let path = self.pending.pop_front()?;
+      if self.visited.insert(path.clone()) {
+        break path;
+      }
+    };
+
+    if let Err(error) = self.assets.load(&path) {
+      self.pending.clear();
+      return Some(Err(error));
+    }
+
+    for dependency in self.assets.dependencies(&path) {
+      if !self.visited.contains(dependency) && !self.pending.contains(dependency) {
+        self.pending.push_back(dependency.clone());
+      }
+    }
+    self.loaded += 1;
+
+    Some(Ok(PreloadProgress {
+      path,
+      loaded: self.loaded,
+      total: self.loaded + self.pending.len(),
+    }))
+  }
+}
@@ -1,14 +1,38 @@
-use foxy_utils::time::TimeCreateInfo;
-use winit::dpi::{LogicalSize, Size};
+use std::{collections::HashMap, time::Duration};
 
-use crate::window::WindowCreateInfo;
+use foxy_renderer::renderer::{context::PresentMode, render_pass::CustomPass, RendererCreateInfo};
+use foxy_utils::{thread::priority::ThreadSettings, time::TimeCreateInfo};
+use winit::{
+  dpi::{LogicalSize, PhysicalSize, Size},
+  window::Theme,
+};
 
+use super::{
+  accessibility::Accessibility,
+  localization::Localization,
+  plugin::{Plugin, Plugins},
+  single_instance::SingleInstance,
+  telemetry::Telemetry,
+  text::FontSettings,
+};
+use crate::window::{splash::SplashCreateInfo, WindowCreateInfo};
+
+/// When frames run.
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[allow(unused)]
 pub enum Polling {
+  /// Runs frames continuously, and never lets the event loop sleep.
   Poll,
+  /// Runs frames continuously, and lets the event loop sleep between them under the frame rate limit.
   #[default]
   Wait,
+  /// Only runs a frame on input, when one is asked for with
+  /// [`Foxy::request_redraw`](super::foxy_state::Foxy::request_redraw) or the UI is animating, or once the timeout
+  /// passes without any, and sleeps in between. Meant for tools and idle apps, which barely use the CPU or GPU while
+  /// nothing happens. [`Duration::MAX`] never times out.
+  ///
+  /// Fixed updates and gamepads only catch up when a frame runs, and the frame rate limit doesn't apply.
+  WaitTimeout(Duration),
 }
 
 #[derive(Debug, Default)]
@@ -18,12 +42,84 @@ pub enum DebugInfo {
   Hidden,
 }
 
+/// How the UI is scaled as the window changes size or moves between displays.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum UiScaling {
+  /// Follows the display's scale factor, like the rest of the OS.
+  #[default]
+  Native,
+  /// Keeps UI elements the same size in physical pixels, scaled by the given factor, at any resolution or DPI.
+  ConstantPixelSize(f32),
+  /// Scales the UI to cover the same fraction of the window's height as it does at the given reference height.
+  ScaleWithHeight(f32),
+  /// Like [`UiScaling::ScaleWithHeight`], but relative to the shorter of the window's sides, so narrow or portrait
+  /// windows don't cut off the UI.
+  ScaleWithShorterSide(f32),
+}
+
+impl UiScaling {
+  /// How many physical pixels a UI point spans for a window of `size` on a display with `scale_factor`.
+  pub fn pixels_per_point(&self, size: PhysicalSize<u32>, scale_factor: f64) -> f32 {
+    let pixels_per_point = match *self {
+      UiScaling::Native => scale_factor as f32,
+      UiScaling::ConstantPixelSize(scale) => scale,
+      UiScaling::ScaleWithHeight(reference_height) => size.height as f32 / reference_height,
+      UiScaling::ScaleWithShorterSide(reference_side) => size.width.min(size.height) as f32 / reference_side,
+    };
+
+    // minimized windows report a size of zero
+    if pixels_per_point.is_normal() {
+      pixels_per_point
+    } else {
+      scale_factor as f32
+    }
+  }
+}
+
+/// The threads the engine does its work on, which can each be given their own [`ThreadSettings`] with
+/// [`FoxyCreateInfo::with_thread_settings`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EngineThread {
+  /// The main thread, which runs the event loop and draws the frames.
+  Render,
+  /// The thread the game loop and the app's stages run on.
+  Game,
+  /// The background threads [`LevelStreamer`](super::streaming::LevelStreamer)s load cells on. Streamers are created
+  /// by the app, so they pick these settings up from
+  /// [`State::thread_settings`](super::foxy_state::State::thread_settings) with
+  /// [`LevelStreamer::with_thread_settings`](super::streaming::LevelStreamer::with_thread_settings).
+  Streaming,
+}
+
+/// The settings the app's [`State`](super::foxy_state::State) starts out with, most of which can be changed through
+/// it later.
+#[derive(Debug, Default)]
+pub struct AppSettings {
+  pub ui_scaling: UiScaling,
+  pub localization: Localization,
+  pub fonts: FontSettings,
+  pub accessibility: Accessibility,
+  pub telemetry: Option<Telemetry>,
+}
+
 #[derive(Debug, Default)]
 pub struct FoxyCreateInfo {
   pub time: TimeCreateInfo,
   pub window: WindowCreateInfo,
+  pub renderer: RendererCreateInfo,
   pub polling_strategy: Polling,
+  /// Caps how many frames the render thread draws per second. Uncapped when `None`.
+  pub frame_rate_limit: Option<u32>,
+  /// Runs a frame for every cursor move and resize, rather than merging those that arrive together into one. Every
+  /// event is available from [`State::raw_events`](super::foxy_state::State::raw_events) either way.
+  pub raw_events: bool,
   pub debug_info: DebugInfo,
+  pub settings: AppSettings,
+  pub splash: Option<SplashCreateInfo>,
+  pub single_instance: Option<SingleInstance>,
+  pub uri_schemes: Vec<String>,
+  pub plugins: Plugins,
+  pub threads: HashMap<EngineThread, ThreadSettings>,
 }
 
 impl FoxyCreateInfo {
@@ -32,6 +128,36 @@ impl FoxyCreateInfo {
     self
   }
 
+  pub fn with_renderer_info(mut self, renderer: RendererCreateInfo) -> Self {
+    self.renderer = renderer;
+    self
+  }
+
+  pub fn with_depth_prepass(mut self, depth_prepass: bool) -> Self {
+    self.renderer.depth_prepass = depth_prepass;
+    self
+  }
+
+  pub fn with_reverse_z(mut self, reverse_z: bool) -> Self {
+    self.renderer.reverse_z = reverse_z;
+    self
+  }
+
+  pub fn with_hot_reload_shaders(mut self, hot_reload_shaders: bool) -> Self {
+    self.renderer.hot_reload_shaders = hot_reload_shaders;
+    self
+  }
+
+  pub fn with_present_mode(mut self, present_mode: PresentMode) -> Self {
+    self.renderer.present_mode = present_mode;
+    self
+  }
+
+  pub fn with_render_pass(mut self, pass: CustomPass) -> Self {
+    self.renderer.custom_passes.push(pass);
+    self
+  }
+
   pub fn with_title(mut self, title: String) -> Self {
     self.window.title = title;
     self
@@ -45,18 +171,108 @@ impl FoxyCreateInfo {
     self
   }
 
+  /// Starts the window's title bar in a dark or light color mode rather than the system's. It can be switched later
+  /// with [`State::set_theme`](super::foxy_state::State::set_theme).
+  pub fn with_theme(mut self, theme: Theme) -> Self {
+    self.window.preferred_theme = Some(theme);
+    self
+  }
+
   pub fn with_polling(mut self, polling_strategy: Polling) -> Self {
     self.polling_strategy = polling_strategy;
     self
   }
 
+  /// Caps the frame rate, e.g. to save power, or uncaps it with `None`. It can be changed later with
+  /// [`State::set_frame_rate_limit`](super::foxy_state::State::set_frame_rate_limit).
+  pub fn with_frame_rate_limit(mut self, frame_rate_limit: Option<u32>) -> Self {
+    self.frame_rate_limit = frame_rate_limit;
+    self
+  }
+
+  pub fn with_raw_events(mut self, raw_events: bool) -> Self {
+    self.raw_events = raw_events;
+    self
+  }
+
   pub fn with_debug_info(mut self, debug_info: DebugInfo) -> Self {
     self.debug_info = debug_info;
     self
   }
 
+  pub fn with_ui_scaling(mut self, ui_scaling: UiScaling) -> Self {
+    self.settings.ui_scaling = ui_scaling;
+    self
+  }
+
+  pub fn with_localization(mut self, localization: Localization) -> Self {
+    self.settings.localization = localization;
+    self
+  }
+
+  pub fn with_fonts(mut self, fonts: FontSettings) -> Self {
+    self.settings.fonts = fonts;
+    self
+  }
+
+  pub fn with_accessibility(mut self, accessibility: Accessibility) -> Self {
+    self.settings.accessibility = accessibility;
+    self
+  }
+
+  /// Shows a splash window while the app starts up.
+  pub fn with_splash(mut self, splash: SplashCreateInfo) -> Self {
+    self.splash = Some(splash);
+    self
+  }
+
+  /// Hands later launches of the app to the already running instance instead of opening another window.
+  pub fn with_single_instance(mut self, single_instance: SingleInstance) -> Self {
+    self.single_instance = Some(single_instance);
+    self
+  }
+
+  /// Delivers links like `scheme://path` from the command line as [`FoxyEvent::DeepLink`] events. See
+  /// [`register_uri_scheme`] for making the OS launch the app with them.
+  ///
+  /// [`FoxyEvent::DeepLink`]: super::event::FoxyEvent::DeepLink
+  /// [`register_uri_scheme`]: super::deep_link::register_uri_scheme
+  pub fn with_uri_scheme(mut self, scheme: impl Into<String>) -> Self {
+    self.uri_schemes.push(scheme.into());
+    self
+  }
+
+  pub fn with_plugin(mut self, plugin: impl Plugin) -> Self {
+    self.plugins.0.push(Box::new(plugin));
+    self
+  }
+
+  pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+    self.settings.telemetry = Some(telemetry);
+    self
+  }
+
+  /// Sets the priority and affinity of one of the engine's threads, e.g. a time critical render thread to keep frame
+  /// pacing steady on a busy system. Only applied on Windows for now.
+  pub fn with_thread_settings(mut self, thread: EngineThread, settings: ThreadSettings) -> Self {
+    self.threads.insert(thread, settings);
+    self
+  }
+
   pub fn with_time(mut self, time: TimeCreateInfo) -> Self {
     self.time = time;
     self
   }
+
+  /// How many fixed updates run per second.
+  pub fn with_tick_rate(mut self, tick_rate: f64) -> Self {
+    self.time.tick_rate = tick_rate;
+    self
+  }
+
+  /// The most fixed updates run in a single frame when catching up after a slow one.
+  pub fn with_max_updates_per_frame(mut self, max_updates_per_frame: u32) -> Self {
+    self.time.bail_threshold = max_updates_per_frame;
+    self
+  }
 }
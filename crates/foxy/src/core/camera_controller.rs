@@ -0,0 +1,445 @@
+use std::{f32::consts::FRAC_PI_2, fmt, sync::Arc};
+
+use foxy_renderer::renderer::camera::Camera;
+use foxy_utils::geometry::Ray;
+use glam::{EulerRot, Mat3, Quat, Vec2, Vec3};
+
+use super::{
+  foxy_state::Foxy,
+  input::{key::KeyCode, mouse::MouseCode},
+};
+use crate::math::damping::smooth_damp;
+
+/// Keeps the camera from flipping over when looking straight up or down.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+/// Turns the camera around a target point by dragging with the mouse and zooms with the scroll wheel, e.g. for model
+/// viewers and editors.
+///
+/// Call [`OrbitController::update`] every frame with the camera to move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrbitController {
+  target: Vec3,
+  distance: f32,
+  min_distance: f32,
+  max_distance: f32,
+  yaw: f32,
+  pitch: f32,
+  button: MouseCode,
+  /// Radians turned per pixel dragged.
+  sensitivity: f32,
+  /// The fraction the distance changes by per line scrolled.
+  zoom_speed: f32,
+}
+
+impl Default for OrbitController {
+  fn default() -> Self {
+    Self {
+      target: Vec3::ZERO,
+      distance: 5.0,
+      min_distance: 0.1,
+      max_distance: 1000.0,
+      yaw: 0.0,
+      pitch: -0.4,
+      button: MouseCode::Right,
+      sensitivity: 0.005,
+      zoom_speed: 0.1,
+    }
+  }
+}
+
+impl OrbitController {
+  pub fn new(target: Vec3, distance: f32) -> Self {
+    Self::default().with_target(target).with_distance(distance)
+  }
+
+  pub fn with_target(mut self, target: Vec3) -> Self {
+    self.target = target;
+    self
+  }
+
+  pub fn with_distance(mut self, distance: f32) -> Self {
+    self.distance = distance;
+    self
+  }
+
+  /// How close and how far the scroll wheel can zoom.
+  pub fn with_distance_limits(mut self, min: f32, max: f32) -> Self {
+    self.min_distance = min.max(f32::EPSILON);
+    self.max_distance = max.max(self.min_distance);
+    self
+  }
+
+  /// The angles the camera starts at: `yaw` turns around the target's Y axis and `pitch` above or below it, both in
+  /// radians. A negative pitch looks down on the target.
+  pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self {
+    self.yaw = yaw;
+    self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    self
+  }
+
+  /// The mouse button to drag with, the right button by default.
+  pub fn with_button(mut self, button: MouseCode) -> Self {
+    self.button = button;
+    self
+  }
+
+  /// Radians turned per pixel dragged.
+  pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+    self.sensitivity = sensitivity;
+    self
+  }
+
+  /// The fraction the distance changes by per line scrolled.
+  pub fn with_zoom_speed(mut self, zoom_speed: f32) -> Self {
+    self.zoom_speed = zoom_speed;
+    self
+  }
+
+  pub fn target(&self) -> Vec3 {
+    self.target
+  }
+
+  /// Moves the point orbited around, e.g. to the object selected in an editor.
+  pub fn set_target(&mut self, target: Vec3) {
+    self.target = target;
+  }
+
+  pub fn distance(&self) -> f32 {
+    self.distance
+  }
+
+  pub fn set_distance(&mut self, distance: f32) {
+    self.distance = distance.clamp(self.min_distance, self.max_distance);
+  }
+
+  /// Turns and zooms by this frame's input, and places `camera`.
+  pub fn update(&mut self, foxy: &Foxy, camera: &mut Camera) {
+    let (dragging, cursor_delta, scroll) = {
+      let state = foxy.read();
+      let input = state.input();
+      (input.mouse(self.button).is_pressed(), input.cursor_delta(), input.scroll())
+    };
+
+    if dragging {
+      self.yaw -= cursor_delta.x * self.sensitivity;
+      self.pitch = (self.pitch - cursor_delta.y * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+    }
+    // zoomed by a fraction rather than a distance, so it feels the same close up and far away
+    self.set_distance(self.distance * (1.0 - self.zoom_speed).powf(scroll.y));
+
+    self.apply(camera);
+  }
+
+  /// Places `camera` without taking input, e.g. after changing the target.
+  pub fn apply(&self, camera: &mut Camera) {
+    camera.rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0);
+    camera.position = self.target + camera.rotation * Vec3::Z * self.distance;
+  }
+}
+
+/// Flies the camera freely, looking with the mouse and moving with the keyboard, e.g. for editors and debug cameras.
+///
+/// W, A, S and D move along the view, E and Q move up and down, holding Shift speeds up and Ctrl slows down. The
+/// scroll wheel changes the speed. Call [`FlyController::update`] every frame with the camera to move.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlyController {
+  yaw: f32,
+  pitch: f32,
+  /// Units per second.
+  speed: f32,
+  fast_multiplier: f32,
+  slow_multiplier: f32,
+  /// The button to hold to look around, or `None` to always look, e.g. with a grabbed cursor.
+  look_button: Option<MouseCode>,
+  /// Radians turned per pixel the cursor moves.
+  sensitivity: f32,
+  /// Whether the speed follows the scroll wheel.
+  scroll_speed: bool,
+}
+
+impl Default for FlyController {
+  fn default() -> Self {
+    Self {
+      yaw: 0.0,
+      pitch: 0.0,
+      speed: 5.0,
+      fast_multiplier: 4.0,
+      slow_multiplier: 0.25,
+      look_button: Some(MouseCode::Right),
+      sensitivity: 0.003,
+      scroll_speed: true,
+    }
+  }
+}
+
+impl FlyController {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Starts out looking the way `camera` does.
+  pub fn from_camera(camera: &Camera) -> Self {
+    let (yaw, pitch, _) = camera.rotation.to_euler(EulerRot::YXZ);
+    Self {
+      yaw,
+      pitch: pitch.clamp(-MAX_PITCH, MAX_PITCH),
+      ..Self::default()
+    }
+  }
+
+  /// Units per second.
+  pub fn with_speed(mut self, speed: f32) -> Self {
+    self.speed = speed;
+    self
+  }
+
+  /// How much faster the camera moves while Shift is held, and how much slower while Ctrl is.
+  pub fn with_speed_multipliers(mut self, fast: f32, slow: f32) -> Self {
+    self.fast_multiplier = fast;
+    self.slow_multiplier = slow;
+    self
+  }
+
+  /// The button to hold to look around, the right mouse button by default. `None` always looks, which suits a grabbed
+  /// cursor.
+  pub fn with_look_button(mut self, look_button: Option<MouseCode>) -> Self {
+    self.look_button = look_button;
+    self
+  }
+
+  /// Radians turned per pixel the cursor moves.
+  pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+    self.sensitivity = sensitivity;
+    self
+  }
+
+  /// Whether scrolling changes the speed, which it does by default.
+  pub fn with_scroll_speed(mut self, scroll_speed: bool) -> Self {
+    self.scroll_speed = scroll_speed;
+    self
+  }
+
+  pub fn speed(&self) -> f32 {
+    self.speed
+  }
+
+  pub fn set_speed(&mut self, speed: f32) {
+    self.speed = speed;
+  }
+
+  /// Turns and moves `camera` by this frame's input.
+  pub fn update(&mut self, foxy: &Foxy, camera: &mut Camera) {
+    let (looking, cursor_delta, scroll, movement, multiplier, delta) = {
+      let state = foxy.read();
+      let input = state.input();
+      let held = |key: KeyCode| if input.key(key).is_held() { 1.0 } else { 0.0 };
+      let axis = |positive, negative| held(positive) - held(negative);
+      let movement = Vec3::new(
+        axis(KeyCode::D, KeyCode::A),
+        axis(KeyCode::E, KeyCode::Q),
+        axis(KeyCode::S, KeyCode::W),
+      );
+      let multiplier = if input.shift().is_pressed() {
+        self.fast_multiplier
+      } else if input.ctrl().is_pressed() {
+        self.slow_multiplier
+      } else {
+        1.0
+      };
+      (
+        self.look_button.is_none_or(|button| input.mouse(button).is_pressed()),
+        input.cursor_delta(),
+        input.scroll(),
+        movement,
+        multiplier,
+        state.time().delta_secs() as f32,
+      )
+    };
+
+    if looking {
+      self.look(cursor_delta);
+    }
+    if self.scroll_speed && scroll.y != 0.0 {
+      self.speed = (self.speed * 1.2f32.powf(scroll.y)).max(f32::EPSILON);
+    }
+
+    camera.rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, self.pitch, 0.0);
+    // moves along the view horizontally but straight up and down, like most editors
+    let horizontal = Quat::from_rotation_y(self.yaw);
+    let direction = horizontal * Vec3::new(movement.x, 0.0, movement.z) + Vec3::Y * movement.y;
+    camera.position += direction.normalize_or_zero() * self.speed * multiplier * delta;
+  }
+
+  fn look(&mut self, cursor_delta: Vec2) {
+    self.yaw -= cursor_delta.x * self.sensitivity;
+    self.pitch = (self.pitch - cursor_delta.y * self.sensitivity).clamp(-MAX_PITCH, MAX_PITCH);
+  }
+}
+
+/// Finds what's between a camera and what it follows: given a ray and how far along it to look, returns how far along
+/// it the first obstacle is, if there's one that close.
+pub type CameraProbe = Arc<dyn Fn(&Ray, f32) -> Option<f32> + Send + Sync>;
+
+/// Follows a target from behind, e.g. a third-person camera, easing after it rather than being rigidly attached.
+///
+/// Call [`FollowController::update`] every frame with the target's transform. With a probe, the camera is pulled in
+/// front of anything that comes between it and the target so walls don't block the view, and eases back out once
+/// they're gone.
+#[derive(Clone)]
+pub struct FollowController {
+  /// Where the camera sits relative to the target, in the target's space.
+  offset: Vec3,
+  /// The point looked at relative to the target, in the target's space, e.g. the head rather than the feet.
+  look_offset: Vec3,
+  /// Roughly how long the camera takes to catch up, in seconds.
+  smooth_time: f32,
+  probe: Option<CameraProbe>,
+  /// How far to keep in front of obstacles.
+  probe_margin: f32,
+  /// The looked at point, the direction from it to the camera and the distance between them, each eased after the
+  /// target separately. `None` until the first update.
+  pivot: Option<Vec3>,
+  direction: Option<Vec3>,
+  /// Snaps in on collisions and eases back out.
+  distance: Option<f32>,
+  pivot_velocity: Vec3,
+  direction_velocity: Vec3,
+  distance_velocity: f32,
+}
+
+impl Default for FollowController {
+  fn default() -> Self {
+    Self {
+      offset: Vec3::new(0.0, 2.0, 5.0),
+      look_offset: Vec3::new(0.0, 1.0, 0.0),
+      smooth_time: 0.2,
+      probe: None,
+      probe_margin: 0.2,
+      pivot: None,
+      direction: None,
+      distance: None,
+      pivot_velocity: Vec3::ZERO,
+      direction_velocity: Vec3::ZERO,
+      distance_velocity: 0.0,
+    }
+  }
+}
+
+impl FollowController {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Where the camera sits relative to the target, in the target's space. Behind and above by default, with the
+  /// target facing -Z.
+  pub fn with_offset(mut self, offset: Vec3) -> Self {
+    self.offset = offset;
+    self
+  }
+
+  /// The point looked at relative to the target, in the target's space, e.g. the head rather than the feet.
+  pub fn with_look_offset(mut self, look_offset: Vec3) -> Self {
+    self.look_offset = look_offset;
+    self
+  }
+
+  /// Roughly how long the camera takes to catch up, in seconds. 0 follows rigidly.
+  pub fn with_smooth_time(mut self, smooth_time: f32) -> Self {
+    self.smooth_time = smooth_time;
+    self
+  }
+
+  /// Checks for obstacles between the camera and the target with `probe`, e.g. a raycast against the level's
+  /// colliders. It's given a ray from the looked at point towards the camera and how far the camera wants to be, and
+  /// returns how far along the ray the first obstacle is.
+  pub fn with_probe(mut self, probe: impl Fn(&Ray, f32) -> Option<f32> + Send + Sync + 'static) -> Self {
+    self.probe = Some(Arc::new(probe));
+    self
+  }
+
+  /// How far to keep the camera in front of obstacles the probe finds, which keeps the near plane out of them.
+  pub fn with_probe_margin(mut self, probe_margin: f32) -> Self {
+    self.probe_margin = probe_margin;
+    self
+  }
+
+  /// Jumps to the target on the next update rather than easing over, e.g. after a teleport or a cut.
+  pub fn reset(&mut self) {
+    self.pivot = None;
+    self.direction = None;
+    self.distance = None;
+    self.pivot_velocity = Vec3::ZERO;
+    self.direction_velocity = Vec3::ZERO;
+    self.distance_velocity = 0.0;
+  }
+
+  /// Moves `camera` after a target at `position` facing `rotation`.
+  pub fn update(&mut self, foxy: &Foxy, camera: &mut Camera, position: Vec3, rotation: Quat) {
+    let delta = foxy.read().time().delta_secs() as f32;
+
+    let pivot = position + rotation * self.look_offset;
+    let desired = position + rotation * self.offset;
+    let desired_distance = desired.distance(pivot);
+    let desired_direction = (desired - pivot).try_normalize().unwrap_or(Vec3::Z);
+
+    let pivot = match self.pivot {
+      Some(current) => smooth_damp(current, pivot, &mut self.pivot_velocity, self.smooth_time, delta),
+      None => pivot,
+    };
+    let direction = match self.direction {
+      Some(current) => smooth_damp(
+        current,
+        desired_direction,
+        &mut self.direction_velocity,
+        self.smooth_time,
+        delta,
+      )
+      .try_normalize()
+      .unwrap_or(desired_direction),
+      None => desired_direction,
+    };
+    let mut distance = match self.distance {
+      Some(distance) => smooth_damp(distance, desired_distance, &mut self.distance_velocity, self.smooth_time, delta),
+      None => desired_distance,
+    };
+    if let Some(probe) = &self.probe {
+      let ray = Ray::new(pivot, direction);
+      if let Some(hit) = probe(&ray, desired_distance + self.probe_margin) {
+        // snapped in rather than eased, which would let walls block the view while the camera catches up
+        let clear = (hit - self.probe_margin).max(0.0);
+        if clear < distance {
+          distance = clear;
+          self.distance_velocity = 0.0;
+        }
+      }
+    }
+    self.pivot = Some(pivot);
+    self.direction = Some(direction);
+    self.distance = Some(distance);
+
+    camera.position = pivot + direction * distance;
+    camera.rotation = look_rotation(-direction);
+  }
+}
+
+impl fmt::Debug for FollowController {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FollowController")
+      .field("offset", &self.offset)
+      .field("look_offset", &self.look_offset)
+      .field("smooth_time", &self.smooth_time)
+      .field("has_probe", &self.probe.is_some())
+      .field("probe_margin", &self.probe_margin)
+      .finish_non_exhaustive()
+  }
+}
+
+/// The rotation of a camera looking along `forward` with +Y up.
+fn look_rotation(forward: Vec3) -> Quat {
+  let forward = forward.try_normalize().unwrap_or(Vec3::NEG_Z);
+  // looking straight up or down has no right, so any horizontal one will do
+  let right = forward.cross(Vec3::Y).try_normalize().unwrap_or(Vec3::X);
+  let up = right.cross(forward);
+  Quat::from_mat3(&Mat3::from_cols(right, up, -forward))
+}
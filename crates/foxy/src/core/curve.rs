@@ -0,0 +1,448 @@
+use egui::{Color32, ComboBox, Grid, Mesh, Pos2, Rect, Sense, Shape, Stroke, Ui, Vec2};
+use glam::Vec4;
+
+use super::{
+  inspector::Inspect,
+  reflect::{Reflect, Value},
+};
+
+/// How a curve or gradient gets from one key to the next.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+  /// Holds the key's value until the next key.
+  Constant,
+  #[default]
+  Linear,
+  /// Eases out of the key and into the next one.
+  Smooth,
+}
+
+impl Interpolation {
+  pub const ALL: [Self; 3] = [Self::Constant, Self::Linear, Self::Smooth];
+
+  /// How far along to blend at `t`, the fraction of the way to the next key.
+  pub fn ease(self, t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match self {
+      Interpolation::Constant => 0.0,
+      Interpolation::Linear => t,
+      Interpolation::Smooth => t * t * (3.0 - 2.0 * t),
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Interpolation::Constant => "Constant",
+      Interpolation::Linear => "Linear",
+      Interpolation::Smooth => "Smooth",
+    }
+  }
+}
+
+impl Inspect for Interpolation {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    let before = *self;
+    ComboBox::from_id_source(ui.next_auto_id())
+      .selected_text(self.name())
+      .show_ui(ui, |ui| {
+        for interpolation in Interpolation::ALL {
+          ui.selectable_value(self, interpolation, interpolation.name());
+        }
+      });
+    *self != before
+  }
+}
+
+/// Saved by name.
+impl Reflect for Interpolation {
+  fn to_value(&self) -> Value {
+    Value::String(self.name().to_string())
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    let Value::String(name) = value else {
+      return false;
+    };
+    match Interpolation::ALL
+      .into_iter()
+      .find(|interpolation| interpolation.name() == name)
+    {
+      Some(interpolation) => {
+        *self = interpolation;
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, crate::core::reflect::Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurveKey {
+  pub time: f32,
+  pub value: f32,
+  /// How the curve gets from this key to the next.
+  pub interpolation: Interpolation,
+}
+
+/// A value over time, e.g. a particle's size over its lifetime, the progress of a tween, the volume of a sound over
+/// distance, or a post-processing parameter over the time of day.
+///
+/// The curve holds its first value before the first key and its last value after the last key. An empty curve is 0
+/// everywhere.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Vec<CurveKey>", into = "Vec<CurveKey>"))]
+pub struct Curve {
+  /// Sorted by time.
+  keys: Vec<CurveKey>,
+}
+
+impl Curve {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// The same value at all times.
+  pub fn constant(value: f32) -> Self {
+    Self::new().with_key(0.0, value, Interpolation::Constant)
+  }
+
+  /// From `from` at 0 to `to` at 1, blending as `interpolation` does.
+  pub fn between(from: f32, to: f32, interpolation: Interpolation) -> Self {
+    Self::new()
+      .with_key(0.0, from, interpolation)
+      .with_key(1.0, to, interpolation)
+  }
+
+  pub fn with_key(mut self, time: f32, value: f32, interpolation: Interpolation) -> Self {
+    self.insert(CurveKey {
+      time,
+      value,
+      interpolation,
+    });
+    self
+  }
+
+  /// Adds `key`, after any keys at the same time.
+  pub fn insert(&mut self, key: CurveKey) {
+    let index = self.keys.partition_point(|other| other.time <= key.time);
+    self.keys.insert(index, key);
+  }
+
+  pub fn remove(&mut self, index: usize) -> Option<CurveKey> {
+    (index < self.keys.len()).then(|| self.keys.remove(index))
+  }
+
+  /// The keys, sorted by time.
+  pub fn keys(&self) -> &[CurveKey] {
+    &self.keys
+  }
+
+  /// The times of the first and last keys.
+  pub fn time_range(&self) -> Option<(f32, f32)> {
+    Some((self.keys.first()?.time, self.keys.last()?.time))
+  }
+
+  pub fn sample(&self, time: f32) -> f32 {
+    let next = self.keys.partition_point(|key| key.time <= time);
+    match (
+      next.checked_sub(1).and_then(|previous| self.keys.get(previous)),
+      self.keys.get(next),
+    ) {
+      (Some(a), Some(b)) => {
+        let t = a.interpolation.ease((time - a.time) / (b.time - a.time));
+        a.value + (b.value - a.value) * t
+      }
+      (Some(a), None) => a.value,
+      (None, Some(b)) => b.value,
+      (None, None) => 0.0,
+    }
+  }
+
+  fn sort(&mut self) {
+    self.keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+  }
+}
+
+/// Sorts `keys` by time.
+impl From<Vec<CurveKey>> for Curve {
+  fn from(keys: Vec<CurveKey>) -> Self {
+    let mut curve = Self { keys };
+    curve.sort();
+    curve
+  }
+}
+
+impl From<Curve> for Vec<CurveKey> {
+  fn from(curve: Curve) -> Self {
+    curve.keys
+  }
+}
+
+/// Edited as a preview of the curve above its keys.
+impl Inspect for Curve {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    ui.vertical(|ui| {
+      let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width().min(200.0), 60.0), Sense::hover());
+      draw_curve(ui, rect, self);
+
+      let mut changed = inspect_keys(ui, &mut self.keys, |ui, key| {
+        key.time.inspect(ui) | key.value.inspect(ui) | key.interpolation.inspect(ui)
+      });
+      if ui.small_button("+").clicked() {
+        let last = self.keys.last().copied().unwrap_or_default();
+        self.keys.push(CurveKey {
+          time: last.time + 1.0,
+          ..last
+        });
+        changed = true;
+      }
+      if changed {
+        self.sort();
+      }
+      changed
+    })
+    .inner
+  }
+}
+
+/// Saved as the list of keys.
+impl Reflect for Curve {
+  fn to_value(&self) -> Value {
+    self.keys.to_value()
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    let applied = self.keys.apply_value(value);
+    self.sort();
+    applied
+  }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, crate::core::reflect::Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GradientStop {
+  /// Where the stop is along the gradient, usually from 0 to 1.
+  pub position: f32,
+  /// Linear RGBA.
+  pub color: Vec4,
+  /// How the gradient gets from this stop to the next.
+  pub interpolation: Interpolation,
+}
+
+/// Colors along a line, e.g. a particle's color over its lifetime or a color grading ramp.
+///
+/// The gradient holds its first color before the first stop and its last color after the last stop. Colors are
+/// blended in linear space. An empty gradient is white everywhere.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Vec<GradientStop>", into = "Vec<GradientStop>"))]
+pub struct Gradient {
+  /// Sorted by position.
+  stops: Vec<GradientStop>,
+}
+
+impl Gradient {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// From `from` at 0 to `to` at 1.
+  pub fn between(from: Vec4, to: Vec4) -> Self {
+    Self::new()
+      .with_stop(0.0, from, Interpolation::Linear)
+      .with_stop(1.0, to, Interpolation::Linear)
+  }
+
+  pub fn with_stop(mut self, position: f32, color: Vec4, interpolation: Interpolation) -> Self {
+    self.insert(GradientStop {
+      position,
+      color,
+      interpolation,
+    });
+    self
+  }
+
+  /// Adds `stop`, after any stops at the same position.
+  pub fn insert(&mut self, stop: GradientStop) {
+    let index = self.stops.partition_point(|other| other.position <= stop.position);
+    self.stops.insert(index, stop);
+  }
+
+  pub fn remove(&mut self, index: usize) -> Option<GradientStop> {
+    (index < self.stops.len()).then(|| self.stops.remove(index))
+  }
+
+  /// The stops, sorted by position.
+  pub fn stops(&self) -> &[GradientStop] {
+    &self.stops
+  }
+
+  pub fn sample(&self, position: f32) -> Vec4 {
+    let next = self.stops.partition_point(|stop| stop.position <= position);
+    match (
+      next.checked_sub(1).and_then(|previous| self.stops.get(previous)),
+      self.stops.get(next),
+    ) {
+      (Some(a), Some(b)) => {
+        let t = a
+          .interpolation
+          .ease((position - a.position) / (b.position - a.position));
+        a.color.lerp(b.color, t)
+      }
+      (Some(a), None) => a.color,
+      (None, Some(b)) => b.color,
+      (None, None) => Vec4::ONE,
+    }
+  }
+
+  fn sort(&mut self) {
+    self.stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+  }
+}
+
+/// Sorts `stops` by position.
+impl From<Vec<GradientStop>> for Gradient {
+  fn from(stops: Vec<GradientStop>) -> Self {
+    let mut gradient = Self { stops };
+    gradient.sort();
+    gradient
+  }
+}
+
+impl From<Gradient> for Vec<GradientStop> {
+  fn from(gradient: Gradient) -> Self {
+    gradient.stops
+  }
+}
+
+/// Edited as a preview of the gradient above its stops.
+impl Inspect for Gradient {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    ui.vertical(|ui| {
+      let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width().min(200.0), 16.0), Sense::hover());
+      draw_gradient(ui, rect, self);
+
+      let mut changed = inspect_keys(ui, &mut self.stops, |ui, stop| {
+        let mut color = stop.color.to_array();
+        let color_changed = ui.color_edit_button_rgba_unmultiplied(&mut color).changed();
+        stop.color = Vec4::from_array(color);
+        stop.position.inspect(ui) | color_changed | stop.interpolation.inspect(ui)
+      });
+      if ui.small_button("+").clicked() {
+        let last = self.stops.last().copied().unwrap_or(GradientStop {
+          color: Vec4::ONE,
+          ..Default::default()
+        });
+        self.stops.push(GradientStop {
+          position: last.position + 0.1,
+          ..last
+        });
+        changed = true;
+      }
+      if changed {
+        self.sort();
+      }
+      changed
+    })
+    .inner
+  }
+}
+
+/// Saved as the list of stops.
+impl Reflect for Gradient {
+  fn to_value(&self) -> Value {
+    self.stops.to_value()
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    let applied = self.stops.apply_value(value);
+    self.sort();
+    applied
+  }
+}
+
+/// Shows a row per key, edited by `inspect_key`, with a button removing it. Returns whether any changed.
+fn inspect_keys<K>(ui: &mut Ui, keys: &mut Vec<K>, mut inspect_key: impl FnMut(&mut Ui, &mut K) -> bool) -> bool {
+  let mut changed = false;
+  let mut removed = None;
+  Grid::new(ui.next_auto_id()).show(ui, |ui| {
+    for (index, key) in keys.iter_mut().enumerate() {
+      ui.push_id(index, |ui| {
+        changed |= inspect_key(ui, key);
+        if ui.small_button("-").clicked() {
+          removed = Some(index);
+        }
+      });
+      ui.end_row();
+    }
+  });
+  if let Some(index) = removed {
+    keys.remove(index);
+    changed = true;
+  }
+  changed
+}
+
+fn draw_curve(ui: &Ui, rect: Rect, curve: &Curve) {
+  let painter = ui.painter_at(rect);
+  painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+
+  let Some((start, end)) = curve.time_range() else {
+    return;
+  };
+  let (min, max) = curve
+    .keys()
+    .iter()
+    .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), key| {
+      (min.min(key.value), max.max(key.value))
+    });
+  let span = (end - start).max(f32::EPSILON);
+  let height = (max - min).max(f32::EPSILON);
+
+  let to_screen = |time: f32, value: f32| {
+    Pos2::new(
+      rect.left() + (time - start) / span * rect.width(),
+      rect.bottom() - (value - min) / height * rect.height(),
+    )
+  };
+  let points: Vec<Pos2> = (0..=rect.width() as usize)
+    .map(|x| {
+      let time = start + x as f32 / rect.width() * span;
+      to_screen(time, curve.sample(time))
+    })
+    .collect();
+  let stroke = Stroke::new(1.5, ui.visuals().selection.bg_fill);
+  painter.add(Shape::line(points, stroke));
+  for key in curve.keys() {
+    painter.circle_filled(to_screen(key.time, key.value), 3.0, ui.visuals().strong_text_color());
+  }
+}
+
+fn draw_gradient(ui: &Ui, rect: Rect, gradient: &Gradient) {
+  let (start, end) = match (gradient.stops().first(), gradient.stops().last()) {
+    (Some(first), Some(last)) => (first.position, last.position),
+    _ => (0.0, 1.0),
+  };
+  let span = (end - start).max(f32::EPSILON);
+
+  // one quad per column, so eased and constant segments show as they'll be sampled
+  let mut mesh = Mesh::default();
+  let columns = rect.width().max(1.0) as usize;
+  for column in 0..=columns {
+    let x = column as f32 / columns as f32;
+    let [r, g, b, a] = gradient.sample(start + x * span).to_array();
+    let color = Color32::from(egui::Rgba::from_rgba_unmultiplied(r, g, b, a));
+    let x = rect.left() + x * rect.width();
+    mesh.colored_vertex(Pos2::new(x, rect.top()), color);
+    mesh.colored_vertex(Pos2::new(x, rect.bottom()), color);
+    if column > 0 {
+      let index = column as u32 * 2;
+      mesh.add_triangle(index - 2, index - 1, index);
+      mesh.add_triangle(index - 1, index, index + 1);
+    }
+  }
+  ui.painter_at(rect).add(mesh);
+}
@@ -0,0 +1,150 @@
+use std::process::Command;
+
+use super::{FoxyError, FoxyResult};
+
+/// A custom URI the app was launched with, e.g. `mygame://join/1234?password=fox` from an invite link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepLink {
+  /// The whole URI, as received.
+  pub uri: String,
+  /// Lowercase, without the `://`.
+  pub scheme: String,
+  /// Everything between the `://` and the query, with percent escapes decoded, e.g. `join/1234`.
+  pub path: String,
+  /// The `key=value` pairs after the `?`, with percent escapes decoded.
+  pub query: Vec<(String, String)>,
+}
+
+impl DeepLink {
+  /// Parses `uri` if it uses one of `schemes`.
+  pub fn parse(uri: &str, schemes: &[String]) -> Option<Self> {
+    let (scheme, rest) = uri.split_once("://")?;
+    let scheme = scheme.to_ascii_lowercase();
+    if !schemes.iter().any(|known| known.eq_ignore_ascii_case(&scheme)) {
+      return None;
+    }
+
+    let rest = rest.split_once('#').map_or(rest, |(rest, _)| rest);
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let query = query
+      .split('&')
+      .filter(|pair| !pair.is_empty())
+      .map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        (percent_decode(key), percent_decode(value))
+      })
+      .collect();
+
+    Some(Self {
+      uri: uri.to_owned(),
+      scheme,
+      // some launchers append a trailing slash to bare links
+      path: percent_decode(path.trim_end_matches('/')),
+      query,
+    })
+  }
+
+  /// The parts of the path, e.g. `["join", "1234"]`.
+  pub fn segments(&self) -> impl Iterator<Item = &str> {
+    self.path.split('/').filter(|segment| !segment.is_empty())
+  }
+
+  /// The value of the first `key` in the query.
+  pub fn query_value(&self, key: &str) -> Option<&str> {
+    self
+      .query
+      .iter()
+      .find(|(query_key, _)| query_key == key)
+      .map(|(_, value)| value.as_str())
+  }
+}
+
+/// Registers this executable as the handler for `scheme://` links for the current user, so the OS launches it with
+/// the link as an argument. Combine with [`FoxyCreateInfo::with_single_instance`] to have links reach the running
+/// game instead of a second copy.
+///
+/// On macOS, schemes can only be declared through `CFBundleURLTypes` in the app bundle's `Info.plist`, and the link
+/// arrives as an Apple event rather than an argument, which this doesn't receive yet.
+///
+/// [`FoxyCreateInfo::with_single_instance`]: super::builder::FoxyCreateInfo::with_single_instance
+pub fn register_uri_scheme(scheme: &str, description: &str) -> FoxyResult<()> {
+  if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)) {
+    return Err(FoxyError::Error(format!("invalid uri scheme: `{scheme}`")));
+  }
+  let executable = std::env::current_exe()?;
+
+  register(scheme, description, &executable.to_string_lossy())
+}
+
+#[cfg(target_os = "windows")]
+fn register(scheme: &str, description: &str, executable: &str) -> FoxyResult<()> {
+  let key = format!(r"HKCU\Software\Classes\{scheme}");
+  let command = format!("\"{executable}\" \"%1\"");
+
+  run(Command::new("reg").args(["add", &key, "/ve", "/d", &format!("URL:{description}"), "/f"]))?;
+  run(Command::new("reg").args(["add", &key, "/v", "URL Protocol", "/d", "", "/f"]))?;
+  run(Command::new("reg").args(["add", &format!(r"{key}\shell\open\command"), "/ve", "/d", &command, "/f"]))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn register(scheme: &str, description: &str, executable: &str) -> FoxyResult<()> {
+  let data_home = std::env::var_os("XDG_DATA_HOME")
+    .map(std::path::PathBuf::from)
+    .or_else(|| std::env::var_os("HOME").map(|home| std::path::PathBuf::from(home).join(".local/share")))
+    .ok_or_else(|| FoxyError::Error("no home directory to register the uri scheme in".into()))?;
+  let applications = data_home.join("applications");
+  std::fs::create_dir_all(&applications)?;
+
+  let desktop_file = format!("{scheme}-handler.desktop");
+  std::fs::write(
+    applications.join(&desktop_file),
+    format!(
+      "[Desktop Entry]\nType=Application\nName={description}\nExec=\"{executable}\" %u\nNoDisplay=true\n\
+       MimeType=x-scheme-handler/{scheme};\n"
+    ),
+  )?;
+
+  run(Command::new("xdg-mime").args(["default", &desktop_file, &format!("x-scheme-handler/{scheme}")]))
+}
+
+#[cfg(not(any(target_os = "windows", all(unix, not(target_os = "macos")))))]
+fn register(scheme: &str, _description: &str, _executable: &str) -> FoxyResult<()> {
+  Err(FoxyError::Error(format!(
+    "`{scheme}://` must be declared in the app bundle's Info.plist on this platform"
+  )))
+}
+
+#[allow(unused)]
+fn run(command: &mut Command) -> FoxyResult<()> {
+  let status = command.status()?;
+  if status.success() {
+    Ok(())
+  } else {
+    Err(FoxyError::Error(format!("`{command:?}` failed with {status}")))
+  }
+}
+
+/// Decodes `%XX` escapes, keeping malformed ones as they are.
+fn percent_decode(text: &str) -> String {
+  let bytes = text.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let escaped = (bytes[i] == b'%')
+      .then(|| text.get(i + 1..i + 3))
+      .flatten()
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    match escaped {
+      Some(byte) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      None => {
+        decoded.push(bytes[i]);
+        i += 1;
+      }
+    }
+  }
+
+  String::from_utf8_lossy(&decoded).into_owned()
+}
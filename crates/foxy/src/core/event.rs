@@ -1,9 +1,14 @@
-use winit::event::KeyEvent;
+use winit::{event::KeyEvent, window::Theme};
 
-use super::input::{
-  key::KeyCode,
+use super::{
+  deep_link::DeepLink,
+  hitch::Hitch,
+  input::{
+  key::{KeyCode, LogicalKey},
   mouse::MouseCode,
   state::{ButtonState, KeyState},
+    touch::Touch,
+  },
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -11,6 +16,10 @@ pub enum FoxyEvent {
   None,
   Window(WindowEvent),
   Input(InputEvent),
+  /// The app was launched, or a later launch was handed to it, with a link using one of its URI schemes.
+  DeepLink(DeepLink),
+  /// A recent frame went over the [`HitchDetector`](super::hitch::HitchDetector)'s budget.
+  Hitch(Hitch),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -18,15 +27,25 @@ pub enum WindowEvent {
   Moved,
   Resized,
   Rescaled,
+  /// The window lost its area, e.g. by being minimized. Frames are still run but not drawn until it's
+  /// [`WindowEvent::Restored`].
+  Minimized,
+  /// The window has an area again after being [`WindowEvent::Minimized`].
+  Restored,
+  /// The window's color mode changed, e.g. because the system's did while the window follows it. See
+  /// [`State::set_theme`](super::foxy_state::State::set_theme).
+  ThemeChanged(Theme),
   Unmapped(winit::event::WindowEvent),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum InputEvent {
   Mouse(MouseCode, ButtonState),
-  Keyboard(KeyCode, KeyState),
+  /// The physical key, its state, and the key it produces in the current keyboard layout.
+  Keyboard(KeyCode, KeyState, LogicalKey),
   Cursor,
   Scroll,
+  Touch(Touch),
 }
 
 impl From<Option<winit::event::WindowEvent>> for FoxyEvent {
@@ -41,18 +60,25 @@ impl From<Option<winit::event::WindowEvent>> for FoxyEvent {
       winit::event::WindowEvent::KeyboardInput {
         event: KeyEvent {
           physical_key,
+          logical_key,
           state,
           repeat,
           ..
         },
         ..
-      } => FoxyEvent::Input(InputEvent::Keyboard(physical_key.into(), KeyState::from_winit(state, repeat))),
+      } => FoxyEvent::Input(InputEvent::Keyboard(
+        physical_key.into(),
+        KeyState::from_winit(state, repeat),
+        logical_key,
+      )),
       winit::event::WindowEvent::CursorMoved { .. } => Self::Input(InputEvent::Cursor),
       winit::event::WindowEvent::MouseWheel { .. } => Self::Input(InputEvent::Scroll),
       winit::event::WindowEvent::MouseInput { state, button, .. } => {
         FoxyEvent::Input(InputEvent::Mouse(button.into(), ButtonState::from_winit(state)))
       }
+      winit::event::WindowEvent::Touch(touch) => Self::Input(InputEvent::Touch(touch.into())),
       winit::event::WindowEvent::ScaleFactorChanged { .. } => Self::Window(WindowEvent::Rescaled),
+      winit::event::WindowEvent::ThemeChanged(theme) => Self::Window(WindowEvent::ThemeChanged(theme)),
       _ => Self::Window(WindowEvent::Unmapped(value)),
     }
   }
@@ -1,34 +1,49 @@
-use std::{sync::Arc, thread::JoinHandle, time::Duration};
+use std::{
+  sync::Arc,
+  thread::JoinHandle,
+  time::{Duration, Instant},
+};
 
-use crossbeam::{channel::TryRecvError, queue::ArrayQueue};
+use crossbeam::channel::{RecvTimeoutError, TryRecvError};
 use foxy_renderer::{
   error::RendererError,
-  renderer::{render_data::RenderData, Renderer},
+  renderer::{proxy::RendererProxy, render_data::RenderData, Renderer},
 };
 use foxy_utils::{
   log::LogErr,
   mailbox::{Mailbox, MessagingError},
-  time::{timer::Timer, EngineTime},
+  thread::priority::ThreadSettings,
+  time::{sleep, timer::Timer, EngineTime},
+  tracking,
 };
 use tracing::*;
 use winit::{
-  event::{Event, KeyEvent, WindowEvent},
+  dpi::PhysicalPosition,
+  event::{ElementState, Event, KeyEvent, MouseButton, WindowEvent},
   event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
   window::Window,
 };
 
 use super::{
-  builder::{DebugInfo, FoxyCreateInfo, Polling},
+  builder::{DebugInfo, EngineThread, FoxyCreateInfo, Polling},
   runnable::Runnable,
   FoxyResult,
 };
 use crate::core::{
-  event::FoxyEvent,
+  deep_link::DeepLink,
+  event::{FoxyEvent, WindowEvent as FoxyWindowEvent},
   foxy_state::{self, Foxy},
-  message::{GameLoopMessage, RenderLoopMessage},
+  input::backend::GamepadBackend,
+  message::{FrameHandoff, GameLoopMessage, MessageQueue, RenderLoopMessage},
+  plugin::{Plugins, Stage},
+  profiling,
   runnable::Flow,
+  single_instance::InstanceListener,
+  timings::FrameTimings,
+  title_bar::WindowArea,
   FoxyError,
 };
+use crate::window::{display, splash::Splash};
 
 struct State {
   polling_strategy: Polling,
@@ -36,22 +51,32 @@ struct State {
 
   renderer: Renderer,
   render_time: EngineTime,
-  render_queue: Arc<ArrayQueue<RenderData>>,
+  frames: Arc<FrameHandoff>,
   render_mailbox: Mailbox<RenderLoopMessage, GameLoopMessage>,
+  foxy: Foxy,
 
   // Keep window below the renderer to ensure proper drop order
   window: Arc<Window>,
+  splash: Option<Splash>,
+  instance: Option<InstanceListener>,
+  uri_schemes: Vec<String>,
 
   game_thread: Option<JoinHandle<FoxyResult<()>>>,
 
   original_title: String,
   fps_timer: Timer,
+  last_frame: Instant,
   had_first_frame: bool,
+  /// Whether the window had no area at the last resize, e.g. because it was minimized.
+  is_zero_sized: bool,
+  cursor_position: PhysicalPosition<f64>,
+  last_caption_press: Option<Instant>,
 }
 
 pub struct Framework<T: 'static + Send + Sync> {
   state: Option<State>,
-  event_loop: EventLoop<T>,
+  /// `None` if another instance was already running and took over.
+  event_loop: Option<EventLoop<T>>,
 }
 
 impl Framework<()> {
@@ -62,24 +87,67 @@ impl Framework<()> {
 
 impl<T: 'static + Send + Sync> Framework<T> {
   const GAME_THREAD_ID: &'static str = "foxy";
-  const MAX_FRAME_DATA_IN_FLIGHT: usize = 2;
+  const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+  /// How long before a frame is due under the frame rate limit the event loop wakes up, so the rest of the wait can be
+  /// slept precisely. The event loop's own timer can be as coarse as the scheduler tick, around 15 ms on Windows.
+  const FRAME_WAKE_UP_MARGIN: Duration = Duration::from_millis(if cfg!(windows) { 16 } else { 2 });
 
   pub fn with_events<App: Runnable>(create_info: FoxyCreateInfo) -> FoxyResult<Self> {
     trace!("Firing up Foxy");
 
+    let instance_lock = match &create_info.single_instance {
+      Some(single_instance) => match single_instance.acquire()? {
+        Some(lock) => Some(lock),
+        None => {
+          return Ok(Self {
+            state: None,
+            event_loop: None,
+          })
+        }
+      },
+      None => None,
+    };
+
+    Self::apply_thread_settings(EngineThread::Render, create_info.threads.get(&EngineThread::Render));
+
     let (event_loop, window) = create_info.window.create_window()?;
     let window = Arc::new(window);
+    display::restore_desktop_mode_on_panic(&window);
+    let splash = create_info
+      .splash
+      .as_ref()
+      .and_then(|splash| splash.show(&event_loop).log_error().ok());
+    let instance = instance_lock.map(|lock| lock.listen(window.clone())).transpose()?;
 
     let time = create_info.time.build();
-    let render_queue = Arc::new(ArrayQueue::new(Self::MAX_FRAME_DATA_IN_FLIGHT));
-
-    let foxy = Foxy::new(foxy_state::State::new(time, window.clone()));
+    let frames = Arc::new(FrameHandoff::default());
+
+    let (renderer_proxy, render_commands) = RendererProxy::new();
+    let foxy = Foxy::new(foxy_state::State::new(
+      time,
+      window.clone(),
+      renderer_proxy,
+      create_info.settings,
+    ));
+    foxy.write().set_frame_rate_limit(create_info.frame_rate_limit);
+    let game_thread_settings = create_info.threads.get(&EngineThread::Game).cloned();
+    foxy.write().thread_settings = create_info.threads;
     let egui_context = foxy.read().egui_context.clone();
     let (game_mailbox, render_mailbox) = Mailbox::new_entangled_pair();
-    let game_thread = Some(Self::game_loop::<App>(game_mailbox, foxy, render_queue.clone())?);
-
-    let renderer = Renderer::new(window.clone(), egui_context)?;
+    let game_thread = Some(Self::game_loop::<App>(
+      game_mailbox,
+      foxy.clone(),
+      frames.clone(),
+      create_info.plugins,
+      create_info.polling_strategy,
+      MessageQueue::new(!create_info.raw_events),
+      game_thread_settings,
+    )?);
+
+    let renderer = Renderer::new(window.clone(), egui_context, render_commands, &create_info.renderer)?;
     let render_time = create_info.time.build();
+    let size = window.inner_size();
+    let is_zero_sized = size.width == 0 || size.height == 0;
 
     Ok(Self {
       state: Some(State {
@@ -87,52 +155,76 @@ impl<T: 'static + Send + Sync> Framework<T> {
         debug_info: create_info.debug_info,
         renderer,
         render_time,
-        render_queue,
+        frames,
         render_mailbox,
+        foxy,
         original_title: window.title(),
         window,
+        splash,
+        instance,
+        uri_schemes: create_info.uri_schemes,
         game_thread,
         fps_timer: Timer::new(),
+        last_frame: Instant::now(),
         had_first_frame: false,
+        is_zero_sized,
+        cursor_position: PhysicalPosition::default(),
+        last_caption_press: None,
       }),
-      event_loop,
+      event_loop: Some(event_loop),
     })
   }
 
   pub fn run(self) -> FoxyResult<()> {
+    let Some(event_loop) = self.event_loop else {
+      info!("Handed off to the running instance");
+      return Ok(());
+    };
+
     info!("KON KON KITSUNE!");
     let Some(mut state) = self.state else {
       return Err(FoxyError::Error(format!("failed to take foxy state")));
     };
 
     let _ = state.render_mailbox.send(RenderLoopMessage::Start).log_error();
+    Self::send_deep_links(&state, std::env::args().skip(1));
 
-    self.event_loop.set_control_flow(match state.polling_strategy {
-      Polling::Poll => ControlFlow::Poll,
-      Polling::Wait => ControlFlow::Wait,
-    });
+    event_loop.set_control_flow(Self::control_flow(state.polling_strategy));
 
-    Ok(self.event_loop.run(move |event, elwt| {
+    let result = event_loop.run(move |event, elwt| {
       let _ = &state; // ensure state is moved
 
       match event {
+        Event::WindowEvent { window_id, event }
+          if state.splash.as_ref().is_some_and(|splash| splash.id() == window_id) =>
+        {
+          if let (WindowEvent::RedrawRequested, Some(splash)) = (event, &mut state.splash) {
+            splash.draw();
+          }
+        }
         Event::WindowEvent { event, .. } => {
           // let was_handled = state.renderer.handle_input(&event);
 
           // first check
           match event {
             WindowEvent::CloseRequested => {
-              let response = state
-                .render_mailbox
-                .send_and_recv(RenderLoopMessage::ExitRequested)
-                .log_error();
-              if let Err(_) | Ok(GameLoopMessage::Exit) = response {
-                elwt.exit();
-              }
+              Self::request_exit(&mut state, elwt);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+              state.cursor_position = position;
+            }
+            WindowEvent::MouseInput {
+              state: ElementState::Pressed,
+              button: MouseButton::Left,
+              ..
+            } if Self::handle_title_bar_press(&mut state) => {
+              // the platform takes over the press, so the app never sees it released
+              return;
             }
             WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
               state.renderer.refresh();
               state.window.request_redraw();
+              Self::notify_zero_size(&mut state);
             }
             WindowEvent::RedrawRequested => {
               Self::render(&mut state, elwt);
@@ -140,7 +232,12 @@ impl<T: 'static + Send + Sync> Framework<T> {
             _ => (),
           }
 
-          if !elwt.exiting() {
+          // the game loop requests the redraws itself when it waits for input, so it mustn't be woken by them
+          let is_own_redraw = matches!(
+            (state.polling_strategy, &event),
+            (Polling::WaitTimeout(_), WindowEvent::RedrawRequested)
+          );
+          if !elwt.exiting() && !is_own_redraw {
             // !was_handled went here
             if let Err(error) = state.render_mailbox.send(RenderLoopMessage::Winit(event)) {
               error!("{error:?}")
@@ -148,39 +245,175 @@ impl<T: 'static + Send + Sync> Framework<T> {
           }
         }
         Event::AboutToWait => {
+          let close_requested = std::mem::take(&mut state.foxy.write().close_requested);
+          if close_requested {
+            Self::request_exit(&mut state, elwt);
+          }
+          Self::take_activations(&mut state);
+
           // redraw
           if !state.had_first_frame {
             Self::render(&mut state, elwt);
+          } else if let Polling::WaitTimeout(_) = state.polling_strategy {
+            // the game loop requests a redraw for each frame it runs
+            elwt.set_control_flow(ControlFlow::Wait);
           } else {
-            state.window.request_redraw();
+            // Wait paces frames under the limit here just like Poll does, with the event loop's timer waking it up a
+            // margin early and the rest slept precisely. Without a limit Wait only sleeps until the next event, which
+            // has no deadline to be precise about
+            match Self::next_frame_time(&state) {
+              Some(next_frame) if next_frame > Instant::now() + Self::FRAME_WAKE_UP_MARGIN => {
+                elwt.set_control_flow(ControlFlow::WaitUntil(next_frame - Self::FRAME_WAKE_UP_MARGIN));
+              }
+              next_frame => {
+                if let Some(next_frame) = next_frame {
+                  sleep::sleep_until(next_frame);
+                }
+                elwt.set_control_flow(Self::control_flow(state.polling_strategy));
+                state.window.request_redraw();
+              }
+            }
           }
         }
         Event::LoopExiting => {
           if let Some(thread) = state.game_thread.take() {
             let _ = thread.join();
           }
+          display::restore_desktop_mode(&state.window);
           info!("OTSU KON DESHITA!");
         }
         _ => (),
       }
-    })?)
+    });
+
+    // the state, and with it every resource, is dropped along with the event loop
+    tracking::report_leaks();
+
+    Ok(result?)
+  }
+
+  /// Tells the game loop when the window loses its area, e.g. by being minimized, or gets it back. The renderer skips
+  /// the frames in between, since there's nothing to present them to.
+  fn notify_zero_size(state: &mut State) {
+    let size = state.window.inner_size();
+    let is_zero_sized = size.width == 0 || size.height == 0;
+    if is_zero_sized != state.is_zero_sized {
+      state.is_zero_sized = is_zero_sized;
+      let message = if is_zero_sized {
+        RenderLoopMessage::Minimized
+      } else {
+        RenderLoopMessage::Restored
+      };
+      let _ = state.render_mailbox.send(message).log_error();
+    }
+  }
+
+  fn request_exit(state: &mut State, elwt: &EventLoopWindowTarget<T>) {
+    let response = state
+      .render_mailbox
+      .send_and_recv(RenderLoopMessage::ExitRequested)
+      .log_error();
+    if let Err(_) | Ok(GameLoopMessage::Exit) = response {
+      elwt.exit();
+    }
+  }
+
+  /// Brings the window to the front for each launch handed over by a later instance and passes them on to the app.
+  fn take_activations(state: &mut State) {
+    let Some(instance) = &state.instance else {
+      return;
+    };
+
+    let activations: Vec<_> = instance.try_iter().collect();
+    if activations.is_empty() {
+      return;
+    }
+
+    state.window.set_minimized(false);
+    state.window.focus_window();
+    for activation in &activations {
+      Self::send_deep_links(state, activation.args.iter().cloned());
+    }
+    state.foxy.write().activations.extend(activations);
+  }
+
+  fn send_deep_links(state: &State, args: impl Iterator<Item = String>) {
+    for link in args.filter_map(|arg| DeepLink::parse(&arg, &state.uri_schemes)) {
+      let _ = state.render_mailbox.send(RenderLoopMessage::DeepLink(link)).log_error();
+    }
+  }
+
+  /// Drags, maximizes or resizes the window if the press landed on the app's title bar or borders. Returns whether
+  /// it did.
+  fn handle_title_bar_press(state: &mut State) -> bool {
+    let area = state.foxy.read().hit_test(state.cursor_position);
+    let result = match area {
+      WindowArea::Client => return false,
+      WindowArea::Caption => {
+        let now = Instant::now();
+        let is_double_click = state
+          .last_caption_press
+          .is_some_and(|last| now.duration_since(last) < Self::DOUBLE_CLICK_TIME);
+
+        if is_double_click {
+          state.last_caption_press = None;
+          state.window.set_maximized(!state.window.is_maximized());
+          Ok(())
+        } else {
+          state.last_caption_press = Some(now);
+          state.window.drag_window()
+        }
+      }
+      WindowArea::Resize(direction) => state.window.drag_resize_window(direction),
+    };
+
+    if let Err(error) = result {
+      error!("failed to move window: {error}");
+    }
+
+    true
+  }
+
+  fn control_flow(polling_strategy: Polling) -> ControlFlow {
+    match polling_strategy {
+      Polling::Poll => ControlFlow::Poll,
+      Polling::Wait | Polling::WaitTimeout(_) => ControlFlow::Wait,
+    }
+  }
+
+  /// When the next frame is due under the frame rate limit, if there is one.
+  fn next_frame_time(state: &State) -> Option<Instant> {
+    let limit = state.foxy.read().frame_rate_limit()?;
+    Some(state.last_frame + Duration::from_secs_f64(1.0 / limit as f64))
   }
 
   fn render(state: &mut State, elwt: &EventLoopWindowTarget<T>) {
-    let render_data = state.render_queue.pop();
-    let Some(render_data) = render_data else {
+    let Some(render_data) = state.frames.take() else {
       return;
     };
+    state.last_frame = Instant::now();
 
     state.render_time.update();
     while state.render_time.should_do_tick_unchecked() {
       state.render_time.tick();
     }
 
-    match state.renderer.draw(state.render_time.time(), render_data) {
+    // timed outside of the lock so the game loop isn't held up while drawing
+    let mut draw_duration = Duration::ZERO;
+    let result = FrameTimings::time(&mut draw_duration, || {
+      state.renderer.draw(state.render_time.time(), render_data)
+    });
+    {
+      let mut foxy = state.foxy.write();
+      foxy.timings.draw = draw_duration;
+      foxy.frame_stats.clone_from(state.renderer.frame_stats());
+      foxy.present_stats = *state.renderer.present_stats();
+    }
+    match result {
       Ok(()) if !state.had_first_frame => {
         state.had_first_frame = true;
         state.window.set_visible(true);
+        state.splash = None;
       }
       Err(RendererError::RebuildSwapchain) => {
         state.renderer.refresh();
@@ -204,23 +437,65 @@ impl<T: 'static + Send + Sync> Framework<T> {
     }
   }
 
+  /// Gives the calling thread the settings `thread` was created with, if any.
+  fn apply_thread_settings(thread: EngineThread, settings: Option<&ThreadSettings>) {
+    if let Some(Err(error)) = settings.map(ThreadSettings::apply) {
+      warn!("failed to apply the {thread:?} thread settings: {error}");
+    }
+  }
+
   fn game_loop<App: Runnable>(
     mailbox: Mailbox<GameLoopMessage, RenderLoopMessage>,
     foxy: Foxy,
-    render_queue: Arc<ArrayQueue<RenderData>>,
+    frames: Arc<FrameHandoff>,
+    mut plugins: Plugins,
+    polling_strategy: Polling,
+    mut messages: MessageQueue,
+    thread_settings: Option<ThreadSettings>,
   ) -> FoxyResult<JoinHandle<FoxyResult<()>>> {
     let handle = std::thread::Builder::new()
       .name(Self::GAME_THREAD_ID.into())
       .spawn(move || -> FoxyResult<()> {
+        Self::apply_thread_settings(EngineThread::Game, thread_settings.as_ref());
         let _ = mailbox.recv().log_error();
         let window = foxy.read().window.clone();
 
+        let mut gamepads = GamepadBackend::new(&mut foxy.write().input);
+
         let mut app = App::new(&foxy);
         app.start(&foxy);
+        for plugin in &mut plugins.0 {
+          plugin.start(&foxy);
+        }
+        let mut pending_hitch = None;
         loop {
-          let next_message = mailbox.try_recv();
+          let played_event = foxy.write().next_played_event();
+          let mut raw_events = Vec::new();
+          let next_message = match played_event {
+            Some(event) => {
+              raw_events.push(event.clone());
+              Ok(RenderLoopMessage::Winit(event))
+            }
+            None => {
+              let redraw_requested = std::mem::take(&mut foxy.write().redraw_requested);
+              let timeout = match polling_strategy {
+                Polling::WaitTimeout(timeout) if !redraw_requested => Some(timeout),
+                _ => None,
+              };
+              match messages.next(&mailbox, timeout, &mut raw_events) {
+                Ok(RenderLoopMessage::Winit(event)) => {
+                  let event = foxy.write().filter_live_event(event);
+                  Ok(event.map_or(RenderLoopMessage::None, RenderLoopMessage::Winit))
+                }
+                message => message,
+              }
+            }
+          };
+          foxy.write().raw_events = raw_events;
 
-          let raw_input = foxy.write().take_egui_input();
+          let mut raw_input = foxy.write().take_egui_input();
+          gamepads.poll(&mut foxy.write().input);
+          foxy.write().navigate_ui(&mut raw_input);
 
           let event = match next_message {
             Ok(RenderLoopMessage::MustExit) => {
@@ -237,27 +512,66 @@ impl<T: 'static + Send + Sync> Framework<T> {
               } else {
                 let _ = mailbox.send(GameLoopMessage::DontExit);
               }
-              None
+              FoxyEvent::None
             }
             Ok(RenderLoopMessage::Winit(event)) => {
               let was_handled = foxy.write().handle_input(&event);
+              if was_handled {
+                // releases always reach the input state, or keys let go over the UI would stay held
+                match event {
+                  WindowEvent::KeyboardInput {
+                    event:
+                      KeyEvent {
+                        physical_key,
+                        ref logical_key,
+                        state: ElementState::Released,
+                        repeat,
+                        ..
+                      },
+                    ..
+                  } => {
+                    foxy
+                      .write()
+                      .input
+                      .update_key_state(physical_key, logical_key, ElementState::Released, repeat);
+                  }
+                  WindowEvent::MouseInput {
+                    button,
+                    state: ElementState::Released,
+                    ..
+                  } => {
+                    foxy
+                      .write()
+                      .input
+                      .update_mouse_button_state(button, ElementState::Released);
+                  }
+                  _ => (),
+                }
+              }
 
               if !was_handled {
                 match event {
-                  WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
-                    foxy.write().egui_context.set_zoom_factor(scale_factor as f32);
+                  WindowEvent::Resized(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                    foxy.read().apply_ui_scaling();
                   }
                   WindowEvent::KeyboardInput {
                     event:
                       KeyEvent {
                         physical_key,
+                        ref logical_key,
                         state: element_state,
                         repeat,
                         ..
                       },
                     ..
                   } => {
-                    foxy.write().input.update_key_state(physical_key, element_state, repeat);
+                    foxy
+                      .write()
+                      .input
+                      .update_key_state(physical_key, logical_key, element_state, repeat);
+                    if element_state == ElementState::Pressed && !repeat {
+                      foxy.write().handle_stepping_key(physical_key.into());
+                    }
                   }
                   WindowEvent::MouseInput {
                     button,
@@ -266,59 +580,164 @@ impl<T: 'static + Send + Sync> Framework<T> {
                   } => {
                     foxy.write().input.update_mouse_button_state(button, element_state);
                   }
+                  WindowEvent::CursorMoved { position, .. } => {
+                    foxy.write().input.update_cursor_position(Some(position));
+                  }
+                  WindowEvent::CursorLeft { .. } => {
+                    foxy.write().input.update_cursor_position(None);
+                  }
+                  WindowEvent::MouseWheel { delta, .. } => {
+                    foxy.write().input.update_scroll(delta);
+                  }
                   WindowEvent::ModifiersChanged(mods) => {
                     foxy.write().input.update_modifiers_state(mods);
                   }
+                  WindowEvent::Touch(touch) => {
+                    foxy.write().input.update_touch(touch);
+                  }
                   _ => (),
                 }
 
-                Some(event)
+                FoxyEvent::from(Some(event))
               } else {
-                None
+                FoxyEvent::None
               }
             }
-            Err(MessagingError::TryRecvError {
-              error: TryRecvError::Disconnected,
-            }) => {
+            Ok(RenderLoopMessage::DeepLink(link)) => FoxyEvent::DeepLink(link),
+            Ok(RenderLoopMessage::Minimized) => FoxyEvent::Window(FoxyWindowEvent::Minimized),
+            Ok(RenderLoopMessage::Restored) => FoxyEvent::Window(FoxyWindowEvent::Restored),
+            Err(
+              MessagingError::TryRecvError {
+                error: TryRecvError::Disconnected,
+              }
+              | MessagingError::RecvTimeoutError {
+                error: RecvTimeoutError::Disconnected,
+              },
+            ) => {
               app.stop(&foxy);
               app.delete();
               break;
             }
-            _ => None,
+            _ => FoxyEvent::None,
+          };
+          // hitches wait for a frame without an event of its own, so that they don't replace one
+          let event = match (event, pending_hitch.take()) {
+            (FoxyEvent::None, Some(hitch)) => FoxyEvent::Hitch(hitch),
+            (event, hitch) => {
+              pending_hitch = hitch;
+              event
+            }
           };
 
           // Loop
 
-          let event = FoxyEvent::from(event);
-
           // let raw_input = foxy.write().egui_state.take_egui_input(&window);
 
-          foxy.write().engine_time.update();
-          while foxy.write().engine_time.should_do_tick_unchecked() {
-            foxy.write().engine_time.tick();
-            app.fixed_update(&foxy, &event);
+          let step = foxy.write().stepping.next_step();
+          {
+            let mut foxy = foxy.write();
+            let frame_duration = *foxy.engine_time.time().average_delta();
+            foxy.engine_time.set_time_scale(step.time_scale());
+            foxy.engine_time.update();
+            let advance = step.advance(foxy.engine_time.tick_duration(), frame_duration);
+            foxy.engine_time.advance(advance);
           }
+          let delta_secs = foxy.read().engine_time.time().delta_secs();
+          foxy.write().input.advance_rumble(delta_secs as f32);
+
+          let mut timings = FrameTimings::default();
+          plugins.around(&foxy, Stage::FixedUpdate, || {
+            while foxy.write().engine_time.should_do_tick_unchecked() {
+              foxy.write().engine_time.tick();
+              foxy.write().input.begin_fixed_tick();
+              FrameTimings::time(&mut timings.fixed_update, || app.fixed_update(&foxy, &event));
+              foxy.write().input.end_fixed_tick();
+              timings.fixed_ticks += 1;
+            }
+          });
 
-          if let FoxyEvent::Input(event) = &event {
-            app.input(&foxy, event);
-          }
+          FrameTimings::time(&mut timings.plugins, || {
+            for plugin in &mut plugins.0 {
+              plugin.update(&foxy, &event);
+            }
+          });
 
-          app.update(&foxy, &event);
+          plugins.around(&foxy, Stage::Update, || {
+            FrameTimings::time(&mut timings.update, || {
+              if let FoxyEvent::Input(event) = &event {
+                app.input(&foxy, event);
+              }
 
-          if let FoxyEvent::Window(event) = &event {
-            app.window(&foxy, event);
-          }
+              if step.runs_update() {
+                app.update(&foxy, &event);
+              }
 
-          let full_output = foxy.read().egui_context.run(raw_input, |ui| {
-            app.gui(&foxy, ui);
+              if let FoxyEvent::Window(event) = &event {
+                app.window(&foxy, event);
+              }
+            })
+          });
+          gamepads.apply_rumble(&foxy.read().input);
+
+          let egui_context = foxy.read().egui_context.clone();
+          let full_output = plugins.around(&foxy, Stage::Gui, || {
+            FrameTimings::time(&mut timings.gui, || {
+              egui_context.run(raw_input, |ui| {
+                app.gui(&foxy, ui);
+              })
+            })
           });
 
           foxy
             .write()
             .egui_state
             .handle_platform_output(&window, full_output.platform_output.clone());
+          if full_output
+            .viewport_output
+            .values()
+            .any(|output| output.repaint_delay.is_zero())
+          {
+            foxy.request_redraw();
+          }
+
+          let mut render_data = RenderData {
+            full_output,
+            color_filter: Some(foxy.read().accessibility.color_filter),
+            ..Default::default()
+          };
+          {
+            let mut foxy = foxy.write();
+            let foxy = &mut *foxy;
+            foxy.adaptive_quality.update(&foxy.frame_stats);
+            foxy.adaptive_quality.apply(&mut render_data);
+          }
+          plugins.around(&foxy, Stage::Render, || {
+            FrameTimings::time(&mut timings.render, || app.render(&foxy, &mut render_data))
+          });
+          {
+            let (frame, scopes) = profiling::end_frame();
+            let mut foxy = foxy.write();
+            // the draw time is filled in by the render thread
+            foxy.timings = FrameTimings {
+              frame,
+              draw: foxy.timings.draw,
+              scopes,
+              ..timings
+            };
+            if let Some(hitch) = foxy.hitch_detector.check(&foxy.timings) {
+              pending_hitch = Some(hitch);
+            }
+          }
+
+          frames.publish(render_data);
+          foxy.write().input.end_frame();
+          if let Polling::WaitTimeout(_) = polling_strategy {
+            window.request_redraw();
+          }
+        }
 
-          render_queue.force_push(RenderData { full_output });
+        for plugin in &mut plugins.0 {
+          plugin.stop(&foxy);
         }
 
         // debug!("BAU BAU FOR NOW");
@@ -1,10 +1,44 @@
-use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::{
+  collections::HashMap,
+  fmt::Display,
+  mem,
+  sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
 
 use egui::{epaint::Shadow, style::HandleShape, Context, RawInput, Rounding, Visuals};
-use foxy_utils::time::{EngineTime, Time};
-use winit::{event::WindowEvent, window::Window};
+use foxy_renderer::renderer::{present::PresentStats, profiler::FrameStats, proxy::RendererProxy};
+use foxy_utils::{
+  thread::priority::ThreadSettings,
+  time::{EngineTime, Time},
+};
+use winit::{
+  dpi::PhysicalPosition,
+  event::WindowEvent,
+  window::{Theme, Window, WindowLevel},
+};
 
-use super::input::Input;
+use super::{
+  accessibility::Accessibility,
+  adaptive_quality::AdaptiveQuality,
+  builder::{AppSettings, EngineThread, UiScaling},
+  hitch::HitchDetector,
+  input::{
+    action::Binding,
+    key::KeyCode,
+    recording::{InputPlayback, InputRecorder, InputRecording},
+    Input,
+  },
+  localization::Localization,
+  single_instance::Activation,
+  stepping::Stepping,
+  telemetry::Telemetry,
+  timings::FrameTimings,
+  text::FontSettings,
+  title_bar::{HitTest, TitleBarButton, WindowArea},
+  ui_navigation::UiNavigation,
+  FoxyResult,
+};
+use crate::window::display::{self, DisplayMode, WindowMode};
 
 #[derive(Clone)]
 pub struct Foxy(Arc<RwLock<State>>);
@@ -21,18 +55,53 @@ impl Foxy {
   pub fn write(&self) -> RwLockWriteGuard<State> {
     self.0.write().expect("reader panicked")
   }
+
+  /// Runs another frame under [`Polling::WaitTimeout`](super::builder::Polling::WaitTimeout) without waiting for
+  /// input, e.g. while something is animating. Call it every frame for as long as frames should keep running.
+  pub fn request_redraw(&self) {
+    self.write().redraw_requested = true;
+  }
+
+  /// Translates `key` for the current locale. See [`Localization::tr`].
+  pub fn tr(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    self.read().localization.tr(key, args)
+  }
 }
 
 pub struct State {
   pub(crate) engine_time: EngineTime,
   pub(crate) window: Arc<Window>,
+  pub(crate) renderer: RendererProxy,
   pub(crate) egui_context: Context,
   pub(crate) egui_state: egui_winit::State,
   pub(crate) input: Input,
+  pub(crate) ui_scaling: UiScaling,
+  pub(crate) localization: Localization,
+  pub(crate) fonts: FontSettings,
+  pub(crate) accessibility: Accessibility,
+  pub(crate) ui_navigation: UiNavigation,
+  pub(crate) hitch_detector: HitchDetector,
+  pub(crate) adaptive_quality: AdaptiveQuality,
+  pub(crate) recorder: Option<InputRecorder>,
+  pub(crate) playback: Option<InputPlayback>,
+  pub(crate) hit_test: Option<Box<HitTest>>,
+  pub(crate) close_requested: bool,
+  pub(crate) redraw_requested: bool,
+  pub(crate) raw_events: Vec<WindowEvent>,
+  pub(crate) activations: Vec<Activation>,
+  pub(crate) telemetry: Option<Telemetry>,
+  pub(crate) stepping: Stepping,
+  pub(crate) timings: FrameTimings,
+  pub(crate) frame_stats: FrameStats,
+  pub(crate) present_stats: PresentStats,
+  pub(crate) frame_rate_limit: Option<u32>,
+  pub(crate) thread_settings: HashMap<EngineThread, ThreadSettings>,
+  pub(crate) opacity: f32,
+  pub(crate) always_on_top: bool,
 }
 
 impl State {
-  pub fn new(engine_time: EngineTime, window: Arc<Window>) -> Self {
+  pub fn new(engine_time: EngineTime, window: Arc<Window>, renderer: RendererProxy, settings: AppSettings) -> Self {
     let egui_context = Context::default();
 
     let id = egui_context.viewport_id();
@@ -48,15 +117,47 @@ impl State {
 
     egui_context.set_visuals(visuals);
 
+    if let Some(telemetry) = &settings.telemetry {
+      telemetry.start();
+    }
+
     let egui_state = egui_winit::State::new(egui_context.clone(), id, &window, None, None);
 
-    Self {
+    let state = Self {
       engine_time,
       window,
+      renderer,
       egui_context,
       egui_state,
       input: Input::new(),
-    }
+      ui_scaling: settings.ui_scaling,
+      localization: settings.localization,
+      fonts: settings.fonts,
+      accessibility: settings.accessibility,
+      ui_navigation: UiNavigation::default(),
+      hitch_detector: HitchDetector::default(),
+      adaptive_quality: AdaptiveQuality::default(),
+      recorder: None,
+      playback: None,
+      hit_test: None,
+      close_requested: false,
+      redraw_requested: false,
+      raw_events: Vec::new(),
+      activations: Vec::new(),
+      telemetry: settings.telemetry,
+      stepping: Stepping::default(),
+      timings: FrameTimings::default(),
+      frame_stats: FrameStats::default(),
+      present_stats: PresentStats::default(),
+      frame_rate_limit: None,
+      thread_settings: HashMap::new(),
+      opacity: 1.0,
+      always_on_top: false,
+    };
+    state.apply_ui_scaling();
+    state.apply_fonts();
+
+    state
   }
 
   pub fn time(&self) -> Time {
@@ -67,10 +168,275 @@ impl State {
     &self.window
   }
 
+  /// The color mode of the window's title bar, or `None` where the platform doesn't tell.
+  pub fn theme(&self) -> Option<Theme> {
+    self.window.theme()
+  }
+
+  /// Switches the window's title bar between dark and light, or back to following the system's color mode with
+  /// `None`. Changes to the system's color mode arrive as
+  /// [`WindowEvent::ThemeChanged`](super::event::WindowEvent::ThemeChanged) events.
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    self.window.set_theme(theme);
+  }
+
+  pub fn window_mode(&self) -> WindowMode {
+    WindowMode::of(&self.window)
+  }
+
+  /// Switches the window between windowed, borderless and exclusive fullscreen. Fails if the display doesn't support
+  /// the mode of [`WindowMode::Exclusive`].
+  pub fn set_window_mode(&self, mode: WindowMode) -> FoxyResult<()> {
+    mode.apply(&self.window)
+  }
+
+  /// The modes the display the window is on supports for [`WindowMode::Exclusive`], largest and fastest first.
+  pub fn display_modes(&self) -> Vec<DisplayMode> {
+    display::display_modes(&self.window)
+  }
+
+  pub fn opacity(&self) -> f32 {
+    self.opacity
+  }
+
+  /// Fades the whole window, decorations included, from 0 for invisible to 1 for opaque, e.g. for overlays that
+  /// shouldn't hide what's under them. Only supported on Windows.
+  pub fn set_opacity(&mut self, opacity: f32) -> FoxyResult<()> {
+    crate::window::set_opacity(&self.window, opacity)?;
+    self.opacity = opacity.clamp(0.0, 1.0);
+    Ok(())
+  }
+
+  pub fn is_always_on_top(&self) -> bool {
+    self.always_on_top
+  }
+
+  /// Keeps the window above every other window, even while it isn't focused, e.g. for overlays and tools that sit on
+  /// top of another app.
+  pub fn set_always_on_top(&mut self, always_on_top: bool) {
+    self.always_on_top = always_on_top;
+    self.window.set_window_level(if always_on_top {
+      WindowLevel::AlwaysOnTop
+    } else {
+      WindowLevel::Normal
+    });
+  }
+
+  /// For creating meshes and materials from the game thread.
+  pub fn renderer(&self) -> &RendererProxy {
+    &self.renderer
+  }
+
   pub fn input(&self) -> &Input {
     &self.input
   }
 
+  pub fn input_mut(&mut self) -> &mut Input {
+    &mut self.input
+  }
+
+  pub fn ui_scaling(&self) -> UiScaling {
+    self.ui_scaling
+  }
+
+  pub fn set_ui_scaling(&mut self, ui_scaling: UiScaling) {
+    self.ui_scaling = ui_scaling;
+    self.apply_ui_scaling();
+  }
+
+  pub fn frame_rate_limit(&self) -> Option<u32> {
+    self.frame_rate_limit
+  }
+
+  /// Caps how many frames are drawn per second, or uncaps it with `None`. The game loop keeps running at its own pace.
+  pub fn set_frame_rate_limit(&mut self, frame_rate_limit: Option<u32>) {
+    self.frame_rate_limit = frame_rate_limit.filter(|limit| *limit > 0);
+  }
+
+  pub fn localization(&self) -> &Localization {
+    &self.localization
+  }
+
+  /// Switches the language of translated text, and the fonts that come with it.
+  pub fn set_locale(&mut self, locale: impl Into<String>) {
+    self.localization.set_locale(locale);
+    self.apply_fonts();
+  }
+
+  pub fn fonts(&self) -> &FontSettings {
+    &self.fonts
+  }
+
+  pub fn set_fonts(&mut self, fonts: FontSettings) {
+    self.fonts = fonts;
+    self.apply_fonts();
+  }
+
+  pub fn accessibility(&self) -> &Accessibility {
+    &self.accessibility
+  }
+
+  pub fn set_accessibility(&mut self, accessibility: Accessibility) {
+    self.accessibility = accessibility;
+    self.apply_ui_scaling();
+  }
+
+  pub fn ui_navigation(&self) -> &UiNavigation {
+    &self.ui_navigation
+  }
+
+  /// Keyboard and gamepad navigation of the UI, e.g. to rebind it or turn it off during gameplay.
+  pub fn ui_navigation_mut(&mut self) -> &mut UiNavigation {
+    &mut self.ui_navigation
+  }
+
+  pub fn hitch_detector(&self) -> &HitchDetector {
+    &self.hitch_detector
+  }
+
+  /// The budget frames are flagged as hitches over, e.g. to tighten it to the target frame rate or turn it off.
+  pub fn hitch_detector_mut(&mut self) -> &mut HitchDetector {
+    &mut self.hitch_detector
+  }
+
+  pub fn adaptive_quality(&self) -> &AdaptiveQuality {
+    &self.adaptive_quality
+  }
+
+  /// The controller trading render scale, shadow resolution and post effects for frame rate, e.g. to enable it with
+  /// bounds from the graphics settings.
+  pub fn adaptive_quality_mut(&mut self) -> &mut AdaptiveQuality {
+    &mut self.adaptive_quality
+  }
+
+  /// Starts recording window and input events, replacing any recording in progress.
+  pub fn start_recording(&mut self) {
+    self.recorder = Some(InputRecorder::new());
+  }
+
+  pub fn stop_recording(&mut self) -> Option<InputRecording> {
+    self.recorder.take().map(InputRecorder::finish)
+  }
+
+  pub fn is_recording(&self) -> bool {
+    self.recorder.is_some()
+  }
+
+  /// Replays `recording` as if its events came from the window, at `speed` times real time. Live input is ignored
+  /// until it's done.
+  pub fn play_recording(&mut self, recording: InputRecording, speed: f64) {
+    self.playback = Some(InputPlayback::new(recording, speed));
+  }
+
+  pub fn stop_playback(&mut self) {
+    self.playback = None;
+  }
+
+  pub fn is_playing_back(&self) -> bool {
+    self.playback.is_some()
+  }
+
+  /// Lets a window without decorations be dragged, snapped and resized natively from its own title bar and borders.
+  /// `hit_test` is asked about every left click before the app sees it.
+  pub fn set_hit_test(&mut self, hit_test: impl Fn(PhysicalPosition<f64>) -> WindowArea + Send + Sync + 'static) {
+    self.hit_test = Some(Box::new(hit_test));
+  }
+
+  pub fn clear_hit_test(&mut self) {
+    self.hit_test = None;
+  }
+
+  /// Does what the native title bar button would, for custom-drawn title bars.
+  pub fn press_title_bar_button(&mut self, button: TitleBarButton) {
+    match button {
+      TitleBarButton::Minimize => self.window.set_minimized(true),
+      TitleBarButton::Maximize => self.window.set_maximized(!self.window.is_maximized()),
+      TitleBarButton::Close => self.close_requested = true,
+    }
+  }
+
+  /// `None` unless the app was created with
+  /// [`FoxyCreateInfo::with_telemetry`](super::builder::FoxyCreateInfo::with_telemetry).
+  pub fn telemetry(&self) -> Option<&Telemetry> {
+    self.telemetry.as_ref()
+  }
+
+  /// The settings `thread` was given with
+  /// [`FoxyCreateInfo::with_thread_settings`](super::builder::FoxyCreateInfo::with_thread_settings), if any.
+  pub fn thread_settings(&self, thread: EngineThread) -> Option<&ThreadSettings> {
+    self.thread_settings.get(&thread)
+  }
+
+  /// How long each stage of the game loop took in the last frame.
+  pub fn timings(&self) -> &FrameTimings {
+    &self.timings
+  }
+
+  /// Every window event that arrived for this frame, oldest first. High-frequency events such as cursor moves are
+  /// merged into the frame's event, so this is the only place the ones before it show up, e.g. for drawing strokes.
+  pub fn raw_events(&self) -> &[WindowEvent] {
+    &self.raw_events
+  }
+
+  /// GPU time per pass, frame time and draw count of a recent frame, from the render thread. See
+  /// [`Renderer::frame_stats`](foxy_renderer::renderer::Renderer::frame_stats).
+  pub fn frame_stats(&self) -> &FrameStats {
+    &self.frame_stats
+  }
+
+  /// The display's refresh rate and missed vsyncs as of the last frame presented, from the render thread. See
+  /// [`Renderer::present_stats`](foxy_renderer::renderer::Renderer::present_stats).
+  pub fn present_stats(&self) -> &PresentStats {
+    &self.present_stats
+  }
+
+  pub fn stepping(&self) -> &Stepping {
+    &self.stepping
+  }
+
+  /// Pauses, steps or slows down the simulation, for debugging.
+  pub fn stepping_mut(&mut self) -> &mut Stepping {
+    &mut self.stepping
+  }
+
+  /// The launches handed to this instance since the last call, oldest first. Only filled when the app was created
+  /// with [`FoxyCreateInfo::with_single_instance`](super::builder::FoxyCreateInfo::with_single_instance).
+  pub fn take_activations(&mut self) -> Vec<Activation> {
+    mem::take(&mut self.activations)
+  }
+
+  /// Zooms the UI so that it matches the scaling policy for the current size and scale factor of the window, times the
+  /// accessibility UI scale.
+  pub(crate) fn apply_ui_scaling(&self) {
+    let scale_factor = self.window.scale_factor();
+    let pixels_per_point = self.ui_scaling.pixels_per_point(self.window.inner_size(), scale_factor)
+      * self.accessibility.ui_scale;
+    self.egui_context.set_zoom_factor(pixels_per_point / scale_factor as f32);
+  }
+
+  /// Installs the UI fonts: egui's defaults, then the fonts of the current locale, then the fallback fonts.
+  pub(crate) fn apply_fonts(&self) {
+    self
+      .egui_context
+      .set_fonts(self.fonts.definitions(self.localization.fonts()));
+  }
+
+  /// Runs the debug stepping action bound to `code`, if any.
+  pub(crate) fn handle_stepping_key(&mut self, code: KeyCode) {
+    let is_bound = |action| self.input.actions().bindings(action).contains(&Binding::Key(code));
+    if is_bound(Stepping::PAUSE_ACTION) {
+      self.stepping.toggle_pause();
+    } else if is_bound(Stepping::STEP_TICK_ACTION) {
+      self.stepping.step_tick();
+    } else if is_bound(Stepping::STEP_FRAME_ACTION) {
+      self.stepping.step_frame();
+    }
+  }
+
+  pub(crate) fn hit_test(&self, point: PhysicalPosition<f64>) -> WindowArea {
+    self.hit_test.as_ref().map_or(WindowArea::Client, |hit_test| hit_test(point))
+  }
+
   pub(crate) fn handle_input(&mut self, event: &WindowEvent) -> bool {
     let response = self.egui_state.on_window_event(&self.window, event);
 
@@ -81,7 +447,36 @@ impl State {
     response.consumed
   }
 
+  /// The next event of the recording being played back whose time has come.
+  pub(crate) fn next_played_event(&mut self) -> Option<WindowEvent> {
+    let playback = self.playback.as_mut()?;
+    let event = playback.next_due();
+    if playback.is_finished() {
+      self.playback = None;
+    }
+    event
+  }
+
+  /// Records an event from the window, then returns it unless playback overrides it.
+  pub(crate) fn filter_live_event(&mut self, event: WindowEvent) -> Option<WindowEvent> {
+    if let Some(recorder) = &mut self.recorder {
+      recorder.record(&event);
+    }
+
+    if self.playback.is_some() && InputPlayback::overrides(&event) {
+      None
+    } else {
+      Some(event)
+    }
+  }
+
   pub(crate) fn take_egui_input(&mut self) -> RawInput {
     self.egui_state.take_egui_input(&self.window)
   }
+
+  /// Adds the UI navigation of this frame's input to `raw_input`.
+  pub(crate) fn navigate_ui(&mut self, raw_input: &mut RawInput) {
+    let has_focus = self.egui_context.memory(|memory| memory.focus().is_some());
+    self.ui_navigation.apply(&self.input, has_focus, raw_input);
+  }
 }
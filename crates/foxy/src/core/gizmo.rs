@@ -0,0 +1,372 @@
+use std::f32::consts::{PI, TAU};
+
+use foxy_renderer::renderer::{
+  camera::Camera,
+  render_data::{DebugLine, RenderData},
+};
+use foxy_utils::geometry::{Plane, Ray};
+use glam::{Quat, Vec2, Vec3};
+
+use super::{foxy_state::Foxy, input::mouse::MouseCode};
+
+/// What dragging a [`Gizmo`] handle does.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GizmoMode {
+  #[default]
+  Translate,
+  Rotate,
+  Scale,
+}
+
+/// Which axes a [`Gizmo`] moves and rotates along. Scaling always happens along the local axes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GizmoSpace {
+  #[default]
+  World,
+  Local,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GizmoAxis {
+  X,
+  Y,
+  Z,
+}
+
+impl GizmoAxis {
+  pub const ALL: [Self; 3] = [Self::X, Self::Y, Self::Z];
+
+  pub fn unit(self) -> Vec3 {
+    match self {
+      GizmoAxis::X => Vec3::X,
+      GizmoAxis::Y => Vec3::Y,
+      GizmoAxis::Z => Vec3::Z,
+    }
+  }
+
+  fn index(self) -> usize {
+    self as usize
+  }
+
+  fn color(self) -> [f32; 4] {
+    match self {
+      GizmoAxis::X => [0.9, 0.2, 0.2, 1.0],
+      GizmoAxis::Y => [0.2, 0.9, 0.2, 1.0],
+      GizmoAxis::Z => [0.2, 0.4, 1.0, 1.0],
+    }
+  }
+}
+
+/// How a drag of a [`Gizmo`] changed the transform it manipulates, since the previous update.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransformDelta {
+  /// Added to the position, in world space.
+  pub translation: Vec3,
+  /// Applied on top of the rotation, in world space.
+  pub rotation: Quat,
+  /// Multiplies the scale along each local axis.
+  pub scale: Vec3,
+}
+
+impl Default for TransformDelta {
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+impl TransformDelta {
+  pub const IDENTITY: Self = Self {
+    translation: Vec3::ZERO,
+    rotation: Quat::IDENTITY,
+    scale: Vec3::ONE,
+  };
+
+  pub fn apply(&self, position: &mut Vec3, rotation: &mut Quat, scale: &mut Vec3) {
+    *position += self.translation;
+    *rotation = (self.rotation * *rotation).normalize();
+    *scale *= self.scale;
+  }
+}
+
+/// Interactive handles for moving, rotating and scaling an object with the mouse, for level editors and other tools.
+///
+/// Call [`Gizmo::update`] every frame with the object's transform, and apply the [`TransformDelta`] it returns while
+/// a handle is dragged. Then call [`Gizmo::draw`] from the app's `render` to draw the handles with the debug lines of
+/// the frame. Handles keep the same size on screen however far the object is.
+#[derive(Debug, Clone)]
+pub struct Gizmo {
+  mode: GizmoMode,
+  space: GizmoSpace,
+  size: f32,
+  handles: Option<Handles>,
+  hovered: Option<GizmoAxis>,
+  drag: Option<Drag>,
+}
+
+impl Default for Gizmo {
+  fn default() -> Self {
+    Self {
+      mode: GizmoMode::default(),
+      space: GizmoSpace::default(),
+      size: Self::DEFAULT_SIZE,
+      handles: None,
+      hovered: None,
+      drag: None,
+    }
+  }
+}
+
+/// Where the handles are, for one frame.
+#[derive(Debug, Copy, Clone)]
+struct Handles {
+  mode: GizmoMode,
+  origin: Vec3,
+  axes: [Vec3; 3],
+  length: f32,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Drag {
+  axis: GizmoAxis,
+  /// The handles when the drag started, so that moving the object doesn't move what the cursor is measured against.
+  handles: Handles,
+  /// The position along the axis, or the angle around it, at the previous update.
+  last: f32,
+}
+
+impl Gizmo {
+  pub const DEFAULT_SIZE: f32 = 0.15;
+  const HIGHLIGHT_COLOR: [f32; 4] = [1.0, 0.85, 0.1, 1.0];
+  /// How close the cursor has to get to a handle to grab it, relative to the handle length.
+  const PICK_TOLERANCE: f32 = 0.08;
+  const RING_SEGMENTS: usize = 48;
+
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_mode(mut self, mode: GizmoMode) -> Self {
+    self.mode = mode;
+    self
+  }
+
+  pub fn with_space(mut self, space: GizmoSpace) -> Self {
+    self.space = space;
+    self
+  }
+
+  /// The length of the handles, as a fraction of the height of the view.
+  pub fn with_size(mut self, size: f32) -> Self {
+    self.size = size;
+    self
+  }
+
+  pub fn mode(&self) -> GizmoMode {
+    self.mode
+  }
+
+  /// Switches what the handles do. Ends any drag in progress.
+  pub fn set_mode(&mut self, mode: GizmoMode) {
+    self.mode = mode;
+    self.drag = None;
+  }
+
+  pub fn space(&self) -> GizmoSpace {
+    self.space
+  }
+
+  pub fn set_space(&mut self, space: GizmoSpace) {
+    self.space = space;
+    self.drag = None;
+  }
+
+  /// The handle being dragged, or else the one under the cursor.
+  pub fn hovered(&self) -> Option<GizmoAxis> {
+    self.drag.map(|drag| drag.axis).or(self.hovered)
+  }
+
+  /// Whether a handle is being dragged, e.g. to keep the click from also selecting what's behind it.
+  pub fn is_dragging(&self) -> bool {
+    self.drag.is_some()
+  }
+
+  /// Picks and drags the handles of an object at `position` and `rotation`, as seen by `camera`, with the left mouse
+  /// button. Returns how the drag changed the transform since the last update, while a handle is dragged.
+  pub fn update(&mut self, foxy: &Foxy, camera: &Camera, position: Vec3, rotation: Quat) -> Option<TransformDelta> {
+    let (cursor, pressed, window_size) = {
+      let state = foxy.read();
+      (
+        state.input().cursor_position(),
+        state.input().mouse(MouseCode::Left).is_pressed(),
+        state.window().inner_size(),
+      )
+    };
+
+    let handles = Handles::new(self.mode, self.space, self.size, camera, position, rotation);
+    self.handles = Some(handles);
+
+    let Some(cursor) = cursor else {
+      self.hovered = None;
+      self.drag = None;
+      return None;
+    };
+    let viewport = Vec2::new(window_size.width.max(1) as f32, window_size.height.max(1) as f32);
+    let ray = camera.screen_to_world_ray(Vec2::new(cursor.x as f32, cursor.y as f32), viewport);
+
+    if !pressed {
+      self.drag = None;
+      self.hovered = handles.pick(&ray);
+      return None;
+    }
+
+    match self.drag {
+      // the hovered handle is only picked while the button is up, so drags only start from presses on a handle
+      None => {
+        let axis = self.hovered?;
+        let last = handles.drag_value(axis, &ray)?;
+        self.drag = Some(Drag { axis, handles, last });
+        None
+      }
+      Some(drag) => {
+        let value = drag.handles.drag_value(drag.axis, &ray)?;
+        self.drag = Some(Drag { last: value, ..drag });
+        Some(drag.handles.delta(drag.axis, drag.last, value))
+      }
+    }
+  }
+
+  /// Adds the handles, as of the last update, to the debug lines of the frame.
+  pub fn draw(&self, render_data: &mut RenderData) {
+    let Some(handles) = self.handles else {
+      return;
+    };
+
+    for axis in GizmoAxis::ALL {
+      let color = if self.hovered() == Some(axis) {
+        Self::HIGHLIGHT_COLOR
+      } else {
+        axis.color()
+      };
+      let direction = handles.axes[axis.index()];
+      let (u, v) = direction.any_orthonormal_pair();
+      let tip = handles.origin + direction * handles.length;
+      let head = handles.length * 0.1;
+      let mut line = |start, end| render_data.debug_lines.push(DebugLine::new(start, end, color));
+
+      match handles.mode {
+        GizmoMode::Translate => {
+          line(handles.origin, tip);
+          for side in [u, -u, v, -v] {
+            line(tip, tip - direction * head + side * head * 0.4);
+          }
+        }
+        GizmoMode::Scale => {
+          line(handles.origin, tip);
+          let corners = [u + v, u - v, -u - v, -u + v].map(|corner| tip + corner * head * 0.5);
+          for (i, corner) in corners.iter().enumerate() {
+            line(*corner, corners[(i + 1) % corners.len()]);
+          }
+        }
+        GizmoMode::Rotate => {
+          for i in 0..Self::RING_SEGMENTS {
+            line(handles.ring_point(direction, i), handles.ring_point(direction, i + 1));
+          }
+        }
+      }
+    }
+  }
+}
+
+impl Handles {
+  fn new(mode: GizmoMode, space: GizmoSpace, size: f32, camera: &Camera, position: Vec3, rotation: Quat) -> Self {
+    let local = space == GizmoSpace::Local || mode == GizmoMode::Scale;
+    let axes = GizmoAxis::ALL.map(|axis| if local { rotation * axis.unit() } else { axis.unit() });
+
+    // the height of the view at the object's depth, so that the handles keep their size on screen
+    let depth = (position - camera.position).dot(camera.forward()).max(camera.near());
+    let view_height = 2.0 * depth * (camera.fov_y / 2.0).tan();
+
+    Self {
+      mode,
+      origin: position,
+      axes,
+      length: view_height * size,
+    }
+  }
+
+  /// The handle under `ray`, nearest to the camera.
+  fn pick(&self, ray: &Ray) -> Option<GizmoAxis> {
+    let tolerance = self.length * Gizmo::PICK_TOLERANCE;
+
+    GizmoAxis::ALL
+      .into_iter()
+      .filter_map(|axis| {
+        let direction = self.axes[axis.index()];
+        match self.mode {
+          GizmoMode::Translate | GizmoMode::Scale => {
+            let (_, along) = ray.closest_to_line(self.origin, direction)?;
+            let point = self.origin + direction * along.clamp(0.0, self.length);
+            (ray.distance_to_point(point) <= tolerance).then_some((axis, ray.closest_distance(point)))
+          }
+          // sampled rather than intersected, so that rings seen edge-on can still be grabbed
+          GizmoMode::Rotate => (0..Gizmo::RING_SEGMENTS)
+            .map(|i| self.ring_point(direction, i))
+            .map(|point| (ray.distance_to_point(point), ray.closest_distance(point)))
+            .filter(|(distance, _)| *distance <= tolerance)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(_, depth)| (axis, depth)),
+        }
+      })
+      .min_by(|(_, a), (_, b)| a.total_cmp(b))
+      .map(|(axis, _)| axis)
+  }
+
+  /// The `i`th of the points around the ring for rotating around `direction`.
+  fn ring_point(&self, direction: Vec3, i: usize) -> Vec3 {
+    let (u, v) = direction.any_orthonormal_pair();
+    let angle = i as f32 / Gizmo::RING_SEGMENTS as f32 * TAU;
+    self.origin + (u * angle.cos() + v * angle.sin()) * self.length
+  }
+
+  /// Where `ray` is along the axis, or the angle it points at around the axis when rotating.
+  fn drag_value(&self, axis: GizmoAxis, ray: &Ray) -> Option<f32> {
+    let direction = self.axes[axis.index()];
+    match self.mode {
+      GizmoMode::Translate | GizmoMode::Scale => ray.closest_to_line(self.origin, direction).map(|(_, along)| along),
+      GizmoMode::Rotate => {
+        let offset = ray.at(ray.intersect_plane(&Plane::from_point_normal(self.origin, direction))?) - self.origin;
+        let u = direction.any_orthonormal_vector();
+        let v = direction.cross(u);
+        Some(offset.dot(v).atan2(offset.dot(u)))
+      }
+    }
+  }
+
+  fn delta(&self, axis: GizmoAxis, from: f32, to: f32) -> TransformDelta {
+    let direction = self.axes[axis.index()];
+    match self.mode {
+      GizmoMode::Translate => TransformDelta {
+        translation: direction * (to - from),
+        ..TransformDelta::IDENTITY
+      },
+      GizmoMode::Rotate => {
+        // wrapped, so that crossing from -PI to PI is a small turn rather than almost a full one
+        let angle = (to - from + PI).rem_euclid(TAU) - PI;
+        TransformDelta {
+          rotation: Quat::from_axis_angle(direction, angle),
+          ..TransformDelta::IDENTITY
+        }
+      }
+      GizmoMode::Scale => {
+        let mut scale = Vec3::ONE;
+        if from.abs() > f32::EPSILON {
+          scale[axis.index()] = (to / from).max(f32::EPSILON);
+        }
+        TransformDelta {
+          scale,
+          ..TransformDelta::IDENTITY
+        }
+      }
+    }
+  }
+}
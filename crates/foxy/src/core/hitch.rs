@@ -0,0 +1,135 @@
+use std::{cmp::Reverse, time::Duration};
+
+use tracing::warn;
+
+use super::{profiling::ScopeTiming, timings::FrameTimings};
+
+/// A frame that went over the [`HitchDetector`]'s budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hitch {
+  /// The number of the frame, as in [`FrameTimings::frame`].
+  pub frame: u64,
+  /// How long the game loop's stages took in the frame.
+  pub time: Duration,
+  /// The budget the frame went over.
+  pub threshold: Duration,
+  /// The stage that took the longest, by name as in [`FrameTimings::stages`].
+  pub slowest_stage: (&'static str, Duration),
+  /// The [`foxy_profile_scope!`](crate::foxy_profile_scope)s that took the longest in the frame, slowest first.
+  pub slowest_scopes: Vec<ScopeTiming>,
+}
+
+/// Flags frames whose game loop stages take longer than a budget, e.g. to find the stutters of loading or garbage
+/// piling up.
+///
+/// Each hitch is logged as a warning with the slowest stage and profiling scopes of the frame, and delivered to the app
+/// as a [`FoxyEvent::Hitch`](super::event::FoxyEvent::Hitch) on the next frame that has no other event, so games can
+/// tie hitches to what was happening in the game at the time.
+///
+/// Only the game thread's own work counts, not waiting for the next event or for the render thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitchDetector {
+  enabled: bool,
+  threshold: Duration,
+  /// How many of the slowest scopes each hitch lists.
+  scope_count: usize,
+}
+
+impl Default for HitchDetector {
+  fn default() -> Self {
+    Self {
+      enabled: true,
+      threshold: Self::DEFAULT_THRESHOLD,
+      scope_count: 5,
+    }
+  }
+}
+
+impl HitchDetector {
+  /// Three frames at 60 Hz, which players notice as a stutter.
+  pub const DEFAULT_THRESHOLD: Duration = Duration::from_millis(50);
+
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+
+  /// The longest a frame can take before it's a hitch, e.g. the frame time of the target frame rate.
+  pub fn with_threshold(mut self, threshold: Duration) -> Self {
+    self.threshold = threshold;
+    self
+  }
+
+  /// How many of the slowest profiling scopes each hitch lists.
+  pub fn with_scope_count(mut self, scope_count: usize) -> Self {
+    self.scope_count = scope_count;
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+
+  pub fn set_enabled(&mut self, enabled: bool) {
+    self.enabled = enabled;
+  }
+
+  pub fn threshold(&self) -> Duration {
+    self.threshold
+  }
+
+  pub fn set_threshold(&mut self, threshold: Duration) {
+    self.threshold = threshold;
+  }
+
+  /// The hitch `timings` are from, if the frame went over the budget, after logging it.
+  pub(crate) fn check(&self, timings: &FrameTimings) -> Option<Hitch> {
+    let time = timings.total();
+    if !self.enabled || time <= self.threshold {
+      return None;
+    }
+
+    let slowest_stage = timings
+      .stages()
+      .into_iter()
+      .max_by_key(|(_, duration)| *duration)
+      .unwrap_or(("none", Duration::ZERO));
+    let mut slowest_scopes = timings.scopes.clone();
+    slowest_scopes.sort_by_key(|scope| Reverse(scope.time));
+    slowest_scopes.truncate(self.scope_count);
+
+    let hitch = Hitch {
+      frame: timings.frame,
+      time,
+      threshold: self.threshold,
+      slowest_stage,
+      slowest_scopes,
+    };
+    Self::log(&hitch);
+    Some(hitch)
+  }
+
+  fn log(hitch: &Hitch) {
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let scopes = hitch
+      .slowest_scopes
+      .iter()
+      .map(|scope| format!("{} {:.2} ms", scope.name, millis(scope.time)))
+      .collect::<Vec<_>>()
+      .join(", ");
+    warn!(
+      "Hitch on frame {}: {:.2} ms over a {:.2} ms budget, slowest stage {} at {:.2} ms{}{}",
+      hitch.frame,
+      millis(hitch.time),
+      millis(hitch.threshold),
+      hitch.slowest_stage.0,
+      millis(hitch.slowest_stage.1),
+      if scopes.is_empty() { "" } else { ", slowest scopes: " },
+      scopes,
+    );
+  }
+}
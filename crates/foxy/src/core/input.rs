@@ -1,27 +1,60 @@
+pub mod action;
+pub mod backend;
+pub mod gamepad;
 pub mod key;
 pub mod modifier;
 pub mod mouse;
+pub mod prompt;
+pub mod recording;
 pub mod state;
+pub mod touch;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use glam::Vec2;
 use strum::IntoEnumIterator;
 use winit::{
-  event::{ElementState, MouseButton},
-  keyboard::PhysicalKey,
+  dpi::PhysicalPosition,
+  event::{ElementState, MouseButton, MouseScrollDelta},
+  keyboard::{Key, PhysicalKey},
 };
 
-use self::state::KeyState;
+use self::{
+  action::{ActionMap, Binding},
+  gamepad::{GamepadButton, GamepadId, InputDevice, RumbleEffect, RumbleMixer},
+  key::LogicalKey,
+  prompt::{Prompt, PromptDatabase},
+  state::KeyState,
+  touch::{ActiveTouch, Pinch, Touch, TouchPhase},
+};
 use crate::core::input::{key::KeyCode, mouse::MouseCode, state::ButtonState};
 
+/// The state of the keyboard, mouse, gamepads and touches. The `just_pressed` and `just_released` queries cover the
+/// current frame, except during `fixed_update`, where they cover the time since the previous fixed tick.
 #[derive(Debug)]
 pub struct Input {
   mouse_buttons: HashMap<MouseCode, ButtonState>,
+  cursor_position: Option<PhysicalPosition<f64>>,
+  cursor_delta: Vec2,
+  scroll: Vec2,
+  just_pressed: HashSet<Binding>,
+  just_released: HashSet<Binding>,
+  /// Presses and releases since the last fixed tick, which can be several frames ago.
+  fixed_just_pressed: HashSet<Binding>,
+  fixed_just_released: HashSet<Binding>,
+  in_fixed_tick: bool,
   keys: HashMap<KeyCode, KeyState>,
+  key_names: HashMap<KeyCode, String>,
+  actions: ActionMap,
+  prompts: PromptDatabase,
+  active_device: InputDevice,
+  gamepads: HashMap<GamepadId, ConnectedGamepad>,
+  rumble: HashMap<GamepadId, RumbleMixer>,
   shift: ButtonState,
   ctrl: ButtonState,
   alt: ButtonState,
   win: ButtonState,
+  touches: Vec<ActiveTouch>,
 }
 
 impl Input {
@@ -44,11 +77,77 @@ impl Input {
 
     Self {
       mouse_buttons,
+      cursor_position: None,
+      cursor_delta: Vec2::ZERO,
+      scroll: Vec2::ZERO,
+      just_pressed: HashSet::new(),
+      just_released: HashSet::new(),
+      fixed_just_pressed: HashSet::new(),
+      fixed_just_released: HashSet::new(),
+      in_fixed_tick: false,
       keys,
+      key_names: HashMap::new(),
+      actions: ActionMap::default(),
+      prompts: PromptDatabase::default(),
+      active_device: InputDevice::default(),
+      gamepads: HashMap::new(),
+      rumble: HashMap::new(),
       shift: Default::default(),
       ctrl: Default::default(),
       alt: Default::default(),
       win: Default::default(),
+      touches: Vec::new(),
+    }
+  }
+
+  /// Forgets what happened during the frame: presses, releases, cursor movement and scrolling. Called once the fixed
+  /// ticks and the update have seen them.
+  pub(crate) fn end_frame(&mut self) {
+    self.cursor_delta = Vec2::ZERO;
+    self.scroll = Vec2::ZERO;
+    self.just_pressed.clear();
+    self.just_released.clear();
+  }
+
+  /// Makes presses and releases refer to the time since the last fixed tick, so that a tick sees every press even when
+  /// no tick was due in the frame it happened.
+  pub(crate) fn begin_fixed_tick(&mut self) {
+    self.in_fixed_tick = true;
+  }
+
+  pub(crate) fn end_fixed_tick(&mut self) {
+    self.in_fixed_tick = false;
+    self.fixed_just_pressed.clear();
+    self.fixed_just_released.clear();
+  }
+
+  fn presses(&self) -> &HashSet<Binding> {
+    if self.in_fixed_tick {
+      &self.fixed_just_pressed
+    } else {
+      &self.just_pressed
+    }
+  }
+
+  fn releases(&self) -> &HashSet<Binding> {
+    if self.in_fixed_tick {
+      &self.fixed_just_released
+    } else {
+      &self.just_released
+    }
+  }
+
+  fn record_transition(&mut self, binding: Binding, was_down: bool, is_down: bool) {
+    match (was_down, is_down) {
+      (false, true) => {
+        self.just_pressed.insert(binding);
+        self.fixed_just_pressed.insert(binding);
+      }
+      (true, false) => {
+        self.just_released.insert(binding);
+        self.fixed_just_released.insert(binding);
+      }
+      _ => (),
     }
   }
 
@@ -58,9 +157,43 @@ impl Input {
     self.keys.get(&code).copied().unwrap_or(KeyState::Released)
   }
 
-  pub(crate) fn update_key_state(&mut self, keycode: PhysicalKey, state: ElementState, repeat: bool) {
-    if let Some(key_state) = self.keys.get_mut(&keycode.into()) {
+  /// Whether the key at `code` went down this frame. Key repeats don't count.
+  pub fn key_just_pressed(&self, code: KeyCode) -> bool {
+    self.presses().contains(&Binding::Key(code))
+  }
+
+  /// Whether the key at `code` came up this frame.
+  pub fn key_just_released(&self, code: KeyCode) -> bool {
+    self.releases().contains(&Binding::Key(code))
+  }
+
+  /// What the key at `code` is labeled in the player's keyboard layout, for showing in prompts. Layouts can't be
+  /// queried up front, so character keys fall back to their US label until they've been pressed once.
+  pub fn key_name(&self, code: KeyCode) -> String {
+    self.key_names.get(&code).cloned().unwrap_or_else(|| code.default_name())
+  }
+
+  pub(crate) fn update_key_state(
+    &mut self,
+    keycode: PhysicalKey,
+    logical_key: &LogicalKey,
+    state: ElementState,
+    repeat: bool,
+  ) {
+    let code = KeyCode::from(keycode);
+    self.active_device = InputDevice::KeyboardMouse;
+    if let Some(key_state) = self.keys.get_mut(&code) {
+      let was_held = key_state.is_held();
       *key_state = KeyState::from_winit(state, repeat);
+      let is_held = key_state.is_held();
+      self.record_transition(Binding::Key(code), was_held, is_held);
+    }
+
+    // shifted characters would name the key after its second symbol
+    if let (Key::Character(text), false) = (logical_key, self.shift.is_pressed()) {
+      if !text.trim().is_empty() {
+        self.key_names.insert(code, text.to_uppercase());
+      }
     }
   }
 
@@ -70,9 +203,268 @@ impl Input {
     self.mouse_buttons.get(&code).copied().unwrap_or(ButtonState::Released)
   }
 
+  /// Whether the mouse button at `code` went down this frame.
+  pub fn mouse_just_pressed(&self, code: MouseCode) -> bool {
+    self.presses().contains(&Binding::Mouse(code))
+  }
+
+  /// Whether the mouse button at `code` came up this frame.
+  pub fn mouse_just_released(&self, code: MouseCode) -> bool {
+    self.releases().contains(&Binding::Mouse(code))
+  }
+
+  /// Where the cursor is in the window, or `None` while it's outside of it.
+  pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+    self.cursor_position
+  }
+
+  /// How far the cursor moved this frame, in physical pixels. Moving into the window doesn't count as movement.
+  pub fn cursor_delta(&self) -> Vec2 {
+    self.cursor_delta
+  }
+
+  /// How far the wheel scrolled this frame, in lines. Positive is up and to the right.
+  pub fn scroll(&self) -> Vec2 {
+    self.scroll
+  }
+
+  pub(crate) fn update_cursor_position(&mut self, position: Option<PhysicalPosition<f64>>) {
+    if let (Some(previous), Some(position)) = (self.cursor_position, position) {
+      self.cursor_delta += Vec2::new((position.x - previous.x) as f32, (position.y - previous.y) as f32);
+    }
+    self.cursor_position = position;
+  }
+
   pub(crate) fn update_mouse_button_state(&mut self, button: MouseButton, state: ElementState) {
-    if let Some(mouse_state) = self.mouse_buttons.get_mut(&button.into()) {
+    let code = MouseCode::from(button);
+    self.active_device = InputDevice::KeyboardMouse;
+    if let Some(mouse_state) = self.mouse_buttons.get_mut(&code) {
+      let was_pressed = mouse_state.is_pressed();
       *mouse_state = ButtonState::from_winit(state);
+      let is_pressed = mouse_state.is_pressed();
+      self.record_transition(Binding::Mouse(code), was_pressed, is_pressed);
+    }
+  }
+
+  pub(crate) fn update_scroll(&mut self, delta: MouseScrollDelta) {
+    // touchpads scroll by pixels, so they're scaled to roughly match a wheel notch
+    const PIXELS_PER_LINE: f64 = 20.0;
+
+    self.active_device = InputDevice::KeyboardMouse;
+    self.scroll += match delta {
+      MouseScrollDelta::LineDelta(x, y) => Vec2::new(x, y),
+      MouseScrollDelta::PixelDelta(delta) => {
+        Vec2::new((delta.x / PIXELS_PER_LINE) as f32, (delta.y / PIXELS_PER_LINE) as f32)
+      }
+    };
+  }
+
+  // GAMEPADS
+
+  /// The gamepads that are connected, in no particular order.
+  pub fn gamepads(&self) -> impl Iterator<Item = GamepadId> + '_ {
+    self.gamepads.keys().copied()
+  }
+
+  /// Whether `button` is held on `pad`.
+  pub fn gamepad_button(&self, pad: GamepadId, button: GamepadButton) -> ButtonState {
+    match self.gamepads.get(&pad) {
+      Some(gamepad) if gamepad.buttons.contains(&button) => ButtonState::Pressed,
+      _ => ButtonState::Released,
+    }
+  }
+
+  /// Whether `button` is held on any gamepad, which is what gamepad bindings check.
+  pub fn gamepad(&self, button: GamepadButton) -> ButtonState {
+    if self.gamepads.values().any(|gamepad| gamepad.buttons.contains(&button)) {
+      ButtonState::Pressed
+    } else {
+      ButtonState::Released
+    }
+  }
+
+  /// Whether `button` went down this frame on any gamepad.
+  pub fn gamepad_just_pressed(&self, button: GamepadButton) -> bool {
+    self.presses().contains(&Binding::Gamepad(button))
+  }
+
+  /// Whether `button` came up this frame, and isn't held on any other gamepad.
+  pub fn gamepad_just_released(&self, button: GamepadButton) -> bool {
+    self.releases().contains(&Binding::Gamepad(button))
+  }
+
+  pub(crate) fn connect_gamepad(&mut self, pad: GamepadId, device: InputDevice) {
+    self.gamepads.insert(pad, ConnectedGamepad {
+      device,
+      buttons: HashSet::new(),
+    });
+  }
+
+  /// Releases every button still held on `pad`.
+  pub(crate) fn disconnect_gamepad(&mut self, pad: GamepadId) {
+    let buttons = self.gamepads.get(&pad).map(|gamepad| gamepad.buttons.clone()).unwrap_or_default();
+    for button in buttons {
+      self.update_gamepad_button_state(pad, button, ButtonState::Released);
+    }
+    self.gamepads.remove(&pad);
+    self.rumble.remove(&pad);
+  }
+
+  pub(crate) fn update_gamepad_button_state(&mut self, pad: GamepadId, button: GamepadButton, state: ButtonState) {
+    let was_pressed = self.gamepad(button).is_pressed();
+    let Some(gamepad) = self.gamepads.get_mut(&pad) else {
+      return;
+    };
+    if state.is_pressed() {
+      gamepad.buttons.insert(button);
+      self.active_device = gamepad.device;
+    } else {
+      gamepad.buttons.remove(&button);
+    }
+    let is_pressed = self.gamepad(button).is_pressed();
+    self.record_transition(Binding::Gamepad(button), was_pressed, is_pressed);
+  }
+
+  // ACTIONS
+
+  pub fn actions(&self) -> &ActionMap {
+    &self.actions
+  }
+
+  pub fn set_actions(&mut self, actions: ActionMap) {
+    self.actions = actions;
+  }
+
+  /// Whether any input bound to `action` is held down.
+  pub fn action(&self, action: &str) -> bool {
+    self.actions.bindings(action).iter().any(|binding| match *binding {
+      Binding::Key(code) => self.key(code).is_held(),
+      Binding::Mouse(code) => self.mouse(code).is_pressed(),
+      Binding::Gamepad(button) => self.gamepad(button).is_pressed(),
+    })
+  }
+
+  /// Whether an input bound to `action` went down this frame.
+  pub fn action_just_pressed(&self, action: &str) -> bool {
+    self
+      .actions
+      .bindings(action)
+      .iter()
+      .any(|binding| self.presses().contains(binding))
+  }
+
+  /// Whether `binding` went down this frame, whatever device it's on.
+  pub fn binding_just_pressed(&self, binding: Binding) -> bool {
+    self.presses().contains(&binding)
+  }
+
+  /// Whether an input bound to `action` came up this frame.
+  pub fn action_just_released(&self, action: &str) -> bool {
+    self
+      .actions
+      .bindings(action)
+      .iter()
+      .any(|binding| self.releases().contains(binding))
+  }
+
+  /// A display name for `binding`, following the player's keyboard layout.
+  pub fn binding_name(&self, binding: Binding) -> String {
+    match binding {
+      Binding::Key(code) => self.key_name(code),
+      Binding::Mouse(code) => format!("Mouse {code:?}"),
+      Binding::Gamepad(button) => format!("{button:?}"),
+    }
+  }
+
+  /// The device the player last used. Switches back to keyboard and mouse whenever either is used.
+  pub fn active_device(&self) -> InputDevice {
+    self.active_device
+  }
+
+  /// Switches prompts to `device`, e.g. when the player picks up a controller.
+  pub fn set_active_device(&mut self, device: InputDevice) {
+    self.active_device = device;
+  }
+
+  pub fn prompts(&self) -> &PromptDatabase {
+    &self.prompts
+  }
+
+  pub fn set_prompts(&mut self, prompts: PromptDatabase) {
+    self.prompts = prompts;
+  }
+
+  /// The prompt for the first binding of `action` on the active device, for button prompts in the UI. Query it
+  /// each frame and it follows the player switching devices.
+  pub fn action_prompt(&self, action: &str) -> Option<Prompt> {
+    let binding = *self
+      .actions
+      .bindings(action)
+      .iter()
+      .find(|binding| binding.is_on(self.active_device))?;
+
+    self
+      .prompts
+      .prompt(self.active_device, binding)
+      .or_else(|| Some(Prompt::new(self.binding_name(binding))))
+  }
+
+  // RUMBLE
+
+  /// Starts playing `effect` on `pad`, combined with the effects already playing there by its stacking rule.
+  pub fn rumble(&mut self, pad: GamepadId, effect: RumbleEffect) {
+    self.rumble.entry(pad).or_default().play(effect);
+  }
+
+  pub fn stop_rumble(&mut self, pad: GamepadId) {
+    self.rumble.remove(&pad);
+  }
+
+  /// The strengths the low and high frequency motors of `pad` run at, from 0 to 1. They're sent to the gamepad after
+  /// each update, if it supports force feedback.
+  pub fn rumble_strength(&self, pad: GamepadId) -> (f32, f32) {
+    self.rumble.get(&pad).map_or((0.0, 0.0), RumbleMixer::strength)
+  }
+
+  pub(crate) fn advance_rumble(&mut self, delta_secs: f32) {
+    for mixer in self.rumble.values_mut() {
+      mixer.advance(delta_secs);
+    }
+    self.rumble.retain(|_, mixer| !mixer.is_empty());
+  }
+
+  // TOUCH
+
+  /// The touches that are currently down, in the order they started.
+  pub fn touches(&self) -> &[ActiveTouch] {
+    &self.touches
+  }
+
+  pub fn touch(&self, id: u64) -> Option<&ActiveTouch> {
+    self.touches.iter().find(|active| active.touch.id == id)
+  }
+
+  /// The pinch between the first two touches that are down, if there are at least two.
+  pub fn pinch(&self) -> Option<Pinch> {
+    match self.touches.as_slice() {
+      [a, b, ..] => Pinch::between(a, b),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn update_touch(&mut self, touch: winit::event::Touch) {
+    let touch = Touch::from(touch);
+    match touch.phase {
+      TouchPhase::Started => self.touches.push(ActiveTouch {
+        touch,
+        start_position: touch.position,
+      }),
+      TouchPhase::Moved => {
+        if let Some(active) = self.touches.iter_mut().find(|active| active.touch.id == touch.id) {
+          active.touch = touch;
+        }
+      }
+      TouchPhase::Ended | TouchPhase::Cancelled => self.touches.retain(|active| active.touch.id != touch.id),
     }
   }
 
@@ -121,6 +513,13 @@ impl Input {
   }
 }
 
+/// The buttons held on a gamepad, and the kind of gamepad it is for prompts.
+#[derive(Debug)]
+struct ConnectedGamepad {
+  device: InputDevice,
+  buttons: HashSet<GamepadButton>,
+}
+
 impl Default for Input {
   fn default() -> Self {
     Self::new()
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use super::{
+  gamepad::{GamepadButton, InputDevice},
+  key::KeyCode,
+  mouse::MouseCode,
+};
+
+/// An input an action can be bound to. Keys are bound by their physical position, so bindings stay in place when
+/// the player switches keyboard layouts.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Binding {
+  Key(KeyCode),
+  Mouse(MouseCode),
+  Gamepad(GamepadButton),
+}
+
+impl Binding {
+  /// Whether the binding can be pressed on `device`.
+  pub fn is_on(self, device: InputDevice) -> bool {
+    matches!(self, Binding::Gamepad(_)) == device.is_gamepad()
+  }
+}
+
+/// Named actions, such as "jump" or "pause", and the inputs bound to them.
+#[derive(Debug, Default, Clone)]
+pub struct ActionMap {
+  bindings: HashMap<String, Vec<Binding>>,
+}
+
+impl ActionMap {
+  pub fn with_binding(mut self, action: impl Into<String>, binding: Binding) -> Self {
+    self.bind(action, binding);
+    self
+  }
+
+  /// Adds `binding` to the inputs that trigger `action`.
+  pub fn bind(&mut self, action: impl Into<String>, binding: Binding) {
+    let bindings = self.bindings.entry(action.into()).or_default();
+    if !bindings.contains(&binding) {
+      bindings.push(binding);
+    }
+  }
+
+  pub fn unbind(&mut self, action: &str) {
+    self.bindings.remove(action);
+  }
+
+  /// The inputs bound to `action`, in the order they were bound.
+  pub fn bindings(&self, action: &str) -> &[Binding] {
+    self.bindings.get(action).map_or(&[], Vec::as_slice)
+  }
+
+  pub fn actions(&self) -> impl Iterator<Item = &str> {
+    self.bindings.keys().map(String::as_str)
+  }
+}
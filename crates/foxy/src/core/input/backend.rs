@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use gilrs::{
+  ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Replay, Ticks},
+  Button,
+  EventType,
+  Gilrs,
+};
+use tracing::*;
+
+use super::{
+  gamepad::{GamepadButton, GamepadId, InputDevice},
+  state::ButtonState,
+  Input,
+};
+
+/// Feeds gamepads into [`Input`] through gilrs, and plays the rumble mixed by [`Input`] on them. Gilrs can't be shared
+/// between threads, so the backend is owned by the game loop rather than the engine state.
+pub(crate) struct GamepadBackend {
+  gilrs: Option<Gilrs>,
+  /// The motor strengths last sent to each gamepad, and the effect playing them.
+  rumble: HashMap<gilrs::GamepadId, ((f32, f32), Effect)>,
+}
+
+impl GamepadBackend {
+  const SONY_VENDOR_ID: u16 = 0x054c;
+  /// Changes in motor strength smaller than this aren't sent, since the effect is recreated for every change.
+  const RUMBLE_THRESHOLD: f32 = 0.01;
+
+  /// Starts the backend and connects the gamepads that are already plugged in.
+  pub fn new(input: &mut Input) -> Self {
+    let gilrs = match Gilrs::new() {
+      Ok(gilrs) => Some(gilrs),
+      Err(gilrs::Error::NotImplemented(gilrs)) => {
+        warn!("gamepads aren't supported on this platform");
+        Some(gilrs)
+      }
+      Err(error) => {
+        error!("failed to start the gamepad backend, gamepads won't work: {error}");
+        None
+      }
+    };
+
+    if let Some(gilrs) = &gilrs {
+      for (id, _) in gilrs.gamepads() {
+        input.connect_gamepad(GamepadId(id.into()), Self::device(gilrs, id));
+      }
+    }
+
+    Self {
+      gilrs,
+      rumble: HashMap::new(),
+    }
+  }
+
+  /// Applies every gamepad event since the last call.
+  pub fn poll(&mut self, input: &mut Input) {
+    let Some(gilrs) = &mut self.gilrs else {
+      return;
+    };
+
+    while let Some(event) = gilrs.next_event() {
+      let pad = GamepadId(event.id.into());
+      match event.event {
+        EventType::Connected => input.connect_gamepad(pad, Self::device(gilrs, event.id)),
+        EventType::Disconnected => input.disconnect_gamepad(pad),
+        EventType::ButtonPressed(button, _) => {
+          if let Some(button) = Self::button(button) {
+            input.update_gamepad_button_state(pad, button, ButtonState::Pressed);
+          }
+        }
+        EventType::ButtonReleased(button, _) => {
+          if let Some(button) = Self::button(button) {
+            input.update_gamepad_button_state(pad, button, ButtonState::Released);
+          }
+        }
+        _ => (),
+      }
+    }
+  }
+
+  /// Sends the rumble strengths of every gamepad to its motors. Gamepads without force feedback are skipped.
+  pub fn apply_rumble(&mut self, input: &Input) {
+    let Some(gilrs) = &mut self.gilrs else {
+      return;
+    };
+
+    let pads: Vec<_> = gilrs
+      .gamepads()
+      .filter(|(_, gamepad)| gamepad.is_ff_supported())
+      .map(|(id, _)| id)
+      .collect();
+    self.rumble.retain(|id, _| pads.contains(id));
+
+    for id in pads {
+      let strength = input.rumble_strength(GamepadId(id.into()));
+      let sent = self.rumble.get(&id).map_or((0.0, 0.0), |(sent, _)| *sent);
+      let is_unchanged =
+        (strength.0 - sent.0).abs() < Self::RUMBLE_THRESHOLD && (strength.1 - sent.1).abs() < Self::RUMBLE_THRESHOLD;
+      if is_unchanged {
+        continue;
+      }
+
+      // dropping the previous effect stops it
+      self.rumble.remove(&id);
+      if strength == (0.0, 0.0) {
+        continue;
+      }
+      match Self::rumble_effect(gilrs, id, strength) {
+        Ok(effect) => {
+          self.rumble.insert(id, (strength, effect));
+        }
+        Err(error) => error!("failed to rumble gamepad {id}: {error}"),
+      }
+    }
+  }
+
+  /// An effect running the low and high frequency motors at a constant strength until it's dropped.
+  fn rumble_effect(
+    gilrs: &mut Gilrs,
+    id: gilrs::GamepadId,
+    (low, high): (f32, f32),
+  ) -> Result<Effect, gilrs::ff::Error> {
+    let magnitude = |strength: f32| (strength * u16::MAX as f32) as u16;
+    let scheduling = Replay {
+      play_for: Ticks::from_ms(1000),
+      ..Default::default()
+    };
+
+    let effect = EffectBuilder::new()
+      .add_effect(BaseEffect {
+        kind: BaseEffectType::Strong {
+          magnitude: magnitude(low),
+        },
+        scheduling,
+        ..Default::default()
+      })
+      .add_effect(BaseEffect {
+        kind: BaseEffectType::Weak {
+          magnitude: magnitude(high),
+        },
+        scheduling,
+        ..Default::default()
+      })
+      .gamepads(&[id])
+      .finish(gilrs)?;
+    effect.play()?;
+    Ok(effect)
+  }
+
+  fn device(gilrs: &Gilrs, id: gilrs::GamepadId) -> InputDevice {
+    match gilrs.gamepad(id).vendor_id() {
+      Some(Self::SONY_VENDOR_ID) => InputDevice::DualShock,
+      _ => InputDevice::Xbox,
+    }
+  }
+
+  /// Gilrs names the shoulder buttons triggers, and the triggers second triggers.
+  fn button(button: Button) -> Option<GamepadButton> {
+    let button = match button {
+      Button::South => GamepadButton::South,
+      Button::East => GamepadButton::East,
+      Button::West => GamepadButton::West,
+      Button::North => GamepadButton::North,
+      Button::LeftTrigger => GamepadButton::LeftShoulder,
+      Button::RightTrigger => GamepadButton::RightShoulder,
+      Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+      Button::RightTrigger2 => GamepadButton::RightTrigger,
+      Button::LeftThumb => GamepadButton::LeftStick,
+      Button::RightThumb => GamepadButton::RightStick,
+      Button::DPadUp => GamepadButton::DPadUp,
+      Button::DPadDown => GamepadButton::DPadDown,
+      Button::DPadLeft => GamepadButton::DPadLeft,
+      Button::DPadRight => GamepadButton::DPadRight,
+      Button::Start => GamepadButton::Start,
+      Button::Select => GamepadButton::Select,
+      Button::C | Button::Z | Button::Mode | Button::Unknown => return None,
+    };
+    Some(button)
+  }
+}
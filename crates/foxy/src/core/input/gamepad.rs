@@ -0,0 +1,180 @@
+use strum::EnumIter;
+
+/// Gamepad buttons by position, named after the Xbox layout's positions rather than labels, so bindings work the
+/// same on every controller.
+#[derive(EnumIter, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GamepadButton {
+  /// A on Xbox, Cross on DualShock.
+  South,
+  /// B on Xbox, Circle on DualShock.
+  East,
+  /// X on Xbox, Square on DualShock.
+  West,
+  /// Y on Xbox, Triangle on DualShock.
+  North,
+  LeftShoulder,
+  RightShoulder,
+  LeftTrigger,
+  RightTrigger,
+  LeftStick,
+  RightStick,
+  DPadUp,
+  DPadDown,
+  DPadLeft,
+  DPadRight,
+  Start,
+  Select,
+}
+
+/// The kind of device the player is using, for showing prompts that match it.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InputDevice {
+  #[default]
+  KeyboardMouse,
+  Xbox,
+  DualShock,
+}
+
+impl InputDevice {
+  pub fn is_gamepad(self) -> bool {
+    self != InputDevice::KeyboardMouse
+  }
+}
+
+/// Identifies a connected gamepad.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct GamepadId(pub usize);
+
+/// The strength of one rumble motor over time: ramping up over `attack`, holding for `hold`, then fading out over
+/// `release`, all in seconds.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct RumbleEnvelope {
+  /// From 0 to 1.
+  pub strength: f32,
+  pub attack: f32,
+  pub hold: f32,
+  pub release: f32,
+}
+
+impl RumbleEnvelope {
+  pub fn new(strength: f32, hold: f32) -> Self {
+    Self {
+      strength,
+      hold,
+      ..Default::default()
+    }
+  }
+
+  pub fn with_attack(mut self, attack: f32) -> Self {
+    self.attack = attack;
+    self
+  }
+
+  pub fn with_release(mut self, release: f32) -> Self {
+    self.release = release;
+    self
+  }
+
+  pub fn duration(&self) -> f32 {
+    self.attack + self.hold + self.release
+  }
+
+  /// The strength `time` seconds into the envelope.
+  pub fn sample(&self, time: f32) -> f32 {
+    let scale = if time < 0.0 || time >= self.duration() {
+      0.0
+    } else if time < self.attack {
+      time / self.attack
+    } else if time < self.attack + self.hold {
+      1.0
+    } else {
+      1.0 - (time - self.attack - self.hold) / self.release
+    };
+
+    self.strength * scale
+  }
+}
+
+/// How a rumble effect combines with the effects already playing on the same gamepad.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RumbleStacking {
+  /// Adds to the other additive effects, e.g. for many small impacts building up.
+  #[default]
+  Add,
+  /// Only takes over while it's stronger than everything else, e.g. for a steady engine hum under impacts.
+  Max,
+  /// Stops every other effect on the gamepad, e.g. for a death or cutscene.
+  Replace,
+}
+
+/// A rumble effect, with separate envelopes for the low frequency (heavy) and high frequency (light) motors.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct RumbleEffect {
+  pub low_frequency: RumbleEnvelope,
+  pub high_frequency: RumbleEnvelope,
+  pub stacking: RumbleStacking,
+}
+
+impl RumbleEffect {
+  pub fn new(low_frequency: RumbleEnvelope, high_frequency: RumbleEnvelope) -> Self {
+    Self {
+      low_frequency,
+      high_frequency,
+      stacking: RumbleStacking::default(),
+    }
+  }
+
+  pub fn with_stacking(mut self, stacking: RumbleStacking) -> Self {
+    self.stacking = stacking;
+    self
+  }
+
+  pub fn duration(&self) -> f32 {
+    self.low_frequency.duration().max(self.high_frequency.duration())
+  }
+}
+
+/// The rumble effects playing on one gamepad.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct RumbleMixer {
+  effects: Vec<(RumbleEffect, f32)>,
+}
+
+impl RumbleMixer {
+  pub fn play(&mut self, effect: RumbleEffect) {
+    if effect.stacking == RumbleStacking::Replace {
+      self.effects.clear();
+    }
+    self.effects.push((effect, 0.0));
+  }
+
+  pub fn advance(&mut self, delta_secs: f32) {
+    for (_, time) in &mut self.effects {
+      *time += delta_secs;
+    }
+    self.effects.retain(|(effect, time)| *time < effect.duration());
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.effects.is_empty()
+  }
+
+  /// The combined strength of the low and high frequency motors.
+  pub fn strength(&self) -> (f32, f32) {
+    let mut added = (0.0, 0.0);
+    let mut strongest = (0.0f32, 0.0f32);
+    for (effect, time) in &self.effects {
+      let low = effect.low_frequency.sample(*time);
+      let high = effect.high_frequency.sample(*time);
+      match effect.stacking {
+        RumbleStacking::Add | RumbleStacking::Replace => added = (added.0 + low, added.1 + high),
+        RumbleStacking::Max => strongest = (strongest.0.max(low), strongest.1.max(high)),
+      }
+    }
+
+    (
+      strongest.0.max(added.0).clamp(0.0, 1.0),
+      strongest.1.max(added.1).clamp(0.0, 1.0),
+    )
+  }
+}
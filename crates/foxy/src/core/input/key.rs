@@ -1,6 +1,9 @@
 use strum::EnumIter;
 use winit::keyboard::{self, PhysicalKey};
 
+/// The key a key press produces in the current keyboard layout, as opposed to its physical position ([`KeyCode`]).
+pub type LogicalKey = keyboard::Key;
+
 // Stolen from winit as a base to start from
 #[derive(EnumIter, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum KeyCode {
@@ -461,6 +464,14 @@ pub enum KeyCode {
   F35,
 }
 
+impl KeyCode {
+  /// A name for the key's physical position, as labeled on a US keyboard. Prefer
+  /// [`Input::key_name`](super::Input::key_name), which follows the player's layout.
+  pub fn default_name(&self) -> String {
+    format!("{self:?}").trim_start_matches('_').to_string()
+  }
+}
+
 impl From<PhysicalKey> for KeyCode {
   fn from(value: PhysicalKey) -> Self {
     let PhysicalKey::Code(value) = value else {
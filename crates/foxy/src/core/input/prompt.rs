@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use super::{
+  action::Binding,
+  gamepad::{GamepadButton, InputDevice},
+};
+
+/// How a binding is shown in UI button prompts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Prompt {
+  pub label: String,
+  /// The name of an image to show instead of the label, if the game provides one.
+  pub icon: Option<String>,
+}
+
+impl Prompt {
+  pub fn new(label: impl Into<String>) -> Self {
+    Self {
+      label: label.into(),
+      icon: None,
+    }
+  }
+
+  pub fn with_icon(mut self, icon: impl Into<String>) -> Self {
+    self.icon = Some(icon.into());
+    self
+  }
+}
+
+/// Prompts for gamepad buttons on each controller layout, with overrides for any binding, e.g. to add icons.
+///
+/// Keys have no entries by default, as their labels come from the player's keyboard layout.
+#[derive(Debug, Default, Clone)]
+pub struct PromptDatabase {
+  overrides: HashMap<(InputDevice, Binding), Prompt>,
+}
+
+impl PromptDatabase {
+  pub fn with_prompt(mut self, device: InputDevice, binding: Binding, prompt: Prompt) -> Self {
+    self.overrides.insert((device, binding), prompt);
+    self
+  }
+
+  /// The prompt for `binding` on `device`, or `None` for keys and mouse buttons without an override.
+  pub fn prompt(&self, device: InputDevice, binding: Binding) -> Option<Prompt> {
+    if let Some(prompt) = self.overrides.get(&(device, binding)) {
+      return Some(prompt.clone());
+    }
+
+    match binding {
+      Binding::Gamepad(button) => Some(Prompt::new(Self::default_label(device, button))),
+      Binding::Key(_) | Binding::Mouse(_) => None,
+    }
+  }
+
+  fn default_label(device: InputDevice, button: GamepadButton) -> &'static str {
+    use GamepadButton::*;
+
+    match (device, button) {
+      (InputDevice::DualShock, South) => "Cross",
+      (InputDevice::DualShock, East) => "Circle",
+      (InputDevice::DualShock, West) => "Square",
+      (InputDevice::DualShock, North) => "Triangle",
+      (InputDevice::DualShock, LeftShoulder) => "L1",
+      (InputDevice::DualShock, RightShoulder) => "R1",
+      (InputDevice::DualShock, LeftTrigger) => "L2",
+      (InputDevice::DualShock, RightTrigger) => "R2",
+      (InputDevice::DualShock, LeftStick) => "L3",
+      (InputDevice::DualShock, RightStick) => "R3",
+      (InputDevice::DualShock, Start) => "Options",
+      (InputDevice::DualShock, Select) => "Share",
+      (_, South) => "A",
+      (_, East) => "B",
+      (_, West) => "X",
+      (_, North) => "Y",
+      (_, LeftShoulder) => "LB",
+      (_, RightShoulder) => "RB",
+      (_, LeftTrigger) => "LT",
+      (_, RightTrigger) => "RT",
+      (_, LeftStick) => "LS",
+      (_, RightStick) => "RS",
+      (_, Start) => "Menu",
+      (_, Select) => "View",
+      (_, DPadUp) => "D-Pad Up",
+      (_, DPadDown) => "D-Pad Down",
+      (_, DPadLeft) => "D-Pad Left",
+      (_, DPadRight) => "D-Pad Right",
+    }
+  }
+}
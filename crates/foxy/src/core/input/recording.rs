@@ -0,0 +1,118 @@
+use std::time::{Duration, Instant};
+
+use winit::event::WindowEvent;
+
+/// Window and input events with the time they arrived at, relative to the start of the recording.
+///
+/// Recordings live in memory, as winit's events can't be rebuilt from a file. They're meant for smoke-testing UI
+/// flows in-process: record a flow once, then replay it against the app after each change.
+#[derive(Debug, Default, Clone)]
+pub struct InputRecording {
+  events: Vec<(Duration, WindowEvent)>,
+}
+
+impl InputRecording {
+  pub fn events(&self) -> &[(Duration, WindowEvent)] {
+    &self.events
+  }
+
+  pub fn len(&self) -> usize {
+    self.events.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.events.is_empty()
+  }
+
+  /// The timestamp of the last event.
+  pub fn duration(&self) -> Duration {
+    self.events.last().map(|(time, _)| *time).unwrap_or_default()
+  }
+}
+
+/// Records the events the app receives until it's finished.
+#[derive(Debug)]
+pub struct InputRecorder {
+  started: Instant,
+  recording: InputRecording,
+}
+
+impl Default for InputRecorder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl InputRecorder {
+  pub fn new() -> Self {
+    Self {
+      started: Instant::now(),
+      recording: InputRecording::default(),
+    }
+  }
+
+  pub fn record(&mut self, event: &WindowEvent) {
+    // redraws are driven by the render loop, not by the user
+    if !matches!(event, WindowEvent::RedrawRequested) {
+      self.recording.events.push((self.started.elapsed(), event.clone()));
+    }
+  }
+
+  pub fn finish(self) -> InputRecording {
+    self.recording
+  }
+}
+
+/// Replays a recording at real time, or faster or slower by a speed factor.
+#[derive(Debug)]
+pub struct InputPlayback {
+  recording: InputRecording,
+  started: Instant,
+  speed: f64,
+  next: usize,
+}
+
+impl InputPlayback {
+  /// Starts playing `recording` now. `speed` must be positive; 2.0 plays twice as fast and `f64::INFINITY` sends
+  /// every event as soon as possible.
+  pub fn new(recording: InputRecording, speed: f64) -> Self {
+    Self {
+      recording,
+      started: Instant::now(),
+      speed,
+      next: 0,
+    }
+  }
+
+  /// The next event whose time has come, if any.
+  pub fn next_due(&mut self) -> Option<WindowEvent> {
+    let (time, event) = self.recording.events.get(self.next)?;
+    let elapsed = self.started.elapsed().as_secs_f64() * self.speed;
+    if time.as_secs_f64() <= elapsed || self.speed == f64::INFINITY {
+      self.next += 1;
+      Some(event.clone())
+    } else {
+      None
+    }
+  }
+
+  pub fn is_finished(&self) -> bool {
+    self.next >= self.recording.events.len()
+  }
+
+  /// Whether live events of this kind are dropped while playing back, so the user can't disturb the replay.
+  pub fn overrides(event: &WindowEvent) -> bool {
+    matches!(
+      event,
+      WindowEvent::KeyboardInput { .. }
+        | WindowEvent::ModifiersChanged(_)
+        | WindowEvent::Ime(_)
+        | WindowEvent::CursorMoved { .. }
+        | WindowEvent::CursorEntered { .. }
+        | WindowEvent::CursorLeft { .. }
+        | WindowEvent::MouseWheel { .. }
+        | WindowEvent::MouseInput { .. }
+        | WindowEvent::Touch(_)
+    )
+  }
+}
@@ -0,0 +1,104 @@
+use winit::{
+  dpi::PhysicalPosition,
+  event::{Force, TouchPhase as WinitTouchPhase},
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TouchPhase {
+  Started,
+  Moved,
+  Ended,
+  /// The system took the touch away, e.g. because it turned into a gesture. Treat it like `Ended`, but don't act
+  /// on it as a tap.
+  Cancelled,
+}
+
+impl From<WinitTouchPhase> for TouchPhase {
+  fn from(value: WinitTouchPhase) -> Self {
+    match value {
+      WinitTouchPhase::Started => TouchPhase::Started,
+      WinitTouchPhase::Moved => TouchPhase::Moved,
+      WinitTouchPhase::Ended => TouchPhase::Ended,
+      WinitTouchPhase::Cancelled => TouchPhase::Cancelled,
+    }
+  }
+}
+
+/// A finger or pen touching the window. Pens report themselves as touches, with pressure and, where the platform
+/// provides it, tilt.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Touch {
+  /// Stays the same from the touch starting until it ends.
+  pub id: u64,
+  pub phase: TouchPhase,
+  pub position: PhysicalPosition<f64>,
+  /// From 0 to 1, if the device reports it.
+  pub pressure: Option<f32>,
+  /// The angle between the pen and the screen in radians, from 0 (flat) to π/2 (upright), if the device reports it.
+  pub altitude: Option<f32>,
+}
+
+impl From<winit::event::Touch> for Touch {
+  fn from(value: winit::event::Touch) -> Self {
+    Self {
+      id: value.id,
+      phase: value.phase.into(),
+      position: value.location,
+      pressure: value.force.map(|force| force.normalized() as f32),
+      altitude: match value.force {
+        Some(Force::Calibrated {
+          altitude_angle: Some(altitude),
+          ..
+        }) => Some(altitude as f32),
+        _ => None,
+      },
+    }
+  }
+}
+
+/// A touch that is currently down, along with where it started.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ActiveTouch {
+  pub touch: Touch,
+  pub start_position: PhysicalPosition<f64>,
+}
+
+/// How two touches moved relative to each other since they started, for zooming and rotating with two fingers.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Pinch {
+  /// The distance between the touches over their starting distance. Above 1 when spreading, below 1 when pinching.
+  pub scale: f64,
+  /// How far the line between the touches turned, in radians, counter-clockwise on screen.
+  pub rotation: f64,
+  /// The point halfway between the touches.
+  pub center: PhysicalPosition<f64>,
+  /// How far the center moved.
+  pub translation: PhysicalPosition<f64>,
+}
+
+impl Pinch {
+  pub(crate) fn between(a: &ActiveTouch, b: &ActiveTouch) -> Option<Self> {
+    let span = |a: PhysicalPosition<f64>, b: PhysicalPosition<f64>| (b.x - a.x, b.y - a.y);
+    let midpoint = |a: PhysicalPosition<f64>, b: PhysicalPosition<f64>| {
+      PhysicalPosition::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0)
+    };
+
+    let start = span(a.start_position, b.start_position);
+    let current = span(a.touch.position, b.touch.position);
+    let start_distance = start.0.hypot(start.1);
+    if start_distance <= f64::EPSILON {
+      return None;
+    }
+
+    let start_center = midpoint(a.start_position, b.start_position);
+    let center = midpoint(a.touch.position, b.touch.position);
+
+    Some(Self {
+      scale: current.0.hypot(current.1) / start_distance,
+      // screen y points down, so flip it to measure counter-clockwise
+      rotation: (-current.1).atan2(current.0) - (-start.1).atan2(start.0),
+      center,
+      translation: PhysicalPosition::new(center.x - start_center.x, center.y - start_center.y),
+    })
+  }
+}
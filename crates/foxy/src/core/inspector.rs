@@ -0,0 +1,296 @@
+use egui::{CollapsingHeader, Context, DragValue, Grid, ScrollArea, SidePanel, Ui};
+use foxy_renderer::renderer::profiler::FrameStats;
+use glam::{EulerRot, Quat, Vec2, Vec3, Vec4};
+
+pub use foxy_derive::Inspect;
+
+use super::{foxy_state::Foxy, timings::FrameTimings};
+
+/// A value the [`Inspector`] can show and edit. Derive it for components with `#[derive(Inspect)]`, or implement it
+/// with [`impl_inspect!`](crate::impl_inspect), to edit them field by field. Fields marked `#[inspect(skip)]` are left
+/// out of the derive.
+pub trait Inspect {
+  /// Draws widgets editing `self`. Returns whether it changed.
+  fn inspect(&mut self, ui: &mut Ui) -> bool;
+}
+
+/// Something the [`Inspector`] lists, such as a game object, made of inspectable components.
+pub trait InspectEntity {
+  fn name(&self) -> String;
+
+  /// Calls `component` with the name and value of each component.
+  fn components(&mut self, component: &mut dyn FnMut(&str, &mut dyn Inspect));
+}
+
+/// Shows `value` in a labeled row of an inspector grid. Returns whether it changed.
+pub fn inspect_field(ui: &mut Ui, label: &str, value: &mut dyn Inspect) -> bool {
+  ui.label(label);
+  let changed = value.inspect(ui);
+  ui.end_row();
+  changed
+}
+
+/// Implements [`Inspect`] for a struct by inspecting the listed fields, e.g.
+/// `impl_inspect!(Transform { position, rotation, scale });`. Useful for types from other crates, which can't derive it.
+#[macro_export]
+macro_rules! impl_inspect {
+  ($type:ty { $($field:ident),* $(,)? }) => {
+    impl $crate::core::inspector::Inspect for $type {
+      fn inspect(&mut self, ui: &mut $crate::egui::Ui) -> bool {
+        let mut changed = false;
+        $crate::egui::Grid::new(stringify!($type)).num_columns(2).show(ui, |ui| {
+          $(changed |= $crate::core::inspector::inspect_field(ui, stringify!($field), &mut self.$field);)*
+        });
+        changed
+      }
+    }
+  };
+}
+
+macro_rules! inspect_number {
+  ($($number:ty),*) => {
+    $(
+      impl Inspect for $number {
+        fn inspect(&mut self, ui: &mut Ui) -> bool {
+          ui.add(DragValue::new(self)).changed()
+        }
+      }
+    )*
+  };
+}
+
+inspect_number!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+impl Inspect for f32 {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    ui.add(DragValue::new(self).speed(0.01)).changed()
+  }
+}
+
+impl Inspect for f64 {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    ui.add(DragValue::new(self).speed(0.01)).changed()
+  }
+}
+
+impl Inspect for bool {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    ui.checkbox(self, "").changed()
+  }
+}
+
+impl Inspect for String {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    ui.text_edit_singleline(self).changed()
+  }
+}
+
+impl Inspect for Vec2 {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    let mut components = self.to_array();
+    let changed = inspect_components(ui, &mut components);
+    *self = Vec2::from_array(components);
+    changed
+  }
+}
+
+impl Inspect for Vec3 {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    let mut components = self.to_array();
+    let changed = inspect_components(ui, &mut components);
+    *self = Vec3::from_array(components);
+    changed
+  }
+}
+
+impl Inspect for Vec4 {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    let mut components = self.to_array();
+    let changed = inspect_components(ui, &mut components);
+    *self = Vec4::from_array(components);
+    changed
+  }
+}
+
+/// Edited as Euler angles in degrees, yaw then pitch then roll.
+impl Inspect for Quat {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    let (yaw, pitch, roll) = self.to_euler(EulerRot::YXZ);
+    let mut degrees = [yaw.to_degrees(), pitch.to_degrees(), roll.to_degrees()];
+    let changed = inspect_components(ui, &mut degrees);
+    if changed {
+      let [yaw, pitch, roll] = degrees.map(f32::to_radians);
+      *self = Quat::from_euler(EulerRot::YXZ, yaw, pitch, roll);
+    }
+    changed
+  }
+}
+
+impl<T: Inspect> Inspect for Option<T> {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    match self {
+      Some(value) => value.inspect(ui),
+      None => {
+        ui.weak("none");
+        false
+      }
+    }
+  }
+}
+
+fn inspect_components(ui: &mut Ui, components: &mut [f32]) -> bool {
+  ui.horizontal(|ui| {
+    components.iter_mut().fold(false, |changed, component| {
+      ui.add(DragValue::new(component).speed(0.01)).changed() | changed
+    })
+  })
+  .inner
+}
+
+/// A debug window listing the app's entities, editing their components, and showing how long each stage of the game
+/// loop took.
+#[derive(Debug, Default)]
+pub struct Inspector {
+  open: bool,
+  selected: Option<usize>,
+  filter: String,
+  toggle_held: bool,
+}
+
+impl Inspector {
+  /// Binding this action in the [`ActionMap`](super::input::action::ActionMap) toggles the inspector.
+  pub const TOGGLE_ACTION: &'static str = "debug_inspector";
+
+  pub fn is_open(&self) -> bool {
+    self.open
+  }
+
+  pub fn set_open(&mut self, open: bool) {
+    self.open = open;
+  }
+
+  pub fn toggle(&mut self) {
+    self.open = !self.open;
+  }
+
+  /// Draws the inspector, if open. Call from the app's `gui` every frame, with every entity that should be listed.
+  pub fn show(&mut self, foxy: &Foxy, egui: &Context, entities: &mut [&mut dyn InspectEntity]) {
+    let toggle_held = foxy.read().input().action(Self::TOGGLE_ACTION);
+    if toggle_held && !self.toggle_held {
+      self.toggle();
+    }
+    self.toggle_held = toggle_held;
+
+    let timings = foxy.read().timings().clone();
+    let frame_stats = foxy.read().frame_stats().clone();
+    let mut open = self.open;
+    egui::Window::new("Inspector")
+      .open(&mut open)
+      .default_size([520.0, 360.0])
+      .show(egui, |ui| {
+        SidePanel::left("inspector_entities")
+          .resizable(true)
+          .show_inside(ui, |ui| self.entity_list(ui, entities));
+
+        Self::timings(ui, &timings);
+        Self::frame_stats(ui, &frame_stats);
+        ui.separator();
+        self.components(ui, entities);
+      });
+    self.open = open;
+  }
+
+  fn entity_list(&mut self, ui: &mut Ui, entities: &mut [&mut dyn InspectEntity]) {
+    ui.text_edit_singleline(&mut self.filter);
+    let filter = self.filter.to_lowercase();
+
+    ScrollArea::vertical().show(ui, |ui| {
+      for (index, entity) in entities.iter().enumerate() {
+        let name = entity.name();
+        if !name.to_lowercase().contains(&filter) {
+          continue;
+        }
+        if ui.selectable_label(self.selected == Some(index), name).clicked() {
+          self.selected = Some(index);
+        }
+      }
+    });
+  }
+
+  fn components(&mut self, ui: &mut Ui, entities: &mut [&mut dyn InspectEntity]) {
+    let Some(entity) = self.selected.and_then(|index| entities.get_mut(index)) else {
+      ui.weak("Select an entity");
+      return;
+    };
+
+    ui.heading(entity.name());
+    ScrollArea::vertical().show(ui, |ui| {
+      entity.components(&mut |name, component| {
+        CollapsingHeader::new(name).default_open(true).show(ui, |ui| {
+          component.inspect(ui);
+        });
+      });
+    });
+  }
+
+  fn timings(ui: &mut Ui, timings: &FrameTimings) {
+    CollapsingHeader::new(format!("Game loop: {:.2} ms", Self::millis(timings.total())))
+      .id_source("inspector_timings")
+      .show(ui, |ui| {
+        Grid::new("inspector_timings_grid").num_columns(2).show(ui, |ui| {
+          for (stage, duration) in timings.stages() {
+            ui.label(stage);
+            ui.label(format!("{:.3} ms", Self::millis(duration)));
+            ui.end_row();
+          }
+          ui.label("fixed ticks");
+          ui.label(timings.fixed_ticks.to_string());
+          ui.end_row();
+          ui.label("draw (render thread)");
+          ui.label(format!("{:.3} ms", Self::millis(timings.draw)));
+          ui.end_row();
+          ui.label("pipelining speedup (estimate)");
+          ui.label(format!("{:.2}x", timings.pipelining_speedup()));
+          ui.end_row();
+        });
+
+        if !timings.scopes.is_empty() {
+          ui.separator();
+          Grid::new("inspector_scopes_grid").num_columns(2).show(ui, |ui| {
+            for scope in &timings.scopes {
+              ui.label(match scope.stage {
+                Some(stage) => format!("{} ({stage:?})", scope.name),
+                None => scope.name.to_string(),
+              });
+              ui.label(format!("{:.3} ms x{}", Self::millis(scope.time), scope.calls));
+              ui.end_row();
+            }
+          });
+        }
+      });
+  }
+
+  fn frame_stats(ui: &mut Ui, frame_stats: &FrameStats) {
+    CollapsingHeader::new(format!("GPU: {:.2} ms", Self::millis(frame_stats.gpu_time())))
+      .id_source("inspector_frame_stats")
+      .show(ui, |ui| {
+        Grid::new("inspector_frame_stats_grid").num_columns(2).show(ui, |ui| {
+          for pass in &frame_stats.passes {
+            ui.label(pass.name);
+            ui.label(format!("{:.3} ms", Self::millis(pass.gpu_time)));
+            ui.end_row();
+          }
+          ui.label("frame time");
+          ui.label(format!("{:.3} ms", Self::millis(frame_stats.frame_time)));
+          ui.end_row();
+          ui.label("draw calls");
+          ui.label(frame_stats.draw_count.to_string());
+          ui.end_row();
+        });
+      });
+  }
+
+  fn millis(duration: std::time::Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+  }
+}
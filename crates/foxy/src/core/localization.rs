@@ -0,0 +1,156 @@
+use std::{collections::HashMap, fmt::Display, path::Path};
+
+use tracing::*;
+
+use super::FoxyResult;
+
+/// Translated strings of one locale.
+///
+/// Catalogs are read from plain text with one `key = value` entry per line. Blank lines and lines starting with `#`
+/// are skipped, and `\n` in a value stands for a line break. Values may contain `{name}` placeholders, which
+/// [`Localization::tr`] fills in from its arguments.
+#[derive(Debug, Default, Clone)]
+pub struct Catalog {
+  entries: HashMap<String, String>,
+}
+
+impl Catalog {
+  pub fn parse(source: &str) -> Self {
+    let entries = source
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+      .filter_map(|line| match line.split_once('=') {
+        Some((key, value)) => Some((key.trim().to_string(), value.trim().replace("\\n", "\n"))),
+        None => {
+          warn!("skipping malformed catalog entry: {line:?}");
+          None
+        }
+      })
+      .collect();
+
+    Self { entries }
+  }
+
+  pub fn load(path: impl AsRef<Path>) -> FoxyResult<Self> {
+    Ok(Self::parse(&std::fs::read_to_string(path)?))
+  }
+
+  pub fn with_entry(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+    self.entries.insert(key.into(), value.into());
+    self
+  }
+
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.entries.get(key).map(String::as_str)
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+}
+
+/// Looks up UI text in the catalog of the current locale, falling back to the fallback locale and then to the key
+/// itself, so missing translations show up without breaking the UI.
+///
+/// Locales can also bring fonts, e.g. for CJK scripts. They are added as fallbacks to the UI's fonts while their
+/// locale is active.
+#[derive(Debug, Clone)]
+pub struct Localization {
+  catalogs: HashMap<String, Catalog>,
+  fonts: HashMap<String, Vec<(String, Vec<u8>)>>,
+  locale: String,
+  fallback_locale: String,
+}
+
+impl Default for Localization {
+  fn default() -> Self {
+    Self::new(Self::DEFAULT_LOCALE)
+  }
+}
+
+impl Localization {
+  pub const DEFAULT_LOCALE: &'static str = "en";
+  pub const CATALOG_EXTENSION: &'static str = "lang";
+
+  /// Creates a localization that starts in and falls back to `locale`.
+  pub fn new(locale: impl Into<String>) -> Self {
+    let locale = locale.into();
+    Self {
+      catalogs: HashMap::new(),
+      fonts: HashMap::new(),
+      fallback_locale: locale.clone(),
+      locale,
+    }
+  }
+
+  pub fn with_fallback_locale(mut self, locale: impl Into<String>) -> Self {
+    self.fallback_locale = locale.into();
+    self
+  }
+
+  pub fn with_catalog(mut self, locale: impl Into<String>, catalog: Catalog) -> Self {
+    self.catalogs.insert(locale.into(), catalog);
+    self
+  }
+
+  /// Adds a font used for the UI while `locale` is active, after the default fonts.
+  pub fn with_font(mut self, locale: impl Into<String>, name: impl Into<String>, data: Vec<u8>) -> Self {
+    self.fonts.entry(locale.into()).or_default().push((name.into(), data));
+    self
+  }
+
+  /// Loads every `<locale>.lang` catalog in `dir`.
+  pub fn with_catalog_dir(mut self, dir: impl AsRef<Path>) -> FoxyResult<Self> {
+    for entry in std::fs::read_dir(dir)? {
+      let path = entry?.path();
+      if path.extension().is_some_and(|extension| extension == Self::CATALOG_EXTENSION) {
+        if let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) {
+          debug!("loading {locale} catalog from {path:?}");
+          self.catalogs.insert(locale.to_string(), Catalog::load(&path)?);
+        }
+      }
+    }
+    Ok(self)
+  }
+
+  pub fn locale(&self) -> &str {
+    &self.locale
+  }
+
+  pub fn fallback_locale(&self) -> &str {
+    &self.fallback_locale
+  }
+
+  pub fn locales(&self) -> impl Iterator<Item = &str> {
+    self.catalogs.keys().map(String::as_str)
+  }
+
+  pub fn set_locale(&mut self, locale: impl Into<String>) {
+    self.locale = locale.into();
+    if !self.catalogs.contains_key(&self.locale) {
+      warn!("no catalog for locale {:?}, falling back to {:?}", self.locale, self.fallback_locale);
+    }
+  }
+
+  /// The fonts of the current locale, in fallback order.
+  pub fn fonts(&self) -> &[(String, Vec<u8>)] {
+    self.fonts.get(&self.locale).map_or(&[], Vec::as_slice)
+  }
+
+  /// Translates `key`, replacing every `{name}` in it with the matching argument.
+  pub fn tr(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let text = [&self.locale, &self.fallback_locale]
+      .into_iter()
+      .find_map(|locale| self.catalogs.get(locale).and_then(|catalog| catalog.get(key)))
+      .unwrap_or(key);
+
+    args.iter().fold(text.to_string(), |text, (name, value)| {
+      text.replace(&format!("{{{name}}}"), &value.to_string())
+    })
+  }
+}
@@ -1,12 +1,23 @@
+use std::{collections::VecDeque, sync::Mutex, time::Duration};
+
 use egui::RawInput;
+use foxy_renderer::renderer::render_data::RenderData;
+use foxy_utils::mailbox::{Mailbox, MessagingError};
 use winit::event::WindowEvent;
 
+use super::deep_link::DeepLink;
+
 #[derive(Debug)]
 pub enum RenderLoopMessage {
   Start,
   MustExit,
   ExitRequested,
   Winit(WindowEvent),
+  DeepLink(DeepLink),
+  /// The window lost its area, e.g. by being minimized. Frames aren't drawn until it's [`Self::Restored`].
+  Minimized,
+  /// The window has an area again after being [`Self::Minimized`].
+  Restored,
   None,
 }
 
@@ -15,3 +26,98 @@ pub enum GameLoopMessage {
   Exit,
   DontExit,
 }
+
+/// Buffers the messages the render thread sends the game loop. Runs of high-frequency window events, e.g. cursor moves
+/// or resizes while dragging the window, are merged into the newest of them, so they cost one frame rather than one
+/// each.
+pub(crate) struct MessageQueue {
+  pending: VecDeque<RenderLoopMessage>,
+  coalesce: bool,
+}
+
+impl MessageQueue {
+  pub fn new(coalesce: bool) -> Self {
+    Self {
+      pending: VecDeque::new(),
+      coalesce,
+    }
+  }
+
+  /// The next message, waiting at most `timeout` for one if none were sent yet, or not at all if `None`. The window
+  /// events merged into it are added to `raw_events`, oldest first and ending with the one returned.
+  pub fn next(
+    &mut self,
+    mailbox: &Mailbox<GameLoopMessage, RenderLoopMessage>,
+    timeout: Option<Duration>,
+    raw_events: &mut Vec<WindowEvent>,
+  ) -> Result<RenderLoopMessage, MessagingError<GameLoopMessage>> {
+    if self.pending.is_empty() {
+      let message = match timeout {
+        Some(timeout) => mailbox.recv_timeout(timeout)?,
+        None => mailbox.try_recv()?,
+      };
+      self.pending.push_back(message);
+    }
+    // only what was already sent can be merged, nothing is waited for
+    while let Ok(message) = mailbox.try_recv() {
+      self.pending.push_back(message);
+    }
+
+    let Some(mut message) = self.pending.pop_front() else {
+      return Ok(RenderLoopMessage::None);
+    };
+    if let RenderLoopMessage::Winit(event) = &message {
+      raw_events.push(event.clone());
+    }
+
+    while self.coalesce {
+      match (&message, self.pending.front()) {
+        (RenderLoopMessage::Winit(event), Some(RenderLoopMessage::Winit(next))) if Self::merges(event, next) => {
+          raw_events.push(next.clone());
+          message = self.pending.pop_front().unwrap_or(message);
+        }
+        _ => break,
+      }
+    }
+
+    Ok(message)
+  }
+
+  /// Whether `next` supersedes `event`. Only events that carry a new state rather than a change are merged, so
+  /// nothing is lost.
+  fn merges(event: &WindowEvent, next: &WindowEvent) -> bool {
+    match (event, next) {
+      (WindowEvent::CursorMoved { device_id, .. }, WindowEvent::CursorMoved { device_id: next, .. }) => {
+        device_id == next
+      }
+      (WindowEvent::Resized(_), WindowEvent::Resized(_)) | (WindowEvent::Moved(_), WindowEvent::Moved(_)) => true,
+      _ => false,
+    }
+  }
+}
+
+/// Hands frames from the game loop to the render thread, double buffered: the render thread draws one frame while the
+/// game loop fills in the next one, so neither waits on the other. A frame the game loop publishes before the last one
+/// was drawn replaces it, taking over its texture updates, since egui only sends each of them once.
+#[derive(Default)]
+pub(crate) struct FrameHandoff {
+  next: Mutex<Option<RenderData>>,
+}
+
+impl FrameHandoff {
+  /// Makes `render_data` the next frame to draw.
+  pub fn publish(&self, mut render_data: RenderData) {
+    let mut next = self.next.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(skipped) = next.take() {
+      let mut textures_delta = skipped.full_output.textures_delta;
+      textures_delta.append(std::mem::take(&mut render_data.full_output.textures_delta));
+      render_data.full_output.textures_delta = textures_delta;
+    }
+    *next = Some(render_data);
+  }
+
+  /// The newest frame, if one was published since the last was taken.
+  pub fn take(&self) -> Option<RenderData> {
+    self.next.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+  }
+}
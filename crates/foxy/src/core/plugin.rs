@@ -0,0 +1,119 @@
+use std::fmt;
+
+use super::{event::FoxyEvent, foxy_state::Foxy, profiling, run_condition::RunCondition};
+
+/// The stages of a frame on the game thread, in the order they run.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Stage {
+  /// All the fixed ticks of the frame together, even when there are none.
+  FixedUpdate,
+  /// The app's `input`, `update` and `window` callbacks.
+  Update,
+  Gui,
+  /// Filling in the render data.
+  Render,
+}
+
+/// An integration, such as a storefront or chat SDK, that runs on the game thread alongside the app's
+/// [`Runnable`](super::runnable::Runnable) without the app having to forward anything to it.
+#[allow(unused)]
+pub trait Plugin: Send + 'static {
+  fn name(&self) -> &str;
+
+  /// Called once, after the app has started.
+  fn start(&mut self, foxy: &Foxy) {}
+
+  /// Called every frame with the frame's event, before the app's update.
+  fn update(&mut self, foxy: &Foxy, event: &FoxyEvent) {}
+
+  /// Called right before each stage of every frame, e.g. to open a profiling scope.
+  fn before_stage(&mut self, foxy: &Foxy, stage: Stage) {}
+
+  /// Called right after each stage of every frame, e.g. to autosave or flush network messages once the update has run.
+  fn after_stage(&mut self, foxy: &Foxy, stage: Stage) {}
+
+  /// Called once, after the app has stopped.
+  fn stop(&mut self, foxy: &Foxy) {}
+
+  /// Only runs the plugin's per-frame hooks on frames where `condition` holds. `start` and `stop` always run.
+  fn run_if(self, condition: RunCondition) -> RunIf<Self>
+  where
+    Self: Sized,
+  {
+    RunIf {
+      plugin: self,
+      condition,
+      is_active: false,
+    }
+  }
+}
+
+/// A plugin whose per-frame hooks only run while a [`RunCondition`] holds, see [`Plugin::run_if`].
+pub struct RunIf<P> {
+  plugin: P,
+  condition: RunCondition,
+  is_active: bool,
+}
+
+impl<P: Plugin> Plugin for RunIf<P> {
+  fn name(&self) -> &str {
+    self.plugin.name()
+  }
+
+  fn start(&mut self, foxy: &Foxy) {
+    self.plugin.start(foxy);
+  }
+
+  fn update(&mut self, foxy: &Foxy, event: &FoxyEvent) {
+    if self.is_active {
+      self.plugin.update(foxy, event);
+    }
+  }
+
+  fn before_stage(&mut self, foxy: &Foxy, stage: Stage) {
+    // checked once at the start of each frame, so the plugin sees either all of a frame's hooks or none of them
+    if stage == Stage::FixedUpdate {
+      self.is_active = self.condition.check(foxy);
+    }
+    if self.is_active {
+      self.plugin.before_stage(foxy, stage);
+    }
+  }
+
+  fn after_stage(&mut self, foxy: &Foxy, stage: Stage) {
+    if self.is_active {
+      self.plugin.after_stage(foxy, stage);
+    }
+  }
+
+  fn stop(&mut self, foxy: &Foxy) {
+    self.plugin.stop(foxy);
+  }
+}
+
+/// The plugins of an app, run in the order they were added.
+#[derive(Default)]
+pub struct Plugins(pub(crate) Vec<Box<dyn Plugin>>);
+
+impl Plugins {
+  /// Runs `stage` between the plugins' stage hooks. Hooks after the stage run in reverse, so scopes opened before it
+  /// nest.
+  pub(crate) fn around<R>(&mut self, foxy: &Foxy, stage: Stage, run: impl FnOnce() -> R) -> R {
+    profiling::set_stage(Some(stage));
+    for plugin in &mut self.0 {
+      plugin.before_stage(foxy, stage);
+    }
+    let result = run();
+    for plugin in self.0.iter_mut().rev() {
+      plugin.after_stage(foxy, stage);
+    }
+    profiling::set_stage(None);
+    result
+  }
+}
+
+impl fmt::Debug for Plugins {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.0.iter().map(|plugin| plugin.name())).finish()
+  }
+}
@@ -0,0 +1,111 @@
+use std::{
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use tracing::{debug_span, span::EnteredSpan};
+
+use super::plugin::Stage;
+
+/// Times the rest of the enclosing block as a profiling scope called `name`, e.g.
+/// `foxy_profile_scope!("pathfinding");` at the top of a function.
+///
+/// The scope is entered as a `profile_scope` tracing span, tagged with the game loop stage and frame it ran in, and its
+/// time is added up with every other run of the same scope in the frame in
+/// [`FrameTimings::scopes`](crate::core::timings::FrameTimings::scopes).
+#[macro_export]
+macro_rules! foxy_profile_scope {
+  ($name:expr) => {
+    let _foxy_profile_scope = $crate::core::profiling::ProfileScope::new($name);
+  };
+}
+
+/// How long one profiling scope took over a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeTiming {
+  pub name: &'static str,
+  /// The game loop stage the scope ran in, or `None` outside of the stages, e.g. on other threads.
+  pub stage: Option<Stage>,
+  /// All the runs of the scope in the frame together.
+  pub time: Duration,
+  pub calls: u32,
+}
+
+/// A running profiling scope, which adds its time to the frame's timings when dropped. Usually opened with
+/// [`foxy_profile_scope!`](crate::foxy_profile_scope).
+#[must_use = "the scope ends as soon as it's dropped"]
+pub struct ProfileScope {
+  name: &'static str,
+  stage: Option<Stage>,
+  start: Instant,
+  _span: EnteredSpan,
+}
+
+impl ProfileScope {
+  pub fn new(name: &'static str) -> Self {
+    let (stage, frame) = {
+      let profiler = lock();
+      (profiler.stage, profiler.frame)
+    };
+    Self {
+      name,
+      stage,
+      start: Instant::now(),
+      _span: debug_span!("profile_scope", scope = name, stage = ?stage, frame).entered(),
+    }
+  }
+}
+
+impl Drop for ProfileScope {
+  fn drop(&mut self) {
+    let time = self.start.elapsed();
+    let mut profiler = lock();
+    match profiler
+      .scopes
+      .iter_mut()
+      .find(|scope| scope.name == self.name && scope.stage == self.stage)
+    {
+      Some(scope) => {
+        scope.time += time;
+        scope.calls += 1;
+      }
+      None => profiler.scopes.push(ScopeTiming {
+        name: self.name,
+        stage: self.stage,
+        time,
+        calls: 1,
+      }),
+    }
+  }
+}
+
+/// What the scopes of the current frame are tagged with, and their timings so far.
+struct Profiler {
+  frame: u64,
+  stage: Option<Stage>,
+  scopes: Vec<ScopeTiming>,
+}
+
+static PROFILER: Mutex<Profiler> = Mutex::new(Profiler {
+  frame: 0,
+  stage: None,
+  scopes: Vec::new(),
+});
+
+fn lock() -> std::sync::MutexGuard<'static, Profiler> {
+  // the timings are still usable after a panic in another scope
+  PROFILER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Tags the scopes opened from now on with `stage`.
+pub(crate) fn set_stage(stage: Option<Stage>) {
+  lock().stage = stage;
+}
+
+/// Ends the frame, returning its number and the timings of its scopes in the order they first finished.
+pub(crate) fn end_frame() -> (u64, Vec<ScopeTiming>) {
+  let mut profiler = lock();
+  let frame = profiler.frame;
+  profiler.frame += 1;
+  (frame, std::mem::take(&mut profiler.scopes))
+}
@@ -0,0 +1,209 @@
+use glam::{Quat, Vec2, Vec3, Vec4};
+
+pub use foxy_derive::Reflect;
+
+/// A component, or any part of one, as plain data. It's what scenes and prefabs are saved as: with the `serde` feature
+/// enabled it can be written to and read from any serde format.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+  #[default]
+  None,
+  Bool(bool),
+  Int(i64),
+  Float(f64),
+  String(String),
+  List(Vec<Value>),
+  /// Fields by name, in declaration order. May hold only some of a type's fields, e.g. a prefab's overrides.
+  Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+  /// The field named `name`, if this is a struct that has it.
+  pub fn field(&self, name: &str) -> Option<&Value> {
+    match self {
+      Value::Struct(fields) => fields.iter().find(|(field, _)| field == name).map(|(_, value)| value),
+      _ => None,
+    }
+  }
+
+  /// The fields of `self` whose values differ from `base`, e.g. what a prefab instance overrides. `None` if nothing
+  /// differs.
+  pub fn diff(&self, base: &Value) -> Option<Value> {
+    match (self, base) {
+      (Value::Struct(fields), Value::Struct(_)) => {
+        let changed: Vec<_> = fields
+          .iter()
+          .filter_map(|(name, value)| match base.field(name) {
+            Some(base_value) => value.diff(base_value).map(|diff| (name.clone(), diff)),
+            None => Some((name.clone(), value.clone())),
+          })
+          .collect();
+        (!changed.is_empty()).then_some(Value::Struct(changed))
+      }
+      _ => (self != base).then(|| self.clone()),
+    }
+  }
+}
+
+/// A type that can be converted to and from a [`Value`], so that the scene serializer and prefab overrides can handle
+/// it without knowing what it is. Derive it with `#[derive(Reflect)]`; fields marked `#[reflect(skip)]` are left out.
+pub trait Reflect {
+  fn to_value(&self) -> Value;
+
+  /// Overwrites `self` with what's in `value`. Fields `value` leaves out are kept, so that overrides can be layered on
+  /// top of a base. Returns `false` if any of it didn't fit `self`; whatever did fit is still applied.
+  fn apply_value(&mut self, value: &Value) -> bool;
+}
+
+macro_rules! reflect_int {
+  ($($int:ty),*) => {
+    $(
+      impl Reflect for $int {
+        fn to_value(&self) -> Value {
+          Value::Int(*self as i64)
+        }
+
+        fn apply_value(&mut self, value: &Value) -> bool {
+          match value {
+            Value::Int(int) => match <$int>::try_from(*int) {
+              Ok(int) => {
+                *self = int;
+                true
+              }
+              Err(_) => false,
+            },
+            _ => false,
+          }
+        }
+      }
+    )*
+  };
+}
+
+reflect_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize, isize);
+
+macro_rules! reflect_float {
+  ($($float:ty),*) => {
+    $(
+      impl Reflect for $float {
+        fn to_value(&self) -> Value {
+          Value::Float(*self as f64)
+        }
+
+        fn apply_value(&mut self, value: &Value) -> bool {
+          match value {
+            Value::Float(float) => *self = *float as $float,
+            Value::Int(int) => *self = *int as $float,
+            _ => return false,
+          }
+          true
+        }
+      }
+    )*
+  };
+}
+
+reflect_float!(f32, f64);
+
+impl Reflect for bool {
+  fn to_value(&self) -> Value {
+    Value::Bool(*self)
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    match value {
+      Value::Bool(bool) => {
+        *self = *bool;
+        true
+      }
+      _ => false,
+    }
+  }
+}
+
+impl Reflect for String {
+  fn to_value(&self) -> Value {
+    Value::String(self.clone())
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    match value {
+      Value::String(string) => {
+        self.clone_from(string);
+        true
+      }
+      _ => false,
+    }
+  }
+}
+
+macro_rules! reflect_vector {
+  ($($vector:ty),*) => {
+    $(
+      impl Reflect for $vector {
+        fn to_value(&self) -> Value {
+          self.to_array().to_value()
+        }
+
+        fn apply_value(&mut self, value: &Value) -> bool {
+          let mut components = self.to_array();
+          let applied = components.apply_value(value);
+          *self = <$vector>::from_array(components);
+          applied
+        }
+      }
+    )*
+  };
+}
+
+reflect_vector!(Vec2, Vec3, Vec4, Quat);
+
+impl<T: Reflect, const N: usize> Reflect for [T; N] {
+  fn to_value(&self) -> Value {
+    Value::List(self.iter().map(Reflect::to_value).collect())
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    match value {
+      Value::List(values) if values.len() == N => self
+        .iter_mut()
+        .zip(values)
+        .fold(true, |applied, (element, value)| element.apply_value(value) && applied),
+      _ => false,
+    }
+  }
+}
+
+impl<T: Reflect + Default> Reflect for Vec<T> {
+  fn to_value(&self) -> Value {
+    Value::List(self.iter().map(Reflect::to_value).collect())
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    let Value::List(values) = value else {
+      return false;
+    };
+    self.resize_with(values.len(), T::default);
+    self
+      .iter_mut()
+      .zip(values)
+      .fold(true, |applied, (element, value)| element.apply_value(value) && applied)
+  }
+}
+
+impl<T: Reflect + Default> Reflect for Option<T> {
+  fn to_value(&self) -> Value {
+    self.as_ref().map_or(Value::None, Reflect::to_value)
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    match value {
+      Value::None => {
+        *self = None;
+        true
+      }
+      value => self.get_or_insert_with(T::default).apply_value(value),
+    }
+  }
+}
@@ -0,0 +1,80 @@
+use std::{fmt, ops::Not};
+
+use super::foxy_state::Foxy;
+
+/// Decides whether something runs this frame, e.g. only while a game state is active, every few frames, or when a
+/// value changed. Conditions keep state between checks, so each should be checked exactly once per frame.
+///
+/// Plugins can be gated with [`Plugin::run_if`](super::plugin::Plugin::run_if), and apps can check conditions in their
+/// own callbacks in place of hand-rolled guards and counters.
+pub struct RunCondition(Box<dyn FnMut(&Foxy) -> bool + Send>);
+
+impl RunCondition {
+  pub fn new(condition: impl FnMut(&Foxy) -> bool + Send + 'static) -> Self {
+    Self(Box::new(condition))
+  }
+
+  /// Runs on the first frame, and then once every `frames` frames.
+  pub fn every_n_frames(frames: u32) -> Self {
+    let mut counter = 0;
+    Self::new(move |_| {
+      let should_run = counter == 0;
+      counter = (counter + 1) % frames.max(1);
+      should_run
+    })
+  }
+
+  /// Runs on the first frame, and then whenever the value picked by `value` differs from the last frame's, e.g. when
+  /// the game state or a setting changes.
+  pub fn when_changed<T: PartialEq + Send + 'static>(mut value: impl FnMut(&Foxy) -> T + Send + 'static) -> Self {
+    let mut previous = None;
+    Self::new(move |foxy| {
+      let current = value(foxy);
+      let changed = previous.as_ref() != Some(&current);
+      previous = Some(current);
+      changed
+    })
+  }
+
+  /// Runs while the simulation isn't paused by the [`Stepping`](super::stepping::Stepping) debug controls.
+  pub fn not_paused() -> Self {
+    Self::new(|foxy| !foxy.read().stepping().is_paused())
+  }
+
+  /// Runs when both conditions do. Both are checked every frame, so neither misses a frame it's keeping track of.
+  pub fn and(mut self, mut other: RunCondition) -> Self {
+    Self::new(move |foxy| {
+      let a = self.check(foxy);
+      let b = other.check(foxy);
+      a && b
+    })
+  }
+
+  /// Runs when either condition does. Both are checked every frame, as with [`RunCondition::and`].
+  pub fn or(mut self, mut other: RunCondition) -> Self {
+    Self::new(move |foxy| {
+      let a = self.check(foxy);
+      let b = other.check(foxy);
+      a || b
+    })
+  }
+
+  pub fn check(&mut self, foxy: &Foxy) -> bool {
+    (self.0)(foxy)
+  }
+}
+
+/// Runs when the condition doesn't.
+impl Not for RunCondition {
+  type Output = Self;
+
+  fn not(mut self) -> Self {
+    Self::new(move |foxy| !self.check(foxy))
+  }
+}
+
+impl fmt::Debug for RunCondition {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("RunCondition").finish_non_exhaustive()
+  }
+}
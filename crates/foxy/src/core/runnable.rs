@@ -1,4 +1,5 @@
 use egui::Context;
+use foxy_renderer::renderer::render_data::RenderData;
 
 use super::{
   builder::FoxyCreateInfo,
@@ -32,6 +33,9 @@ pub trait Runnable {
 
   fn gui(&mut self, foxy: &Foxy, egui: &Context) {}
 
+  /// Fills in what the renderer should draw this frame, e.g. per-draw material overrides.
+  fn render(&mut self, foxy: &Foxy, render_data: &mut RenderData) {}
+
   fn stop(&mut self, foxy: &Foxy) -> Flow {
     Flow::Exit
   }
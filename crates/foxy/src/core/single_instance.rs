@@ -0,0 +1,201 @@
+use std::{
+  fs,
+  io::{Read, Write},
+  net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+  path::PathBuf,
+  sync::Arc,
+  time::Duration,
+};
+
+use crossbeam::channel::{Receiver, Sender};
+use tracing::*;
+use winit::window::Window;
+
+use super::FoxyResult;
+
+/// Keeps the app to one running instance. Launching it again, e.g. by opening a file or URL the game handles, hands
+/// the new command line to the running instance and focuses its window instead of opening a second one.
+#[derive(Debug, Clone)]
+pub struct SingleInstance {
+  /// Identifies the app. Instances only hand off to instances with the same id.
+  pub app_id: String,
+  /// How long to wait for the running instance to answer before starting anyway.
+  pub timeout: Duration,
+}
+
+impl SingleInstance {
+  pub fn new(app_id: impl Into<String>) -> Self {
+    Self {
+      app_id: app_id.into(),
+      timeout: Duration::from_millis(500),
+    }
+  }
+
+  pub fn with_timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = timeout;
+    self
+  }
+
+  /// Hands this instance's command line to the running instance, returning `None` if it took it. Otherwise, this
+  /// becomes the running instance.
+  ///
+  /// Two instances launched at the same moment may both end up running.
+  pub(crate) fn acquire(&self) -> FoxyResult<Option<InstanceLock>> {
+    let lock_path = self.lock_path();
+
+    if let Some(port) = fs::read_to_string(&lock_path).ok().and_then(|port| port.trim().parse().ok()) {
+      match self.forward(port) {
+        Ok(true) => return Ok(None),
+        Ok(false) => (),
+        // most likely left behind by an instance that crashed
+        Err(error) => debug!("no running instance answered: {error}"),
+      }
+    }
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    fs::write(&lock_path, listener.local_addr()?.port().to_string())?;
+
+    Ok(Some(InstanceLock {
+      app_id: self.app_id.clone(),
+      lock_path,
+      listener,
+    }))
+  }
+
+  fn forward(&self, port: u16) -> std::io::Result<bool> {
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&address, self.timeout)?;
+    stream.set_read_timeout(Some(self.timeout))?;
+    stream.set_write_timeout(Some(self.timeout))?;
+
+    let activation = Activation {
+      args: std::env::args().skip(1).collect(),
+      working_directory: std::env::current_dir().ok(),
+    };
+    stream.write_all(&activation.encode(&self.app_id))?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply)?;
+
+    Ok(reply == InstanceLock::ACCEPTED)
+  }
+
+  fn lock_path(&self) -> PathBuf {
+    let file_name: String = self
+      .app_id
+      .chars()
+      .map(|c| if c.is_ascii_alphanumeric() || "-_.".contains(c) { c } else { '_' })
+      .collect();
+
+    std::env::temp_dir().join(format!("{file_name}.instance"))
+  }
+}
+
+/// A later launch of the app, handed off to the running instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activation {
+  /// The command line arguments, without the executable.
+  pub args: Vec<String>,
+  /// Where the launch happened, for resolving relative paths in `args`.
+  pub working_directory: Option<PathBuf>,
+}
+
+impl Activation {
+  const SEPARATOR: char = '\0';
+
+  fn encode(&self, app_id: &str) -> Vec<u8> {
+    let working_directory = self
+      .working_directory
+      .as_ref()
+      .map(|path| path.to_string_lossy().into_owned())
+      .unwrap_or_default();
+
+    [app_id.to_owned(), working_directory]
+      .into_iter()
+      .chain(self.args.iter().cloned())
+      .collect::<Vec<_>>()
+      .join(&Self::SEPARATOR.to_string())
+      .into_bytes()
+  }
+
+  fn decode(app_id: &str, message: &str) -> Option<Self> {
+    let mut parts = message.split(Self::SEPARATOR);
+    if parts.next()? != app_id {
+      return None;
+    }
+    let working_directory = Some(parts.next()?).filter(|path| !path.is_empty()).map(PathBuf::from);
+
+    Some(Self {
+      args: parts.map(str::to_owned).collect(),
+      working_directory,
+    })
+  }
+}
+
+/// Marks this instance as the running one until it's dropped.
+pub(crate) struct InstanceLock {
+  app_id: String,
+  lock_path: PathBuf,
+  listener: TcpListener,
+}
+
+impl InstanceLock {
+  const ACCEPTED: &'static [u8] = b"ok";
+
+  /// Starts taking activations from later launches, waking `window` whenever one comes in.
+  pub fn listen(self, window: Arc<Window>) -> FoxyResult<InstanceListener> {
+    let (sender, activations) = crossbeam::channel::unbounded();
+    let listener = self.listener.try_clone()?;
+    let app_id = self.app_id.clone();
+
+    std::thread::Builder::new()
+      .name("foxy instance listener".into())
+      .spawn(move || {
+        for stream in listener.incoming() {
+          let result = stream.and_then(|stream| Self::accept(&app_id, stream, &sender));
+          match result {
+            Ok(true) => window.request_redraw(),
+            Ok(false) => (),
+            Err(error) => debug!("failed to take activation: {error}"),
+          }
+        }
+      })?;
+
+    Ok(InstanceListener {
+      activations,
+      _lock: self,
+    })
+  }
+
+  fn accept(app_id: &str, mut stream: TcpStream, sender: &Sender<Activation>) -> std::io::Result<bool> {
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+    let mut message = String::new();
+    stream.read_to_string(&mut message)?;
+
+    let Some(activation) = Activation::decode(app_id, &message) else {
+      return Ok(false);
+    };
+    stream.write_all(Self::ACCEPTED)?;
+
+    Ok(sender.send(activation).is_ok())
+  }
+}
+
+impl Drop for InstanceLock {
+  fn drop(&mut self) {
+    let _ = fs::remove_file(&self.lock_path);
+  }
+}
+
+pub(crate) struct InstanceListener {
+  activations: Receiver<Activation>,
+  _lock: InstanceLock,
+}
+
+impl InstanceListener {
+  pub fn try_iter(&self) -> impl Iterator<Item = Activation> + '_ {
+    self.activations.try_iter()
+  }
+}
@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+/// Debug controls for the simulation: pausing it, stepping through it one tick or frame at a time, and slowing it
+/// down. While paused, the app's `fixed_update` and `update` stop being called, but input, the UI and rendering carry
+/// on, so the last frame stays on screen and debug tools stay usable.
+///
+/// Bind [`Stepping::PAUSE_ACTION`], [`Stepping::STEP_TICK_ACTION`] and [`Stepping::STEP_FRAME_ACTION`] in the
+/// [`ActionMap`](super::input::action::ActionMap) to drive them from the keyboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stepping {
+  paused: bool,
+  time_scale: f64,
+  pending_ticks: u32,
+  pending_frames: u32,
+}
+
+impl Default for Stepping {
+  fn default() -> Self {
+    Self {
+      paused: false,
+      time_scale: 1.0,
+      pending_ticks: 0,
+      pending_frames: 0,
+    }
+  }
+}
+
+/// What the game loop runs this frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum Step {
+  /// Everything, with simulation time scaled by the given factor.
+  Run(f64),
+  /// Exactly this many fixed ticks, without `update`.
+  Ticks(u32),
+  /// One `update`, and the fixed ticks that fit in a frame.
+  Frame,
+  /// Nothing from the simulation.
+  Paused,
+}
+
+impl Stepping {
+  pub const PAUSE_ACTION: &'static str = "debug_pause";
+  pub const STEP_TICK_ACTION: &'static str = "debug_step_tick";
+  pub const STEP_FRAME_ACTION: &'static str = "debug_step_frame";
+
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  pub fn pause(&mut self) {
+    self.paused = true;
+  }
+
+  pub fn resume(&mut self) {
+    self.paused = false;
+    self.pending_ticks = 0;
+    self.pending_frames = 0;
+  }
+
+  pub fn toggle_pause(&mut self) {
+    if self.paused {
+      self.resume();
+    } else {
+      self.pause();
+    }
+  }
+
+  /// Pauses, if running, and advances by one fixed tick.
+  pub fn step_tick(&mut self) {
+    self.paused = true;
+    self.pending_ticks += 1;
+  }
+
+  /// Pauses, if running, and advances by one frame: one `update` and the fixed ticks that fit in it.
+  pub fn step_frame(&mut self) {
+    self.paused = true;
+    self.pending_frames += 1;
+  }
+
+  pub fn time_scale(&self) -> f64 {
+    self.time_scale
+  }
+
+  /// Runs the simulation slower (or faster) than real time, e.g. `0.25` for quarter speed. Frame deltas are left in
+  /// real time.
+  pub fn set_time_scale(&mut self, time_scale: f64) {
+    self.time_scale = time_scale.max(0.0);
+  }
+
+  pub(crate) fn next_step(&mut self) -> Step {
+    if !self.paused {
+      Step::Run(self.time_scale)
+    } else if self.pending_ticks > 0 {
+      Step::Ticks(std::mem::take(&mut self.pending_ticks))
+    } else if self.pending_frames > 0 {
+      self.pending_frames -= 1;
+      Step::Frame
+    } else {
+      Step::Paused
+    }
+  }
+}
+
+impl Step {
+  pub fn runs_update(self) -> bool {
+    matches!(self, Step::Run(_) | Step::Frame)
+  }
+
+  /// Extra simulation time to tick through this frame, beyond what passed in real time.
+  pub fn advance(self, tick_duration: Duration, frame_duration: Duration) -> Duration {
+    match self {
+      Step::Ticks(ticks) => tick_duration * ticks,
+      Step::Frame => frame_duration,
+      Step::Run(_) | Step::Paused => Duration::ZERO,
+    }
+  }
+
+  pub fn time_scale(self) -> f64 {
+    match self {
+      Step::Run(time_scale) => time_scale,
+      Step::Ticks(_) | Step::Frame | Step::Paused => 0.0,
+    }
+  }
+}
@@ -0,0 +1,287 @@
+use std::{
+  collections::{HashSet, VecDeque},
+  path::{Path, PathBuf},
+  thread::JoinHandle,
+};
+
+use crossbeam::channel::{Receiver, Sender};
+use foxy_utils::thread::priority::ThreadSettings;
+use glam::Vec3;
+use tracing::*;
+
+use super::{
+  assets::{Asset, AssetSource, Assets},
+  FoxyResult,
+};
+
+/// A chunk of a level: a scene covering a box of the world, streamed in when the viewer gets near it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamingCell {
+  pub scene: PathBuf,
+  pub min: Vec3,
+  pub max: Vec3,
+}
+
+impl StreamingCell {
+  pub fn new(scene: impl Into<PathBuf>, min: Vec3, max: Vec3) -> Self {
+    Self {
+      scene: scene.into(),
+      min,
+      max,
+    }
+  }
+
+  /// How far `point` is from the cell's box, or 0 inside of it.
+  pub fn distance(&self, point: Vec3) -> f32 {
+    point.clamp(self.min, self.max).distance(point)
+  }
+}
+
+/// What changed in the streamed level during an update.
+#[derive(Debug, Default)]
+pub struct StreamingEvents {
+  /// Scenes whose assets are all in the [`Assets`] now, ready to be added to the world.
+  pub loaded: Vec<PathBuf>,
+  /// Scenes that were unloaded with their assets, to be removed from the world.
+  pub unloaded: Vec<PathBuf>,
+  /// Scenes that failed to load. They aren't retried.
+  pub failed: Vec<PathBuf>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CellState {
+  Unloaded,
+  Loading,
+  Loaded,
+  Failed,
+}
+
+enum Streamed {
+  Asset {
+    path: PathBuf,
+    result: FoxyResult<(Asset, Vec<PathBuf>)>,
+  },
+  CellDone,
+}
+
+struct LoadRequest {
+  cell: usize,
+  scene: PathBuf,
+  /// Assets that are already loaded, which don't have to be read again.
+  loaded: HashSet<PathBuf>,
+}
+
+enum StreamRequest {
+  Load(LoadRequest),
+  /// Settings for the streaming thread to apply to itself.
+  ThreadSettings(ThreadSettings),
+}
+
+/// Streams the cells of a level in and out as the viewer moves, loading them on a background thread.
+///
+/// Cells start loading once the viewer comes within the load distance of them, and are unloaded once the viewer is
+/// further than the unload distance, which should be larger so that cells on the edge don't thrash. Loaded assets are
+/// handed over to the [`Assets`] a few per frame, so that the GPU uploads they cause don't make the frame hitch.
+pub struct LevelStreamer {
+  cells: Vec<(StreamingCell, CellState)>,
+  load_distance: f32,
+  unload_distance: f32,
+  assets_per_frame: usize,
+  /// Streamed assets not handed over yet, with the cell they were streamed for.
+  arrived: VecDeque<(usize, Streamed)>,
+  failed_cells: HashSet<usize>,
+  requests: Option<Sender<StreamRequest>>,
+  results: Receiver<(usize, Streamed)>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl LevelStreamer {
+  pub const DEFAULT_ASSETS_PER_FRAME: usize = 4;
+  pub const DEFAULT_LOAD_DISTANCE: f32 = 64.0;
+  pub const DEFAULT_UNLOAD_DISTANCE: f32 = 96.0;
+  const THREAD_ID: &'static str = "level_streaming";
+
+  /// Streams assets from the same asset directory and with the same loaders as `assets`.
+  pub fn new(assets: &Assets, cells: Vec<StreamingCell>) -> FoxyResult<Self> {
+    let (request_sender, request_receiver) = crossbeam::channel::unbounded();
+    let (result_sender, result_receiver) = crossbeam::channel::unbounded();
+    let source = assets.source().clone();
+
+    let thread = std::thread::Builder::new()
+      .name(Self::THREAD_ID.into())
+      .spawn(move || Self::stream(&source, &request_receiver, &result_sender))?;
+
+    Ok(Self {
+      cells: cells.into_iter().map(|cell| (cell, CellState::Unloaded)).collect(),
+      load_distance: Self::DEFAULT_LOAD_DISTANCE,
+      unload_distance: Self::DEFAULT_UNLOAD_DISTANCE,
+      assets_per_frame: Self::DEFAULT_ASSETS_PER_FRAME,
+      arrived: VecDeque::new(),
+      failed_cells: HashSet::new(),
+      requests: Some(request_sender),
+      results: result_receiver,
+      thread: Some(thread),
+    })
+  }
+
+  /// Sets how near the viewer has to be for a cell to load, and how far for it to unload again.
+  pub fn with_distances(mut self, load_distance: f32, unload_distance: f32) -> Self {
+    self.load_distance = load_distance;
+    self.unload_distance = unload_distance.max(load_distance);
+    self
+  }
+
+  /// The most assets handed over to the [`Assets`] per update.
+  pub fn with_assets_per_frame(mut self, assets_per_frame: usize) -> Self {
+    self.assets_per_frame = assets_per_frame.max(1);
+    self
+  }
+
+  /// Gives the streaming thread a priority and affinity, e.g. those of
+  /// [`EngineThread::Streaming`](super::builder::EngineThread::Streaming) from
+  /// [`State::thread_settings`](super::foxy_state::State::thread_settings).
+  pub fn with_thread_settings(self, settings: ThreadSettings) -> Self {
+    self.set_thread_settings(settings);
+    self
+  }
+
+  /// Gives the streaming thread a priority and affinity. They take effect once it's through the cells already queued.
+  pub fn set_thread_settings(&self, settings: ThreadSettings) {
+    if let Some(requests) = &self.requests {
+      let _ = requests.send(StreamRequest::ThreadSettings(settings));
+    }
+  }
+
+  pub fn cells(&self) -> impl Iterator<Item = &StreamingCell> {
+    self.cells.iter().map(|(cell, _)| cell)
+  }
+
+  pub fn is_loaded(&self, scene: impl AsRef<Path>) -> bool {
+    self
+      .cells
+      .iter()
+      .any(|(cell, state)| cell.scene == scene.as_ref() && *state == CellState::Loaded)
+  }
+
+  /// Whether any cell is still loading.
+  pub fn is_busy(&self) -> bool {
+    self.cells.iter().any(|(_, state)| *state == CellState::Loading)
+  }
+
+  /// Starts loading and unloads cells around `viewer`, e.g. the camera position, and hands over some of the assets
+  /// loaded since the last update. Call once per frame.
+  pub fn update(&mut self, assets: &mut Assets, viewer: Vec3) -> StreamingEvents {
+    let mut events = StreamingEvents::default();
+
+    for (index, (cell, state)) in self.cells.iter_mut().enumerate() {
+      let distance = cell.distance(viewer);
+      match state {
+        CellState::Unloaded if distance <= self.load_distance => {
+          let request = StreamRequest::Load(LoadRequest {
+            cell: index,
+            scene: cell.scene.clone(),
+            loaded: assets.loaded_paths(),
+          });
+          if self.requests.as_ref().is_some_and(|requests| requests.send(request).is_ok()) {
+            *state = CellState::Loading;
+          }
+        }
+        CellState::Loaded if distance > self.unload_distance => {
+          assets.unload(&cell.scene);
+          events.unloaded.push(cell.scene.clone());
+          *state = CellState::Unloaded;
+        }
+        _ => (),
+      }
+    }
+
+    self.arrived.extend(self.results.try_iter());
+
+    let mut handed_over = 0;
+    while handed_over < self.assets_per_frame {
+      let Some((cell, streamed)) = self.arrived.pop_front() else {
+        break;
+      };
+
+      match streamed {
+        Streamed::Asset { path, result } => match result {
+          Ok((asset, dependencies)) => {
+            assets.insert(path, asset, dependencies);
+            handed_over += 1;
+          }
+          Err(error) => {
+            error!("failed to stream {}: {error}", path.display());
+            self.failed_cells.insert(cell);
+          }
+        },
+        Streamed::CellDone => {
+          let Some((cell_info, state)) = self.cells.get_mut(cell) else {
+            continue;
+          };
+          if self.failed_cells.remove(&cell) {
+            *state = CellState::Failed;
+            events.failed.push(cell_info.scene.clone());
+          } else if !assets.closure(&cell_info.scene).iter().all(|path| assets.is_loaded(path)) {
+            // another cell unloaded assets this one counted on while it was loading, so it starts over
+            *state = CellState::Unloaded;
+          } else {
+            assets.add_root(cell_info.scene.clone());
+            *state = CellState::Loaded;
+            events.loaded.push(cell_info.scene.clone());
+          }
+        }
+      }
+    }
+
+    events
+  }
+
+  /// Loads the requested cells one at a time, sending back their assets as they're read.
+  fn stream(source: &AssetSource, requests: &Receiver<StreamRequest>, results: &Sender<(usize, Streamed)>) {
+    for request in requests {
+      let LoadRequest { cell, scene, loaded } = match request {
+        StreamRequest::Load(request) => request,
+        StreamRequest::ThreadSettings(settings) => {
+          if let Err(error) = settings.apply() {
+            warn!("failed to apply the streaming thread settings: {error}");
+          }
+          continue;
+        }
+      };
+      let mut visited = loaded;
+      let mut pending = VecDeque::from([scene]);
+
+      while let Some(path) = pending.pop_front() {
+        if !visited.insert(path.clone()) {
+          continue;
+        }
+
+        let result = source.read(&path);
+        let failed = result.is_err();
+        if let Ok((_, dependencies)) = &result {
+          pending.extend(dependencies.iter().filter(|dependency| !visited.contains(*dependency)).cloned());
+        }
+        if results.send((cell, Streamed::Asset { path, result })).is_err() {
+          return;
+        }
+        if failed {
+          break;
+        }
+      }
+
+      if results.send((cell, Streamed::CellDone)).is_err() {
+        return;
+      }
+    }
+  }
+}
+
+impl Drop for LevelStreamer {
+  fn drop(&mut self) {
+    // closing the request channel ends the thread once it's done with the current cell
+    self.requests = None;
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
@@ -0,0 +1,328 @@
+use std::{
+  collections::BTreeMap,
+  fmt,
+  fs,
+  io::{self, Write},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+  thread::JoinHandle,
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender};
+use tracing::*;
+
+use super::FoxyResult;
+
+/// A named thing that happened, e.g. `level_completed` with `level = "shrine"` and `time = "312"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryEvent {
+  pub name: String,
+  pub properties: BTreeMap<String, String>,
+  /// Milliseconds since the Unix epoch.
+  pub timestamp: u64,
+  /// Tells apart the sessions events were recorded in, as they may be uploaded in a later one.
+  pub session: u64,
+}
+
+impl TelemetryEvent {
+  pub fn new(name: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      properties: BTreeMap::new(),
+      timestamp: 0,
+      session: 0,
+    }
+  }
+
+  pub fn with_property(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+    self.properties.insert(key.into(), value.to_string());
+    self
+  }
+
+  /// One line per event, with tabs between the fields and `key=value` properties.
+  fn encode(&self) -> String {
+    let mut line = format!("{}\t{}\t{}", self.timestamp, self.session, escape(&self.name));
+    for (key, value) in &self.properties {
+      line += &format!("\t{}={}", escape(key), escape(value));
+    }
+
+    line
+  }
+
+  fn decode(line: &str) -> Option<Self> {
+    let mut fields = line.split('\t');
+    let timestamp = fields.next()?.parse().ok()?;
+    let session = fields.next()?.parse().ok()?;
+    let name = unescape(fields.next()?);
+    let properties = fields
+      .map(|property| {
+        let (key, value) = property.split_once('=')?;
+        Some((unescape(key), unescape(value)))
+      })
+      .collect::<Option<_>>()?;
+
+    Some(Self {
+      name,
+      properties,
+      timestamp,
+      session,
+    })
+  }
+}
+
+/// Where batches of telemetry events go, e.g. an HTTP endpoint or a log file.
+pub trait TelemetrySink: Send + 'static {
+  /// Sends `batch`, oldest first. Failed batches are kept and retried later.
+  fn upload(&mut self, batch: &[TelemetryEvent]) -> Result<(), String>;
+}
+
+/// Opt-in telemetry. Events are only recorded once the player has consented, and are queued on disk so that those not
+/// yet uploaded survive to the next session.
+///
+/// Consent is not remembered between sessions, so the app should store the player's choice in its settings and pass
+/// it to [`Telemetry::with_consent`]. Events left over from earlier sessions are uploaded once the app starts if the
+/// player has consented by then, and deleted otherwise.
+pub struct Telemetry {
+  sender: Sender<Message>,
+  consent: Arc<AtomicBool>,
+  session: u64,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for Telemetry {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Telemetry")
+      .field("consent", &self.has_consent())
+      .field("session", &self.session)
+      .finish_non_exhaustive()
+  }
+}
+
+enum Message {
+  Start,
+  Record(TelemetryEvent),
+  Flush,
+  Revoke,
+  Stop,
+}
+
+impl Telemetry {
+  /// How many events are batched before uploading early.
+  const BATCH_SIZE: usize = 32;
+  /// How often queued events are uploaded.
+  const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+  const QUEUE_FILE: &'static str = "telemetry_queue";
+
+  /// Queues events in `directory` and uploads them to `sink` from a background thread. Nothing is recorded until
+  /// consent is given.
+  pub fn new(directory: impl Into<PathBuf>, sink: impl TelemetrySink) -> FoxyResult<Self> {
+    let directory = directory.into();
+    fs::create_dir_all(&directory)?;
+
+    let (sender, receiver) = crossbeam::channel::unbounded();
+    let consent = Arc::new(AtomicBool::new(false));
+    let queue = Queue {
+      path: directory.join(Self::QUEUE_FILE),
+      sink: Box::new(sink),
+      consent: consent.clone(),
+    };
+    let thread = std::thread::Builder::new()
+      .name("foxy telemetry".into())
+      .spawn(move || queue.run(receiver))?;
+
+    Ok(Self {
+      sender,
+      consent,
+      session: unix_millis(SystemTime::now()),
+      thread: Some(thread),
+    })
+  }
+
+  pub fn with_consent(self, consent: bool) -> Self {
+    self.set_consent(consent);
+    self
+  }
+
+  /// Revoking consent also deletes the events still waiting to be uploaded.
+  pub fn set_consent(&self, consent: bool) {
+    let had_consent = self.consent.swap(consent, Ordering::Relaxed);
+    if had_consent && !consent {
+      let _ = self.sender.send(Message::Revoke);
+    }
+  }
+
+  pub fn has_consent(&self) -> bool {
+    self.consent.load(Ordering::Relaxed)
+  }
+
+  /// Queues `event`, stamped with the current time and session, if the player has consented.
+  pub fn record(&self, mut event: TelemetryEvent) {
+    if !self.has_consent() {
+      return;
+    }
+
+    event.timestamp = unix_millis(SystemTime::now());
+    event.session = self.session;
+    let _ = self.sender.send(Message::Record(event));
+  }
+
+  /// Settles the events earlier sessions left behind, once the app has had its chance to give consent.
+  pub(crate) fn start(&self) {
+    let _ = self.sender.send(Message::Start);
+  }
+
+  /// Uploads the queued events now rather than at the next interval.
+  pub fn flush(&self) {
+    let _ = self.sender.send(Message::Flush);
+  }
+}
+
+impl Drop for Telemetry {
+  fn drop(&mut self) {
+    let _ = self.sender.send(Message::Stop);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+struct Queue {
+  path: PathBuf,
+  sink: Box<dyn TelemetrySink>,
+  consent: Arc<AtomicBool>,
+}
+
+impl Queue {
+  fn run(mut self, receiver: Receiver<Message>) {
+    let mut pending = Vec::new();
+    let mut last_upload = Instant::now();
+    let mut should_upload = false;
+
+    loop {
+      if should_upload {
+        self.upload(&mut pending);
+        last_upload = Instant::now();
+        should_upload = false;
+      }
+
+      let timeout = Telemetry::FLUSH_INTERVAL.saturating_sub(last_upload.elapsed());
+      match receiver.recv_timeout(timeout) {
+        Ok(Message::Start) => {
+          if self.consent.load(Ordering::Relaxed) {
+            // upload what earlier sessions left behind right away, along with anything recorded so far
+            pending = self.load();
+            should_upload = !pending.is_empty();
+          } else {
+            // the player may have declined since those events were recorded
+            pending.clear();
+            self.delete();
+          }
+        }
+        Ok(Message::Record(event)) => {
+          self.append(&event);
+          pending.push(event);
+          should_upload = pending.len() >= Telemetry::BATCH_SIZE;
+        }
+        Ok(Message::Flush) => should_upload = true,
+        Ok(Message::Revoke) => {
+          pending.clear();
+          self.delete();
+        }
+        Err(RecvTimeoutError::Timeout) => should_upload = !pending.is_empty(),
+        Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+      }
+    }
+  }
+
+  fn upload(&mut self, pending: &mut Vec<TelemetryEvent>) {
+    if !self.consent.load(Ordering::Relaxed) {
+      return;
+    }
+
+    match self.sink.upload(pending) {
+      Ok(()) => {
+        pending.clear();
+        self.save(pending);
+      }
+      Err(error) => debug!("failed to upload telemetry, retrying later: {error}"),
+    }
+  }
+
+  fn load(&self) -> Vec<TelemetryEvent> {
+    let Ok(contents) = fs::read_to_string(&self.path) else {
+      return Vec::new();
+    };
+
+    contents.lines().filter_map(TelemetryEvent::decode).collect()
+  }
+
+  fn append(&self, event: &TelemetryEvent) {
+    let result = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(&self.path)
+      .and_then(|mut file| writeln!(file, "{}", event.encode()));
+    if let Err(error) = result {
+      error!("failed to queue telemetry event: {error}");
+    }
+  }
+
+  fn save(&self, pending: &[TelemetryEvent]) {
+    let contents: String = pending.iter().map(|event| event.encode() + "\n").collect();
+    if let Err(error) = fs::write(&self.path, contents) {
+      error!("failed to save telemetry queue: {error}");
+    }
+  }
+
+  fn delete(&self) {
+    match fs::remove_file(&self.path) {
+      Err(error) if error.kind() != io::ErrorKind::NotFound => error!("failed to delete telemetry queue: {error}"),
+      _ => (),
+    }
+  }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+  time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}
+
+fn escape(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '\\' => escaped += r"\\",
+      '\t' => escaped += r"\t",
+      '\n' => escaped += r"\n",
+      '\r' => escaped += r"\r",
+      '=' => escaped += r"\e",
+      c => escaped.push(c),
+    }
+  }
+
+  escaped
+}
+
+fn unescape(text: &str) -> String {
+  let mut unescaped = String::with_capacity(text.len());
+  let mut chars = text.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      unescaped.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('t') => unescaped.push('\t'),
+      Some('n') => unescaped.push('\n'),
+      Some('r') => unescaped.push('\r'),
+      Some('e') => unescaped.push('='),
+      Some(c) => unescaped.push(c),
+      None => unescaped.push('\\'),
+    }
+  }
+
+  unescaped
+}
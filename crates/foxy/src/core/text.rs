@@ -0,0 +1,212 @@
+use std::fmt::Debug;
+
+use egui::{
+  text::{LayoutJob, TextFormat},
+  Align,
+  Color32,
+  FontData,
+  FontDefinitions,
+  FontFamily,
+  FontId,
+  Stroke,
+};
+use tracing::*;
+
+/// Fonts added on top of egui's defaults.
+///
+/// Fallback fonts are searched in order for glyphs the fonts before them lack, e.g. a CJK font followed by an emoji
+/// font. Fonts that come with the current locale are searched before these.
+#[derive(Default, Clone)]
+pub struct FontSettings {
+  fallbacks: Vec<(String, Vec<u8>)>,
+  bold: Option<Vec<u8>>,
+}
+
+impl Debug for FontSettings {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FontSettings")
+      .field("fallbacks", &self.fallbacks.iter().map(|(name, _)| name).collect::<Vec<_>>())
+      .field("bold", &self.bold.is_some())
+      .finish()
+  }
+}
+
+impl FontSettings {
+  /// The font family used for `[b]` spans in [`Markup`]. It's the regular proportional family unless a bold font is
+  /// set.
+  pub const BOLD_FAMILY: &'static str = "bold";
+
+  pub fn with_fallback(mut self, name: impl Into<String>, data: Vec<u8>) -> Self {
+    self.fallbacks.push((name.into(), data));
+    self
+  }
+
+  pub fn with_bold(mut self, data: Vec<u8>) -> Self {
+    self.bold = Some(data);
+    self
+  }
+
+  /// Builds egui's font definitions, searching `locale_fonts` before the fallbacks.
+  pub(crate) fn definitions(&self, locale_fonts: &[(String, Vec<u8>)]) -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+
+    for (name, data) in locale_fonts.iter().chain(&self.fallbacks) {
+      fonts.font_data.insert(name.clone(), FontData::from_owned(data.clone()));
+      for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts.families.entry(family).or_default().push(name.clone());
+      }
+    }
+
+    let mut bold_family = fonts.families.get(&FontFamily::Proportional).cloned().unwrap_or_default();
+    if let Some(data) = &self.bold {
+      fonts
+        .font_data
+        .insert(Self::BOLD_FAMILY.to_string(), FontData::from_owned(data.clone()));
+      bold_family.insert(0, Self::BOLD_FAMILY.to_string());
+    }
+    fonts
+      .families
+      .insert(FontFamily::Name(Self::BOLD_FAMILY.into()), bold_family);
+
+    fonts
+  }
+}
+
+/// Lays out text with inline styling, wrapped to a width and aligned, for use as an egui label.
+///
+/// Spans are marked with `[b]bold[/b]`, `[i]italics[/i]`, `[u]underline[/u]` and `[color=#rrggbb]color[/color]`,
+/// and may be nested. A `[` that doesn't start a known tag is kept as-is.
+#[derive(Debug, Clone)]
+pub struct Markup {
+  font_size: f32,
+  color: Color32,
+  wrap_width: f32,
+  align: Align,
+}
+
+impl Default for Markup {
+  fn default() -> Self {
+    Self {
+      font_size: Self::DEFAULT_FONT_SIZE,
+      color: Color32::GRAY,
+      wrap_width: f32::INFINITY,
+      align: Align::LEFT,
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Span {
+  Bold,
+  Italics,
+  Underline,
+  Color(Color32),
+}
+
+impl Markup {
+  pub const DEFAULT_FONT_SIZE: f32 = 14.0;
+
+  pub fn with_font_size(mut self, font_size: f32) -> Self {
+    self.font_size = font_size;
+    self
+  }
+
+  pub fn with_color(mut self, color: Color32) -> Self {
+    self.color = color;
+    self
+  }
+
+  /// Wraps lines at word boundaries once they reach `wrap_width` points.
+  pub fn with_wrap_width(mut self, wrap_width: f32) -> Self {
+    self.wrap_width = wrap_width;
+    self
+  }
+
+  pub fn with_align(mut self, align: Align) -> Self {
+    self.align = align;
+    self
+  }
+
+  pub fn layout(&self, markup: &str) -> LayoutJob {
+    let mut job = LayoutJob {
+      halign: self.align,
+      ..Default::default()
+    };
+    job.wrap.max_width = self.wrap_width;
+
+    let mut spans: Vec<Span> = Vec::new();
+    let mut rest = markup;
+    while !rest.is_empty() {
+      let (text, tag) = match rest.find('[') {
+        Some(start) => rest.split_at(start),
+        None => (rest, ""),
+      };
+      if !text.is_empty() {
+        job.append(text, 0.0, self.format(&spans));
+      }
+
+      let Some(end) = tag.find(']') else {
+        if !tag.is_empty() {
+          job.append(tag, 0.0, self.format(&spans));
+        }
+        break;
+      };
+      let (name, remainder) = (&tag[1..end], &tag[end + 1..]);
+
+      match Self::parse_tag(name) {
+        Some(Ok(span)) => spans.push(span),
+        Some(Err(())) => {
+          if spans.pop().is_none() {
+            warn!("unbalanced closing tag [{name}] in {markup:?}");
+          }
+        }
+        None => job.append(&tag[..=end], 0.0, self.format(&spans)),
+      }
+
+      rest = remainder;
+    }
+
+    job
+  }
+}
+
+impl Markup {
+  /// `Ok` for opening tags and `Err` for closing ones, or `None` if it's not a tag.
+  fn parse_tag(name: &str) -> Option<Result<Span, ()>> {
+    match name {
+      "b" => Some(Ok(Span::Bold)),
+      "i" => Some(Ok(Span::Italics)),
+      "u" => Some(Ok(Span::Underline)),
+      "/b" | "/i" | "/u" | "/color" => Some(Err(())),
+      _ => {
+        let hex = name.strip_prefix("color=#").filter(|hex| hex.len() == 6)?;
+        let [_, r, g, b] = u32::from_str_radix(hex, 16).ok()?.to_be_bytes();
+        Some(Ok(Span::Color(Color32::from_rgb(r, g, b))))
+      }
+    }
+  }
+
+  fn format(&self, spans: &[Span]) -> TextFormat {
+    let mut format = TextFormat {
+      font_id: FontId::proportional(self.font_size),
+      color: self.color,
+      ..Default::default()
+    };
+
+    let mut underline = false;
+    for span in spans {
+      match *span {
+        Span::Bold => format.font_id.family = FontFamily::Name(FontSettings::BOLD_FAMILY.into()),
+        Span::Italics => format.italics = true,
+        Span::Underline => underline = true,
+        Span::Color(color) => format.color = color,
+      }
+    }
+
+    if underline {
+      format.underline = Stroke::new(1.0, format.color);
+    }
+
+    format
+  }
+}
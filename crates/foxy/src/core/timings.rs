@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use super::profiling::ScopeTiming;
+
+/// How long each stage of the game loop took in the last frame, for profiling tools such as the
+/// [`Inspector`](super::inspector::Inspector).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrameTimings {
+  /// The number of the frame, counting from 0.
+  pub frame: u64,
+  /// All the fixed ticks of the frame together.
+  pub fixed_update: Duration,
+  pub fixed_ticks: u32,
+  pub plugins: Duration,
+  /// The app's `input`, `update` and `window` callbacks.
+  pub update: Duration,
+  pub gui: Duration,
+  /// Filling in the render data.
+  pub render: Duration,
+  /// Recording and submitting the last frame on the render thread, which runs alongside the game loop.
+  pub draw: Duration,
+  /// The [`foxy_profile_scope!`](crate::foxy_profile_scope)s that ran in the frame, in the order they first finished.
+  pub scopes: Vec<ScopeTiming>,
+}
+
+impl FrameTimings {
+  pub fn total(&self) -> Duration {
+    self.fixed_update + self.plugins + self.update + self.gui + self.render
+  }
+
+  /// An estimate of how many times faster frames come out than if the game loop waited for each frame to be drawn
+  /// before starting the next one. Approaches 2 when both threads take equally long.
+  ///
+  /// This is only an upper bound worked out from the stage timings, assuming the threads overlap perfectly: it doesn't
+  /// measure time spent waiting on the state lock, the GPU or vsync, and `draw` is from the render thread's last frame,
+  /// which may not be the frame the other timings are from.
+  pub fn pipelining_speedup(&self) -> f64 {
+    let pipelined = self.total().max(self.draw);
+    if pipelined.is_zero() {
+      return 1.0;
+    }
+    (self.total() + self.draw).as_secs_f64() / pipelined.as_secs_f64()
+  }
+
+  /// The stages by name, in the order they run.
+  pub fn stages(&self) -> [(&'static str, Duration); 5] {
+    [
+      ("fixed update", self.fixed_update),
+      ("plugins", self.plugins),
+      ("update", self.update),
+      ("gui", self.gui),
+      ("render", self.render),
+    ]
+  }
+
+  /// Runs `stage`, adding the time it took to `duration`.
+  pub(crate) fn time<R>(duration: &mut Duration, stage: impl FnOnce() -> R) -> R {
+    let start = Instant::now();
+    let result = stage();
+    *duration += start.elapsed();
+    result
+  }
+}
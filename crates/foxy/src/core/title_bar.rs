@@ -0,0 +1,26 @@
+use winit::{dpi::PhysicalPosition, window::ResizeDirection};
+
+/// What a point of the window does when pressed, for windows that draw their own title bar and borders.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum WindowArea {
+  /// Pressing goes to the app as usual.
+  #[default]
+  Client,
+  /// Drags the window, with the platform's snapping. Double-clicking toggles maximized.
+  Caption,
+  /// Resizes the window from the given edge or corner.
+  Resize(ResizeDirection),
+}
+
+/// Decides which [`WindowArea`] a point is in, in physical pixels from the top left of the window's client area.
+pub type HitTest = dyn Fn(PhysicalPosition<f64>) -> WindowArea + Send + Sync;
+
+/// The buttons of a custom-drawn title bar.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TitleBarButton {
+  Minimize,
+  /// Maximizes the window, or restores it if it already is.
+  Maximize,
+  /// Asks the app to close, like the native close button does.
+  Close,
+}
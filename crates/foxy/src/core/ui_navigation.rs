@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{Event, Key, Modifiers, RawInput};
+
+use super::input::{action::Binding, gamepad::GamepadButton, Input};
+
+/// A step of moving through the UI without a mouse.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UiAction {
+  /// Moves focus to the nearest widget above the focused one.
+  Up,
+  Down,
+  Left,
+  Right,
+  /// Moves focus to the next widget in tab order.
+  Next,
+  Previous,
+  /// Presses the focused widget.
+  Submit,
+  /// Drops focus and closes open popups, and tells the app to back out of the menu.
+  Cancel,
+}
+
+impl UiAction {
+  /// The key egui already handles the action with.
+  fn key(self) -> (Key, Modifiers) {
+    match self {
+      UiAction::Up => (Key::ArrowUp, Modifiers::NONE),
+      UiAction::Down => (Key::ArrowDown, Modifiers::NONE),
+      UiAction::Left => (Key::ArrowLeft, Modifiers::NONE),
+      UiAction::Right => (Key::ArrowRight, Modifiers::NONE),
+      UiAction::Next => (Key::Tab, Modifiers::NONE),
+      UiAction::Previous => (Key::Tab, Modifiers::SHIFT),
+      UiAction::Submit => (Key::Enter, Modifiers::NONE),
+      UiAction::Cancel => (Key::Escape, Modifiers::NONE),
+    }
+  }
+
+  fn from_key(key: Key, modifiers: Modifiers) -> Option<Self> {
+    match key {
+      Key::ArrowUp => Some(UiAction::Up),
+      Key::ArrowDown => Some(UiAction::Down),
+      Key::ArrowLeft => Some(UiAction::Left),
+      Key::ArrowRight => Some(UiAction::Right),
+      Key::Tab if modifiers.shift => Some(UiAction::Previous),
+      Key::Tab => Some(UiAction::Next),
+      Key::Enter | Key::Space => Some(UiAction::Submit),
+      Key::Escape => Some(UiAction::Cancel),
+      _ => None,
+    }
+  }
+
+  fn is_directional(self) -> bool {
+    matches!(self, UiAction::Up | UiAction::Down | UiAction::Left | UiAction::Right)
+  }
+}
+
+/// Keyboard and gamepad focus handling for the UI, so menus can be used without a mouse.
+///
+/// The keyboard works as egui has it: Tab and Shift+Tab move through widgets in order, the arrow keys move to the
+/// nearest widget in their direction, Enter or Space presses the focused widget and Escape drops focus. Inputs bound
+/// to a [`UiAction`], by default the gamepad's D-pad, shoulder buttons, South and East, are turned into those keys
+/// before the UI runs. When nothing has focus, the first directional press focuses the first widget instead.
+///
+/// Each press moves one step; holding a button doesn't repeat.
+#[derive(Debug, Clone)]
+pub struct UiNavigation {
+  enabled: bool,
+  bindings: HashMap<UiAction, Vec<Binding>>,
+  /// The actions taken this frame, from bindings and the keyboard alike.
+  pressed: HashSet<UiAction>,
+}
+
+impl Default for UiNavigation {
+  fn default() -> Self {
+    Self::new()
+      .with_binding(UiAction::Up, Binding::Gamepad(GamepadButton::DPadUp))
+      .with_binding(UiAction::Down, Binding::Gamepad(GamepadButton::DPadDown))
+      .with_binding(UiAction::Left, Binding::Gamepad(GamepadButton::DPadLeft))
+      .with_binding(UiAction::Right, Binding::Gamepad(GamepadButton::DPadRight))
+      .with_binding(UiAction::Next, Binding::Gamepad(GamepadButton::RightShoulder))
+      .with_binding(UiAction::Previous, Binding::Gamepad(GamepadButton::LeftShoulder))
+      .with_binding(UiAction::Submit, Binding::Gamepad(GamepadButton::South))
+      .with_binding(UiAction::Cancel, Binding::Gamepad(GamepadButton::East))
+  }
+}
+
+impl UiNavigation {
+  /// Navigation with nothing bound, leaving only egui's own keyboard handling.
+  pub fn new() -> Self {
+    Self {
+      enabled: true,
+      bindings: HashMap::new(),
+      pressed: HashSet::new(),
+    }
+  }
+
+  /// Whether bound inputs navigate the UI, e.g. off while the gamepad controls the game rather than a menu.
+  pub fn with_enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+
+  pub fn with_binding(mut self, action: UiAction, binding: Binding) -> Self {
+    self.bind(action, binding);
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+
+  pub fn set_enabled(&mut self, enabled: bool) {
+    self.enabled = enabled;
+  }
+
+  /// Adds `binding` to the inputs that take `action`.
+  pub fn bind(&mut self, action: UiAction, binding: Binding) {
+    let bindings = self.bindings.entry(action).or_default();
+    if !bindings.contains(&binding) {
+      bindings.push(binding);
+    }
+  }
+
+  pub fn unbind(&mut self, action: UiAction) {
+    self.bindings.remove(&action);
+  }
+
+  /// The inputs bound to `action`, in the order they were bound.
+  pub fn bindings(&self, action: UiAction) -> &[Binding] {
+    self.bindings.get(&action).map_or(&[], Vec::as_slice)
+  }
+
+  /// Whether `action` was taken this frame, e.g. [`UiAction::Cancel`] to close the open menu.
+  pub fn just_pressed(&self, action: UiAction) -> bool {
+    self.pressed.contains(&action)
+  }
+
+  /// Adds key presses for the actions bound inputs took this frame to `raw_input`, and notes every action taken.
+  pub(crate) fn apply(&mut self, input: &Input, has_focus: bool, raw_input: &mut RawInput) {
+    self.pressed.clear();
+
+    for event in &raw_input.events {
+      if let Event::Key {
+        key,
+        pressed: true,
+        repeat: false,
+        modifiers,
+        ..
+      } = event
+      {
+        self.pressed.extend(UiAction::from_key(*key, *modifiers));
+      }
+    }
+
+    if !self.enabled {
+      return;
+    }
+
+    let mut has_focus = has_focus;
+    for (&action, bindings) in &self.bindings {
+      if !bindings.iter().any(|&binding| input.binding_just_pressed(binding)) {
+        continue;
+      }
+
+      // egui only moves focus by direction from a focused widget, so start from the first one in tab order
+      let (key, modifiers) = if action.is_directional() && !has_focus {
+        has_focus = true;
+        UiAction::Next.key()
+      } else {
+        action.key()
+      };
+
+      raw_input.events.extend([true, false].map(|pressed| Event::Key {
+        key,
+        physical_key: None,
+        pressed,
+        repeat: false,
+        modifiers,
+      }));
+      self.pressed.insert(action);
+    }
+  }
+}
@@ -3,7 +3,11 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 #![feature(associated_type_defaults)]
 
+// lets the derives, which name the crate `::foxy`, be used inside it
+extern crate self as foxy;
+
 pub mod core;
+pub mod math;
 pub mod prelude;
 pub mod window;
 
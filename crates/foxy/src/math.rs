@@ -0,0 +1,3 @@
+pub mod damping;
+pub mod easing;
+pub mod noise;
@@ -0,0 +1,89 @@
+use std::ops::{Add, Mul, Sub};
+
+use glam::{Quat, Vec2, Vec3, Vec4};
+
+/// Values [`smooth_damp`] can move towards a target.
+pub trait SmoothDamp: Copy {
+  /// How fast the value is changing, per second.
+  type Velocity: Copy + Default;
+
+  fn smooth_damp(self, target: Self, velocity: &mut Self::Velocity, smooth_time: f32, delta: f32) -> Self;
+}
+
+/// Moves `current` towards `target` like a critically damped spring: as quickly as possible without overshooting, and
+/// without the jolt of snapping to a new target, e.g. for a camera following the player or a UI element sliding into
+/// place.
+///
+/// `velocity` carries the motion from one frame to the next, so it has to be kept between calls for the same value,
+/// starting at zero. `smooth_time` is roughly how long it takes to reach the target, in seconds, and `delta` is the
+/// time since the last call, e.g. [`Clock::delta_secs`](foxy_utils::time::Clock::delta_secs). Unlike easing with a
+/// fixed fraction per frame, the motion is the same at any frame rate.
+pub fn smooth_damp<T: SmoothDamp>(
+  current: T,
+  target: T,
+  velocity: &mut T::Velocity,
+  smooth_time: f32,
+  delta: f32,
+) -> T {
+  current.smooth_damp(target, velocity, smooth_time, delta)
+}
+
+impl SmoothDamp for f32 {
+  type Velocity = f32;
+
+  fn smooth_damp(self, target: Self, velocity: &mut Self::Velocity, smooth_time: f32, delta: f32) -> Self {
+    damp(self, target, velocity, smooth_time, delta, |a, b| a * b)
+  }
+}
+
+macro_rules! smooth_damp_vector {
+  ($($vector:ty),*) => {
+    $(
+      impl SmoothDamp for $vector {
+        type Velocity = $vector;
+
+        fn smooth_damp(self, target: Self, velocity: &mut Self::Velocity, smooth_time: f32, delta: f32) -> Self {
+          damp(self, target, velocity, smooth_time, delta, <$vector>::dot)
+        }
+      }
+    )*
+  };
+}
+
+smooth_damp_vector!(Vec2, Vec3, Vec4);
+
+/// Damps the components of the rotation and renormalizes, which is close to damping along the shortest arc for the
+/// small steps of a frame.
+impl SmoothDamp for Quat {
+  type Velocity = Vec4;
+
+  fn smooth_damp(self, target: Self, velocity: &mut Self::Velocity, smooth_time: f32, delta: f32) -> Self {
+    // `target` and its negation are the same rotation, so head for whichever is the short way round
+    let target = if self.dot(target) < 0.0 { -target } else { target };
+    let damped = Vec4::from(self).smooth_damp(Vec4::from(target), velocity, smooth_time, delta);
+    Quat::from_vec4(damped).normalize()
+  }
+}
+
+/// The critically damped spring of Game Programming Gems 4, chapter 1.10, with the exponential decay approximated by a
+/// polynomial that's accurate for the time steps of a frame.
+fn damp<T>(current: T, target: T, velocity: &mut T, smooth_time: f32, delta: f32, dot: impl Fn(T, T) -> f32) -> T
+where
+  T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+  let omega = 2.0 / smooth_time.max(1e-4);
+  let x = omega * delta;
+  let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+
+  let change = current - target;
+  let temp = (*velocity + change * omega) * delta;
+  *velocity = (*velocity - temp * omega) * decay;
+  let output = target + (change + temp) * decay;
+
+  // the approximation can step past the target on long frames, so stop on it instead
+  if dot(target - current, output - target) > 0.0 {
+    *velocity = T::default();
+    return target;
+  }
+  output
+}
@@ -0,0 +1,224 @@
+use std::{
+  f32::consts::{FRAC_PI_2, TAU},
+  ops::{Add, Mul, Sub},
+};
+
+use egui::{ComboBox, Ui};
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+
+use crate::core::{
+  inspector::Inspect,
+  reflect::{Reflect, Value},
+};
+
+/// The shape of a tween: how far along to be at each fraction of its duration.
+///
+/// Every easing goes from 0 at the start to 1 at the end. `In` easings start slowly, `Out` easings end slowly and
+/// `InOut` easings do both. Back and elastic easings overshoot, going below 0 or above 1 on the way.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, EnumIter, IntoStaticStr)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+  #[default]
+  Linear,
+  QuadIn,
+  QuadOut,
+  QuadInOut,
+  CubicIn,
+  CubicOut,
+  CubicInOut,
+  QuartIn,
+  QuartOut,
+  QuartInOut,
+  QuintIn,
+  QuintOut,
+  QuintInOut,
+  SineIn,
+  SineOut,
+  SineInOut,
+  ExpoIn,
+  ExpoOut,
+  ExpoInOut,
+  CircIn,
+  CircOut,
+  CircInOut,
+  /// Pulls back before setting off.
+  BackIn,
+  /// Overshoots the end before settling on it.
+  BackOut,
+  BackInOut,
+  /// Winds up with growing oscillations.
+  ElasticIn,
+  /// Settles on the end with shrinking oscillations, like a spring.
+  ElasticOut,
+  ElasticInOut,
+  BounceIn,
+  /// Bounces on the end like a dropped ball.
+  BounceOut,
+  BounceInOut,
+}
+
+impl Easing {
+  /// How far along to be at `t`, the fraction of the tween's duration that has passed.
+  pub fn ease(self, t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    match self {
+      Easing::Linear => t,
+      Easing::QuadIn => quad(t),
+      Easing::QuadOut => out(quad, t),
+      Easing::QuadInOut => in_out(quad, t),
+      Easing::CubicIn => cubic(t),
+      Easing::CubicOut => out(cubic, t),
+      Easing::CubicInOut => in_out(cubic, t),
+      Easing::QuartIn => quart(t),
+      Easing::QuartOut => out(quart, t),
+      Easing::QuartInOut => in_out(quart, t),
+      Easing::QuintIn => quint(t),
+      Easing::QuintOut => out(quint, t),
+      Easing::QuintInOut => in_out(quint, t),
+      Easing::SineIn => sine(t),
+      Easing::SineOut => out(sine, t),
+      Easing::SineInOut => in_out(sine, t),
+      Easing::ExpoIn => expo(t),
+      Easing::ExpoOut => out(expo, t),
+      Easing::ExpoInOut => in_out(expo, t),
+      Easing::CircIn => circ(t),
+      Easing::CircOut => out(circ, t),
+      Easing::CircInOut => in_out(circ, t),
+      Easing::BackIn => back(t),
+      Easing::BackOut => out(back, t),
+      Easing::BackInOut => in_out(back, t),
+      Easing::ElasticIn => elastic(t),
+      Easing::ElasticOut => out(elastic, t),
+      Easing::ElasticInOut => in_out(elastic, t),
+      Easing::BounceIn => out(bounce, t),
+      Easing::BounceOut => bounce(t),
+      Easing::BounceInOut => in_out(|t| out(bounce, t), t),
+    }
+  }
+
+  /// The value `t` of the way from `from` to `to` along the easing, e.g. a position, scale or color.
+  pub fn tween<T>(self, from: T, to: T, t: f32) -> T
+  where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+  {
+    from + (to - from) * self.ease(t)
+  }
+
+  pub fn name(self) -> &'static str {
+    self.into()
+  }
+}
+
+impl Inspect for Easing {
+  fn inspect(&mut self, ui: &mut Ui) -> bool {
+    let before = *self;
+    ComboBox::from_id_source(ui.next_auto_id())
+      .selected_text(self.name())
+      .show_ui(ui, |ui| {
+        for easing in Easing::iter() {
+          ui.selectable_value(self, easing, easing.name());
+        }
+      });
+    *self != before
+  }
+}
+
+/// Saved by name.
+impl Reflect for Easing {
+  fn to_value(&self) -> Value {
+    Value::String(self.name().to_string())
+  }
+
+  fn apply_value(&mut self, value: &Value) -> bool {
+    let Value::String(name) = value else {
+      return false;
+    };
+    match Easing::iter().find(|easing| easing.name() == name) {
+      Some(easing) => {
+        *self = easing;
+        true
+      }
+      None => false,
+    }
+  }
+}
+
+/// The ease-out version of the ease-in `ease_in`, which is it played backwards.
+fn out(ease_in: impl Fn(f32) -> f32, t: f32) -> f32 {
+  1.0 - ease_in(1.0 - t)
+}
+
+/// The ease-in-out version of the ease-in `ease_in`, which is it squeezed into the first half and played backwards in
+/// the second.
+fn in_out(ease_in: impl Fn(f32) -> f32, t: f32) -> f32 {
+  if t < 0.5 {
+    ease_in(2.0 * t) / 2.0
+  } else {
+    1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+  }
+}
+
+fn quad(t: f32) -> f32 {
+  t * t
+}
+
+fn cubic(t: f32) -> f32 {
+  t * t * t
+}
+
+fn quart(t: f32) -> f32 {
+  t * t * t * t
+}
+
+fn quint(t: f32) -> f32 {
+  t * t * t * t * t
+}
+
+fn sine(t: f32) -> f32 {
+  1.0 - (t * FRAC_PI_2).cos()
+}
+
+fn expo(t: f32) -> f32 {
+  // exactly 0 at the start, which the exponential only approaches
+  if t <= 0.0 {
+    0.0
+  } else {
+    2f32.powf(10.0 * t - 10.0)
+  }
+}
+
+fn circ(t: f32) -> f32 {
+  1.0 - (1.0 - t * t).max(0.0).sqrt()
+}
+
+fn back(t: f32) -> f32 {
+  // pulls back by 10% at its lowest
+  const OVERSHOOT: f32 = 1.701_58;
+  t * t * ((OVERSHOOT + 1.0) * t - OVERSHOOT)
+}
+
+fn elastic(t: f32) -> f32 {
+  if t <= 0.0 || t >= 1.0 {
+    return t;
+  }
+  -(2f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * TAU / 3.0).sin()
+}
+
+/// Bounces with ever smaller hops, ending on 1. It's an ease-out: the ball is dropped rather than thrown.
+fn bounce(t: f32) -> f32 {
+  const GRAVITY: f32 = 7.5625;
+  const HOP: f32 = 2.75;
+
+  if t < 1.0 / HOP {
+    GRAVITY * t * t
+  } else if t < 2.0 / HOP {
+    let t = t - 1.5 / HOP;
+    GRAVITY * t * t + 0.75
+  } else if t < 2.5 / HOP {
+    let t = t - 2.25 / HOP;
+    GRAVITY * t * t + 0.9375
+  } else {
+    let t = t - 2.625 / HOP;
+    GRAVITY * t * t + 0.984_375
+  }
+}
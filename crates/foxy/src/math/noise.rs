@@ -0,0 +1,244 @@
+use foxy_renderer::renderer::shader::ShaderStore;
+use glam::{IVec2, IVec3, Vec2, Vec3};
+
+/// The WGSL version of these functions, for pasting in front of a shader's own source. Shaders loaded through the
+/// [`ShaderStore`] can pull it in with `#include "noise.wgsl"` instead.
+///
+/// With the same seed, the shader functions (`perlin2`, `simplex3`, `worley2`, ...) return the same values as
+/// [`Noise`], up to floating point differences between the CPU and GPU.
+pub const WGSL: &str = ShaderStore::NOISE_SNIPPET;
+
+/// Seedable gradient and cellular noise, e.g. for terrain heights, particle turbulence and procedural textures.
+///
+/// Every function is a pure function of the seed and the point, so the same seed gives the same noise on every
+/// machine, and on the GPU through [`WGSL`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Noise {
+  pub seed: u32,
+}
+
+impl Noise {
+  pub fn new(seed: u32) -> Self {
+    Self { seed }
+  }
+
+  /// Perlin noise, roughly from -1 to 1 and 0 at every integer point.
+  pub fn perlin2(&self, point: Vec2) -> f32 {
+    let cell = point.floor();
+    let local = point - cell;
+    let cell = cell.as_ivec2();
+    let fade = local.to_array().map(fade);
+
+    let corner = |offset: IVec2| {
+      let hash = self.hash2(cell + offset);
+      grad2(hash, local - offset.as_vec2())
+    };
+    let bottom = lerp(corner(IVec2::new(0, 0)), corner(IVec2::new(1, 0)), fade[0]);
+    let top = lerp(corner(IVec2::new(0, 1)), corner(IVec2::new(1, 1)), fade[0]);
+    lerp(bottom, top, fade[1])
+  }
+
+  /// Perlin noise, roughly from -1 to 1 and 0 at every integer point.
+  pub fn perlin3(&self, point: Vec3) -> f32 {
+    let cell = point.floor();
+    let local = point - cell;
+    let cell = cell.as_ivec3();
+    let fade = local.to_array().map(fade);
+
+    let corner = |offset: IVec3| {
+      let hash = self.hash3(cell + offset);
+      grad3(hash, local - offset.as_vec3())
+    };
+    let layer = |z: i32| {
+      let bottom = lerp(corner(IVec3::new(0, 0, z)), corner(IVec3::new(1, 0, z)), fade[0]);
+      let top = lerp(corner(IVec3::new(0, 1, z)), corner(IVec3::new(1, 1, z)), fade[0]);
+      lerp(bottom, top, fade[1])
+    };
+    lerp(layer(0), layer(1), fade[2])
+  }
+
+  /// Simplex noise, roughly from -1 to 1. Cheaper than Perlin noise in 3D and without its grid aligned artifacts.
+  pub fn simplex2(&self, point: Vec2) -> f32 {
+    const SKEW: f32 = 0.366_025_42; // (sqrt(3) - 1) / 2
+    const UNSKEW: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+
+    let cell = (point + (point.x + point.y) * SKEW).floor();
+    let first = point - (cell - (cell.x + cell.y) * UNSKEW);
+    let step = if first.x > first.y {
+      IVec2::new(1, 0)
+    } else {
+      IVec2::new(0, 1)
+    };
+    let cell = cell.as_ivec2();
+
+    let corner = |offset: IVec2, local: Vec2| {
+      let t = 0.5 - local.length_squared();
+      if t <= 0.0 {
+        0.0
+      } else {
+        t * t * t * t * grad2(self.hash2(cell + offset), local)
+      }
+    };
+    let sum = corner(IVec2::ZERO, first)
+      + corner(step, first - step.as_vec2() + UNSKEW)
+      + corner(IVec2::ONE, first - 1.0 + 2.0 * UNSKEW);
+    70.0 * sum
+  }
+
+  /// Simplex noise, roughly from -1 to 1. Cheaper than Perlin noise in 3D and without its grid aligned artifacts.
+  pub fn simplex3(&self, point: Vec3) -> f32 {
+    const SKEW: f32 = 1.0 / 3.0;
+    const UNSKEW: f32 = 1.0 / 6.0;
+
+    let cell = (point + (point.x + point.y + point.z) * SKEW).floor();
+    let first = point - (cell - (cell.x + cell.y + cell.z) * UNSKEW);
+    // the corners of the tetrahedron the point is in, walking from the cell's origin towards its far corner
+    let (step1, step2) = if first.x >= first.y {
+      if first.y >= first.z {
+        (IVec3::new(1, 0, 0), IVec3::new(1, 1, 0))
+      } else if first.x >= first.z {
+        (IVec3::new(1, 0, 0), IVec3::new(1, 0, 1))
+      } else {
+        (IVec3::new(0, 0, 1), IVec3::new(1, 0, 1))
+      }
+    } else if first.y < first.z {
+      (IVec3::new(0, 0, 1), IVec3::new(0, 1, 1))
+    } else if first.x < first.z {
+      (IVec3::new(0, 1, 0), IVec3::new(0, 1, 1))
+    } else {
+      (IVec3::new(0, 1, 0), IVec3::new(1, 1, 0))
+    };
+    let cell = cell.as_ivec3();
+
+    let corner = |offset: IVec3, local: Vec3| {
+      let t = 0.5 - local.length_squared();
+      if t <= 0.0 {
+        0.0
+      } else {
+        t * t * t * t * grad3(self.hash3(cell + offset), local)
+      }
+    };
+    let sum = corner(IVec3::ZERO, first)
+      + corner(step1, first - step1.as_vec3() + UNSKEW)
+      + corner(step2, first - step2.as_vec3() + 2.0 * UNSKEW)
+      + corner(IVec3::ONE, first - 1.0 + 3.0 * UNSKEW);
+    76.0 * sum
+  }
+
+  /// Worley (cellular) noise: the distance to the nearest of a scattering of points, one per grid cell. 0 at the
+  /// points themselves and rarely above 1.
+  pub fn worley2(&self, point: Vec2) -> f32 {
+    let cell = point.floor();
+    let local = point - cell;
+    let cell = cell.as_ivec2();
+
+    let mut nearest = f32::MAX;
+    for y in -1..=1 {
+      for x in -1..=1 {
+        let offset = IVec2::new(x, y);
+        let hash = self.hash2(cell + offset);
+        let feature = offset.as_vec2() + Vec2::new(unit(hash), unit(hash_u32(hash)));
+        nearest = nearest.min(feature.distance_squared(local));
+      }
+    }
+    nearest.sqrt()
+  }
+
+  /// Worley (cellular) noise: the distance to the nearest of a scattering of points, one per grid cell. 0 at the
+  /// points themselves and rarely above 1.
+  pub fn worley3(&self, point: Vec3) -> f32 {
+    let cell = point.floor();
+    let local = point - cell;
+    let cell = cell.as_ivec3();
+
+    let mut nearest = f32::MAX;
+    for z in -1..=1 {
+      for y in -1..=1 {
+        for x in -1..=1 {
+          let offset = IVec3::new(x, y, z);
+          let hash = self.hash3(cell + offset);
+          let hash_y = hash_u32(hash);
+          let feature = offset.as_vec3() + Vec3::new(unit(hash), unit(hash_y), unit(hash_u32(hash_y)));
+          nearest = nearest.min(feature.distance_squared(local));
+        }
+      }
+    }
+    nearest.sqrt()
+  }
+
+  /// Sums `octaves` layers of `noise`, each at `lacunarity` times the frequency and `gain` times the amplitude of the
+  /// one before, for detail at several scales. The sum is normalized back to the range of a single layer, e.g.
+  /// `Noise::fbm(5, 2.0, 0.5, |frequency| noise.perlin2(point * frequency))`.
+  pub fn fbm(octaves: u32, lacunarity: f32, gain: f32, mut noise: impl FnMut(f32) -> f32) -> f32 {
+    let mut sum = 0.0;
+    let mut total_amplitude = 0.0;
+    let mut frequency = 1.0;
+    let mut amplitude = 1.0;
+    for _ in 0..octaves {
+      sum += amplitude * noise(frequency);
+      total_amplitude += amplitude;
+      frequency *= lacunarity;
+      amplitude *= gain;
+    }
+    if total_amplitude > 0.0 {
+      sum / total_amplitude
+    } else {
+      0.0
+    }
+  }
+
+  fn hash2(&self, cell: IVec2) -> u32 {
+    hash_u32(cell.x as u32 ^ hash_u32(cell.y as u32 ^ hash_u32(self.seed)))
+  }
+
+  fn hash3(&self, cell: IVec3) -> u32 {
+    hash_u32(cell.x as u32 ^ hash_u32(cell.y as u32 ^ hash_u32(cell.z as u32 ^ hash_u32(self.seed))))
+  }
+}
+
+/// PCG's output permutation, which spreads every input bit over the whole output.
+fn hash_u32(value: u32) -> u32 {
+  let state = value.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+  let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277_803_737);
+  (word >> 22) ^ word
+}
+
+/// `hash` as a number from 0 to 1.
+fn unit(hash: u32) -> f32 {
+  (hash >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// The dot product of `local` with one of 8 gradients picked by `hash`.
+fn grad2(hash: u32, local: Vec2) -> f32 {
+  match hash & 7 {
+    0 => local.x + local.y,
+    1 => -local.x + local.y,
+    2 => local.x - local.y,
+    3 => -local.x - local.y,
+    4 => local.x,
+    5 => -local.x,
+    6 => local.y,
+    _ => -local.y,
+  }
+}
+
+/// The dot product of `local` with one of the 12 edge gradients of improved Perlin noise, picked by `hash`.
+fn grad3(hash: u32, local: Vec3) -> f32 {
+  let hash = hash & 15;
+  let u = if hash < 8 { local.x } else { local.y };
+  let v = match hash {
+    0..=3 => local.y,
+    12 | 14 => local.x,
+    _ => local.z,
+  };
+  (if hash & 1 == 0 { u } else { -u }) + (if hash & 2 == 0 { v } else { -v })
+}
+
+/// Perlin's quintic fade, which smooths the blend between corners so the noise has no visible creases.
+fn fade(t: f32) -> f32 {
+  t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+  a + (b - a) * t
+}
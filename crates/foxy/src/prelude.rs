@@ -1,11 +1,77 @@
-pub use foxy_utils::log::prelude::*;
+pub use foxy_renderer::renderer::{
+  camera::Camera,
+  context::PresentMode,
+  present::PresentStats,
+  procedural::ProceduralTexture,
+  profiler::{FrameStats, PassTiming},
+  proxy::{RendererProxy, ResourceHandle},
+  render_data::{
+    ColorBlindness,
+    ColorFilter,
+    DebugLine,
+    DisplayCalibration,
+    Draw,
+    MaterialOverrides,
+    RenderData,
+    RenderLayers,
+    SkinnedDraw,
+  },
+  render_pass::{CustomPass, Pass, PassOrder},
+};
+pub use foxy_utils::{
+  geometry::{Aabb, Frustum, Obb, Plane, Ray, Sphere},
+  log::prelude::*,
+  thread::priority::{ThreadPriority, ThreadSettings},
+  tracking::{resource_stats, ResourceKind, ResourceStats},
+};
 
 pub use crate::core::{
-  builder::{DebugInfo, FoxyCreateInfo, Polling},
+  accessibility::Accessibility,
+  adaptive_quality::{AdaptiveQuality, QualitySettings},
+  assets::{Asset, AssetLoader, Assets, LoadContext, Preload, PreloadProgress},
+  builder::{AppSettings, DebugInfo, EngineThread, FoxyCreateInfo, Polling, UiScaling},
+  camera_controller::{CameraProbe, FlyController, FollowController, OrbitController},
+  curve::{Curve, CurveKey, Gradient, GradientStop, Interpolation},
+  deep_link::{register_uri_scheme, DeepLink},
   foxy_state::Foxy,
   event::{FoxyEvent, InputEvent, WindowEvent},
   foxy_loop::Framework,
+  gizmo::{Gizmo, GizmoAxis, GizmoMode, GizmoSpace, TransformDelta},
+  hitch::{Hitch, HitchDetector},
+  inspector::{Inspect, InspectEntity, Inspector},
+  input::{
+    action::{ActionMap, Binding},
+    gamepad::{GamepadButton, GamepadId, InputDevice, RumbleEffect, RumbleEnvelope, RumbleStacking},
+    key::{KeyCode, LogicalKey},
+    prompt::{Prompt, PromptDatabase},
+    recording::{InputPlayback, InputRecorder, InputRecording},
+    touch::{ActiveTouch, Pinch, Touch, TouchPhase},
+  },
+  localization::{Catalog, Localization},
   message::RenderLoopMessage,
+  plugin::{Plugin, RunIf, Stage},
+  profiling::{ProfileScope, ScopeTiming},
+  run_condition::RunCondition,
+  reflect::{Reflect, Value},
   runnable::Runnable,
+  single_instance::{Activation, SingleInstance},
+  stepping::Stepping,
+  streaming::{LevelStreamer, StreamingCell, StreamingEvents},
+  telemetry::{Telemetry, TelemetryEvent, TelemetrySink},
+  text::{FontSettings, Markup},
+  timings::FrameTimings,
+  title_bar::{HitTest, TitleBarButton, WindowArea},
+  ui_navigation::{UiAction, UiNavigation},
   FoxyResult,
 };
+pub use crate::{
+  math::{
+    damping::{smooth_damp, SmoothDamp},
+    easing::Easing,
+    noise::Noise,
+  },
+  window::{
+    display::{DisplayMode, WindowMode},
+    splash::SplashCreateInfo,
+  },
+};
@@ -1,3 +1,6 @@
+pub mod display;
+pub mod splash;
+
 use winit::{
   dpi::{Position, Size},
   event_loop::{EventLoop, EventLoopBuilder},
@@ -92,3 +95,63 @@ impl WindowCreateInfo {
     Ok((event_loop, window))
   }
 }
+
+/// Fades the whole window, decorations included, to `opacity`, from 0 for invisible to 1 for opaque.
+pub(crate) fn set_opacity(window: &Window, opacity: f32) -> FoxyResult<()> {
+  os::set_opacity(window, opacity.clamp(0.0, 1.0))
+}
+
+#[cfg(windows)]
+mod os {
+  use windows_sys::Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{
+      GetWindowLongPtrW,
+      SetLayeredWindowAttributes,
+      SetWindowLongPtrW,
+      GWL_EXSTYLE,
+      LWA_ALPHA,
+      WS_EX_LAYERED,
+    },
+  };
+  use winit::{
+    raw_window_handle::{HasWindowHandle, RawWindowHandle},
+    window::Window,
+  };
+
+  use crate::core::{FoxyError, FoxyResult};
+
+  pub fn set_opacity(window: &Window, opacity: f32) -> FoxyResult<()> {
+    let RawWindowHandle::Win32(handle) = window.window_handle()?.as_raw() else {
+      return Err(FoxyError::Error("expected a Win32 window".into()));
+    };
+    let hwnd = handle.hwnd.get() as HWND;
+
+    // SAFETY: the handle belongs to `window`, which outlives the calls
+    unsafe {
+      let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+      // opaque windows leave the layered style again, which keeps presenting to them as fast as before
+      if opacity >= 1.0 {
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style & !(WS_EX_LAYERED as isize));
+        return Ok(());
+      }
+
+      SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED as isize);
+      if SetLayeredWindowAttributes(hwnd, 0, (opacity * 255.0).round() as u8, LWA_ALPHA) == 0 {
+        return Err(std::io::Error::last_os_error().into());
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(not(windows))]
+mod os {
+  use winit::window::Window;
+
+  use crate::core::{FoxyError, FoxyResult};
+
+  pub fn set_opacity(_window: &Window, _opacity: f32) -> FoxyResult<()> {
+    Err(FoxyError::Error("window opacity is only supported on Windows".into()))
+  }
+}
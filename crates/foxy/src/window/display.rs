@@ -0,0 +1,129 @@
+use std::{
+  cmp::Reverse,
+  fmt,
+  sync::{Arc, Weak},
+};
+
+use winit::{
+  dpi::PhysicalSize,
+  monitor::VideoMode,
+  window::{Fullscreen, Window},
+};
+
+use crate::core::{FoxyError, FoxyResult};
+
+/// A resolution and refresh rate a display can be switched to for exclusive fullscreen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DisplayMode {
+  pub size: PhysicalSize<u32>,
+  pub refresh_rate_millihertz: u32,
+  pub bit_depth: u16,
+}
+
+impl DisplayMode {
+  /// The refresh rate in hertz, which isn't always a whole number, e.g. 59.94 Hz.
+  pub fn refresh_rate(&self) -> f32 {
+    self.refresh_rate_millihertz as f32 / 1000.0
+  }
+}
+
+impl From<&VideoMode> for DisplayMode {
+  fn from(video_mode: &VideoMode) -> Self {
+    Self {
+      size: video_mode.size(),
+      refresh_rate_millihertz: video_mode.refresh_rate_millihertz(),
+      bit_depth: video_mode.bit_depth(),
+    }
+  }
+}
+
+impl fmt::Display for DisplayMode {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}x{} @ {} Hz", self.size.width, self.size.height, self.refresh_rate())
+  }
+}
+
+/// How the window covers the screen.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum WindowMode {
+  #[default]
+  Windowed,
+  /// Covers the display the window is on without changing its mode, so switching to other windows is instant.
+  Borderless,
+  /// Takes the display the window is on over and switches it to the given mode, one of
+  /// [`State::display_modes`](crate::core::foxy_state::State::display_modes), e.g. for a refresh rate the desktop
+  /// doesn't run at.
+  ///
+  /// The desktop's mode comes back when the window leaves exclusive fullscreen or the app exits, also after a panic.
+  /// Should the process die outright, the OS restores it.
+  Exclusive(DisplayMode),
+}
+
+impl WindowMode {
+  pub(crate) fn of(window: &Window) -> Self {
+    match window.fullscreen() {
+      None => WindowMode::Windowed,
+      Some(Fullscreen::Borderless(_)) => WindowMode::Borderless,
+      Some(Fullscreen::Exclusive(video_mode)) => WindowMode::Exclusive(DisplayMode::from(&video_mode)),
+    }
+  }
+
+  pub(crate) fn apply(self, window: &Window) -> FoxyResult<()> {
+    let fullscreen = match self {
+      WindowMode::Windowed => None,
+      WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+      WindowMode::Exclusive(mode) => {
+        let monitor = window
+          .current_monitor()
+          .ok_or_else(|| FoxyError::Error("the window isn't on a display".into()))?;
+        let video_mode = monitor
+          .video_modes()
+          .find(|video_mode| DisplayMode::from(video_mode) == mode)
+          .ok_or_else(|| FoxyError::Error(format!("the display doesn't support {mode}")))?;
+        Some(Fullscreen::Exclusive(video_mode))
+      }
+    };
+    window.set_fullscreen(fullscreen);
+    Ok(())
+  }
+}
+
+/// The modes the display the window is on supports, largest and fastest first.
+pub(crate) fn display_modes(window: &Window) -> Vec<DisplayMode> {
+  let Some(monitor) = window.current_monitor() else {
+    return Vec::new();
+  };
+  let mut modes: Vec<_> = monitor
+    .video_modes()
+    .map(|video_mode| DisplayMode::from(&video_mode))
+    .collect();
+  modes.sort_by_key(|mode| {
+    Reverse((
+      mode.size.width * mode.size.height,
+      mode.size.width,
+      mode.refresh_rate_millihertz,
+      mode.bit_depth,
+    ))
+  });
+  modes.dedup();
+  modes
+}
+
+/// Switches the display back to the desktop's mode if the window has it in exclusive fullscreen.
+pub(crate) fn restore_desktop_mode(window: &Window) {
+  if let Some(Fullscreen::Exclusive(_)) = window.fullscreen() {
+    window.set_fullscreen(None);
+  }
+}
+
+/// Restores the desktop's mode when any thread panics, before the panic is reported as usual.
+pub(crate) fn restore_desktop_mode_on_panic(window: &Arc<Window>) {
+  let window: Weak<Window> = Arc::downgrade(window);
+  let report = std::panic::take_hook();
+  std::panic::set_hook(Box::new(move |info| {
+    if let Some(window) = window.upgrade() {
+      restore_desktop_mode(&window);
+    }
+    report(info);
+  }));
+}
@@ -0,0 +1,159 @@
+use std::{num::NonZeroU32, sync::Arc};
+
+use image::RgbaImage;
+use tracing::*;
+use winit::{
+  dpi::{LogicalSize, PhysicalSize},
+  event_loop::EventLoopWindowTarget,
+  window::{Window, WindowBuilder, WindowId, WindowLevel},
+};
+
+use crate::core::{FoxyError, FoxyResult};
+
+/// A small borderless window shown the moment the app starts, while the renderer and shaders load. It closes once
+/// the main window has drawn its first frame.
+#[derive(Debug, Clone)]
+pub struct SplashCreateInfo {
+  pub size: LogicalSize<u32>,
+  /// As `0xRRGGBB`.
+  pub background: u32,
+  /// Drawn centered, scaled down if it doesn't fit.
+  pub image: Option<RgbaImage>,
+}
+
+impl Default for SplashCreateInfo {
+  fn default() -> Self {
+    Self {
+      size: LogicalSize::new(480, 270),
+      background: 0x1a1a1a,
+      image: None,
+    }
+  }
+}
+
+impl SplashCreateInfo {
+  pub fn with_size(mut self, width: u32, height: u32) -> Self {
+    self.size = LogicalSize::new(width, height);
+    self
+  }
+
+  pub fn with_background(mut self, background: u32) -> Self {
+    self.background = background;
+    self
+  }
+
+  /// Decodes a PNG or JPEG image to show.
+  pub fn with_image(mut self, bytes: &[u8]) -> FoxyResult<Self> {
+    let image =
+      image::load_from_memory(bytes).map_err(|error| FoxyError::Error(format!("invalid splash image: {error}")))?;
+    self.image = Some(image.to_rgba8());
+    Ok(self)
+  }
+
+  pub(crate) fn show<T>(&self, event_loop: &EventLoopWindowTarget<T>) -> FoxyResult<Splash> {
+    let window = WindowBuilder::new()
+      .with_title("")
+      .with_inner_size(self.size)
+      .with_decorations(false)
+      .with_resizable(false)
+      .with_window_level(WindowLevel::AlwaysOnTop)
+      .build(event_loop)?;
+    let window = Arc::new(window);
+
+    // winit has no way to center on creation, so move it once the monitor is known
+    if let Some(monitor) = window.current_monitor() {
+      let (monitor_position, monitor_size) = (monitor.position(), monitor.size());
+      let size = window.outer_size();
+      window.set_outer_position(winit::dpi::PhysicalPosition::new(
+        monitor_position.x + (monitor_size.width as i32 - size.width as i32) / 2,
+        monitor_position.y + (monitor_size.height as i32 - size.height as i32) / 2,
+      ));
+    }
+
+    let context = softbuffer::Context::new(window.clone()).map_err(Self::surface_error)?;
+    let surface = softbuffer::Surface::new(&context, window.clone()).map_err(Self::surface_error)?;
+
+    let mut splash = Splash {
+      surface,
+      background: self.background,
+      image: self.image.clone(),
+      window,
+    };
+    splash.draw();
+
+    Ok(splash)
+  }
+
+  fn surface_error(error: softbuffer::SoftBufferError) -> FoxyError {
+    FoxyError::Error(format!("failed to create splash surface: {error}"))
+  }
+}
+
+pub(crate) struct Splash {
+  surface: softbuffer::Surface<Arc<Window>, Arc<Window>>,
+  background: u32,
+  image: Option<RgbaImage>,
+  // Keep window below the surface to ensure proper drop order
+  window: Arc<Window>,
+}
+
+impl Splash {
+  pub fn id(&self) -> WindowId {
+    self.window.id()
+  }
+
+  pub fn draw(&mut self) {
+    let PhysicalSize { width, height } = self.window.inner_size();
+    let (Some(nonzero_width), Some(nonzero_height)) = (NonZeroU32::new(width), NonZeroU32::new(height)) else {
+      return;
+    };
+    if let Err(error) = self.surface.resize(nonzero_width, nonzero_height) {
+      error!("failed to resize splash surface: {error}");
+      return;
+    }
+
+    let mut buffer = match self.surface.buffer_mut() {
+      Ok(buffer) => buffer,
+      Err(error) => {
+        error!("failed to draw splash: {error}");
+        return;
+      }
+    };
+    buffer.fill(self.background);
+
+    if let Some(image) = &self.image {
+      // scale down to fit, keeping the aspect ratio, and sample the nearest pixel
+      let scale = (width as f32 / image.width() as f32)
+        .min(height as f32 / image.height() as f32)
+        .min(1.0);
+      let drawn_width = (image.width() as f32 * scale) as u32;
+      let drawn_height = (image.height() as f32 * scale) as u32;
+      let (left, top) = ((width - drawn_width) / 2, (height - drawn_height) / 2);
+
+      for y in 0..drawn_height {
+        for x in 0..drawn_width {
+          let source = image.get_pixel(
+            ((x as f32 / scale) as u32).min(image.width() - 1),
+            ((y as f32 / scale) as u32).min(image.height() - 1),
+          );
+          if let Some(pixel) = buffer.get_mut(((top + y) * width + left + x) as usize) {
+            *pixel = Self::blend(*pixel, source.0);
+          }
+        }
+      }
+    }
+
+    if let Err(error) = buffer.present() {
+      error!("failed to present splash: {error}");
+    }
+  }
+
+  fn blend(background: u32, [r, g, b, a]: [u8; 4]) -> u32 {
+    let channel = |shift: u32, value: u8| {
+      let under = (background >> shift) & 0xff;
+      (value as u32 * a as u32 + under * (255 - a as u32)) / 255
+    };
+
+    channel(16, r) << 16 | channel(8, g) << 8 | channel(0, b)
+  }
+}
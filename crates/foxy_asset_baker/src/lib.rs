@@ -0,0 +1,260 @@
+mod model;
+mod shader;
+mod sound;
+mod texture;
+
+use std::{
+  fs,
+  io,
+  path::{Path, PathBuf},
+};
+
+use foxy_utils::baked::{
+  hash::{ContentHash, ContentHasher},
+  manifest::AssetManifest,
+  mesh::BakedModel,
+  sound::BakedSound,
+  texture::BakedTexture,
+};
+use thiserror::Error;
+use tracing::*;
+
+#[derive(Error, Debug)]
+pub enum BakeError {
+  #[error("{0}")]
+  IO(#[from] io::Error),
+  #[error("failed to bake {}: {message}", path.display())]
+  Asset { path: PathBuf, message: String },
+}
+
+/// A source asset about to be baked.
+pub(crate) struct Source<'a> {
+  /// The path of the asset, relative to the source directory.
+  pub path: &'a Path,
+  /// The directory the asset is in, for resolving the files it references.
+  pub dir: &'a Path,
+  pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Importer {
+  Texture,
+  Model,
+  Sound,
+  Shader,
+}
+
+impl Importer {
+  fn for_extension(extension: &str) -> Option<Self> {
+    match extension.to_lowercase().as_str() {
+      "png" | "jpg" | "jpeg" => Some(Importer::Texture),
+      "gltf" | "glb" => Some(Importer::Model),
+      "wav" => Some(Importer::Sound),
+      "vert" | "frag" | "comp" => Some(Importer::Shader),
+      _ => None,
+    }
+  }
+
+  fn artifact_extension(self) -> &'static str {
+    match self {
+      Importer::Texture => BakedTexture::EXTENSION,
+      Importer::Model => BakedModel::EXTENSION,
+      Importer::Sound => BakedSound::EXTENSION,
+      Importer::Shader => shader::EXTENSION,
+    }
+  }
+
+  /// The other files the asset is baked from, e.g. the buffers of a glTF model.
+  fn inputs(self, source: &Source) -> Result<Vec<PathBuf>, String> {
+    match self {
+      Importer::Model => model::inputs(source),
+      _ => Ok(Vec::new()),
+    }
+  }
+
+  fn bake(self, source: &Source, baker: &Baker) -> Result<Vec<u8>, String> {
+    match self {
+      Importer::Texture => texture::bake(source, baker.compress_textures).map(|texture| texture.to_bytes()),
+      Importer::Model => model::bake(source).map(|model| model.to_bytes()),
+      Importer::Sound => sound::bake(source).map(|sound| sound.to_bytes()),
+      Importer::Shader => shader::bake(source),
+    }
+  }
+}
+
+/// What a [`Baker::bake`] did.
+#[derive(Debug, Clone, Default)]
+pub struct BakeReport {
+  pub manifest: AssetManifest,
+  /// Assets that were baked.
+  pub baked: Vec<PathBuf>,
+  /// Assets whose artifact was taken from the cache directory.
+  pub cached: Vec<PathBuf>,
+  /// Assets left as they were, as nothing they're baked from changed.
+  pub unchanged: Vec<PathBuf>,
+  /// Artifacts deleted because their source asset is gone.
+  pub removed: Vec<PathBuf>,
+}
+
+/// Bakes every source asset in a directory into an output directory, along with an [`AssetManifest`].
+///
+/// Baking is incremental: each asset is keyed on a hash of everything it's baked from, and is skipped if its artifact
+/// was baked from the same content last time. With a cache directory, artifacts are also stored there by hash, so that
+/// checkouts, branches and output directories sharing it only ever bake the same content once.
+#[derive(Debug, Clone)]
+pub struct Baker {
+  source_dir: PathBuf,
+  output_dir: PathBuf,
+  cache_dir: Option<PathBuf>,
+  compress_textures: bool,
+}
+
+impl Baker {
+  pub fn new(source_dir: impl Into<PathBuf>, output_dir: impl Into<PathBuf>) -> Self {
+    Self {
+      source_dir: source_dir.into(),
+      output_dir: output_dir.into(),
+      cache_dir: None,
+      compress_textures: true,
+    }
+  }
+
+  /// Stores artifacts in `cache_dir` by content hash, and reuses the ones already there.
+  pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+    self.cache_dir = Some(cache_dir.into());
+    self
+  }
+
+  /// Whether textures are BC3 compressed, which needs GPU support to load. On by default. Textures whose size isn't
+  /// a multiple of 4 are never compressed.
+  pub fn with_texture_compression(mut self, compress_textures: bool) -> Self {
+    self.compress_textures = compress_textures;
+    self
+  }
+
+  /// Bakes the assets that changed since the last bake, writes the manifest and removes the artifacts of deleted
+  /// assets. Stops at the first asset that fails.
+  pub fn bake(&self) -> Result<BakeReport, BakeError> {
+    let mut sources = Vec::new();
+    collect_files(&self.source_dir, &mut sources)?;
+    sources.sort();
+
+    let previous = AssetManifest::read(&self.output_dir).unwrap_or_default();
+    let mut report = BakeReport::default();
+    for file in sources {
+      let path = file.strip_prefix(&self.source_dir).unwrap_or(&file);
+      let Some(importer) = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(Importer::for_extension)
+      else {
+        continue;
+      };
+      let asset_error = |message: String| BakeError::Asset {
+        path: path.to_path_buf(),
+        message,
+      };
+
+      let source = Source {
+        path,
+        dir: file.parent().unwrap_or(&self.source_dir),
+        bytes: fs::read(&file)?,
+      };
+      let hash = self.hash(importer, &source).map_err(asset_error)?;
+
+      // keeping the source extension keeps e.g. `rock.png` and `rock.jpg` apart
+      let mut artifact = path.as_os_str().to_owned();
+      artifact.push(".");
+      artifact.push(importer.artifact_extension());
+      let artifact = PathBuf::from(artifact);
+      let output = self.output_dir.join(&artifact);
+      report.manifest.insert_hashed(path, &artifact, hash);
+
+      if previous.hash(path) == Some(hash) && previous.artifact(path) == Some(&artifact) && output.is_file() {
+        debug!("{} is unchanged", path.display());
+        report.unchanged.push(path.to_path_buf());
+        continue;
+      }
+
+      if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+      }
+      let cached = self.cached(hash, importer);
+      if let Some(cached) = cached.as_ref().filter(|cached| cached.is_file()) {
+        fs::copy(cached, &output)?;
+        info!("took {} from the cache", artifact.display());
+        report.cached.push(path.to_path_buf());
+        continue;
+      }
+
+      let bytes = importer.bake(&source, self).map_err(asset_error)?;
+      fs::write(&output, &bytes)?;
+      if let Some(cached) = cached {
+        store(&cached, &bytes)?;
+      }
+      info!("baked {} into {}", path.display(), artifact.display());
+      report.baked.push(path.to_path_buf());
+    }
+
+    for (source, artifact) in previous.iter() {
+      if report.manifest.artifact(source) != Some(artifact) {
+        match fs::remove_file(self.output_dir.join(artifact)) {
+          Ok(()) => info!("removed {}", artifact.display()),
+          Err(error) if error.kind() == io::ErrorKind::NotFound => (),
+          Err(error) => return Err(error.into()),
+        }
+        report.removed.push(artifact.to_path_buf());
+      }
+    }
+
+    fs::create_dir_all(&self.output_dir)?;
+    report.manifest.write(&self.output_dir)?;
+
+    Ok(report)
+  }
+
+  /// Hashes everything the artifact of `source` depends on: the baker, its settings, the source and its inputs.
+  fn hash(&self, importer: Importer, source: &Source) -> Result<ContentHash, String> {
+    let mut hasher = ContentHasher::new()
+      .update_piece(env!("CARGO_PKG_VERSION").as_bytes())
+      .update_piece(importer.artifact_extension().as_bytes())
+      .update_piece(&[(importer == Importer::Texture && self.compress_textures) as u8])
+      .update_piece(&source.bytes);
+    for input in importer.inputs(source)? {
+      let bytes = fs::read(&input).map_err(|error| format!("failed to read {}: {error}", input.display()))?;
+      hasher = hasher.update_piece(&bytes);
+    }
+
+    Ok(hasher.finish())
+  }
+
+  /// Where the artifact baked from content hashing to `hash` is kept in the cache directory, if there is one.
+  fn cached(&self, hash: ContentHash, importer: Importer) -> Option<PathBuf> {
+    let name = format!("{hash}.{}", importer.artifact_extension());
+    // sharding by the first byte of the hash keeps directories small
+    Some(self.cache_dir.as_ref()?.join(&name[..2]).join(name))
+  }
+}
+
+/// Writes `bytes` to `path` through a temporary file, so that other bakers sharing the cache never see half of it.
+fn store(path: &Path, bytes: &[u8]) -> io::Result<()> {
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+  let mut temporary = path.as_os_str().to_owned();
+  temporary.push(format!(".{}.tmp", std::process::id()));
+  fs::write(&temporary, bytes)?;
+  fs::rename(&temporary, path)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+  for entry in fs::read_dir(dir)? {
+    let path = entry?.path();
+    if path.is_dir() {
+      collect_files(&path, files)?;
+    } else {
+      files.push(path);
+    }
+  }
+  Ok(())
+}
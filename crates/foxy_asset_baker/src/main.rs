@@ -0,0 +1,59 @@
+use std::process::ExitCode;
+
+use foxy_asset_baker::Baker;
+use foxy_utils::log::builder::LoggingSession;
+use tracing::*;
+
+const USAGE: &str = "usage: foxy_asset_baker [--uncompressed] [--cache <cache dir>] <source dir> <output dir>";
+
+fn main() -> ExitCode {
+  LoggingSession::new().with_filter("info").start();
+
+  let mut compress_textures = true;
+  let mut cache_dir = None;
+  let mut dirs = Vec::new();
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--uncompressed" => compress_textures = false,
+      "--cache" => match args.next() {
+        Some(dir) => cache_dir = Some(dir),
+        None => {
+          eprintln!("{USAGE}");
+          return ExitCode::FAILURE;
+        }
+      },
+      "-h" | "--help" => {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+      }
+      _ => dirs.push(arg),
+    }
+  }
+  let [source_dir, output_dir] = dirs.as_slice() else {
+    eprintln!("{USAGE}");
+    return ExitCode::FAILURE;
+  };
+
+  let mut baker = Baker::new(source_dir, output_dir).with_texture_compression(compress_textures);
+  if let Some(cache_dir) = cache_dir {
+    baker = baker.with_cache_dir(cache_dir);
+  }
+
+  match baker.bake() {
+    Ok(report) => {
+      info!(
+        "baked {} assets into {output_dir}, {} from the cache, {} unchanged, {} removed",
+        report.baked.len(),
+        report.cached.len(),
+        report.unchanged.len(),
+        report.removed.len()
+      );
+      ExitCode::SUCCESS
+    }
+    Err(error) => {
+      error!("{error}");
+      ExitCode::FAILURE
+    }
+  }
+}
@@ -0,0 +1,268 @@
+use std::{fs, path::PathBuf};
+
+use foxy_utils::baked::mesh::{BakedMesh, BakedModel, BakedVertex};
+use serde_json::Value;
+
+use crate::Source;
+
+const GLB_MAGIC: &[u8] = b"glTF";
+const GLB_JSON_CHUNK: u32 = 0x4E4F534A;
+const GLB_BIN_CHUNK: u32 = 0x004E4942;
+const TRIANGLES: u64 = 4;
+
+/// Packs every triangle primitive of a glTF model into a mesh, in the space of the mesh. Node transforms, materials
+/// and animations aren't baked.
+pub(crate) fn bake(source: &Source) -> Result<BakedModel, String> {
+  let (gltf, bin) = parse(source)?;
+
+  let buffers = array(&gltf, "buffers")
+    .iter()
+    .enumerate()
+    .map(|(index, buffer)| match buffer_uri(buffer) {
+      Some(uri) if uri.starts_with("data:") => {
+        let (_, data) = uri
+          .split_once(";base64,")
+          .ok_or("only base64 data URIs are supported")?;
+        decode_base64(data)
+      }
+      Some(uri) => fs::read(source.dir.join(uri)).map_err(|error| format!("failed to read buffer {uri}: {error}")),
+      None if index == 0 => bin
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| "buffer 0 has no data".to_string()),
+      None => Err(format!("buffer {index} has no data")),
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let accessors = Accessors {
+    gltf: &gltf,
+    buffers: &buffers,
+  };
+
+  let mut meshes = Vec::new();
+  for (mesh_index, mesh) in array(&gltf, "meshes").iter().enumerate() {
+    let name = mesh
+      .get("name")
+      .and_then(Value::as_str)
+      .map_or_else(|| format!("mesh{mesh_index}"), str::to_owned);
+
+    for primitive in array(mesh, "primitives") {
+      if primitive.get("mode").and_then(Value::as_u64).unwrap_or(TRIANGLES) != TRIANGLES {
+        return Err(format!("{name} has a primitive that isn't a triangle list"));
+      }
+
+      let attribute = |name: &str| primitive.get("attributes")?.get(name)?.as_u64();
+      let positions = accessors.floats(attribute("POSITION").ok_or(format!("{name} has no positions"))?)?;
+      let colors = attribute("COLOR_0").map(|index| accessors.floats(index)).transpose()?;
+      let uvs = attribute("TEXCOORD_0")
+        .map(|index| accessors.floats(index))
+        .transpose()?;
+
+      let vertices: Vec<BakedVertex> = (0..positions.len())
+        .map(|i| BakedVertex {
+          position: [0, 1, 2].map(|c| positions[i].get(c).copied().unwrap_or_default()),
+          color: colors
+            .as_ref()
+            .and_then(|colors| colors.get(i))
+            .map_or([1.0; 4], |color| [0, 1, 2, 3].map(|c| color.get(c).copied().unwrap_or(1.0))),
+          uv: uvs
+            .as_ref()
+            .and_then(|uvs| uvs.get(i))
+            .map_or([0.0; 2], |uv| [0, 1].map(|c| uv.get(c).copied().unwrap_or_default())),
+        })
+        .collect();
+
+      let indices = match primitive.get("indices").and_then(Value::as_u64) {
+        Some(index) => accessors.indices(index)?,
+        None => (0..vertices.len() as u32).collect(),
+      };
+      if indices.iter().any(|&index| index as usize >= vertices.len()) {
+        return Err(format!("{name} has an out of range index"));
+      }
+
+      meshes.push(BakedMesh {
+        name: name.clone(),
+        vertices,
+        indices,
+      });
+    }
+  }
+
+  Ok(BakedModel { meshes })
+}
+
+/// The buffer files a glTF model references.
+pub(crate) fn inputs(source: &Source) -> Result<Vec<PathBuf>, String> {
+  let (gltf, _) = parse(source)?;
+  Ok(
+    array(&gltf, "buffers")
+      .iter()
+      .filter_map(buffer_uri)
+      .filter(|uri| !uri.starts_with("data:"))
+      .map(|uri| source.dir.join(uri))
+      .collect(),
+  )
+}
+
+/// The JSON of a glTF model, and its binary buffer if it's a GLB.
+fn parse<'a>(source: &'a Source) -> Result<(Value, Option<&'a [u8]>), String> {
+  let (json, bin) = if source.bytes.starts_with(GLB_MAGIC) {
+    split_glb(&source.bytes)?
+  } else {
+    (source.bytes.as_slice(), None)
+  };
+  let gltf = serde_json::from_slice(json).map_err(|error| error.to_string())?;
+
+  Ok((gltf, bin))
+}
+
+fn buffer_uri(buffer: &Value) -> Option<&str> {
+  buffer.get("uri").and_then(Value::as_str)
+}
+
+/// Splits a binary glTF into its JSON and its binary buffer.
+fn split_glb(bytes: &[u8]) -> Result<(&[u8], Option<&[u8]>), String> {
+  let u32_at = |offset: usize| {
+    bytes
+      .get(offset..offset + 4)
+      .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+      .ok_or("GLB ends unexpectedly")
+  };
+
+  let mut json = None;
+  let mut bin = None;
+  let mut offset = 12;
+  while offset < bytes.len() {
+    let length = u32_at(offset)? as usize;
+    let kind = u32_at(offset + 4)?;
+    let chunk = bytes
+      .get(offset + 8..offset + 8 + length)
+      .ok_or("GLB chunk ends unexpectedly")?;
+    match kind {
+      GLB_JSON_CHUNK => json = Some(chunk),
+      GLB_BIN_CHUNK => bin = Some(chunk),
+      _ => (),
+    }
+    offset += 8 + length;
+  }
+
+  Ok((json.ok_or("GLB has no JSON chunk")?, bin))
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+  value.get(key).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+struct Accessors<'a> {
+  gltf: &'a Value,
+  buffers: &'a [Vec<u8>],
+}
+
+impl Accessors<'_> {
+  /// The elements of a float accessor, or a normalized integer one.
+  fn floats(&self, index: u64) -> Result<Vec<Vec<f32>>, String> {
+    self.read(index, |component_type, normalized, bytes| {
+      let value = match (component_type, bytes.len()) {
+        (5126, 4) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        (5121, 1) if normalized => bytes[0] as f32 / u8::MAX as f32,
+        (5123, 2) if normalized => u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32,
+        _ => return Err(format!("accessor {index} isn't floats")),
+      };
+      Ok(value)
+    })
+  }
+
+  fn indices(&self, index: u64) -> Result<Vec<u32>, String> {
+    let elements = self.read(index, |component_type, _, bytes| match (component_type, bytes.len()) {
+      (5121, 1) => Ok(bytes[0] as u32),
+      (5123, 2) => Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as u32),
+      (5125, 4) => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+      _ => Err(format!("accessor {index} isn't indices")),
+    })?;
+    Ok(elements.into_iter().flatten().collect())
+  }
+
+  /// Reads each component of each element of an accessor with `component`, given the component type, whether it's
+  /// normalized and its bytes.
+  fn read<T: Default + Clone>(
+    &self,
+    index: u64,
+    component: impl Fn(u64, bool, &[u8]) -> Result<T, String>,
+  ) -> Result<Vec<Vec<T>>, String> {
+    let accessor = array(self.gltf, "accessors")
+      .get(index as usize)
+      .ok_or(format!("accessor {index} doesn't exist"))?;
+    if accessor.get("sparse").is_some() {
+      return Err(format!("accessor {index} is sparse, which isn't supported"));
+    }
+
+    let field = |key: &str| accessor.get(key).and_then(Value::as_u64);
+    let count = field("count").ok_or(format!("accessor {index} has no count"))? as usize;
+    let component_type = field("componentType").ok_or(format!("accessor {index} has no component type"))?;
+    let normalized = accessor.get("normalized").and_then(Value::as_bool).unwrap_or(false);
+    let components = match accessor.get("type").and_then(Value::as_str) {
+      Some("SCALAR") => 1,
+      Some("VEC2") => 2,
+      Some("VEC3") => 3,
+      Some("VEC4") => 4,
+      _ => return Err(format!("accessor {index} has an unsupported type")),
+    };
+    let component_size = match component_type {
+      5120 | 5121 => 1,
+      5122 | 5123 => 2,
+      5125 | 5126 => 4,
+      _ => return Err(format!("accessor {index} has an unknown component type")),
+    };
+
+    // accessors without a buffer view are all zeros
+    let Some(view_index) = field("bufferView") else {
+      return Ok(vec![vec![T::default(); components]; count]);
+    };
+    let view = array(self.gltf, "bufferViews")
+      .get(view_index as usize)
+      .ok_or(format!("buffer view {view_index} doesn't exist"))?;
+    let view_field = |key: &str| view.get(key).and_then(Value::as_u64);
+    let buffer = view_field("buffer")
+      .and_then(|buffer| self.buffers.get(buffer as usize))
+      .ok_or(format!("buffer view {view_index} has no buffer"))?;
+    let start = view_field("byteOffset").unwrap_or(0) as usize + field("byteOffset").unwrap_or(0) as usize;
+    let stride = view_field("byteStride").map_or(components * component_size, |stride| stride as usize);
+
+    (0..count)
+      .map(|element| {
+        (0..components)
+          .map(|c| {
+            let offset = start + element * stride + c * component_size;
+            let bytes = buffer
+              .get(offset..offset + component_size)
+              .ok_or(format!("accessor {index} reads past its buffer"))?;
+            component(component_type, normalized, bytes)
+          })
+          .collect()
+      })
+      .collect()
+  }
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, String> {
+  let sextet = |byte: u8| match byte {
+    b'A'..=b'Z' => Ok(byte - b'A'),
+    b'a'..=b'z' => Ok(byte - b'a' + 26),
+    b'0'..=b'9' => Ok(byte - b'0' + 52),
+    b'+' | b'-' => Ok(62),
+    b'/' | b'_' => Ok(63),
+    _ => Err(format!("invalid base64 character {:?}", byte as char)),
+  };
+
+  let mut bytes = Vec::with_capacity(data.len() / 4 * 3);
+  let mut bits = 0u32;
+  let mut bit_count = 0;
+  for byte in data.bytes().filter(|&byte| byte != b'=' && !byte.is_ascii_whitespace()) {
+    bits = (bits << 6) | sextet(byte)? as u32;
+    bit_count += 6;
+    if bit_count >= 8 {
+      bit_count -= 8;
+      bytes.push((bits >> bit_count) as u8);
+    }
+  }
+
+  Ok(bytes)
+}
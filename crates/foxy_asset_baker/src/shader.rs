@@ -0,0 +1,38 @@
+use naga::{
+  back::spv,
+  front::glsl,
+  valid::{Capabilities, ValidationFlags, Validator},
+  ShaderStage,
+};
+
+use crate::Source;
+
+/// The extension of compiled shaders.
+pub(crate) const EXTENSION: &str = "spv";
+
+/// Compiles a GLSL shader to SPIR-V, taking its stage from its extension.
+pub(crate) fn bake(source: &Source) -> Result<Vec<u8>, String> {
+  let stage = match source.path.extension().and_then(|extension| extension.to_str()) {
+    Some("vert") => ShaderStage::Vertex,
+    Some("frag") => ShaderStage::Fragment,
+    Some("comp") => ShaderStage::Compute,
+    _ => return Err("unknown shader stage".into()),
+  };
+  let code = std::str::from_utf8(&source.bytes).map_err(|error| error.to_string())?;
+
+  let module = glsl::Frontend::default()
+    .parse(&glsl::Options::from(stage), code)
+    .map_err(|errors| {
+      errors
+        .iter()
+        .map(|error| format!("line {}: {error}", error.meta.location(code).line_number))
+        .collect::<Vec<_>>()
+        .join("; ")
+    })?;
+  let info = Validator::new(ValidationFlags::all(), Capabilities::all())
+    .validate(&module)
+    .map_err(|error| error.emit_to_string(code))?;
+  let words = spv::write_vec(&module, &info, &spv::Options::default(), None).map_err(|error| error.to_string())?;
+
+  Ok(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+}
@@ -0,0 +1,69 @@
+use foxy_utils::baked::sound::BakedSound;
+
+use crate::Source;
+
+const PCM: u16 = 1;
+const IEEE_FLOAT: u16 = 3;
+const EXTENSIBLE: u16 = 0xFFFE;
+
+/// Decodes a PCM or float WAV file.
+pub(crate) fn bake(source: &Source) -> Result<BakedSound, String> {
+  let bytes = &source.bytes;
+  if bytes.get(..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+    return Err("not a WAV file".into());
+  }
+
+  let mut format = None;
+  let mut data = None;
+  let mut offset = 12;
+  while let Some(header) = bytes.get(offset..offset + 8) {
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let chunk = bytes
+      .get(offset + 8..offset + 8 + length)
+      .ok_or("WAV chunk ends unexpectedly")?;
+    match &header[..4] {
+      b"fmt " => format = Some(chunk),
+      b"data" => data = Some(chunk),
+      _ => (),
+    }
+    // chunks are padded to an even length
+    offset += 8 + length + length % 2;
+  }
+
+  let format = format.filter(|format| format.len() >= 16).ok_or("WAV has no format")?;
+  let data = data.ok_or("WAV has no data")?;
+  let u16_at = |offset: usize| u16::from_le_bytes([format[offset], format[offset + 1]]);
+
+  let mut encoding = u16_at(0);
+  if encoding == EXTENSIBLE && format.len() >= 26 {
+    // the encoding is the start of the sub-format GUID
+    encoding = u16_at(24);
+  }
+  let channels = u16_at(2);
+  let sample_rate = u32::from_le_bytes([format[4], format[5], format[6], format[7]]);
+  let bits = u16_at(14);
+  if channels == 0 || sample_rate == 0 {
+    return Err("WAV has no channels or sample rate".into());
+  }
+
+  let decode: fn(&[u8]) -> f32 = match (encoding, bits) {
+    (PCM, 8) => |bytes| (bytes[0] as f32 - 128.0) / 128.0,
+    (PCM, 16) => |bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+    // shifting into the top of an i32 sign extends the sample
+    (PCM, 24) => |bytes| i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) as f32 / 2147483648.0,
+    (PCM, 32) => |bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2147483648.0,
+    (IEEE_FLOAT, 32) => |bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    (IEEE_FLOAT, 64) => |bytes| {
+      f64::from_le_bytes([
+        bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+      ]) as f32
+    },
+    _ => return Err(format!("WAV encoding {encoding} with {bits} bit samples isn't supported")),
+  };
+
+  Ok(BakedSound {
+    channels,
+    sample_rate,
+    samples: data.chunks_exact(bits as usize / 8).map(decode).collect(),
+  })
+}
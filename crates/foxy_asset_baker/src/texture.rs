@@ -0,0 +1,138 @@
+use foxy_utils::baked::texture::{BakedTexture, BakedTextureFormat};
+use image::{imageops::FilterType, RgbaImage};
+use tracing::*;
+
+use crate::Source;
+
+/// Decodes an image and builds its mip chain, compressing it if asked and possible.
+pub(crate) fn bake(source: &Source, compress: bool) -> Result<BakedTexture, String> {
+  let image = image::load_from_memory(&source.bytes)
+    .map_err(|error| error.to_string())?
+    .to_rgba8();
+  let (width, height) = image.dimensions();
+
+  let format = if compress && width % 4 == 0 && height % 4 == 0 {
+    BakedTextureFormat::Bc3
+  } else {
+    if compress {
+      warn!(
+        "{} is {width}x{height}, not a multiple of 4, so it isn't compressed",
+        source.path.display()
+      );
+    }
+    BakedTextureFormat::Rgba8
+  };
+
+  let mut mips = vec![encode(&image, format)];
+  let mut mip = image;
+  while mip.width() > 1 || mip.height() > 1 {
+    mip = image::imageops::resize(&mip, (mip.width() / 2).max(1), (mip.height() / 2).max(1), FilterType::Triangle);
+    mips.push(encode(&mip, format));
+  }
+
+  Ok(BakedTexture {
+    format,
+    width,
+    height,
+    mips,
+  })
+}
+
+fn encode(image: &RgbaImage, format: BakedTextureFormat) -> Vec<u8> {
+  match format {
+    BakedTextureFormat::Rgba8 => image.as_raw().clone(),
+    BakedTextureFormat::Bc3 => encode_bc3(image),
+  }
+}
+
+/// Compresses the image into BC3 blocks, row by row. Blocks hanging over the edge repeat the edge texels.
+fn encode_bc3(image: &RgbaImage) -> Vec<u8> {
+  let (width, height) = image.dimensions();
+  let mut blocks = Vec::with_capacity(BakedTextureFormat::Bc3.data_len(width, height));
+
+  for block_y in (0..height).step_by(4) {
+    for block_x in (0..width).step_by(4) {
+      let mut texels = [[0u8; 4]; 16];
+      for (i, texel) in texels.iter_mut().enumerate() {
+        let x = (block_x + i as u32 % 4).min(width - 1);
+        let y = (block_y + i as u32 / 4).min(height - 1);
+        *texel = image.get_pixel(x, y).0;
+      }
+      blocks.extend_from_slice(&encode_alpha_block(&texels));
+      blocks.extend_from_slice(&encode_color_block(&texels));
+    }
+  }
+
+  blocks
+}
+
+/// Interpolates 8 alphas between the block's extremes, with 3 bit indices.
+fn encode_alpha_block(texels: &[[u8; 4]; 16]) -> [u8; 8] {
+  let max = texels.iter().map(|texel| texel[3]).max().unwrap_or(255);
+  let min = texels.iter().map(|texel| texel[3]).min().unwrap_or(255);
+
+  let mut palette = [max, min, 0, 0, 0, 0, 0, 0];
+  for (i, alpha) in palette.iter_mut().enumerate().skip(2) {
+    *alpha = (((8 - i) as u32 * max as u32 + (i - 1) as u32 * min as u32) / 7) as u8;
+  }
+
+  let mut indices = 0u64;
+  if max > min {
+    for (i, texel) in texels.iter().enumerate() {
+      indices |= (nearest(&palette, |alpha| alpha.abs_diff(texel[3]) as u32) as u64) << (3 * i);
+    }
+  }
+
+  let mut block = [max, min, 0, 0, 0, 0, 0, 0];
+  block[2..].copy_from_slice(&indices.to_le_bytes()[..6]);
+  block
+}
+
+/// Interpolates 4 colors along the block's bounding box diagonal, with 2 bit indices.
+fn encode_color_block(texels: &[[u8; 4]; 16]) -> [u8; 8] {
+  let mut max = [0u8; 3];
+  let mut min = [255u8; 3];
+  for texel in texels {
+    for channel in 0..3 {
+      max[channel] = max[channel].max(texel[channel]);
+      min[channel] = min[channel].min(texel[channel]);
+    }
+  }
+
+  let endpoints = [to_rgb565(max), to_rgb565(min)];
+  let [start, end] = endpoints.map(from_rgb565);
+  let mix = |a: [u8; 3], b: [u8; 3]| [0, 1, 2].map(|c| ((2 * a[c] as u32 + b[c] as u32) / 3) as u8);
+  let palette = [start, end, mix(start, end), mix(end, start)];
+
+  let mut indices = 0u32;
+  if endpoints[0] != endpoints[1] {
+    for (i, texel) in texels.iter().enumerate() {
+      let distance = |color: &[u8; 3]| {
+        (0..3)
+          .map(|channel| (color[channel].abs_diff(texel[channel]) as u32).pow(2))
+          .sum()
+      };
+      indices |= (nearest(&palette, distance) as u32) << (2 * i);
+    }
+  }
+
+  let mut block = [0u8; 8];
+  block[..2].copy_from_slice(&endpoints[0].to_le_bytes());
+  block[2..4].copy_from_slice(&endpoints[1].to_le_bytes());
+  block[4..].copy_from_slice(&indices.to_le_bytes());
+  block
+}
+
+fn nearest<T>(palette: &[T], distance: impl Fn(&T) -> u32) -> usize {
+  (0..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0)
+}
+
+fn to_rgb565([r, g, b]: [u8; 3]) -> u16 {
+  let quantize = |value: u8, max: u32| ((value as u32 * max + 127) / 255) as u16;
+  (quantize(r, 31) << 11) | (quantize(g, 63) << 5) | quantize(b, 31)
+}
+
+fn from_rgb565(color: u16) -> [u8; 3] {
+  let (r, g, b) = ((color >> 11) as u8, ((color >> 5) & 0x3f) as u8, (color & 0x1f) as u8);
+  [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
@@ -0,0 +1,131 @@
+//! Derive macros for Foxy's reflection traits. Use them through `foxy`, which re-exports them next to their traits.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Index, Member};
+
+/// Implements `Inspect` by showing every field in a labeled grid. Fields marked `#[inspect(skip)]` are left out.
+#[proc_macro_derive(Inspect, attributes(inspect))]
+pub fn derive_inspect(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  let name = &input.ident;
+  let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+  let fields = match fields(&input, "Inspect") {
+    Ok(fields) => fields,
+    Err(error) => return error.to_compile_error().into(),
+  };
+  let rows = fields.iter().map(|(label, member)| {
+    quote! {
+      changed |= ::foxy::core::inspector::inspect_field(ui, #label, &mut self.#member);
+    }
+  });
+
+  quote! {
+    impl #impl_generics ::foxy::core::inspector::Inspect for #name #type_generics #where_clause {
+      fn inspect(&mut self, ui: &mut ::foxy::egui::Ui) -> bool {
+        let mut changed = false;
+        ::foxy::egui::Grid::new(concat!(module_path!(), "::", stringify!(#name)))
+          .num_columns(2)
+          .show(ui, |ui| {
+            #(#rows)*
+          });
+        changed
+      }
+    }
+  }
+  .into()
+}
+
+/// Implements `Reflect` by converting to and from a `Value::Struct` keyed by field name (or index, for tuple structs).
+/// Fields marked `#[reflect(skip)]` are left out.
+#[proc_macro_derive(Reflect, attributes(reflect))]
+pub fn derive_reflect(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  let name = &input.ident;
+  let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+  let fields = match fields(&input, "Reflect") {
+    Ok(fields) => fields,
+    Err(error) => return error.to_compile_error().into(),
+  };
+  let to_value = fields.iter().map(|(label, member)| {
+    quote! {
+      (::std::string::String::from(#label), ::foxy::core::reflect::Reflect::to_value(&self.#member)),
+    }
+  });
+  let apply_value = fields.iter().map(|(label, member)| {
+    quote! {
+      #label => ::foxy::core::reflect::Reflect::apply_value(&mut self.#member, value),
+    }
+  });
+
+  quote! {
+    impl #impl_generics ::foxy::core::reflect::Reflect for #name #type_generics #where_clause {
+      fn to_value(&self) -> ::foxy::core::reflect::Value {
+        ::foxy::core::reflect::Value::Struct(::std::vec![#(#to_value)*])
+      }
+
+      fn apply_value(&mut self, value: &::foxy::core::reflect::Value) -> bool {
+        let ::foxy::core::reflect::Value::Struct(fields) = value else {
+          return false;
+        };
+        fields.iter().fold(true, |applied, (field, value)| {
+          let field_applied = match field.as_str() {
+            #(#apply_value)*
+            _ => false,
+          };
+          applied && field_applied
+        })
+      }
+    }
+  }
+  .into()
+}
+
+/// The label and accessor of each field of a struct, minus those marked skipped with the derive's attribute, e.g.
+/// `#[inspect(skip)]`.
+fn fields(input: &DeriveInput, derive: &str) -> syn::Result<Vec<(String, Member)>> {
+  let Data::Struct(data) = &input.data else {
+    return Err(syn::Error::new(
+      input.span(),
+      format!("`{derive}` can only be derived for structs"),
+    ));
+  };
+  let attribute = derive.to_lowercase();
+
+  let mut members = Vec::new();
+  for (index, field) in data.fields.iter().enumerate() {
+    if is_skipped(field, &attribute)? {
+      continue;
+    }
+
+    let member = match (&data.fields, &field.ident) {
+      (Fields::Named(_), Some(ident)) => Member::Named(ident.clone()),
+      _ => Member::Unnamed(Index::from(index)),
+    };
+    let label = match &member {
+      Member::Named(ident) => ident.to_string(),
+      Member::Unnamed(index) => index.index.to_string(),
+    };
+    members.push((label, member));
+  }
+
+  Ok(members)
+}
+
+fn is_skipped(field: &syn::Field, attribute: &str) -> syn::Result<bool> {
+  let mut skipped = false;
+  for attr in field.attrs.iter().filter(|attr| attr.path().is_ident(attribute)) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("skip") {
+        skipped = true;
+        Ok(())
+      } else {
+        Err(meta.error(format!("unknown `{attribute}` option")))
+      }
+    })?;
+  }
+
+  Ok(skipped)
+}
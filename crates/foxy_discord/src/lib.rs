@@ -0,0 +1,240 @@
+//! Discord Rich Presence for Foxy, and a small example of a [`Plugin`].
+//!
+//! [`DiscordPlugin`] talks to the Discord client on a background thread, so a slow or missing client never stalls the
+//! game. Presence can be set from anywhere through a [`Discord`] handle, or derived from the engine's events every
+//! frame with [`DiscordPlugin::with_presence_from`].
+
+use std::{
+  thread::JoinHandle,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender};
+use discord_rich_presence::{
+  activity::{Activity, Assets, Party, Timestamps},
+  DiscordIpc,
+  DiscordIpcClient,
+};
+use foxy::core::{event::FoxyEvent, foxy_state::Foxy, plugin::Plugin, FoxyResult};
+use tracing::*;
+
+/// What the player is doing, as shown on their Discord profile.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Presence {
+  /// The first line, e.g. "Exploring the Shrine".
+  pub details: Option<String>,
+  /// The second line, e.g. "In a party".
+  pub state: Option<String>,
+  /// How many players are in the party, out of how many it fits.
+  pub party: Option<(u32, u32)>,
+  /// Shows the time elapsed since then.
+  pub start: Option<SystemTime>,
+  /// Shows the time left until then, instead of the elapsed time.
+  pub end: Option<SystemTime>,
+  /// The key of an art asset uploaded to the Discord application, and its hover text.
+  pub large_image: Option<(String, String)>,
+}
+
+impl Presence {
+  pub fn with_details(mut self, details: impl Into<String>) -> Self {
+    self.details = Some(details.into());
+    self
+  }
+
+  pub fn with_state(mut self, state: impl Into<String>) -> Self {
+    self.state = Some(state.into());
+    self
+  }
+
+  pub fn with_party(mut self, size: u32, max: u32) -> Self {
+    self.party = Some((size, max));
+    self
+  }
+
+  pub fn with_start(mut self, start: SystemTime) -> Self {
+    self.start = Some(start);
+    self
+  }
+
+  pub fn with_end(mut self, end: SystemTime) -> Self {
+    self.end = Some(end);
+    self
+  }
+
+  pub fn with_large_image(mut self, key: impl Into<String>, text: impl Into<String>) -> Self {
+    self.large_image = Some((key.into(), text.into()));
+    self
+  }
+
+  fn activity(&self) -> Activity<'_> {
+    let mut activity = Activity::new();
+    if let Some(details) = &self.details {
+      activity = activity.details(details.as_str());
+    }
+    if let Some(state) = &self.state {
+      activity = activity.state(state.as_str());
+    }
+    if let Some((size, max)) = self.party {
+      activity = activity.party(Party::new().size([size as i32, max as i32]));
+    }
+    if self.start.is_some() || self.end.is_some() {
+      let mut timestamps = Timestamps::new();
+      if let Some(start) = self.start.and_then(Self::unix_millis) {
+        timestamps = timestamps.start(start);
+      }
+      if let Some(end) = self.end.and_then(Self::unix_millis) {
+        timestamps = timestamps.end(end);
+      }
+      activity = activity.timestamps(timestamps);
+    }
+    if let Some((key, text)) = &self.large_image {
+      activity = activity.assets(Assets::new().large_image(key.as_str()).large_text(text.as_str()));
+    }
+
+    activity
+  }
+
+  fn unix_millis(time: SystemTime) -> Option<i64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|since| since.as_millis() as i64)
+  }
+}
+
+enum Message {
+  Set(Presence),
+  Clear,
+  Stop,
+}
+
+/// Sets the presence shown on Discord. Updates are sent in the background, and only the latest pending one is.
+#[derive(Clone)]
+pub struct Discord {
+  sender: Sender<Message>,
+}
+
+impl Discord {
+  pub fn set_presence(&self, presence: Presence) {
+    let _ = self.sender.send(Message::Set(presence));
+  }
+
+  pub fn clear_presence(&self) {
+    let _ = self.sender.send(Message::Clear);
+  }
+}
+
+type PresenceFrom = dyn FnMut(&Foxy, &FoxyEvent) -> Option<Presence> + Send;
+
+/// Keeps a connection to the Discord client on a background thread, reconnecting whenever Discord restarts.
+pub struct DiscordPlugin {
+  discord: Discord,
+  presence_from: Option<Box<PresenceFrom>>,
+  last_presence: Option<Presence>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl DiscordPlugin {
+  const RECONNECT_INTERVAL: Duration = Duration::from_secs(15);
+
+  /// Connects as the Discord application with `client_id`, from the Discord developer portal.
+  pub fn new(client_id: impl Into<String>) -> FoxyResult<Self> {
+    let client_id = client_id.into();
+    let (sender, receiver) = crossbeam::channel::unbounded();
+    let thread = std::thread::Builder::new()
+      .name("foxy discord".into())
+      .spawn(move || Self::run(DiscordIpcClient::new(client_id), receiver))?;
+
+    Ok(Self {
+      discord: Discord { sender },
+      presence_from: None,
+      last_presence: None,
+      thread: Some(thread),
+    })
+  }
+
+  /// Asks `presence_from` for the presence every frame. Returning `None` keeps the current one, and returning the
+  /// same presence again sends nothing.
+  pub fn with_presence_from(
+    mut self,
+    presence_from: impl FnMut(&Foxy, &FoxyEvent) -> Option<Presence> + Send + 'static,
+  ) -> Self {
+    self.presence_from = Some(Box::new(presence_from));
+    self
+  }
+
+  pub fn discord(&self) -> Discord {
+    self.discord.clone()
+  }
+
+  fn run(mut client: DiscordIpcClient, receiver: Receiver<Message>) {
+    let mut connected = false;
+    let mut presence: Option<Presence> = None;
+    // whether Discord is behind on `presence`
+    let mut dirty = false;
+
+    loop {
+      if !connected {
+        connected = client.connect().is_ok();
+        dirty |= connected;
+      }
+
+      if connected && dirty {
+        let result = match &presence {
+          Some(presence) => client.set_activity(presence.activity()),
+          None => client.clear_activity(),
+        };
+        match result {
+          Ok(()) => dirty = false,
+          Err(error) => {
+            debug!("lost connection to discord: {error}");
+            connected = false;
+          }
+        }
+      }
+
+      // wait for the next update, or for a chance to reconnect
+      let message = match receiver.recv_timeout(Self::RECONNECT_INTERVAL) {
+        // only the latest update matters, but stopping always wins
+        Ok(message) => receiver.try_iter().fold(message, |latest, next| match latest {
+          Message::Stop => latest,
+          _ => next,
+        }),
+        Err(RecvTimeoutError::Timeout) => continue,
+        Err(RecvTimeoutError::Disconnected) => Message::Stop,
+      };
+      match message {
+        Message::Set(new_presence) => presence = Some(new_presence),
+        Message::Clear => presence = None,
+        Message::Stop => break,
+      }
+      dirty = true;
+    }
+
+    if connected {
+      let _ = client.clear_activity();
+      let _ = client.close();
+    }
+  }
+}
+
+impl Plugin for DiscordPlugin {
+  fn name(&self) -> &str {
+    "discord"
+  }
+
+  fn update(&mut self, foxy: &Foxy, event: &FoxyEvent) {
+    let Some(presence) = self.presence_from.as_mut().and_then(|presence_from| presence_from(foxy, event)) else {
+      return;
+    };
+
+    if self.last_presence.as_ref() != Some(&presence) {
+      self.last_presence = Some(presence.clone());
+      self.discord.set_presence(presence);
+    }
+  }
+
+  fn stop(&mut self, _foxy: &Foxy) {
+    let _ = self.discord.sender.send(Message::Stop);
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
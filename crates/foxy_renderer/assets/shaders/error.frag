@@ -0,0 +1,9 @@
+#version 450
+
+// Fallback used when a shader is missing or fails to compile
+
+layout (location = 0) out vec4 color;
+
+void main() {
+  color = vec4(1.0, 0.0, 1.0, 1.0);
+}
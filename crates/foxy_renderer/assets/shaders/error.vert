@@ -0,0 +1,9 @@
+#version 450
+
+// Fallback used when a shader is missing or fails to compile
+
+layout (location = 0) in vec3 position;
+
+void main() {
+  gl_Position = vec4(position, 1.0);
+}
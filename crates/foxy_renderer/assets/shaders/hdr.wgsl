@@ -17,6 +17,97 @@ fn aces_tone_map(hdr: vec3<f32>) -> vec3<f32> {
     return clamp(m2 * (a / b), vec3(0.0), vec3(1.0));
 }
 
+// Applies the sRGB transfer function for outputs that lack an sRGB view
+fn linear_to_srgb(linear: vec3<f32>) -> vec3<f32> {
+    let lower = linear * 12.92;
+    let higher = 1.055 * pow(linear, vec3(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, linear <= vec3(0.0031308));
+}
+
+fn srgb_to_linear(srgb: vec3<f32>) -> vec3<f32> {
+    let lower = srgb / 12.92;
+    let higher = pow((srgb + 0.055) / 1.055, vec3(2.4));
+    return select(higher, lower, srgb <= vec3(0.04045));
+}
+
+struct OutputInfo {
+    encode_srgb: u32,
+    gamma: f32,
+    brightness: f32,
+    contrast: f32,
+    show_test_pattern: u32,
+    color_filter: u32,
+    color_blindness: u32,
+};
+
+// Simulates dichromacy in linear RGB, using the matrices by Machado et al. (2009) at full severity
+fn simulate_color_blindness(color: vec3<f32>) -> vec3<f32> {
+    switch output_info.color_blindness {
+        case 0u: {
+            return transpose(mat3x3(
+                0.152286, 1.052583, -0.204868,
+                0.114503, 0.786281, 0.099216,
+                -0.003882, -0.048116, 1.051998,
+            )) * color;
+        }
+        case 1u: {
+            return transpose(mat3x3(
+                0.367322, 0.860646, -0.227968,
+                0.280085, 0.672501, 0.047413,
+                -0.011820, 0.042940, 0.968881,
+            )) * color;
+        }
+        default: {
+            return transpose(mat3x3(
+                1.255528, -0.076749, -0.178779,
+                -0.078411, 0.930809, 0.147602,
+                0.004733, 0.691367, 0.303900,
+            )) * color;
+        }
+    }
+}
+
+// Simulates or corrects for the selected color blindness. Correction moves the difference the viewer can't see
+// into channels they can (daltonization)
+fn filter_color(color: vec3<f32>) -> vec3<f32> {
+    if output_info.color_filter == 0u {
+        return color;
+    }
+
+    let simulated = simulate_color_blindness(color);
+    if output_info.color_filter == 1u {
+        return clamp(simulated, vec3(0.0), vec3(1.0));
+    }
+
+    let error = color - simulated;
+    var shift = vec3(0.0, 0.7 * error.r + error.g, 0.7 * error.r + error.b);
+    if output_info.color_blindness == 2u {
+        shift = vec3(error.r + 0.7 * error.b, error.g + 0.7 * error.b, 0.0);
+    }
+    return clamp(color + shift, vec3(0.0), vec3(1.0));
+}
+
+// Applies the user's display calibration to a tonemapped color
+fn calibrate(color: vec3<f32>) -> vec3<f32> {
+    let adjusted = pow(color, vec3(1.0 / max(output_info.gamma, 0.01)));
+    return clamp((adjusted - 0.5) * output_info.contrast + 0.5 + output_info.brightness, vec3(0.0), vec3(1.0));
+}
+
+// A gray ramp on the top half, and patches one to eight steps away from black and white on the bottom half, in
+// display (sRGB) values
+fn test_pattern(uv: vec2<f32>) -> vec3<f32> {
+    if uv.y < 0.5 {
+        return srgb_to_linear(vec3(floor(uv.x * 16.0) / 15.0));
+    }
+
+    let cell = vec2<i32>(floor(vec2(uv.x * 16.0, (uv.y - 0.5) * 8.0)));
+    let is_white_row = cell.y >= 2;
+    let is_patch = cell.x % 2 == 1 && cell.y % 2 == 1;
+    let step = f32(cell.x / 2 + 1) / 255.0;
+    let value = select(0.0, 1.0, is_white_row) + select(0.0, select(step, -step, is_white_row), is_patch);
+    return srgb_to_linear(vec3(value));
+}
+
 struct VertexOutput {
     @location(0) uv: vec2<f32>,
     @builtin(position) clip_position: vec4<f32>,
@@ -45,9 +136,21 @@ var hdr_image: texture_2d<f32>;
 @binding(1)
 var hdr_sampler: sampler;
 
+@group(0)
+@binding(2)
+var<uniform> output_info: OutputInfo;
+
 @fragment
 fn fs_main(vs: VertexOutput) -> @location(0) vec4<f32> {
     let hdr = textureSample(hdr_image, hdr_sampler, vs.uv);
-    let sdr = aces_tone_map(hdr.rgb);
+    var sdr = filter_color(aces_tone_map(hdr.rgb));
+    if output_info.show_test_pattern != 0u {
+        // undo the horizontal flip so the pattern reads left to right
+        sdr = test_pattern(vec2(1.0 - vs.uv.x, vs.uv.y));
+    }
+    sdr = calibrate(sdr);
+    if output_info.encode_srgb != 0u {
+        sdr = linear_to_srgb(sdr);
+    }
     return vec4(sdr, hdr.a);
 }
\ No newline at end of file
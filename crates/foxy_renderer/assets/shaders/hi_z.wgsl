@@ -0,0 +1,52 @@
+// Builds a hierarchical depth pyramid where every texel holds the (min, max) depth of the texels it covers in the
+// level below it
+
+@group(0) @binding(0)
+var depth: texture_depth_2d;
+@group(0) @binding(1)
+var previous_level: texture_2d<f32>;
+@group(0) @binding(2)
+var next_level: texture_storage_2d<rg32float, write>;
+
+@compute
+@workgroup_size(8, 8)
+fn cs_copy_depth(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(next_level);
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+
+    let value = textureLoad(depth, vec2<i32>(id.xy), 0);
+    textureStore(next_level, vec2<i32>(id.xy), vec4(value, value, 0.0, 0.0));
+}
+
+@compute
+@workgroup_size(8, 8)
+fn cs_downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(next_level);
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+
+    let previous_size = vec2<i32>(textureDimensions(previous_level));
+    let base = vec2<i32>(id.xy) * 2;
+
+    // the last row/column of an odd sized level has to cover an extra texel so none are skipped
+    let extent = vec2<i32>(
+        select(2, 3, (previous_size.x & 1) == 1 && id.x == size.x - 1u),
+        select(2, 3, (previous_size.y & 1) == 1 && id.y == size.y - 1u),
+    );
+
+    var near = 1.0;
+    var far = 0.0;
+    for (var y = 0; y < extent.y; y++) {
+        for (var x = 0; x < extent.x; x++) {
+            let coord = min(base + vec2(x, y), previous_size - 1);
+            let value = textureLoad(previous_level, coord, 0).rg;
+            near = min(near, value.r);
+            far = max(far, value.g);
+        }
+    }
+
+    textureStore(next_level, vec2<i32>(id.xy), vec4(near, far, 0.0, 0.0));
+}
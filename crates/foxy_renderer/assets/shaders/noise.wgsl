@@ -0,0 +1,194 @@
+// Seedable Perlin, simplex and Worley noise, matching `foxy::math::noise::Noise` on the CPU. Pull it into a shader
+// with `#include "noise.wgsl"`.
+
+// PCG's output permutation, which spreads every input bit over the whole output
+fn noise_hash(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn noise_hash2(cell: vec2<i32>, seed: u32) -> u32 {
+    return noise_hash(bitcast<u32>(cell.x) ^ noise_hash(bitcast<u32>(cell.y) ^ noise_hash(seed)));
+}
+
+fn noise_hash3(cell: vec3<i32>, seed: u32) -> u32 {
+    return noise_hash(
+        bitcast<u32>(cell.x) ^ noise_hash(bitcast<u32>(cell.y) ^ noise_hash(bitcast<u32>(cell.z) ^ noise_hash(seed)))
+    );
+}
+
+fn noise_unit(hash: u32) -> f32 {
+    return f32(hash >> 8u) / 16777216.0;
+}
+
+fn noise_grad2(hash: u32, local: vec2<f32>) -> f32 {
+    switch hash & 7u {
+        case 0u: { return local.x + local.y; }
+        case 1u: { return -local.x + local.y; }
+        case 2u: { return local.x - local.y; }
+        case 3u: { return -local.x - local.y; }
+        case 4u: { return local.x; }
+        case 5u: { return -local.x; }
+        case 6u: { return local.y; }
+        default: { return -local.y; }
+    }
+}
+
+fn noise_grad3(hash: u32, local: vec3<f32>) -> f32 {
+    let h = hash & 15u;
+    let u = select(local.y, local.x, h < 8u);
+    var v = local.z;
+    if h < 4u {
+        v = local.y;
+    } else if h == 12u || h == 14u {
+        v = local.x;
+    }
+    return select(-u, u, (h & 1u) == 0u) + select(-v, v, (h & 2u) == 0u);
+}
+
+fn noise_fade(t: vec3<f32>) -> vec3<f32> {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+// Perlin noise, roughly from -1 to 1 and 0 at every integer point
+fn perlin2(point: vec2<f32>, seed: u32) -> f32 {
+    let local = point - floor(point);
+    let cell = vec2<i32>(floor(point));
+    let fade = local * local * local * (local * (local * 6.0 - 15.0) + 10.0);
+
+    let c00 = noise_grad2(noise_hash2(cell, seed), local);
+    let c10 = noise_grad2(noise_hash2(cell + vec2(1, 0), seed), local - vec2(1.0, 0.0));
+    let c01 = noise_grad2(noise_hash2(cell + vec2(0, 1), seed), local - vec2(0.0, 1.0));
+    let c11 = noise_grad2(noise_hash2(cell + vec2(1, 1), seed), local - vec2(1.0, 1.0));
+    return mix(mix(c00, c10, fade.x), mix(c01, c11, fade.x), fade.y);
+}
+
+// Perlin noise, roughly from -1 to 1 and 0 at every integer point
+fn perlin3(point: vec3<f32>, seed: u32) -> f32 {
+    let local = point - floor(point);
+    let cell = vec3<i32>(floor(point));
+    let fade = noise_fade(local);
+
+    var layers: array<f32, 2>;
+    for (var z = 0; z < 2; z++) {
+        let c00 = noise_grad3(noise_hash3(cell + vec3(0, 0, z), seed), local - vec3(0.0, 0.0, f32(z)));
+        let c10 = noise_grad3(noise_hash3(cell + vec3(1, 0, z), seed), local - vec3(1.0, 0.0, f32(z)));
+        let c01 = noise_grad3(noise_hash3(cell + vec3(0, 1, z), seed), local - vec3(0.0, 1.0, f32(z)));
+        let c11 = noise_grad3(noise_hash3(cell + vec3(1, 1, z), seed), local - vec3(1.0, 1.0, f32(z)));
+        layers[z] = mix(mix(c00, c10, fade.x), mix(c01, c11, fade.x), fade.y);
+    }
+    return mix(layers[0], layers[1], fade.z);
+}
+
+fn noise_simplex2_corner(cell: vec2<i32>, local: vec2<f32>, seed: u32) -> f32 {
+    let t = 0.5 - dot(local, local);
+    if t <= 0.0 {
+        return 0.0;
+    }
+    return t * t * t * t * noise_grad2(noise_hash2(cell, seed), local);
+}
+
+// Simplex noise, roughly from -1 to 1
+fn simplex2(point: vec2<f32>, seed: u32) -> f32 {
+    let skew = 0.36602542; // (sqrt(3) - 1) / 2
+    let unskew = 0.21132487; // (3 - sqrt(3)) / 6
+
+    let cell = floor(point + (point.x + point.y) * skew);
+    let first = point - (cell - (cell.x + cell.y) * unskew);
+    let step = select(vec2(0, 1), vec2(1, 0), first.x > first.y);
+    let base = vec2<i32>(cell);
+
+    let sum = noise_simplex2_corner(base, first, seed)
+        + noise_simplex2_corner(base + step, first - vec2<f32>(step) + unskew, seed)
+        + noise_simplex2_corner(base + vec2(1, 1), first - 1.0 + 2.0 * unskew, seed);
+    return 70.0 * sum;
+}
+
+fn noise_simplex3_corner(cell: vec3<i32>, local: vec3<f32>, seed: u32) -> f32 {
+    let t = 0.5 - dot(local, local);
+    if t <= 0.0 {
+        return 0.0;
+    }
+    return t * t * t * t * noise_grad3(noise_hash3(cell, seed), local);
+}
+
+// Simplex noise, roughly from -1 to 1
+fn simplex3(point: vec3<f32>, seed: u32) -> f32 {
+    let skew = 1.0 / 3.0;
+    let unskew = 1.0 / 6.0;
+
+    let cell = floor(point + (point.x + point.y + point.z) * skew);
+    let first = point - (cell - (cell.x + cell.y + cell.z) * unskew);
+    // the corners of the tetrahedron the point is in, walking from the cell's origin towards its far corner
+    var step1: vec3<i32>;
+    var step2: vec3<i32>;
+    if first.x >= first.y {
+        if first.y >= first.z {
+            step1 = vec3(1, 0, 0);
+            step2 = vec3(1, 1, 0);
+        } else if first.x >= first.z {
+            step1 = vec3(1, 0, 0);
+            step2 = vec3(1, 0, 1);
+        } else {
+            step1 = vec3(0, 0, 1);
+            step2 = vec3(1, 0, 1);
+        }
+    } else if first.y < first.z {
+        step1 = vec3(0, 0, 1);
+        step2 = vec3(0, 1, 1);
+    } else if first.x < first.z {
+        step1 = vec3(0, 1, 0);
+        step2 = vec3(0, 1, 1);
+    } else {
+        step1 = vec3(0, 1, 0);
+        step2 = vec3(1, 1, 0);
+    }
+    let base = vec3<i32>(cell);
+
+    let sum = noise_simplex3_corner(base, first, seed)
+        + noise_simplex3_corner(base + step1, first - vec3<f32>(step1) + unskew, seed)
+        + noise_simplex3_corner(base + step2, first - vec3<f32>(step2) + 2.0 * unskew, seed)
+        + noise_simplex3_corner(base + vec3(1, 1, 1), first - 1.0 + 3.0 * unskew, seed);
+    return 76.0 * sum;
+}
+
+// Worley noise: the distance to the nearest of a scattering of points, one per grid cell
+fn worley2(point: vec2<f32>, seed: u32) -> f32 {
+    let local = point - floor(point);
+    let cell = vec2<i32>(floor(point));
+
+    var nearest = 3.4028235e38;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2(x, y);
+            let hash = noise_hash2(cell + offset, seed);
+            let feature = vec2<f32>(offset) + vec2(noise_unit(hash), noise_unit(noise_hash(hash)));
+            let to_feature = feature - local;
+            nearest = min(nearest, dot(to_feature, to_feature));
+        }
+    }
+    return sqrt(nearest);
+}
+
+// Worley noise: the distance to the nearest of a scattering of points, one per grid cell
+fn worley3(point: vec3<f32>, seed: u32) -> f32 {
+    let local = point - floor(point);
+    let cell = vec3<i32>(floor(point));
+
+    var nearest = 3.4028235e38;
+    for (var z = -1; z <= 1; z++) {
+        for (var y = -1; y <= 1; y++) {
+            for (var x = -1; x <= 1; x++) {
+                let offset = vec3(x, y, z);
+                let hash = noise_hash3(cell + offset, seed);
+                let hash_y = noise_hash(hash);
+                let jitter = vec3(noise_unit(hash), noise_unit(hash_y), noise_unit(noise_hash(hash_y)));
+                let feature = vec3<f32>(offset) + jitter;
+                let to_feature = feature - local;
+                nearest = min(nearest, dot(to_feature, to_feature));
+            }
+        }
+    }
+    return sqrt(nearest);
+}
@@ -0,0 +1,39 @@
+// Draws a flat-colored copy of a mesh shifted by a clip-space offset. Drawn once unshifted to mark the mesh in the
+// stencil buffer, then once per direction around it where the stencil isn't marked, leaving only the outline.
+
+struct OutlineInfo {
+    color: vec4<f32>,
+    offset: vec2<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> outline: OutlineInfo;
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    instance: InstanceInput,
+) -> @builtin(position) vec4<f32> {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    let clip_position = camera.view_projection * model * vec4<f32>(position, 1.0);
+    return vec4<f32>(clip_position.xy + outline.offset * clip_position.w, clip_position.zw);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return outline.color;
+}
@@ -0,0 +1,30 @@
+// Flat gray stand-in for materials whose pipelines are still compiling. Uses the same bind group layout as
+// texture.wgsl so it can be swapped in without rebinding.
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(2) @binding(0)
+var<uniform> camera: Camera;
+
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec3<f32>,
+    instance: InstanceInput,
+) -> @builtin(position) vec4<f32> {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+    return camera.view_projection * model * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.5, 0.5, 0.5, 1.0);
+}
@@ -0,0 +1,48 @@
+// Draws text from a signed distance field atlas, where 0.5 lies on a glyph's outline. The edge is smoothed over
+// about a pixel using screen-space derivatives, so it stays sharp at any distance, scale or rotation.
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var t_atlas: texture_2d<f32>;
+@group(0) @binding(1)
+var s_atlas: sampler;
+
+@group(1) @binding(0)
+var<uniform> camera: Camera;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) uv: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) uv: vec2<f32>,
+}
+
+@vertex
+fn vs_main(
+    vertex: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_projection * vec4<f32>(vertex.position, 1.0);
+    out.color = vertex.color;
+    out.uv = vertex.uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = textureSample(t_atlas, s_atlas, in.uv).r;
+    let width = max(fwidth(distance), 0.0001);
+    let alpha = smoothstep(0.5 - width, 0.5 + width, distance) * in.color.a;
+    if alpha <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, alpha);
+}
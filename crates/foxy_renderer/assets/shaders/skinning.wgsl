@@ -0,0 +1,41 @@
+// Skins vertices by blending up to four joint matrices per vertex, writing plain vertices that the regular
+// pipelines can draw. Both vertex arrays are tightly packed, so they are read one float at a time.
+
+// position (3), color (4), uv (2), joints (4), weights (4)
+const SOURCE_STRIDE: u32 = 17u;
+// position (3), color (4), uv (2)
+const OUTPUT_STRIDE: u32 = 9u;
+
+@group(0) @binding(0)
+var<storage, read> source: array<f32>;
+@group(0) @binding(1)
+var<storage, read> joints: array<mat4x4<f32>>;
+@group(0) @binding(2)
+var<storage, read_write> skinned: array<f32>;
+
+@compute
+@workgroup_size(64)
+fn cs_skin(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= arrayLength(&skinned) / OUTPUT_STRIDE {
+        return;
+    }
+
+    let input = id.x * SOURCE_STRIDE;
+    let position = vec4<f32>(source[input], source[input + 1u], source[input + 2u], 1.0);
+
+    var skinned_position = vec4<f32>(0.0);
+    for (var i = 0u; i < 4u; i++) {
+        let joint = bitcast<u32>(source[input + 9u + i]);
+        let weight = source[input + 13u + i];
+        skinned_position += weight * (joints[joint] * position);
+    }
+
+    let output = id.x * OUTPUT_STRIDE;
+    skinned[output] = skinned_position.x;
+    skinned[output + 1u] = skinned_position.y;
+    skinned[output + 2u] = skinned_position.z;
+    // color and uv are passed through untouched
+    for (var i = 3u; i < OUTPUT_STRIDE; i++) {
+        skinned[output + i] = source[input + i];
+    }
+}
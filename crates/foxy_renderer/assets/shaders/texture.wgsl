@@ -6,6 +6,29 @@ struct VertexInput {
     @location(2) tex_coords: vec2<f32>,
 }
 
+struct InstanceInput {
+    @location(3) model_0: vec4<f32>,
+    @location(4) model_1: vec4<f32>,
+    @location(5) model_2: vec4<f32>,
+    @location(6) model_3: vec4<f32>,
+}
+
+struct MaterialOverrides {
+    tint: vec4<f32>,
+    uv_offset: vec2<f32>,
+    emissive_intensity: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> overrides: MaterialOverrides;
+
+struct Camera {
+    view_projection: mat4x4<f32>,
+}
+
+@group(2) @binding(0)
+var<uniform> camera: Camera;
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) color: vec4<f32>,
@@ -15,11 +38,14 @@ struct VertexOutput {
 @vertex
 fn vs_main(
     in: VertexInput,
+    instance: InstanceInput,
 ) -> VertexOutput {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+
     var out: VertexOutput;
-    out.clip_position = vec4<f32>(in.position, 1.0);
+    out.clip_position = camera.view_projection * model * vec4<f32>(in.position, 1.0);
     out.color = in.color;
-    out.tex_coords = in.tex_coords;
+    out.tex_coords = in.tex_coords + overrides.uv_offset;
     return out;
 }
 
@@ -33,6 +59,6 @@ var s_diffuse: sampler;
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
   // TODO: Fix texture reading, cause it broke
-  var color = textureSample(t_diffuse, s_diffuse, in.tex_coords);
-  return color * in.color;
+  var color = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color * overrides.tint;
+  return vec4<f32>(color.rgb * (1.0 + overrides.emissive_intensity), color.a);
 }
\ No newline at end of file
@@ -1,20 +1,18 @@
-use std::sync::Arc;
-
 use egui::{epaint::Shadow, Context, FullOutput, RawInput, Rounding, Visuals};
 use egui_wgpu::{Renderer, ScreenDescriptor};
 use egui_winit::State;
 use wgpu::{CommandEncoder, Device, Queue, TextureFormat, TextureView};
-use winit::{event::WindowEvent, window::Window};
+use winit::event::WindowEvent;
+
+use crate::renderer::render_pass::RenderingInfo;
 
 pub struct EguiRenderer {
-  window: Arc<Window>,
   context: Context,
   renderer: Renderer,
 }
 
 impl EguiRenderer {
   pub fn new(
-    window: Arc<Window>,
     device: &Device,
     egui_context: Context,
     output_color_format: TextureFormat,
@@ -25,7 +23,6 @@ impl EguiRenderer {
     let egui_renderer = Renderer::new(device, output_color_format, output_depth_format, msaa_samples);
 
     EguiRenderer {
-      window,
       context: egui_context,
       renderer: egui_renderer,
     }
@@ -54,20 +51,9 @@ impl EguiRenderer {
       .update_buffers(device, queue, encoder, &tris, &screen_descriptor);
 
     {
-      let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-        label: Some("EGUI Pass"),
-        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-          view: window_surface_view,
-          resolve_target: None,
-          ops: wgpu::Operations {
-            load: wgpu::LoadOp::Load,
-            store: wgpu::StoreOp::Store,
-          },
-        })],
-        depth_stencil_attachment: None,
-        timestamp_writes: None,
-        occlusion_query_set: None,
-      });
+      let mut rpass = RenderingInfo::new("EGUI Pass")
+        .with_loaded_color(window_surface_view)
+        .begin_rendering(encoder);
       self.renderer.render(&mut rpass, &tris, &screen_descriptor);
     }
 
@@ -75,4 +61,15 @@ impl EguiRenderer {
       self.renderer.free_texture(x)
     }
   }
+
+  /// Applies the texture updates of a frame that isn't drawn, since egui only sends each of them once.
+  pub fn skip(&mut self, device: &Device, queue: &Queue, full_output: FullOutput) {
+    for (id, image_delta) in &full_output.textures_delta.set {
+      self.renderer.update_texture(device, queue, *id, image_delta);
+    }
+
+    for id in &full_output.textures_delta.free {
+      self.renderer.free_texture(id)
+    }
+  }
 }
@@ -1,3 +1,5 @@
+use std::sync::Mutex;
+
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,6 +16,12 @@ pub enum RendererError {
   CreateSurfaceError(#[from] wgpu::CreateSurfaceError),
   #[error("{0}")]
   RequestDeviceError(#[from] wgpu::RequestDeviceError),
+  #[error("{0}")]
+  IOError(#[from] std::io::Error),
+  #[error("{0}")]
+  NotifyError(#[from] notify::Error),
+  #[error("{0}")]
+  ImageError(#[from] image::ImageError),
 }
 
 #[macro_export]
@@ -25,3 +33,19 @@ macro_rules! renderer_error {
     $crate::error::RendererError::Error(format!($($arg)*))
   }}
 }
+
+/// Error scopes are a single stack per device, shared by every thread, so a scope pushed on one thread could otherwise
+/// be popped by another along with its errors.
+static ERROR_SCOPES: Mutex<()> = Mutex::new(());
+
+/// Runs `f` inside a validation error scope, returning the first validation error it raised. Every error scope in the
+/// renderer goes through here, so the scopes of different threads can't interleave.
+pub(crate) fn validated<T>(device: &wgpu::Device, f: impl FnOnce() -> T) -> Result<T, wgpu::Error> {
+  let _scopes = ERROR_SCOPES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+  device.push_error_scope(wgpu::ErrorFilter::Validation);
+  let value = f();
+  match pollster::block_on(device.pop_error_scope()) {
+    Some(error) => Err(error),
+    None => Ok(value),
+  }
+}
@@ -1,48 +1,145 @@
-use std::sync::Arc;
+use std::{
+  sync::{mpsc, Arc},
+  time::Instant,
+};
 
 use egui::{Context, FullOutput, RawInput};
 use egui_wgpu::ScreenDescriptor;
-use foxy_utils::time::Time;
-use image::{DynamicImage, GenericImageView};
-use tracing::debug;
+use foxy_utils::{geometry::Frustum, time::Time};
+use glam::Mat4;
+use image::{DynamicImage, GenericImageView, RgbaImage};
+use tracing::{debug, error, warn};
 use wgpu::{Color, TextureFormat};
-use winit::{event::WindowEvent, window::Window};
+use winit::{
+  event::WindowEvent,
+  raw_window_handle::{RawDisplayHandle, RawWindowHandle},
+  window::Window,
+};
 
 use self::{
-  context::GraphicsContext,
+  camera::Camera,
+  context::{Frame, GraphicsContext, PresentMode},
+  frame_graph::{Access, FrameGraph, ResourceUsage},
   material::StandardMaterial,
-  mesh::Mesh,
+  mesh::{InstanceBuffer, Mesh, MeshDraw},
+  present::{PresentStats, PresentTracker},
+  profiler::{FrameStats, GpuProfiler},
+  proxy::RenderCommands,
   render_data::{Drawable, RenderData},
-  render_pass::{simple::SimplePass, tonemap::ToneMapPass, Pass},
+  render_pass::{
+    debug_draw::DebugDrawPass,
+    hi_z::HiZPass,
+    outline::OutlinePass,
+    sdf_text::SdfTextPass,
+    simple::SimplePass,
+    skinning::{SkinnedMesh, SkinningPass},
+    tonemap::ToneMapPass,
+    CustomPass,
+    Pass,
+    PassOrder,
+    RenderingInfo,
+  },
+  shader::ShaderStore,
   target::RenderTarget,
+  text::SdfFont,
+  texture::TextureStore,
 };
 use crate::{
   egui::EguiRenderer,
   error::RendererError,
   renderer::{material::Material, texture::DiffuseTexture, vertex::Vertex},
+  renderer_error,
 };
 
+pub mod animation;
+pub mod camera;
+pub mod compute;
 pub mod context;
+pub mod frame_graph;
 pub mod material;
 pub mod mesh;
+pub mod pipeline;
+pub mod present;
+pub mod procedural;
+pub mod profiler;
+pub mod proxy;
 pub mod render_data;
 pub mod render_pass;
+pub mod shader;
 pub mod target;
+pub mod text;
 pub mod texture;
+pub mod tracking;
 pub mod vertex;
 
+#[derive(Debug, Default, Clone)]
+pub struct RendererCreateInfo {
+  /// Renders depth in a separate depth-only pass before the opaque pass, so fragment shaders only run for visible
+  /// fragments. Worth enabling for scenes with heavy overdraw or expensive fragment shaders.
+  pub depth_prepass: bool,
+  /// Stores depth reversed, with the near plane at 1 and the far plane at 0, which greatly improves depth precision
+  /// at long view distances.
+  pub reverse_z: bool,
+  /// Reloads shaders when they're edited in the asset directory, and rebuilds the pipelines using them. Meant for
+  /// development, as watching the directory has a cost.
+  pub hot_reload_shaders: bool,
+  /// The application's own passes, created along with the renderer.
+  pub custom_passes: Vec<CustomPass>,
+  pub present_mode: PresentMode,
+}
+
+impl RendererCreateInfo {
+  pub fn with_depth_prepass(mut self, depth_prepass: bool) -> Self {
+    self.depth_prepass = depth_prepass;
+    self
+  }
+
+  pub fn with_reverse_z(mut self, reverse_z: bool) -> Self {
+    self.reverse_z = reverse_z;
+    self
+  }
+
+  pub fn with_hot_reload_shaders(mut self, hot_reload_shaders: bool) -> Self {
+    self.hot_reload_shaders = hot_reload_shaders;
+    self
+  }
+
+  pub fn with_pass(mut self, pass: CustomPass) -> Self {
+    self.custom_passes.push(pass);
+    self
+  }
+
+  pub fn with_present_mode(mut self, present_mode: PresentMode) -> Self {
+    self.present_mode = present_mode;
+    self
+  }
+}
+
 pub struct Renderer {
-  window: Arc<Window>,
   context: GraphicsContext,
   egui: EguiRenderer,
   render_target: RenderTarget,
+  shader_store: ShaderStore,
+  texture_store: TextureStore,
+  commands: RenderCommands,
+  camera: Camera,
 
   simple_pass: SimplePass,
+  skinning_pass: SkinningPass,
+  hi_z_pass: HiZPass,
+  sdf_text_pass: SdfTextPass,
+  outline_pass: OutlinePass,
+  debug_draw_pass: DebugDrawPass,
   tone_map_pass: ToneMapPass,
+  custom_passes: Vec<(PassOrder, Box<dyn Pass>)>,
 
   textured_material: Arc<StandardMaterial>,
   standard_material: Arc<StandardMaterial>,
   mesh: Mesh,
+  instances: InstanceBuffer,
+  profiler: GpuProfiler,
+  present_tracker: PresentTracker,
+  render_scale: f32,
 
   is_dirty: bool,
 }
@@ -55,22 +152,104 @@ impl Renderer {
     a: 1.0,
   };
 
-  pub fn new(window: Arc<Window>, egui_context: Context) -> Result<Self, RendererError> {
+  pub const MIN_RENDER_SCALE: f32 = 0.25;
+  pub const MAX_RENDER_SCALE: f32 = 2.0;
+
+  pub fn new(
+    window: Arc<Window>,
+    egui_context: Context,
+    commands: RenderCommands,
+    create_info: &RendererCreateInfo,
+  ) -> Result<Self, RendererError> {
+    let context = GraphicsContext::new(window, create_info.present_mode)?;
+    Self::with_context(context, egui_context, commands, create_info)
+  }
+
+  /// A renderer presenting to a window of another windowing library, e.g. SDL or an editor's viewport panel, rather
+  /// than a winit window. Frames are `width` by `height` until [`Self::resize`]d.
+  ///
+  /// # Safety
+  ///
+  /// The handles must be valid, and stay valid until the renderer is dropped.
+  pub unsafe fn new_from_raw(
+    raw_window_handle: RawWindowHandle,
+    raw_display_handle: RawDisplayHandle,
+    width: u32,
+    height: u32,
+    commands: RenderCommands,
+    create_info: &RendererCreateInfo,
+  ) -> Result<Self, RendererError> {
+    // SAFETY: the caller keeps the handles valid for as long as the renderer, which owns the context
+    let context = unsafe {
+      GraphicsContext::new_from_raw(raw_window_handle, raw_display_handle, width, height, create_info.present_mode)?
+    };
+    Self::with_context(context, Context::default(), commands, create_info)
+  }
+
+  /// A renderer that draws offscreen instead of to a window, for rendering without a display, e.g. golden image
+  /// tests in CI. Frames are `width` by `height`, and can be copied back with [`Self::read_back_frame`].
+  pub fn new_headless(
+    width: u32,
+    height: u32,
+    commands: RenderCommands,
+    create_info: &RendererCreateInfo,
+  ) -> Result<Self, RendererError> {
+    let context = GraphicsContext::new_headless(width, height)?;
+    Self::with_context(context, Context::default(), commands, create_info)
+  }
+
+  fn with_context(
+    context: GraphicsContext,
+    egui_context: Context,
+    commands: RenderCommands,
+    create_info: &RendererCreateInfo,
+  ) -> Result<Self, RendererError> {
     pollster::block_on(async {
-      let context = GraphicsContext::new(window.clone())?;
-      let egui = EguiRenderer::new(
-        window.clone(),
+      let egui = EguiRenderer::new(context.device(), egui_context, context.view_format(), None, 1);
+
+      let render_target = RenderTarget::new(
         context.device(),
-        egui_context,
-        GraphicsContext::SURFACE_FORMAT,
-        None,
-        1,
+        context.config().width,
+        context.config().height,
+        create_info.reverse_z,
       );
+      let mut shader_store = ShaderStore::default();
+      if create_info.hot_reload_shaders {
+        if let Err(error) = shader_store.enable_hot_reload() {
+          warn!("failed to enable shader hot reloading: {error}");
+        }
+      }
 
-      let render_target = RenderTarget::new(window.clone(), context.device());
+      let simple_pass = SimplePass::new(
+        context.shared_device(),
+        context.queue(),
+        &mut shader_store,
+        &render_target,
+        create_info.depth_prepass,
+      )?;
+      let skinning_pass = SkinningPass::new(context.device(), &mut shader_store)?;
+      let hi_z_pass = HiZPass::new(context.device(), &mut shader_store, &render_target)?;
+      let sdf_text_pass = SdfTextPass::new(
+        context.device(),
+        context.queue(),
+        &mut shader_store,
+        &render_target,
+        Self::default_font()?,
+      )?;
+      let outline_pass = OutlinePass::new(context.device(), &mut shader_store, &render_target)?;
+      let debug_draw_pass = DebugDrawPass::new(context.device(), &mut shader_store)?;
+      let tone_map_pass = ToneMapPass::new(
+        context.device(),
+        &mut shader_store,
+        context.view_format(),
+        &render_target,
+      )?;
 
-      let simple_pass = SimplePass::new(context.device());
-      let tone_map_pass = ToneMapPass::new(context.device(), context.config(), &render_target);
+      let custom_passes = create_info
+        .custom_passes
+        .iter()
+        .map(|pass| Ok((pass.order, (pass.create)(context.device(), &mut shader_store, &render_target)?)))
+        .collect::<Result<_, RendererError>>()?;
 
       let diffuse_texture = DiffuseTexture::new(
         context.device(),
@@ -113,33 +292,209 @@ impl Renderer {
         textured_material.clone(),
       );
 
+      let profiler = GpuProfiler::new(context.device(), context.queue());
+
       Ok(Self {
-        window,
         context,
         egui,
         render_target,
+        shader_store,
+        texture_store: TextureStore::default(),
+        commands,
+        camera: Camera::default(),
         simple_pass,
+        skinning_pass,
+        hi_z_pass,
+        sdf_text_pass,
+        outline_pass,
+        debug_draw_pass,
         tone_map_pass,
+        custom_passes,
         textured_material,
         standard_material,
         mesh,
+        instances: InstanceBuffer::default(),
+        profiler,
+        present_tracker: PresentTracker::default(),
+        render_scale: 1.0,
         is_dirty: false,
       })
     })
   }
 
-  pub fn window(&self) -> &Window {
-    self.window.as_ref()
+  /// For creating textures to draw onto with [`Self::set_output_texture`].
+  pub fn device(&self) -> &wgpu::Device {
+    self.context.device()
+  }
+
+  /// `None` for headless renderers, and those created from raw handles.
+  pub fn window(&self) -> Option<&Window> {
+    self.context.window()
+  }
+
+  pub fn shader_store(&mut self) -> &mut ShaderStore {
+    &mut self.shader_store
+  }
+
+  /// For loading textures on the render thread. Other threads load them through
+  /// [`RendererProxy::load_material`](proxy::RendererProxy::load_material).
+  pub fn texture_store(&mut self) -> &mut TextureStore {
+    &mut self.texture_store
+  }
+
+  /// For creating [`SkinnedMesh`]es.
+  pub fn skinning_pass(&self) -> &SkinningPass {
+    &self.skinning_pass
+  }
+
+  pub fn camera(&self) -> &Camera {
+    &self.camera
+  }
+
+  pub fn render_target(&self) -> &RenderTarget {
+    &self.render_target
+  }
+
+  /// GPU time per pass, frame time and draw count of the newest frame the GPU has finished, which lags a few frames
+  /// behind the frame being drawn. Pass timings are also logged as `gpu_frame` and `gpu_pass` tracing spans.
+  pub fn frame_stats(&self) -> &FrameStats {
+    self.profiler.stats()
+  }
+
+  /// The display's refresh rate, missed vsyncs and swapchain waits as of the last frame presented.
+  pub fn present_stats(&self) -> &PresentStats {
+    self.present_tracker.stats()
+  }
+
+  /// Adds a pass of the application's, to run at `order` after the passes already there.
+  pub fn insert_pass(&mut self, order: PassOrder, pass: Box<dyn Pass>) {
+    self.custom_passes.push((order, pass));
+  }
+
+  /// Describes the passes the renderer runs each frame with its current configuration. See
+  /// [`FrameGraph::export_graphviz`] for viewing it.
+  pub fn frame_graph(&self) -> FrameGraph {
+    const COLOR: &str = "Render Target";
+    const DEPTH: &str = "Depth Stencil";
+    const HI_Z: &str = "Hi-Z Pyramid";
+    const SURFACE: &str = "Surface";
+
+    let graph = FrameGraph::new().with_pass("Clearing Pass", [(COLOR, Access::Write, ResourceUsage::ColorAttachment)]);
+
+    let graph = if self.simple_pass.has_depth_prepass() {
+      graph
+        .with_pass("Simple Depth Prepass", [(
+          DEPTH,
+          Access::Write,
+          ResourceUsage::DepthStencilAttachment,
+        )])
+        .with_pass("Simple Pass", [
+          (COLOR, Access::ReadWrite, ResourceUsage::ColorAttachment),
+          (DEPTH, Access::Read, ResourceUsage::DepthStencilAttachment),
+        ])
+    } else {
+      graph.with_pass("Simple Pass", [
+        (COLOR, Access::ReadWrite, ResourceUsage::ColorAttachment),
+        (DEPTH, Access::Write, ResourceUsage::DepthStencilAttachment),
+      ])
+    };
+
+    let graph = graph.with_pass("Hi-Z Pass", [
+      (DEPTH, Access::Read, ResourceUsage::Sampled),
+      (HI_Z, Access::Write, ResourceUsage::Storage),
+    ]);
+    let graph = self
+      .with_custom_passes(graph, PassOrder::AfterOpaque, COLOR)
+      .with_pass("SDF Text Pass", [
+        (COLOR, Access::ReadWrite, ResourceUsage::ColorAttachment),
+        (DEPTH, Access::Read, ResourceUsage::DepthStencilAttachment),
+      ])
+      .with_pass("Outline Pass", [
+        (COLOR, Access::ReadWrite, ResourceUsage::ColorAttachment),
+        (DEPTH, Access::ReadWrite, ResourceUsage::DepthStencilAttachment),
+      ])
+      .with_pass("Debug Draw Pass", [(COLOR, Access::ReadWrite, ResourceUsage::ColorAttachment)]);
+    let graph = self
+      .with_custom_passes(graph, PassOrder::BeforeToneMap, COLOR)
+      .with_pass("Tone Map Pass", [
+        (COLOR, Access::Read, ResourceUsage::Sampled),
+        (SURFACE, Access::Write, ResourceUsage::ColorAttachment),
+      ]);
+    self
+      .with_custom_passes(graph, PassOrder::AfterToneMap, SURFACE)
+      .with_pass("Egui Pass", [(SURFACE, Access::ReadWrite, ResourceUsage::ColorAttachment)])
   }
 
   pub fn refresh(&mut self) {
     self.is_dirty = true;
   }
 
+  pub fn render_scale(&self) -> f32 {
+    self.render_scale
+  }
+
+  /// Renders the scene at `render_scale` times the resolution of the frames, e.g. below 1 to save GPU time on fill
+  /// rate. The scene is scaled back up when tone mapped, and the UI is always drawn at full resolution. Clamped between
+  /// [`Self::MIN_RENDER_SCALE`] and [`Self::MAX_RENDER_SCALE`].
+  pub fn set_render_scale(&mut self, render_scale: f32) {
+    let render_scale = render_scale.clamp(Self::MIN_RENDER_SCALE, Self::MAX_RENDER_SCALE);
+    if render_scale != self.render_scale {
+      self.render_scale = render_scale;
+      self.is_dirty = true;
+    }
+  }
+
+  /// Whether frames are being skipped because the window has no area, e.g. while it's minimized.
+  pub fn is_zero_sized(&self) -> bool {
+    self.context.is_zero_sized()
+  }
+
+  /// Resizes the frames of a renderer created with [`Self::new_from_raw`]. Renderers of winit windows follow the
+  /// window's size on [`Self::refresh`] instead.
+  pub fn resize(&mut self, width: u32, height: u32) {
+    self.context.resize(width, height);
+    self.is_dirty = true;
+  }
+
   pub fn draw(&mut self, render_time: Time, render_data: RenderData) -> Result<(), RendererError> {
-    match self.next_frame() {
+    self.reload_shaders();
+    self.commands.process(
+      self.context.device(),
+      self.context.queue(),
+      &self.skinning_pass,
+      &mut self.shader_store,
+      &mut self.texture_store,
+    );
+
+    if let Some(render_scale) = render_data.render_scale {
+      self.set_render_scale(render_scale);
+    }
+    if self.is_dirty {
+      self.reconfigure();
+      // there's nothing to resize to while the window has no area, so it's checked again every frame until it has one
+      self.is_dirty = self.context.is_zero_sized();
+    }
+    if self.context.is_zero_sized() {
+      self
+        .egui
+        .skip(self.context.device(), self.context.queue(), render_data.full_output);
+      self.present_tracker.pause();
+      return Ok(());
+    }
+
+    let acquire_start = Instant::now();
+    let next_frame = self.context.next_frame();
+    let acquire_time = acquire_start.elapsed();
+    match next_frame {
       Ok(frame) => {
-        let view = frame.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        if matches!(&frame, Frame::Surface(frame) if frame.suboptimal) {
+          self.is_dirty = true;
+        }
+
+        let view = frame.texture().create_view(&wgpu::TextureViewDescriptor {
+          format: Some(self.context.view_format()),
+          ..Default::default()
+        });
 
         let mut command_encoder = self
           .context
@@ -147,87 +502,384 @@ impl Renderer {
           .create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
           });
+        self.profiler.begin_frame(self.context.device(), *render_time.delta());
 
         {
           // clear attachment
-          let _render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Clearing Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-              view: &self.render_target.view,
-              resolve_target: None,
-              ops: wgpu::Operations {
-                load: wgpu::LoadOp::Clear(Self::CLEAR_VALUE),
-                store: wgpu::StoreOp::Store,
-              },
-            })],
-            depth_stencil_attachment: None,
-            occlusion_query_set: None,
-            timestamp_writes: None,
-          });
+          let _render_pass = RenderingInfo::new("Clearing Pass")
+            .with_cleared_color(&self.render_target.view, Self::CLEAR_VALUE)
+            .begin_rendering(&mut command_encoder);
+        }
+
+        if let Some(camera) = render_data.camera {
+          self.camera = camera;
         }
 
+        let skinned_meshes: Vec<&SkinnedMesh> = render_data
+          .skinned_draws
+          .iter()
+          .filter_map(|draw| {
+            let mesh = draw.mesh.get()?;
+            mesh.set_joint_matrices(self.context.queue(), &draw.joints);
+            Some(mesh)
+          })
+          .collect();
+        self.profiler.scope(&mut command_encoder, "Skinning Pass", |encoder| {
+          self.skinning_pass.dispatch(encoder, &skinned_meshes);
+        });
+
+        let aspect_ratio = self.render_target.width() as f32 / self.render_target.height() as f32;
+        let frustum = self.camera.frustum(aspect_ratio);
+        let visible: Vec<(usize, &Mesh, Vec<Mat4>)> =
+          if render_data.draws.is_empty() && render_data.skinned_draws.is_empty() {
+            vec![(0, &self.mesh, vec![Mat4::IDENTITY])]
+          } else {
+            let skinned_draws = render_data
+              .skinned_draws
+              .iter()
+              .map(|draw| (draw.mesh.get().map(SkinnedMesh::mesh), &draw.instances));
+            render_data
+              .draws
+              .iter()
+              .map(|draw| (draw.mesh.get(), &draw.instances))
+              .chain(skinned_draws)
+              .enumerate()
+              .filter_map(|(index, (mesh, instances))| {
+                let mesh = mesh?;
+                Some((index, mesh, Self::cull(&frustum, mesh, instances)))
+              })
+              .filter(|(_, _, instances)| !instances.is_empty())
+              .collect()
+          };
+        let draws: Vec<(usize, &Mesh, &[Mat4])> = visible
+          .iter()
+          .map(|(index, mesh, instances)| (*index, *mesh, instances.as_slice()))
+          .collect();
+        let draws = self
+          .instances
+          .upload(self.context.device(), self.context.queue(), &draws);
+        self.profiler.set_draw_count(draws.len());
+        self
+          .simple_pass
+          .set_camera(self.context.queue(), &self.camera, &self.render_target);
+        self
+          .sdf_text_pass
+          .set_camera(self.context.queue(), &self.camera, &self.render_target);
+        self
+          .outline_pass
+          .set_camera(self.context.queue(), &self.camera, &self.render_target);
+        self
+          .debug_draw_pass
+          .set_camera(self.context.queue(), &self.camera, &self.render_target);
+
         self
           .simple_pass
-          .draw(&mut command_encoder, &self.render_target.view, &self.mesh)?;
+          .set_material_overrides(self.context.queue(), &render_data.material_overrides);
+        self.profiler.scope(&mut command_encoder, "Simple Pass", |encoder| {
+          self
+            .simple_pass
+            .draw(encoder, &self.render_target, &self.render_target.view, &draws)
+        })?;
+
+        self.profiler.scope(&mut command_encoder, "Hi-Z Pass", |encoder| {
+          self
+            .hi_z_pass
+            .draw(encoder, &self.render_target, &self.render_target.view, &draws)
+        })?;
+
+        Self::draw_custom_passes(
+          Self::enabled_passes(&mut self.custom_passes, PassOrder::AfterOpaque, &render_data.disabled_passes),
+          &mut self.profiler,
+          &mut command_encoder,
+          &self.render_target,
+          &self.render_target.view,
+          &draws,
+        )?;
+
+        self
+          .sdf_text_pass
+          .set_text(self.context.device(), self.context.queue(), &render_data.world_text);
+        self.profiler.scope(&mut command_encoder, "SDF Text Pass", |encoder| {
+          self
+            .sdf_text_pass
+            .draw(encoder, &self.render_target, &self.render_target.view, &draws)
+        })?;
+
+        self.outline_pass.set_highlighted(&render_data.highlighted_draws);
+        self.profiler.scope(&mut command_encoder, "Outline Pass", |encoder| {
+          self
+            .outline_pass
+            .draw(encoder, &self.render_target, &self.render_target.view, &draws)
+        })?;
+
+        self
+          .debug_draw_pass
+          .set_lines(self.context.device(), self.context.queue(), &render_data.debug_lines);
+        self
+          .profiler
+          .scope(&mut command_encoder, "Debug Draw Pass", |encoder| {
+            self
+              .debug_draw_pass
+              .draw(encoder, &self.render_target, &self.render_target.view, &draws)
+          })?;
+
+        Self::draw_custom_passes(
+          Self::enabled_passes(&mut self.custom_passes, PassOrder::BeforeToneMap, &render_data.disabled_passes),
+          &mut self.profiler,
+          &mut command_encoder,
+          &self.render_target,
+          &self.render_target.view,
+          &draws,
+        )?;
 
         // Finish by rendering onto the primary view
-        self.tone_map_pass.draw(&mut command_encoder, &view, &self.mesh)?;
+        if let Some(calibration) = render_data.display_calibration {
+          self.tone_map_pass.set_calibration(self.context.queue(), calibration);
+        }
+        if let Some(color_filter) = render_data.color_filter {
+          self.tone_map_pass.set_color_filter(self.context.queue(), color_filter);
+        }
+        self
+          .tone_map_pass
+          .set_test_pattern(self.context.queue(), render_data.show_calibration_pattern);
+        self.profiler.scope(&mut command_encoder, "Tone Map Pass", |encoder| {
+          self.tone_map_pass.draw(encoder, &self.render_target, &view, &draws)
+        })?;
+
+        Self::draw_custom_passes(
+          Self::enabled_passes(&mut self.custom_passes, PassOrder::AfterToneMap, &render_data.disabled_passes),
+          &mut self.profiler,
+          &mut command_encoder,
+          &self.render_target,
+          &view,
+          &draws,
+        )?;
 
         // EGUI
 
         let screen_descriptor = ScreenDescriptor {
           size_in_pixels: [self.context.config().width, self.context.config().height],
-          pixels_per_point: self.window().scale_factor() as f32,
+          pixels_per_point: render_data.full_output.pixels_per_point,
         };
 
-        self.egui.draw(
-          self.context.device(),
-          self.context.queue(),
-          &mut command_encoder,
-          &view,
-          screen_descriptor,
-          render_data.full_output,
-        );
+        self.profiler.scope(&mut command_encoder, "Egui Pass", |encoder| {
+          self.egui.draw(
+            self.context.device(),
+            self.context.queue(),
+            encoder,
+            &view,
+            screen_descriptor,
+            render_data.full_output,
+          );
+        });
 
+        self.profiler.resolve(&mut command_encoder);
         // submit will accept anything that implements IntoIter
         self.context.queue().submit(Some(command_encoder.finish()));
-        self.window.pre_present_notify();
-        frame.present();
+        self.profiler.end_frame();
+        self.context.present(frame);
+        self.present_tracker.presented(acquire_time, self.context.refresh_rate());
 
         Ok(())
       }
-      Err(RendererError::RebuildSwapchain) => Ok(()),
-      Err(error) => Err(error),
+      Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated | wgpu::SurfaceError::OutOfMemory) => {
+        self.refresh();
+        Ok(())
+      }
+      Err(error) => Err(RendererError::SurfaceError(error)),
     }
   }
+
+  /// The texture a headless renderer draws its frames onto.
+  pub fn output_texture(&self) -> Option<&wgpu::Texture> {
+    self.context.offscreen_texture()
+  }
+
+  /// Draws the frames of a headless renderer onto `texture` from the next frame on, rather than the renderer's own
+  /// texture. This is how the engine's viewport is embedded in another UI, e.g. an editor that shows the texture in a
+  /// panel, swapping in a new texture whenever the panel is resized.
+  ///
+  /// The texture must be created on [`Self::device`], in [`GraphicsContext::SURFACE_FORMAT`], and usable as a render
+  /// attachment. Sharing the texture with other graphics APIs or processes isn't supported.
+  pub fn set_output_texture(&mut self, texture: wgpu::Texture) -> Result<(), RendererError> {
+    self.context.set_external_texture(texture)?;
+    self.is_dirty = true;
+    Ok(())
+  }
+
+  /// Copies the last frame drawn by a headless renderer back to the CPU, e.g. to compare against a golden image or
+  /// save a screenshot. Blocks until the GPU has finished the frame.
+  pub fn read_back_frame(&self) -> Result<RgbaImage, RendererError> {
+    let texture = self
+      .context
+      .offscreen_texture()
+      .ok_or_else(|| renderer_error!("only headless renderers can read back frames"))?;
+    let (width, height) = (texture.width(), texture.height());
+
+    let unpadded_bytes_per_row = width * 4;
+    let padded_bytes_per_row = unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = self.context.device().create_buffer(&wgpu::BufferDescriptor {
+      label: Some("Frame Read Back Buffer"),
+      size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+      usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+      mapped_at_creation: false,
+    });
+
+    let mut command_encoder = self
+      .context
+      .device()
+      .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Read Back Encoder"),
+      });
+    command_encoder.copy_texture_to_buffer(
+      texture.as_image_copy(),
+      wgpu::ImageCopyBuffer {
+        buffer: &buffer,
+        layout: wgpu::ImageDataLayout {
+          offset: 0,
+          bytes_per_row: Some(padded_bytes_per_row),
+          rows_per_image: Some(height),
+        },
+      },
+      texture.size(),
+    );
+    self.context.queue().submit(Some(command_encoder.finish()));
+
+    let (sender, receiver) = mpsc::channel();
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+      let _ = sender.send(result);
+    });
+    self.context.device().poll(wgpu::Maintain::Wait);
+    receiver
+      .recv()
+      .map_err(|_| renderer_error!("the frame was never mapped"))?
+      .map_err(|error| renderer_error!("failed to map the frame: {error}"))?;
+
+    let pixels = slice
+      .get_mapped_range()
+      .chunks(padded_bytes_per_row as usize)
+      .flat_map(|row| row.iter().take(unpadded_bytes_per_row as usize))
+      .copied()
+      .collect();
+    buffer.unmap();
+
+    RgbaImage::from_raw(width, height, pixels).ok_or_else(|| renderer_error!("read back a frame of the wrong size"))
+  }
 }
 
 impl Renderer {
   fn reconfigure(&mut self) {
     self.context.reconfigure();
-    self.render_target.resize(self.context.device());
+    if self.context.is_zero_sized() {
+      return;
+    }
+    let config = self.context.config();
+    let max_size = self.context.device().limits().max_texture_dimension_2d;
+    let scaled = |size: u32| ((size as f32 * self.render_scale).round() as u32).clamp(1, max_size);
+    self
+      .render_target
+      .resize(self.context.device(), scaled(config.width), scaled(config.height));
     self.simple_pass.resize(self.context.device(), &self.render_target);
+    self.hi_z_pass.resize(self.context.device(), &self.render_target);
+    self.outline_pass.resize(self.context.device(), &self.render_target);
     self.tone_map_pass.resize(self.context.device(), &self.render_target);
+    for (_, pass) in &mut self.custom_passes {
+      pass.resize(self.context.device(), &self.render_target);
+    }
   }
 
-  fn next_frame(&mut self) -> Result<wgpu::SurfaceTexture, RendererError> {
-    if self.is_dirty {
-      self.reconfigure();
-      self.is_dirty = false;
+  /// The instances of `mesh` that are at least partly inside `frustum`.
+  fn cull(frustum: &Frustum, mesh: &Mesh, instances: &[Mat4]) -> Vec<Mat4> {
+    let Some(bounds) = mesh.bounds else {
+      return instances.to_vec();
+    };
+    instances
+      .iter()
+      .filter(|transform| frustum.intersects_sphere(&bounds.transformed(transform)))
+      .copied()
+      .collect()
+  }
+
+  /// The custom passes that run at `order`, leaving out the ones disabled for the frame.
+  fn enabled_passes<'a>(
+    custom_passes: &'a mut [(PassOrder, Box<dyn Pass>)],
+    order: PassOrder,
+    disabled_passes: &'a [&'static str],
+  ) -> impl Iterator<Item = &'a mut Box<dyn Pass>> {
+    custom_passes
+      .iter_mut()
+      .filter(move |(pass_order, pass)| *pass_order == order && !disabled_passes.contains(&pass.name()))
+      .map(|(_, pass)| pass)
+  }
+
+  fn draw_custom_passes<'a>(
+    passes: impl Iterator<Item = &'a mut Box<dyn Pass>>,
+    profiler: &mut GpuProfiler,
+    command_encoder: &mut wgpu::CommandEncoder,
+    render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    draws: &[MeshDraw],
+  ) -> Result<(), RendererError> {
+    for pass in passes {
+      profiler.scope(command_encoder, pass.name(), |encoder| {
+        pass.draw(encoder, render_target, output, draws)
+      })?;
     }
+    Ok(())
+  }
 
-    match self.context.surface().get_current_texture() {
-      Ok(frame) => {
-        if frame.suboptimal {
-          self.refresh();
-        }
-        Ok(frame)
-      }
-      Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated | wgpu::SurfaceError::OutOfMemory) => {
-        self.refresh();
-        Err(RendererError::RebuildSwapchain)
-      }
-      Err(error) => Err(RendererError::SurfaceError(error)),
+  fn with_custom_passes(&self, graph: FrameGraph, order: PassOrder, target: &'static str) -> FrameGraph {
+    self
+      .custom_passes
+      .iter()
+      .filter(|(pass_order, _)| *pass_order == order)
+      .fold(graph, |graph, (_, pass)| {
+        graph.with_pass(pass.name(), [(target, Access::ReadWrite, ResourceUsage::ColorAttachment)])
+      })
+  }
+
+  /// Rebuilds the pipelines of every pass using a shader that was edited since the last frame.
+  fn reload_shaders(&mut self) {
+    let device = self.context.shared_device();
+    let reloaded = self.shader_store.reload_changed(device);
+    if reloaded.is_empty() {
+      return;
+    }
+
+    let skinning_pass = &mut self.skinning_pass;
+    let mut results = vec![skinning_pass.reload_shaders(device, &mut self.shader_store, &reloaded)];
+    let passes: [&mut dyn Pass; 6] = [
+      &mut self.simple_pass,
+      &mut self.hi_z_pass,
+      &mut self.sdf_text_pass,
+      &mut self.outline_pass,
+      &mut self.debug_draw_pass,
+      &mut self.tone_map_pass,
+    ];
+    let custom_passes = self.custom_passes.iter_mut().map(|(_, pass)| pass.as_mut());
+    results.extend(
+      passes
+        .into_iter()
+        .chain(custom_passes)
+        .map(|pass| pass.reload_shaders(device, &mut self.shader_store, &self.render_target, &reloaded)),
+    );
+
+    for error in results.into_iter().filter_map(Result::err) {
+      error!("failed to rebuild pipelines with reloaded shaders, keeping the old ones: {error}");
     }
   }
+
+  /// Egui's default proportional font, so world text matches the UI.
+  fn default_font() -> Result<SdfFont, RendererError> {
+    let fonts = egui::FontDefinitions::default();
+    let data = fonts
+      .families
+      .get(&egui::FontFamily::Proportional)
+      .and_then(|family| family.first())
+      .and_then(|name| fonts.font_data.get(name))
+      .ok_or_else(|| renderer_error!("no default font to build the SDF font from"))?;
+
+    SdfFont::new(data.font.to_vec(), SdfFont::DEFAULT_CHARACTERS)
+  }
 }
@@ -0,0 +1,315 @@
+pub mod graph;
+pub mod ik;
+
+use std::sync::Arc;
+
+use glam::{Mat4, Quat, Vec3};
+
+/// The local transform of a single joint.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct JointTransform {
+  pub translation: Vec3,
+  pub rotation: Quat,
+  pub scale: Vec3,
+}
+
+impl Default for JointTransform {
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+impl JointTransform {
+  pub const IDENTITY: Self = Self {
+    translation: Vec3::ZERO,
+    rotation: Quat::IDENTITY,
+    scale: Vec3::ONE,
+  };
+
+  pub fn lerp(&self, other: &Self, t: f32) -> Self {
+    Self {
+      translation: self.translation.lerp(other.translation, t),
+      rotation: self.rotation.slerp(other.rotation, t),
+      scale: self.scale.lerp(other.scale, t),
+    }
+  }
+
+  pub fn to_matrix(&self) -> Mat4 {
+    Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+  }
+
+  /// Places `local`, relative to this transform, in this transform's parent space.
+  pub fn combine(&self, local: &Self) -> Self {
+    Self {
+      translation: self.translation + self.rotation * (self.scale * local.translation),
+      rotation: self.rotation * local.rotation,
+      scale: self.scale * local.scale,
+    }
+  }
+}
+
+/// The joint hierarchy of a skeleton. Parents must come before their children.
+#[derive(Debug, Default, Clone)]
+pub struct Skeleton {
+  parents: Vec<Option<usize>>,
+}
+
+impl Skeleton {
+  pub fn new(parents: Vec<Option<usize>>) -> Self {
+    Self { parents }
+  }
+
+  pub fn joint_count(&self) -> usize {
+    self.parents.len()
+  }
+
+  pub fn parent(&self, joint: usize) -> Option<usize> {
+    self.parents.get(joint).copied().flatten()
+  }
+
+  /// Transforms every joint of `pose` from its parent's space into model space.
+  pub fn model_transforms(&self, pose: &Pose) -> Vec<JointTransform> {
+    let mut model = Vec::with_capacity(pose.joints.len());
+    for (joint, local) in pose.joints.iter().enumerate() {
+      let transform = match self.parent(joint).and_then(|parent| model.get(parent)) {
+        Some(parent) => JointTransform::combine(parent, local),
+        None => *local,
+      };
+      model.push(transform);
+    }
+    model
+  }
+}
+
+/// The transforms of every joint of a skeleton at one point in time.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Pose {
+  pub joints: Vec<JointTransform>,
+}
+
+impl Pose {
+  pub fn with_joint_count(count: usize) -> Self {
+    Self {
+      joints: vec![JointTransform::IDENTITY; count],
+    }
+  }
+
+  /// Blends towards `other` by `t`. Joints only one of the poses has are taken from it as-is.
+  pub fn blend(&self, other: &Self, t: f32) -> Self {
+    let count = self.joints.len().max(other.joints.len());
+    let joints = (0..count)
+      .map(|joint| match (self.joints.get(joint), other.joints.get(joint)) {
+        (Some(a), Some(b)) => a.lerp(b, t),
+        (Some(a), None) => *a,
+        (None, Some(b)) => *b,
+        (None, None) => JointTransform::IDENTITY,
+      })
+      .collect();
+
+    Self { joints }
+  }
+}
+
+/// How far the root joint moved and turned over a span of playback, for moving the animated entity (or feeding a
+/// character controller) instead of the skeleton drifting away from it.
+///
+/// Apply it in the entity's local frame, e.g. `position += rotation * translation` then `rotation *= motion.rotation`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RootMotion {
+  pub translation: Vec3,
+  pub rotation: Quat,
+}
+
+impl Default for RootMotion {
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+impl RootMotion {
+  pub const IDENTITY: Self = Self {
+    translation: Vec3::ZERO,
+    rotation: Quat::IDENTITY,
+  };
+
+  /// This motion followed by `next`.
+  pub fn then(&self, next: &Self) -> Self {
+    Self {
+      translation: self.translation + next.translation,
+      rotation: next.rotation * self.rotation,
+    }
+  }
+
+  /// This motion weighted by `weight`, for blending several motions together.
+  pub fn scaled(&self, weight: f32) -> Self {
+    Self {
+      translation: self.translation * weight,
+      rotation: Quat::IDENTITY.slerp(self.rotation, weight),
+    }
+  }
+
+  fn between(from: &JointTransform, to: &JointTransform) -> Self {
+    Self {
+      translation: to.translation - from.translation,
+      rotation: to.rotation * from.rotation.inverse(),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Keyframe {
+  pub time: f32,
+  pub transform: JointTransform,
+}
+
+/// Keyframes for every joint of a skeleton, one track per joint. Keyframes must be sorted by time.
+#[derive(Debug, Default, Clone)]
+pub struct AnimationClip {
+  pub duration: f32,
+  pub tracks: Vec<Vec<Keyframe>>,
+  /// The joint whose translation and rotation are extracted as root motion, if any.
+  pub root_motion_joint: Option<usize>,
+}
+
+impl AnimationClip {
+  pub fn new(duration: f32, tracks: Vec<Vec<Keyframe>>) -> Self {
+    Self {
+      duration,
+      tracks,
+      root_motion_joint: None,
+    }
+  }
+
+  /// Extracts the motion of `joint` as root motion. Sampled poses then hold that joint where it is at the start of
+  /// the clip, and its movement is reported by [`AnimationClip::root_motion`] instead.
+  pub fn with_root_motion(mut self, joint: usize) -> Self {
+    self.root_motion_joint = Some(joint);
+    self
+  }
+
+  /// Samples every track at `time` seconds, interpolating between the surrounding keyframes.
+  pub fn sample(&self, time: f32) -> Pose {
+    let joints = self
+      .tracks
+      .iter()
+      .enumerate()
+      .map(|(joint, track)| {
+        let transform = Self::sample_track(track, time);
+        if self.root_motion_joint == Some(joint) {
+          let start = Self::sample_track(track, 0.0);
+          JointTransform {
+            translation: start.translation,
+            rotation: start.rotation,
+            scale: transform.scale,
+          }
+        } else {
+          transform
+        }
+      })
+      .collect();
+
+    Pose { joints }
+  }
+
+  /// The root motion of playing forward from `from` to `to` seconds, through the end of the clip and back to the
+  /// start if `looped`.
+  pub fn root_motion(&self, from: f32, to: f32, looped: bool) -> RootMotion {
+    let Some(track) = self.root_motion_joint.and_then(|joint| self.tracks.get(joint)) else {
+      return RootMotion::IDENTITY;
+    };
+
+    let from = Self::sample_track(track, from);
+    let to = Self::sample_track(track, to);
+    if looped {
+      let start = Self::sample_track(track, 0.0);
+      let end = Self::sample_track(track, self.duration);
+      RootMotion::between(&from, &end).then(&RootMotion::between(&start, &to))
+    } else {
+      RootMotion::between(&from, &to)
+    }
+  }
+
+  fn sample_track(track: &[Keyframe], time: f32) -> JointTransform {
+    let next = track.partition_point(|keyframe| keyframe.time <= time);
+    match (next.checked_sub(1).and_then(|previous| track.get(previous)), track.get(next)) {
+      (Some(a), Some(b)) => a.transform.lerp(&b.transform, (time - a.time) / (b.time - a.time)),
+      (Some(a), None) => a.transform,
+      (None, Some(b)) => b.transform,
+      (None, None) => JointTransform::IDENTITY,
+    }
+  }
+}
+
+/// Plays a single clip.
+#[derive(Debug, Clone)]
+pub struct AnimationPlayer {
+  clip: Arc<AnimationClip>,
+  time: f32,
+  speed: f32,
+  looping: bool,
+  root_motion: RootMotion,
+}
+
+impl AnimationPlayer {
+  pub fn new(clip: Arc<AnimationClip>) -> Self {
+    Self {
+      clip,
+      time: 0.0,
+      speed: 1.0,
+      looping: true,
+      root_motion: RootMotion::IDENTITY,
+    }
+  }
+
+  pub fn with_speed(mut self, speed: f32) -> Self {
+    self.speed = speed;
+    self
+  }
+
+  pub fn with_looping(mut self, looping: bool) -> Self {
+    self.looping = looping;
+    self
+  }
+
+  pub fn clip(&self) -> &Arc<AnimationClip> {
+    &self.clip
+  }
+
+  pub fn time(&self) -> f32 {
+    self.time
+  }
+
+  pub fn set_time(&mut self, time: f32) {
+    self.time = time;
+  }
+
+  /// Whether a non-looping clip has played to its end.
+  pub fn is_finished(&self) -> bool {
+    !self.looping && self.time >= self.clip.duration
+  }
+
+  pub fn advance(&mut self, delta_secs: f32) {
+    let previous = self.time;
+    let time = self.time + delta_secs * self.speed;
+    let looped = self.looping && time >= self.clip.duration;
+
+    if self.looping && self.clip.duration > 0.0 {
+      self.time = time.rem_euclid(self.clip.duration);
+    } else {
+      self.time = time.clamp(0.0, self.clip.duration);
+    }
+
+    self.root_motion = self
+      .root_motion
+      .then(&self.clip.root_motion(previous, self.time, looped));
+  }
+
+  /// The root motion accumulated since the last call, if the clip extracts any.
+  pub fn take_root_motion(&mut self) -> RootMotion {
+    std::mem::take(&mut self.root_motion)
+  }
+
+  pub fn pose(&self) -> Pose {
+    self.clip.sample(self.time)
+  }
+}
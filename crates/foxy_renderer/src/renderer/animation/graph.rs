@@ -0,0 +1,393 @@
+use std::{collections::HashMap, sync::Arc};
+
+use glam::Vec2;
+
+use super::{AnimationClip, Pose, RootMotion};
+
+/// Blends clips placed along one parameter, e.g. idle at 0, walk at 2 and run at 6 for speed.
+#[derive(Debug, Clone)]
+pub struct BlendSpace1D {
+  parameter: String,
+  points: Vec<(f32, Arc<AnimationClip>)>,
+}
+
+impl BlendSpace1D {
+  pub fn new(parameter: impl Into<String>) -> Self {
+    Self {
+      parameter: parameter.into(),
+      points: Vec::new(),
+    }
+  }
+
+  pub fn with_clip(mut self, position: f32, clip: Arc<AnimationClip>) -> Self {
+    let index = self.points.partition_point(|(p, _)| *p <= position);
+    self.points.insert(index, (position, clip));
+    self
+  }
+
+  fn weights(&self, parameters: &HashMap<String, f32>) -> Vec<(f32, &Arc<AnimationClip>)> {
+    let value = parameters.get(&self.parameter).copied().unwrap_or_default();
+    let next = self.points.partition_point(|(p, _)| *p <= value);
+
+    match (next.checked_sub(1).and_then(|previous| self.points.get(previous)), self.points.get(next)) {
+      (Some((a, a_clip)), Some((b, b_clip))) => {
+        let t = (value - a) / (b - a);
+        vec![(1.0 - t, a_clip), (t, b_clip)]
+      }
+      (Some((_, clip)), None) | (None, Some((_, clip))) => vec![(1.0, clip)],
+      (None, None) => Vec::new(),
+    }
+  }
+}
+
+/// Blends clips placed on a plane of two parameters, e.g. strafing clips by direction. Clips are weighted by their
+/// inverse squared distance to the parameters.
+#[derive(Debug, Clone)]
+pub struct BlendSpace2D {
+  x_parameter: String,
+  y_parameter: String,
+  points: Vec<(Vec2, Arc<AnimationClip>)>,
+}
+
+impl BlendSpace2D {
+  pub fn new(x_parameter: impl Into<String>, y_parameter: impl Into<String>) -> Self {
+    Self {
+      x_parameter: x_parameter.into(),
+      y_parameter: y_parameter.into(),
+      points: Vec::new(),
+    }
+  }
+
+  pub fn with_clip(mut self, position: Vec2, clip: Arc<AnimationClip>) -> Self {
+    self.points.push((position, clip));
+    self
+  }
+
+  fn weights(&self, parameters: &HashMap<String, f32>) -> Vec<(f32, &Arc<AnimationClip>)> {
+    let value = Vec2::new(
+      parameters.get(&self.x_parameter).copied().unwrap_or_default(),
+      parameters.get(&self.y_parameter).copied().unwrap_or_default(),
+    );
+
+    if let Some((_, clip)) = self
+      .points
+      .iter()
+      .find(|(position, _)| position.distance_squared(value) <= f32::EPSILON)
+    {
+      return vec![(1.0, clip)];
+    }
+
+    let weights = self
+      .points
+      .iter()
+      .map(|(position, clip)| (1.0 / position.distance_squared(value), clip))
+      .collect::<Vec<_>>();
+    let total = weights.iter().map(|(weight, _)| weight).sum::<f32>();
+
+    weights.into_iter().map(|(weight, clip)| (weight / total, clip)).collect()
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum Motion {
+  Clip(Arc<AnimationClip>),
+  BlendSpace1D(BlendSpace1D),
+  BlendSpace2D(BlendSpace2D),
+}
+
+impl Motion {
+  fn weights(&self, parameters: &HashMap<String, f32>) -> Vec<(f32, &Arc<AnimationClip>)> {
+    match self {
+      Motion::Clip(clip) => vec![(1.0, clip)],
+      Motion::BlendSpace1D(space) => space.weights(parameters),
+      Motion::BlendSpace2D(space) => space.weights(parameters),
+    }
+  }
+
+  /// The weighted duration of the blended clips, so that blended cycles stay in step.
+  fn duration(&self, parameters: &HashMap<String, f32>) -> f32 {
+    self
+      .weights(parameters)
+      .iter()
+      .map(|(weight, clip)| weight * clip.duration)
+      .sum()
+  }
+
+  /// The weighted root motion of every clip playing forward between two normalized times.
+  fn root_motion(&self, parameters: &HashMap<String, f32>, from: f32, to: f32, looped: bool) -> RootMotion {
+    self
+      .weights(parameters)
+      .into_iter()
+      .fold(RootMotion::IDENTITY, |motion, (weight, clip)| {
+        motion.then(
+          &clip
+            .root_motion(from * clip.duration, to * clip.duration, looped)
+            .scaled(weight),
+        )
+      })
+  }
+
+  /// Samples every clip at the same fraction of its duration and blends them by weight.
+  fn sample(&self, parameters: &HashMap<String, f32>, normalized_time: f32) -> Pose {
+    let mut total = 0.0;
+    self
+      .weights(parameters)
+      .into_iter()
+      .filter(|(weight, _)| *weight > 0.0)
+      .fold(Pose::default(), |pose, (weight, clip)| {
+        let sample = clip.sample(normalized_time * clip.duration);
+        total += weight;
+        pose.blend(&sample, weight / total)
+      })
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationState {
+  pub name: String,
+  pub motion: Motion,
+  pub speed: f32,
+  pub looping: bool,
+}
+
+impl AnimationState {
+  pub fn new(name: impl Into<String>, motion: Motion) -> Self {
+    Self {
+      name: name.into(),
+      motion,
+      speed: 1.0,
+      looping: true,
+    }
+  }
+
+  pub fn with_speed(mut self, speed: f32) -> Self {
+    self.speed = speed;
+    self
+  }
+
+  pub fn with_looping(mut self, looping: bool) -> Self {
+    self.looping = looping;
+    self
+  }
+}
+
+#[derive(Debug, Clone)]
+pub enum Condition {
+  Above(String, f32),
+  Below(String, f32),
+  /// The parameter is non-zero.
+  Flag(String),
+  /// The current state doesn't loop and has played to its end.
+  Finished,
+}
+
+#[derive(Debug, Clone)]
+pub struct Transition {
+  /// The state the transition leaves from, or any state when `None`.
+  pub from: Option<String>,
+  pub to: String,
+  pub condition: Condition,
+  /// Seconds to cross-fade over.
+  pub duration: f32,
+}
+
+impl Transition {
+  pub fn new(from: impl Into<String>, to: impl Into<String>, condition: Condition, duration: f32) -> Self {
+    Self {
+      from: Some(from.into()),
+      to: to.into(),
+      condition,
+      duration,
+    }
+  }
+
+  pub fn from_any(to: impl Into<String>, condition: Condition, duration: f32) -> Self {
+    Self {
+      from: None,
+      to: to.into(),
+      condition,
+      duration,
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct ActiveTransition {
+  from: usize,
+  from_time: f32,
+  elapsed: f32,
+  duration: f32,
+}
+
+/// A state machine of motions, each a clip or a blend space driven by named parameters. Transitions cross-fade
+/// between states when their condition holds.
+///
+/// Meant to be updated from `Runnable::fixed_update` with the fixed tick length, so that animation stays in step with
+/// gameplay.
+#[derive(Debug, Clone)]
+pub struct AnimationGraph {
+  states: Vec<AnimationState>,
+  transitions: Vec<Transition>,
+  parameters: HashMap<String, f32>,
+  current: usize,
+  normalized_time: f32,
+  transition: Option<ActiveTransition>,
+  root_motion: RootMotion,
+}
+
+impl AnimationGraph {
+  /// Creates a graph starting in `initial`.
+  pub fn new(initial: AnimationState) -> Self {
+    Self {
+      states: vec![initial],
+      transitions: Vec::new(),
+      parameters: HashMap::new(),
+      current: 0,
+      normalized_time: 0.0,
+      transition: None,
+      root_motion: RootMotion::IDENTITY,
+    }
+  }
+
+  pub fn with_state(mut self, state: AnimationState) -> Self {
+    self.states.push(state);
+    self
+  }
+
+  /// Transitions are checked in the order they were added.
+  pub fn with_transition(mut self, transition: Transition) -> Self {
+    self.transitions.push(transition);
+    self
+  }
+
+  pub fn parameter(&self, name: &str) -> f32 {
+    self.parameters.get(name).copied().unwrap_or_default()
+  }
+
+  pub fn set_parameter(&mut self, name: impl Into<String>, value: f32) {
+    self.parameters.insert(name.into(), value);
+  }
+
+  pub fn current_state(&self) -> &str {
+    self.states.get(self.current).map_or("", |state| &state.name)
+  }
+
+  pub fn is_transitioning(&self) -> bool {
+    self.transition.is_some()
+  }
+
+  /// Cross-fades to the state called `name` over `duration` seconds. Does nothing if there is no such state.
+  pub fn transition_to(&mut self, name: &str, duration: f32) {
+    let Some(to) = self.states.iter().position(|state| state.name == name) else {
+      return;
+    };
+
+    self.transition = (duration > 0.0).then_some(ActiveTransition {
+      from: self.current,
+      from_time: self.normalized_time,
+      elapsed: 0.0,
+      duration,
+    });
+    self.current = to;
+    self.normalized_time = 0.0;
+  }
+
+  pub fn update(&mut self, delta_secs: f32) {
+    let (normalized_time, motion) = self.advance(self.current, self.normalized_time, delta_secs);
+    self.normalized_time = normalized_time;
+
+    if let Some(mut transition) = self.transition.take() {
+      let (from_time, from_motion) = self.advance(transition.from, transition.from_time, delta_secs);
+      let weight = transition.elapsed / transition.duration;
+      self.root_motion = self
+        .root_motion
+        .then(&from_motion.scaled(1.0 - weight))
+        .then(&motion.scaled(weight));
+
+      transition.from_time = from_time;
+      transition.elapsed += delta_secs;
+      self.transition = (transition.elapsed < transition.duration).then_some(transition);
+      return;
+    }
+
+    self.root_motion = self.root_motion.then(&motion);
+
+    let next = self
+      .transitions
+      .iter()
+      .filter(|transition| {
+        transition
+          .from
+          .as_ref()
+          .is_none_or(|from| *from == self.current_state())
+      })
+      .filter(|transition| transition.to != self.current_state())
+      .find(|transition| self.holds(&transition.condition))
+      .map(|transition| (transition.to.clone(), transition.duration));
+
+    if let Some((to, duration)) = next {
+      self.transition_to(&to, duration);
+    }
+  }
+
+  /// The root motion accumulated since the last call, blended across transitions like the pose.
+  pub fn take_root_motion(&mut self) -> RootMotion {
+    std::mem::take(&mut self.root_motion)
+  }
+
+  pub fn pose(&self) -> Pose {
+    let pose = self.sample(self.current, self.normalized_time);
+
+    match self.transition {
+      Some(transition) => self
+        .sample(transition.from, transition.from_time)
+        .blend(&pose, transition.elapsed / transition.duration),
+      None => pose,
+    }
+  }
+}
+
+impl AnimationGraph {
+  /// Advances a state's normalized time, returning the new time and the root motion covered.
+  fn advance(&self, state: usize, normalized_time: f32, delta_secs: f32) -> (f32, RootMotion) {
+    let Some(state) = self.states.get(state) else {
+      return (normalized_time, RootMotion::IDENTITY);
+    };
+
+    let duration = state.motion.duration(&self.parameters);
+    if duration <= 0.0 {
+      return (normalized_time, RootMotion::IDENTITY);
+    }
+
+    let time = normalized_time + delta_secs * state.speed / duration;
+    let looped = state.looping && time >= 1.0;
+    let time = if state.looping {
+      time.rem_euclid(1.0)
+    } else {
+      time.clamp(0.0, 1.0)
+    };
+
+    let motion = state
+      .motion
+      .root_motion(&self.parameters, normalized_time, time, looped);
+    (time, motion)
+  }
+
+  fn sample(&self, state: usize, normalized_time: f32) -> Pose {
+    self.states.get(state).map_or_else(Pose::default, |state| {
+      state.motion.sample(&self.parameters, normalized_time)
+    })
+  }
+
+  fn holds(&self, condition: &Condition) -> bool {
+    match condition {
+      Condition::Above(name, threshold) => self.parameter(name) > *threshold,
+      Condition::Below(name, threshold) => self.parameter(name) < *threshold,
+      Condition::Flag(name) => self.parameter(name) != 0.0,
+      Condition::Finished => self
+        .states
+        .get(self.current)
+        .is_some_and(|state| !state.looping && self.normalized_time >= 1.0),
+    }
+  }
+}
@@ -0,0 +1,262 @@
+use glam::{Quat, Vec3};
+
+use super::{JointTransform, Pose, Skeleton};
+
+/// Adjusts a sampled pose so that some joints reach for a target, e.g. feet onto uneven ground or a head towards a
+/// point of interest. Targets are in model space and solvers are meant to run after sampling, before skinning.
+pub trait IkSolver {
+  fn solve(&self, skeleton: &Skeleton, pose: &mut Pose);
+}
+
+/// Analytically solves a chain of three joints, each the parent of the next, such as hip, knee and ankle. The middle
+/// joint bends towards `pole`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TwoBoneIk {
+  pub root: usize,
+  pub middle: usize,
+  pub end: usize,
+  pub target: Vec3,
+  /// A point the middle joint bends towards, e.g. in front of a knee. Keeps the current bend direction when `None`.
+  pub pole: Option<Vec3>,
+  /// How much of the solution to apply, from 0 (none) to 1.
+  pub weight: f32,
+}
+
+impl TwoBoneIk {
+  pub fn new(root: usize, middle: usize, end: usize, target: Vec3) -> Self {
+    Self {
+      root,
+      middle,
+      end,
+      target,
+      pole: None,
+      weight: 1.0,
+    }
+  }
+
+  pub fn with_pole(mut self, pole: Vec3) -> Self {
+    self.pole = Some(pole);
+    self
+  }
+
+  pub fn with_weight(mut self, weight: f32) -> Self {
+    self.weight = weight;
+    self
+  }
+}
+
+impl IkSolver for TwoBoneIk {
+  fn solve(&self, skeleton: &Skeleton, pose: &mut Pose) {
+    let model = skeleton.model_transforms(pose);
+    let (Some(root), Some(middle), Some(end)) = (model.get(self.root), model.get(self.middle), model.get(self.end))
+    else {
+      return;
+    };
+
+    let (a, b, c) = (root.translation, middle.translation, end.translation);
+    let upper = a.distance(b);
+    let lower = b.distance(c);
+    // stay just short of full extension so the middle joint doesn't snap
+    let reach = a.distance(self.target).clamp(0.001, upper + lower - 0.001);
+
+    let angle = |x: Vec3, y: Vec3| x.normalize_or_zero().dot(y.normalize_or_zero()).clamp(-1.0, 1.0).acos();
+    let cosine_rule = |opposite: f32, x: f32, y: f32| {
+      ((x * x + y * y - opposite * opposite) / (2.0 * x * y))
+        .clamp(-1.0, 1.0)
+        .acos()
+    };
+
+    let root_angle = angle(c - a, b - a);
+    let middle_angle = angle(a - b, c - b);
+    let target_angle = angle(c - a, self.target - a);
+    let solved_root_angle = cosine_rule(lower, upper, reach);
+    let solved_middle_angle = cosine_rule(reach, upper, lower);
+
+    let bend = self.pole.map_or(b - a, |pole| pole - a);
+    let bend_axis = (c - a).cross(bend).try_normalize().unwrap_or(Vec3::X);
+    let target_axis = (c - a).cross(self.target - a).try_normalize().unwrap_or(Vec3::X);
+
+    let root_rotation = Quat::from_axis_angle(bend_axis, solved_root_angle - root_angle);
+    let middle_rotation = Quat::from_axis_angle(bend_axis, solved_middle_angle - middle_angle);
+    let aim_rotation = Quat::from_axis_angle(target_axis, target_angle);
+
+    let root_model = aim_rotation * root_rotation * root.rotation;
+    let middle_model = aim_rotation * root_rotation * middle_rotation * middle.rotation;
+
+    let parent = skeleton
+      .parent(self.root)
+      .and_then(|parent| model.get(parent))
+      .map_or(Quat::IDENTITY, |parent| parent.rotation);
+
+    set_local_rotation(pose, self.root, parent.inverse() * root_model, self.weight);
+    set_local_rotation(pose, self.middle, root_model.inverse() * middle_model, self.weight);
+  }
+}
+
+/// Solves a chain of any length by forward and backward reaching, e.g. tails, spines or tentacles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FabrikIk {
+  /// The joints of the chain from its root to its tip, each the parent of the next.
+  pub chain: Vec<usize>,
+  pub target: Vec3,
+  pub iterations: usize,
+  /// The distance from the target at which the tip counts as having reached it.
+  pub tolerance: f32,
+  pub weight: f32,
+}
+
+impl FabrikIk {
+  pub const DEFAULT_ITERATIONS: usize = 10;
+  pub const DEFAULT_TOLERANCE: f32 = 0.001;
+
+  pub fn new(chain: Vec<usize>, target: Vec3) -> Self {
+    Self {
+      chain,
+      target,
+      iterations: Self::DEFAULT_ITERATIONS,
+      tolerance: Self::DEFAULT_TOLERANCE,
+      weight: 1.0,
+    }
+  }
+
+  pub fn with_iterations(mut self, iterations: usize) -> Self {
+    self.iterations = iterations;
+    self
+  }
+
+  pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+    self.tolerance = tolerance;
+    self
+  }
+
+  pub fn with_weight(mut self, weight: f32) -> Self {
+    self.weight = weight;
+    self
+  }
+}
+
+impl IkSolver for FabrikIk {
+  fn solve(&self, skeleton: &Skeleton, pose: &mut Pose) {
+    let model = skeleton.model_transforms(pose);
+    let Some(original) = self
+      .chain
+      .iter()
+      .map(|joint| model.get(*joint).map(|transform| transform.translation))
+      .collect::<Option<Vec<_>>>()
+    else {
+      return;
+    };
+    let (Some(&root), Some(_)) = (original.first(), original.last()) else {
+      return;
+    };
+
+    let lengths = original
+      .iter()
+      .zip(original.iter().skip(1))
+      .map(|(parent, child)| parent.distance(*child))
+      .collect::<Vec<_>>();
+    let mut positions = original;
+
+    for _ in 0..self.iterations {
+      if positions.last().is_some_and(|tip| tip.distance(self.target) <= self.tolerance) {
+        break;
+      }
+
+      // backward: pin the tip to the target and pull the rest of the chain after it
+      let mut next = self.target;
+      let backward_lengths = std::iter::once(0.0).chain(lengths.iter().rev().copied());
+      for (position, length) in positions.iter_mut().rev().zip(backward_lengths) {
+        *position = next + (*position - next).normalize_or_zero() * length;
+        next = *position;
+      }
+
+      // forward: pin the root back in place and pull the rest of the chain after it
+      let mut previous = root;
+      let forward_lengths = std::iter::once(0.0).chain(lengths.iter().copied());
+      for (position, length) in positions.iter_mut().zip(forward_lengths) {
+        *position = previous + (*position - previous).normalize_or_zero() * length;
+        previous = *position;
+      }
+    }
+
+    // turn each bone from where it pointed to where the solve put its child
+    for (index, joint) in self.chain.iter().enumerate() {
+      let (Some(&child), Some(&solved_child)) = (self.chain.get(index + 1), positions.get(index + 1)) else {
+        break;
+      };
+
+      let model = skeleton.model_transforms(pose);
+      let (Some(current), Some(current_child)) = (model.get(*joint), model.get(child)) else {
+        return;
+      };
+
+      let delta = Quat::from_rotation_arc(
+        (current_child.translation - current.translation).normalize_or_zero(),
+        (solved_child - current.translation).normalize_or_zero(),
+      );
+      let parent = skeleton
+        .parent(*joint)
+        .and_then(|parent| model.get(parent))
+        .map_or(Quat::IDENTITY, |parent| parent.rotation);
+
+      set_local_rotation(pose, *joint, parent.inverse() * delta * current.rotation, self.weight);
+    }
+  }
+}
+
+/// Turns a joint so that one of its local axes points at a target, e.g. a head or eyes following something.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LookAtIk {
+  pub joint: usize,
+  pub target: Vec3,
+  /// The local axis of the joint that should face the target.
+  pub forward: Vec3,
+  pub weight: f32,
+}
+
+impl LookAtIk {
+  pub fn new(joint: usize, target: Vec3) -> Self {
+    Self {
+      joint,
+      target,
+      forward: Vec3::NEG_Z,
+      weight: 1.0,
+    }
+  }
+
+  pub fn with_forward(mut self, forward: Vec3) -> Self {
+    self.forward = forward;
+    self
+  }
+
+  pub fn with_weight(mut self, weight: f32) -> Self {
+    self.weight = weight;
+    self
+  }
+}
+
+impl IkSolver for LookAtIk {
+  fn solve(&self, skeleton: &Skeleton, pose: &mut Pose) {
+    let model = skeleton.model_transforms(pose);
+    let Some(current) = model.get(self.joint) else {
+      return;
+    };
+
+    let delta = Quat::from_rotation_arc(
+      (current.rotation * self.forward).normalize_or_zero(),
+      (self.target - current.translation).normalize_or_zero(),
+    );
+    let parent = skeleton
+      .parent(self.joint)
+      .and_then(|parent| model.get(parent))
+      .map_or(Quat::IDENTITY, |parent| parent.rotation);
+
+    set_local_rotation(pose, self.joint, parent.inverse() * delta * current.rotation, self.weight);
+  }
+}
+
+fn set_local_rotation(pose: &mut Pose, joint: usize, rotation: Quat, weight: f32) {
+  if let Some(JointTransform { rotation: local, .. }) = pose.joints.get_mut(joint) {
+    *local = local.slerp(rotation.normalize(), weight.clamp(0.0, 1.0));
+  }
+}
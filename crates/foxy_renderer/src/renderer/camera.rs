@@ -0,0 +1,230 @@
+use std::{mem::size_of, num::NonZeroU64};
+
+use bytemuck::{Pod, Zeroable};
+use foxy_utils::{
+  geometry::{Frustum, Ray},
+  tracking::Tracked,
+};
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4Swizzles};
+
+use super::{render_data::RenderLayers, target::RenderTarget, tracking::TrackedDevice};
+
+/// A perspective camera. Looks down its local -Z axis with +Y up.
+///
+/// The far plane is optional: without one, the projection extends to infinity, which removes far plane clipping and
+/// pairs well with reverse-Z.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+  pub position: Vec3,
+  pub rotation: Quat,
+  /// Vertical field of view, in radians.
+  pub fov_y: f32,
+  near: f32,
+  far: Option<f32>,
+  /// The layers the camera sees. Meshes on none of them aren't drawn.
+  pub layers: RenderLayers,
+}
+
+impl Default for Camera {
+  fn default() -> Self {
+    Self {
+      position: Vec3::new(0.0, 0.0, 2.0),
+      rotation: Quat::IDENTITY,
+      fov_y: 60f32.to_radians(),
+      near: Self::DEFAULT_NEAR,
+      far: Some(Self::DEFAULT_FAR),
+      layers: RenderLayers::ALL,
+    }
+  }
+}
+
+impl Camera {
+  pub const DEFAULT_FAR: f32 = 1000.0;
+  pub const DEFAULT_NEAR: f32 = 0.1;
+  pub const MIN_NEAR: f32 = 1e-4;
+
+  pub fn with_position(mut self, position: Vec3) -> Self {
+    self.position = position;
+    self
+  }
+
+  pub fn with_rotation(mut self, rotation: Quat) -> Self {
+    self.rotation = rotation;
+    self
+  }
+
+  pub fn with_fov_y(mut self, fov_y: f32) -> Self {
+    self.fov_y = fov_y;
+    self
+  }
+
+  pub fn with_clip_planes(mut self, near: f32, far: Option<f32>) -> Self {
+    self.set_clip_planes(near, far);
+    self
+  }
+
+  pub fn with_infinite_far(mut self) -> Self {
+    self.far = None;
+    self
+  }
+
+  pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+    self.layers = layers;
+    self
+  }
+
+  pub fn near(&self) -> f32 {
+    self.near
+  }
+
+  /// The distance to the far plane, or `None` for an infinite projection.
+  pub fn far(&self) -> Option<f32> {
+    self.far
+  }
+
+  /// Changes the clip planes, e.g. to trade precision for view distance at runtime. `near` is kept above
+  /// [`Camera::MIN_NEAR`] and `far` beyond `near`.
+  pub fn set_clip_planes(&mut self, near: f32, far: Option<f32>) {
+    self.near = near.max(Self::MIN_NEAR);
+    self.far = far.map(|far| far.max(self.near * 2.0));
+  }
+
+  pub fn forward(&self) -> Vec3 {
+    self.rotation * Vec3::NEG_Z
+  }
+
+  pub fn view(&self) -> Mat4 {
+    Mat4::from_rotation_translation(self.rotation, self.position).inverse()
+  }
+
+  /// Projects into wgpu's 0..1 depth range, reversed when `reverse_z` is set.
+  pub fn projection(&self, aspect_ratio: f32, reverse_z: bool) -> Mat4 {
+    match (self.far, reverse_z) {
+      (Some(far), false) => Mat4::perspective_rh(self.fov_y, aspect_ratio, self.near, far),
+      (Some(far), true) => Mat4::perspective_rh(self.fov_y, aspect_ratio, far, self.near),
+      (None, false) => Mat4::perspective_infinite_rh(self.fov_y, aspect_ratio, self.near),
+      (None, true) => Mat4::perspective_infinite_reverse_rh(self.fov_y, aspect_ratio, self.near),
+    }
+  }
+
+  pub fn view_projection(&self, aspect_ratio: f32, reverse_z: bool) -> Mat4 {
+    self.projection(aspect_ratio, reverse_z) * self.view()
+  }
+
+  /// The world-space view frustum, for culling. Infinite projections have no far plane.
+  pub fn frustum(&self, aspect_ratio: f32) -> Frustum {
+    // planes are extracted from the forward projection so the depth range doesn't matter
+    Frustum::from_view_projection(&self.view_projection(aspect_ratio, false))
+  }
+
+  /// The world-space ray through `cursor`, in pixels from the top left of a `viewport` of the given size, starting on
+  /// the near plane. For picking and for placing things where the cursor points.
+  pub fn screen_to_world_ray(&self, cursor: Vec2, viewport: Vec2) -> Ray {
+    let ndc = Self::screen_to_ndc(cursor, viewport);
+    let inverse = self.view_projection(viewport.x / viewport.y, false).inverse();
+    let near = inverse.project_point3(ndc.extend(0.0));
+    // halfway into the depth range, which even infinite projections have a finite point for
+    let far = inverse.project_point3(ndc.extend(0.5));
+    Ray::between(near, far)
+  }
+
+  /// The world-space point at `cursor`, in pixels from the top left of a `viewport` of the given size, and `depth`,
+  /// as the depth buffer stores it: 0 on the near plane and 1 on the far one, or the other way round when
+  /// `reverse_z` is set. For placing things on the surface under the cursor from a depth read back from the GPU.
+  pub fn screen_to_world(&self, cursor: Vec2, depth: f32, viewport: Vec2, reverse_z: bool) -> Vec3 {
+    let inverse = self.view_projection(viewport.x / viewport.y, reverse_z).inverse();
+    inverse.project_point3(Self::screen_to_ndc(cursor, viewport).extend(depth))
+  }
+
+  /// Where `point` appears on a `viewport` of the given size, in pixels from the top left, with its depth as the
+  /// depth buffer would store it in `z`. `None` when the point is behind the camera. For anchoring UI to objects in
+  /// the scene; points off the side of the screen are still returned, outside of the viewport.
+  pub fn world_to_screen(&self, point: Vec3, viewport: Vec2, reverse_z: bool) -> Option<Vec3> {
+    let clip = self.view_projection(viewport.x / viewport.y, reverse_z) * point.extend(1.0);
+    if clip.w <= 0.0 {
+      return None;
+    }
+
+    let ndc = clip.xyz() / clip.w;
+    let screen = Vec2::new((ndc.x + 1.0) / 2.0 * viewport.x, (1.0 - ndc.y) / 2.0 * viewport.y);
+    Some(screen.extend(ndc.z))
+  }
+
+  /// `cursor` in normalized device coordinates, with Y up.
+  fn screen_to_ndc(cursor: Vec2, viewport: Vec2) -> Vec2 {
+    Vec2::new(cursor.x / viewport.x * 2.0 - 1.0, 1.0 - cursor.y / viewport.y * 2.0)
+  }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct CameraUniforms {
+  view_projection: [[f32; 4]; 4],
+}
+
+/// A uniform buffer holding a camera's view projection, for passes that transform geometry by it.
+pub struct CameraBuffer {
+  buffer: Tracked<wgpu::Buffer>,
+  bind_group_layout: wgpu::BindGroupLayout,
+  bind_group: wgpu::BindGroup,
+}
+
+impl CameraBuffer {
+  pub fn new(device: &wgpu::Device) -> Self {
+    let buffer = device.create_tracked_buffer(&wgpu::BufferDescriptor {
+      label: Some("Camera Buffer"),
+      size: size_of::<CameraUniforms>() as u64,
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Camera Layout"),
+      entries: &[wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::VERTEX,
+        ty: wgpu::BindingType::Buffer {
+          ty: wgpu::BufferBindingType::Uniform,
+          has_dynamic_offset: false,
+          min_binding_size: NonZeroU64::new(size_of::<CameraUniforms>() as u64),
+        },
+        count: None,
+      }],
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("Camera Bind Group"),
+      layout: &bind_group_layout,
+      entries: &[wgpu::BindGroupEntry {
+        binding: 0,
+        resource: buffer.as_entire_binding(),
+      }],
+    });
+
+    Self {
+      buffer,
+      bind_group_layout,
+      bind_group,
+    }
+  }
+
+  pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+    &self.bind_group_layout
+  }
+
+  pub fn bind_group(&self) -> &wgpu::BindGroup {
+    &self.bind_group
+  }
+
+  /// Uploads the view projection of `camera` for the size and depth range of `render_target`.
+  pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, render_target: &RenderTarget) {
+    let aspect_ratio = render_target.width() as f32 / render_target.height() as f32;
+    let uniforms = CameraUniforms {
+      view_projection: camera
+        .view_projection(aspect_ratio, render_target.is_reverse_z())
+        .to_cols_array_2d(),
+    };
+
+    queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&uniforms));
+  }
+}
@@ -0,0 +1,148 @@
+use std::{
+  path::Path,
+  sync::{mpsc, Arc},
+};
+
+use foxy_utils::tracking::Tracked;
+use wgpu::util::BufferInitDescriptor;
+
+use super::{context::GraphicsContext, shader::ShaderStore, tracking::TrackedDevice};
+use crate::{error::RendererError, renderer_error};
+
+/// GPU compute without a window or any of the renderer's passes, e.g. for tools that crunch data on the GPU with the
+/// same shaders and resource tracking as the engine.
+///
+/// Work is submitted as soon as it's dispatched, and [`ComputeContext::read_buffer`] waits for it to finish.
+pub struct ComputeContext {
+  device: Arc<wgpu::Device>,
+  queue: wgpu::Queue,
+  shader_store: ShaderStore,
+}
+
+impl ComputeContext {
+  pub fn new() -> Result<Self, RendererError> {
+    pollster::block_on(async {
+      let instance = GraphicsContext::create_instance();
+      let adapter = GraphicsContext::request_adapter(&instance, None).await?;
+      let (device, queue) = GraphicsContext::request_device(&adapter).await?;
+
+      Ok(Self {
+        device: Arc::new(device),
+        queue,
+        shader_store: ShaderStore::default(),
+      })
+    })
+  }
+
+  /// Loads shaders from `shader_store` rather than the default shader asset directory.
+  pub fn with_shader_store(mut self, shader_store: ShaderStore) -> Self {
+    self.shader_store = shader_store;
+    self
+  }
+
+  pub fn device(&self) -> &wgpu::Device {
+    &self.device
+  }
+
+  /// The device, for handing to background threads.
+  pub fn shared_device(&self) -> &Arc<wgpu::Device> {
+    &self.device
+  }
+
+  pub fn queue(&self) -> &wgpu::Queue {
+    &self.queue
+  }
+
+  pub fn shader_store(&mut self) -> &mut ShaderStore {
+    &mut self.shader_store
+  }
+
+  /// Builds a pipeline running `entry_point` of the compute shader at `path`. Its bind group layouts are worked out
+  /// from the shader, and can be had from [`wgpu::ComputePipeline::get_bind_group_layout`].
+  pub fn create_pipeline(
+    &mut self,
+    path: impl AsRef<Path>,
+    entry_point: &str,
+  ) -> Result<Tracked<wgpu::ComputePipeline>, RendererError> {
+    let shader = self.shader_store.compute(&self.device, path)?;
+    let module = shader.module();
+
+    let pipeline = self
+      .device
+      .create_tracked_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some(entry_point),
+        layout: None,
+        module: &module,
+        entry_point: shader.entry_point(entry_point),
+      });
+    Ok(pipeline)
+  }
+
+  /// A storage buffer holding `contents`, which can be bound to a pipeline and read back.
+  pub fn create_storage_buffer(&self, label: &str, contents: &[u8]) -> Tracked<wgpu::Buffer> {
+    self.device.create_tracked_buffer_init(&BufferInitDescriptor {
+      label: Some(label),
+      contents,
+      usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+    })
+  }
+
+  /// Runs `pipeline` over `x` by `y` by `z` workgroups, with `bind_groups` bound in order.
+  pub fn dispatch(
+    &self,
+    pipeline: &wgpu::ComputePipeline,
+    bind_groups: &[&wgpu::BindGroup],
+    (x, y, z): (u32, u32, u32),
+  ) {
+    let mut command_encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+      label: Some("Compute Encoder"),
+    });
+
+    {
+      let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+        label: Some("Compute Pass"),
+        timestamp_writes: None,
+      });
+      compute_pass.set_pipeline(pipeline);
+      for (index, bind_group) in bind_groups.iter().enumerate() {
+        compute_pass.set_bind_group(index as u32, bind_group, &[]);
+      }
+      compute_pass.dispatch_workgroups(x, y, z);
+    }
+
+    self.queue.submit(Some(command_encoder.finish()));
+  }
+
+  /// Copies `buffer` back to the CPU, once the work dispatched before it has finished. The buffer must be usable as
+  /// a copy source, as storage buffers from [`ComputeContext::create_storage_buffer`] are.
+  pub fn read_buffer(&self, buffer: &wgpu::Buffer) -> Result<Vec<u8>, RendererError> {
+    let staging_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some("Compute Read Back Buffer"),
+      size: buffer.size(),
+      usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+      mapped_at_creation: false,
+    });
+
+    let mut command_encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+      label: Some("Read Back Encoder"),
+    });
+    command_encoder.copy_buffer_to_buffer(buffer, 0, &staging_buffer, 0, buffer.size());
+    self.queue.submit(Some(command_encoder.finish()));
+
+    let (sender, receiver) = mpsc::channel();
+    let slice = staging_buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+      let _ = sender.send(result);
+    });
+    self.device.poll(wgpu::Maintain::Wait);
+    receiver
+      .recv()
+      .map_err(|_| renderer_error!("the buffer was never mapped"))?
+      .map_err(|error| renderer_error!("failed to map the buffer: {error}"))?;
+
+    let contents = slice.get_mapped_range().to_vec();
+    staging_buffer.unmap();
+
+    Ok(contents)
+  }
+}
@@ -1,52 +1,133 @@
 use std::sync::Arc;
 
-use tracing::debug;
+use foxy_utils::tracking::Tracked;
+use tracing::{debug, warn};
 use wgpu::TextureFormat;
-use winit::window::Window;
+use winit::{
+  raw_window_handle::{RawDisplayHandle, RawWindowHandle},
+  window::Window,
+};
 
-use crate::error::RendererError;
+use super::tracking::TrackedDevice;
+use crate::{error::RendererError, renderer_error};
+
+enum Output {
+  /// Surfaces created from raw handles have no window to follow the size of.
+  Surface {
+    window: Option<Arc<Window>>,
+    surface: wgpu::Surface<'static>,
+  },
+  /// Headless contexts draw every frame onto the same texture.
+  Offscreen(Tracked<wgpu::Texture>),
+  /// A texture handed to a headless context to draw onto instead of its own, e.g. one an editor's UI shows.
+  External(wgpu::Texture),
+}
+
+/// How finished frames are shown on the window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PresentMode {
+  /// Waits for the display to refresh before showing each frame, which caps the frame rate at the refresh rate and
+  /// never tears.
+  Vsync,
+  /// Shows frames as soon as they're ready without waiting for the display, using mailbox presentation where it's
+  /// supported so it doesn't tear, and immediate presentation otherwise.
+  #[default]
+  NoVsync,
+  /// Replaces the frame waiting to be shown with each newer one, so the latest frame is shown at every refresh
+  /// without tearing or capping the frame rate. Falls back to [`PresentMode::Vsync`] where it's not supported.
+  Mailbox,
+  /// Shows frames as soon as they're ready, which can tear but has the lowest latency. Falls back to
+  /// [`PresentMode::NoVsync`] where it's not supported.
+  Immediate,
+}
+
+impl PresentMode {
+  fn select(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+    let (wanted, fallback) = match self {
+      PresentMode::Vsync => return wgpu::PresentMode::AutoVsync,
+      PresentMode::NoVsync => return wgpu::PresentMode::AutoNoVsync,
+      PresentMode::Mailbox => (wgpu::PresentMode::Mailbox, wgpu::PresentMode::AutoVsync),
+      PresentMode::Immediate => (wgpu::PresentMode::Immediate, wgpu::PresentMode::AutoNoVsync),
+    };
+
+    if supported.contains(&wanted) {
+      wanted
+    } else {
+      warn!("the surface doesn't support {wanted:?} presentation, falling back to {fallback:?}");
+      fallback
+    }
+  }
+}
+
+/// The texture a frame is drawn onto.
+pub enum Frame<'a> {
+  Surface(wgpu::SurfaceTexture),
+  Offscreen(&'a wgpu::Texture),
+}
+
+impl Frame<'_> {
+  pub fn texture(&self) -> &wgpu::Texture {
+    match self {
+      Frame::Surface(frame) => &frame.texture,
+      Frame::Offscreen(texture) => texture,
+    }
+  }
+}
 
 pub struct GraphicsContext {
-  window: Arc<Window>,
-  surface: wgpu::Surface<'static>,
+  output: Output,
   config: wgpu::SurfaceConfiguration,
-  device: wgpu::Device,
+  device: Arc<wgpu::Device>,
   queue: wgpu::Queue,
+  view_format: TextureFormat,
+  is_zero_sized: bool,
 }
 
 impl GraphicsContext {
   pub const SURFACE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
 
-  pub fn new(window: Arc<Window>) -> Result<Self, RendererError> {
-    pollster::block_on(async {
-      let size = window.inner_size();
-
-      let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-        backends: wgpu::Backends::all(),
-        ..Default::default()
-      });
-
-      let surface = instance.create_surface(window.clone())?;
+  pub fn new(window: Arc<Window>, present_mode: PresentMode) -> Result<Self, RendererError> {
+    let size = window.inner_size();
+    let instance = Self::create_instance();
+    let surface = instance.create_surface(window.clone())?;
+    Self::with_surface(&instance, surface, Some(window), size.width, size.height, present_mode)
+  }
 
-      let adapter = instance
-        .request_adapter(&wgpu::RequestAdapterOptions {
-          power_preference: wgpu::PowerPreference::HighPerformance,
-          compatible_surface: Some(&surface),
-          force_fallback_adapter: false,
-        })
-        .await
-        .expect("failed to request adapter");
+  /// A context presenting to a window of another windowing library, e.g. SDL or an editor's viewport panel. Its
+  /// surface is `width` by `height` until [`Self::resize`]d.
+  ///
+  /// # Safety
+  ///
+  /// The handles must be valid, and stay valid until the context is dropped.
+  pub unsafe fn new_from_raw(
+    raw_window_handle: RawWindowHandle,
+    raw_display_handle: RawDisplayHandle,
+    width: u32,
+    height: u32,
+    present_mode: PresentMode,
+  ) -> Result<Self, RendererError> {
+    let instance = Self::create_instance();
+    // SAFETY: the caller keeps the handles valid for as long as the context, which owns the surface
+    let surface = unsafe {
+      instance.create_surface_unsafe(wgpu::SurfaceTargetUnsafe::RawHandle {
+        raw_display_handle,
+        raw_window_handle,
+      })?
+    };
+    Self::with_surface(&instance, surface, None, width, height, present_mode)
+  }
 
-      let (device, queue) = adapter
-        .request_device(
-          &wgpu::DeviceDescriptor {
-            required_features: wgpu::Features::empty(),
-            required_limits: wgpu::Limits::default(),
-            label: None,
-          },
-          None,
-        )
-        .await?;
+  fn with_surface(
+    instance: &wgpu::Instance,
+    surface: wgpu::Surface<'static>,
+    window: Option<Arc<Window>>,
+    width: u32,
+    height: u32,
+    present_mode: PresentMode,
+  ) -> Result<Self, RendererError> {
+    pollster::block_on(async {
+      let adapter = Self::request_adapter(instance, Some(&surface)).await?;
+      let (device, queue) = Self::request_device(&adapter).await?;
 
       let surface_caps = surface.get_capabilities(&adapter);
       debug!("{surface_caps:#?}");
@@ -57,25 +138,89 @@ impl GraphicsContext {
         .find(|f| *f == Self::SURFACE_FORMAT)
         .unwrap_or(*surface_caps.formats.first().unwrap());
 
+      // Render through an sRGB view whenever the surface format has one, so that UI and tonemapped output are
+      // encoded by the hardware regardless of which format the surface ended up with.
+      let view_format = if adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS)
+      {
+        surface_format.add_srgb_suffix()
+      } else {
+        surface_format
+      };
+      debug!("surface format: {surface_format:?}, view format: {view_format:?}");
+
       let config = wgpu::SurfaceConfiguration {
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
         format: surface_format,
-        width: size.width,
-        height: size.height,
-        present_mode: wgpu::PresentMode::AutoNoVsync,
+        width: width.max(1),
+        height: height.max(1),
+        present_mode: present_mode.select(&surface_caps.present_modes),
         alpha_mode: *surface_caps.alpha_modes.first().unwrap(),
-        view_formats: vec![],
+        view_formats: if view_format != surface_format {
+          vec![view_format]
+        } else {
+          vec![]
+        },
         desired_maximum_frame_latency: 2,
       };
 
       surface.configure(&device, &config);
 
       Ok(Self {
-        window,
-        surface,
+        output: Output::Surface { window, surface },
         config,
-        device,
+        device: Arc::new(device),
         queue,
+        view_format,
+        is_zero_sized: width == 0 || height == 0,
+      })
+    })
+  }
+
+  /// A context without a window to present to, for rendering offscreen, e.g. in tests or on machines without a
+  /// display. Frames are `width` by `height` and in [`Self::SURFACE_FORMAT`].
+  pub fn new_headless(width: u32, height: u32) -> Result<Self, RendererError> {
+    pollster::block_on(async {
+      let instance = Self::create_instance();
+      let adapter = Self::request_adapter(&instance, None).await?;
+      let (device, queue) = Self::request_device(&adapter).await?;
+
+      // never configured on a surface, only kept for the frame size and format
+      let config = wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        format: Self::SURFACE_FORMAT,
+        width: width.max(1),
+        height: height.max(1),
+        present_mode: wgpu::PresentMode::AutoNoVsync,
+        alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        view_formats: vec![],
+        desired_maximum_frame_latency: 2,
+      };
+
+      let texture = device.create_tracked_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen Frame"),
+        size: wgpu::Extent3d {
+          width: config.width,
+          height: config.height,
+          depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: config.usage,
+        view_formats: &[],
+      });
+
+      Ok(Self {
+        output: Output::Offscreen(texture),
+        config,
+        device: Arc::new(device),
+        queue,
+        view_format: Self::SURFACE_FORMAT,
+        is_zero_sized: false,
       })
     })
   }
@@ -84,22 +229,173 @@ impl GraphicsContext {
     &self.device
   }
 
+  /// The device, for handing to background threads, e.g. to compile pipelines off the render thread.
+  pub fn shared_device(&self) -> &Arc<wgpu::Device> {
+    &self.device
+  }
+
   pub fn queue(&self) -> &wgpu::Queue {
     &self.queue
   }
 
-  pub fn surface(&self) -> &wgpu::Surface<'static> {
-    &self.surface
+  /// `None` for headless contexts, and those created from raw handles.
+  pub fn window(&self) -> Option<&Window> {
+    match &self.output {
+      Output::Surface { window, .. } => window.as_deref(),
+      Output::Offscreen(_) | Output::External(_) => None,
+    }
+  }
+
+  /// The texture frames are drawn onto when headless, or `None` when presenting to a window.
+  pub fn offscreen_texture(&self) -> Option<&wgpu::Texture> {
+    match &self.output {
+      Output::Surface { .. } => None,
+      Output::Offscreen(texture) => Some(texture),
+      Output::External(texture) => Some(texture),
+    }
+  }
+
+  /// Draws the frames of a headless context onto `texture` rather than its own texture, from the next frame on. The
+  /// frame size follows the texture's.
+  ///
+  /// The texture must be created on this context's device, in [`Self::view_format`], and usable as a render
+  /// attachment. Frames can only be read back from it if it's also usable as a copy source.
+  pub fn set_external_texture(&mut self, texture: wgpu::Texture) -> Result<(), RendererError> {
+    if let Output::Surface { .. } = self.output {
+      return Err(renderer_error!("only headless contexts can draw onto external textures"));
+    }
+    if texture.format() != self.view_format {
+      return Err(renderer_error!(
+        "external textures must be in {:?}, not {:?}",
+        self.view_format,
+        texture.format()
+      ));
+    }
+    if !texture.usage().contains(wgpu::TextureUsages::RENDER_ATTACHMENT) {
+      return Err(renderer_error!("external textures must be usable as render attachments"));
+    }
+
+    self.config.width = texture.width();
+    self.config.height = texture.height();
+    self.output = Output::External(texture);
+    Ok(())
+  }
+
+  pub fn next_frame(&self) -> Result<Frame<'_>, wgpu::SurfaceError> {
+    match &self.output {
+      Output::Surface { surface, .. } => surface.get_current_texture().map(Frame::Surface),
+      Output::Offscreen(texture) => Ok(Frame::Offscreen(texture)),
+      Output::External(texture) => Ok(Frame::Offscreen(texture)),
+    }
   }
 
+  /// Shows the frame in the window. Offscreen frames stay in their texture, to be read back.
+  pub fn present(&self, frame: Frame) {
+    if let (Output::Surface { window, .. }, Frame::Surface(frame)) = (&self.output, frame) {
+      if let Some(window) = window {
+        window.pre_present_notify();
+      }
+      frame.present();
+    }
+  }
+
+  /// Resizes the surface to match the window. Headless contexts keep their size, and those created from raw handles
+  /// the size they were last [`Self::resize`]d to.
   pub fn reconfigure(&mut self) {
-    let new_size = self.window.inner_size();
-    self.config.width = new_size.width.max(1);
-    self.config.height = new_size.height.max(1);
-    self.surface.configure(&self.device, &self.config);
+    let Output::Surface { window, surface } = &self.output else {
+      return;
+    };
+    if let Some(window) = window {
+      let new_size = window.inner_size();
+      self.is_zero_sized = new_size.width == 0 || new_size.height == 0;
+      if !self.is_zero_sized {
+        self.config.width = new_size.width;
+        self.config.height = new_size.height;
+      }
+    }
+    // surfaces can't be configured without an extent, so the last size is kept until there's one again
+    if !self.is_zero_sized {
+      surface.configure(&self.device, &self.config);
+    }
+  }
+
+  /// Resizes the surface of a context created from raw handles, whose size isn't known otherwise. Surfaces of
+  /// windows follow the window's size instead, and headless contexts keep their size.
+  pub fn resize(&mut self, width: u32, height: u32) {
+    if let Output::Surface { window: None, .. } = &self.output {
+      self.is_zero_sized = width == 0 || height == 0;
+      if !self.is_zero_sized {
+        self.config.width = width;
+        self.config.height = height;
+      }
+    }
+    self.reconfigure();
+  }
+
+  /// Whether the window has no area to present to, e.g. while it's minimized. The surface keeps its last size
+  /// meanwhile, and no frames should be presented until it has an area again.
+  pub fn is_zero_sized(&self) -> bool {
+    self.is_zero_sized
+  }
+
+  /// The refresh rate of the display the window is on, in hertz. `None` for headless contexts, those created from raw
+  /// handles, and where the platform doesn't tell.
+  pub fn refresh_rate(&self) -> Option<f32> {
+    let refresh_rate_millihertz = self.window()?.current_monitor()?.refresh_rate_millihertz()?;
+    Some(refresh_rate_millihertz as f32 / 1000.0)
   }
 
   pub fn config(&self) -> &wgpu::SurfaceConfiguration {
     &self.config
   }
+
+  /// The format that frame views should be created with. This is the sRGB variant of the surface format when one
+  /// is available.
+  pub fn view_format(&self) -> TextureFormat {
+    self.view_format
+  }
+
+  /// Whether shaders writing to the frame view must apply the sRGB transfer function themselves.
+  pub fn needs_srgb_encode(format: TextureFormat) -> bool {
+    !format.is_srgb() && !matches!(format, TextureFormat::Rgba16Float | TextureFormat::Rgba32Float)
+  }
+}
+
+impl GraphicsContext {
+  pub(super) fn create_instance() -> wgpu::Instance {
+    wgpu::Instance::new(wgpu::InstanceDescriptor {
+      backends: wgpu::Backends::all(),
+      ..Default::default()
+    })
+  }
+
+  pub(super) async fn request_adapter(
+    instance: &wgpu::Instance,
+    compatible_surface: Option<&wgpu::Surface<'static>>,
+  ) -> Result<wgpu::Adapter, RendererError> {
+    instance
+      .request_adapter(&wgpu::RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        compatible_surface,
+        force_fallback_adapter: false,
+      })
+      .await
+      .ok_or_else(|| renderer_error!("no suitable graphics adapter"))
+  }
+
+  pub(super) async fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), RendererError> {
+    Ok(
+      adapter
+        .request_device(
+          &wgpu::DeviceDescriptor {
+            // for timing passes with the profiler, where it's supported
+            required_features: adapter.features() & wgpu::Features::TIMESTAMP_QUERY,
+            required_limits: wgpu::Limits::default(),
+            label: None,
+          },
+          None,
+        )
+        .await?,
+    )
+  }
 }
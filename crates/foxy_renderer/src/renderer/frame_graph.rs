@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+/// How a pass accesses a resource.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Access {
+  Read,
+  Write,
+  ReadWrite,
+}
+
+impl Access {
+  fn reads(self) -> bool {
+    matches!(self, Access::Read | Access::ReadWrite)
+  }
+
+  fn writes(self) -> bool {
+    matches!(self, Access::Write | Access::ReadWrite)
+  }
+}
+
+/// What a resource is bound as. A resource changing usage between passes needs a barrier.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ResourceUsage {
+  ColorAttachment,
+  DepthStencilAttachment,
+  Sampled,
+  Storage,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceAccess {
+  pub resource: &'static str,
+  pub access: Access,
+  pub usage: ResourceUsage,
+}
+
+#[derive(Debug, Clone)]
+pub struct FramePass {
+  pub name: &'static str,
+  pub accesses: Vec<ResourceAccess>,
+}
+
+/// A description of the passes of a frame in execution order, and of the resources each of them touches.
+#[derive(Debug, Default, Clone)]
+pub struct FrameGraph {
+  passes: Vec<FramePass>,
+}
+
+impl FrameGraph {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_pass(
+    mut self,
+    name: &'static str,
+    accesses: impl IntoIterator<Item = (&'static str, Access, ResourceUsage)>,
+  ) -> Self {
+    self.passes.push(FramePass {
+      name,
+      accesses: accesses
+        .into_iter()
+        .map(|(resource, access, usage)| ResourceAccess { resource, access, usage })
+        .collect(),
+    });
+    self
+  }
+
+  pub fn passes(&self) -> &[FramePass] {
+    &self.passes
+  }
+
+  /// Renders the graph in the DOT language, e.g. for `dot -Tsvg frame.dot -o frame.svg`.
+  ///
+  /// Passes are boxes and resources are ellipses. Every write produces a new version of the resource, so the graph
+  /// reads top to bottom in execution order. Edges where the resource changes usage, and so needs a barrier, are
+  /// drawn in red.
+  pub fn export_graphviz(&self) -> String {
+    struct ResourceState {
+      name: &'static str,
+      version: usize,
+      usage: Option<ResourceUsage>,
+    }
+
+    let mut resources: Vec<ResourceState> = Vec::new();
+    let mut dot = String::from("digraph frame {\n  rankdir=TB;\n  node [fontname=\"sans-serif\"];\n");
+
+    let resource_node = |dot: &mut String, index: usize, state: &ResourceState| {
+      let _ = writeln!(
+        dot,
+        "  r{index}_{} [label=\"{} #{}\", shape=ellipse];",
+        state.version, state.name, state.version
+      );
+    };
+
+    for (pass_index, pass) in self.passes.iter().enumerate() {
+      let _ = writeln!(dot, "  p{pass_index} [label=\"{}\", shape=box, style=filled];", pass.name);
+
+      for access in &pass.accesses {
+        let index = resources
+          .iter()
+          .position(|state| state.name == access.resource)
+          .unwrap_or_else(|| {
+            resources.push(ResourceState {
+              name: access.resource,
+              version: 0,
+              usage: None,
+            });
+            resources.len() - 1
+          });
+
+        let Some(state) = resources.get_mut(index) else {
+          continue;
+        };
+
+        // first time the resource is seen
+        if state.usage.is_none() {
+          resource_node(&mut dot, index, state);
+        }
+
+        let barrier = state.usage.is_some_and(|usage| usage != access.usage);
+        let style = if barrier {
+          format!("label=\"{:?} (barrier)\", color=red", access.usage)
+        } else {
+          format!("label=\"{:?}\"", access.usage)
+        };
+        state.usage = Some(access.usage);
+
+        if access.access.reads() {
+          let _ = writeln!(dot, "  r{index}_{} -> p{pass_index} [{style}];", state.version);
+        }
+
+        if access.access.writes() {
+          state.version += 1;
+          resource_node(&mut dot, index, state);
+          let _ = writeln!(dot, "  p{pass_index} -> r{index}_{} [{style}];", state.version);
+        }
+      }
+    }
+
+    dot.push_str("}\n");
+    dot
+  }
+}
@@ -1,15 +1,25 @@
-use std::sync::Arc;
+use std::{mem::size_of, num::NonZeroU64, sync::Arc};
 
+use bytemuck::{Pod, Zeroable};
+use foxy_utils::tracking::Tracked;
+use tracing::*;
 use wgpu::{include_wgsl, Device, Queue, TextureFormat};
 
-use super::{context::GraphicsContext, texture::DiffuseTexture, vertex::Vertex, Renderer};
+use super::{
+  context::GraphicsContext,
+  render_data::MaterialOverrides,
+  texture::DiffuseTexture,
+  tracking::TrackedDevice,
+  vertex::Vertex,
+  Renderer,
+};
 
 #[repr(C)]
 pub struct MaterialUniforms {
   pub color: [f32; 4],
 }
 
-pub trait Material {
+pub trait Material: Send + Sync {
   fn format() -> TextureFormat
   where
     Self: Sized,
@@ -23,7 +33,7 @@ pub trait Material {
 pub struct StandardMaterial {
   // pub uniforms: MaterialUniforms,
   // pub uniforms_buffer: wgpu::Buffer,
-  pub albedo: DiffuseTexture,
+  pub albedo: Arc<DiffuseTexture>,
 }
 
 impl Material for StandardMaterial {
@@ -36,9 +46,144 @@ impl StandardMaterial {
   pub fn new(device: &Device, queue: &Queue, texture: Option<DiffuseTexture>) -> Arc<Self> {
     let albedo = match texture {
       Some(texture) => texture,
-      None => DiffuseTexture::new(device, queue, include_bytes!("../../assets/textures/default.png")),
+      None => DiffuseTexture::new(device, queue, DiffuseTexture::DEFAULT),
     };
 
+    Self::with_albedo(Arc::new(albedo))
+  }
+
+  /// A material sharing `albedo` with other materials, e.g. a texture from the
+  /// [`TextureStore`](super::texture::TextureStore).
+  pub fn with_albedo(albedo: Arc<DiffuseTexture>) -> Arc<Self> {
     Arc::new(Self { albedo })
   }
 }
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct MaterialOverrideUniforms {
+  tint: [f32; 4],
+  uv_offset: [f32; 2],
+  emissive_intensity: f32,
+  _padding: f32,
+}
+
+impl From<&MaterialOverrides> for MaterialOverrideUniforms {
+  fn from(value: &MaterialOverrides) -> Self {
+    Self {
+      tint: value.tint,
+      uv_offset: value.uv_offset,
+      emissive_intensity: value.emissive_intensity,
+      _padding: 0.,
+    }
+  }
+}
+
+/// Per-draw [`MaterialOverrides`] packed into a single uniform buffer and bound with a dynamic offset, so one
+/// material can be drawn with different parameters in the same frame.
+///
+/// The slot at offset 0 always holds the default overrides. It is used by draws without overrides and once the ring
+/// runs out of slots for the frame.
+pub struct MaterialOverrideRing {
+  buffer: Tracked<wgpu::Buffer>,
+  bind_group_layout: wgpu::BindGroupLayout,
+  bind_group: wgpu::BindGroup,
+  stride: u32,
+  capacity: u32,
+  cursor: u32,
+}
+
+impl MaterialOverrideRing {
+  pub const DEFAULT_CAPACITY: u32 = 256;
+  pub const DEFAULT_OFFSET: u32 = 0;
+
+  pub fn new(device: &Device, queue: &Queue, capacity: u32) -> Self {
+    let size = size_of::<MaterialOverrideUniforms>() as u32;
+    let stride = size.next_multiple_of(device.limits().min_uniform_buffer_offset_alignment);
+    // one extra slot for the defaults
+    let capacity = capacity + 1;
+
+    let buffer = device.create_tracked_buffer(&wgpu::BufferDescriptor {
+      label: Some("Material Override Buffer"),
+      size: (stride * capacity) as u64,
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+
+    queue.write_buffer(
+      &buffer,
+      Self::DEFAULT_OFFSET as u64,
+      bytemuck::bytes_of(&MaterialOverrideUniforms::from(&MaterialOverrides::default())),
+    );
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Material Override Layout"),
+      entries: &[wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+          ty: wgpu::BufferBindingType::Uniform,
+          has_dynamic_offset: true,
+          min_binding_size: NonZeroU64::new(size as u64),
+        },
+        count: None,
+      }],
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("Material Override Bind Group"),
+      layout: &bind_group_layout,
+      entries: &[wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+          buffer: &buffer,
+          offset: 0,
+          size: NonZeroU64::new(size as u64),
+        }),
+      }],
+    });
+
+    Self {
+      buffer,
+      bind_group_layout,
+      bind_group,
+      stride,
+      capacity,
+      cursor: 1,
+    }
+  }
+
+  pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+    &self.bind_group_layout
+  }
+
+  pub fn bind_group(&self) -> &wgpu::BindGroup {
+    &self.bind_group
+  }
+
+  /// Frees every slot. Call once per frame, after the previous frame was submitted and before pushing new overrides.
+  pub fn reset(&mut self) {
+    self.cursor = 1;
+  }
+
+  /// Writes `overrides` into the next free slot and returns the dynamic offset to bind it with.
+  pub fn push(&mut self, queue: &Queue, overrides: &MaterialOverrides) -> u32 {
+    if self.cursor >= self.capacity {
+      if self.cursor == self.capacity {
+        warn!("material override ring is full, ignoring overrides for the rest of the frame");
+        self.cursor += 1;
+      }
+      return Self::DEFAULT_OFFSET;
+    }
+
+    let offset = self.cursor * self.stride;
+    queue.write_buffer(
+      &self.buffer,
+      offset as u64,
+      bytemuck::bytes_of(&MaterialOverrideUniforms::from(overrides)),
+    );
+    self.cursor += 1;
+
+    offset
+  }
+}
@@ -1,16 +1,24 @@
-use std::sync::Arc;
+use std::{mem::size_of, ops::Range, sync::Arc};
 
-use wgpu::{util::DeviceExt, IndexFormat};
+use foxy_utils::{geometry::Sphere, tracking::Tracked};
+use glam::{Mat4, Vec3};
+use wgpu::IndexFormat;
 
-use super::{material::Material, render_data::Drawable, vertex::Vertex};
+use super::{
+  material::Material,
+  render_data::{Drawable, RenderLayers},
+  render_pass::write_buffer,
+  tracking::TrackedDevice,
+  vertex::{Instance, Vertex},
+};
 
 pub struct VertexData {
-  buffer: wgpu::Buffer,
+  buffer: Tracked<wgpu::Buffer>,
   count: u32,
 }
 
 pub struct IndexData {
-  buffer: wgpu::Buffer,
+  buffer: Tracked<wgpu::Buffer>,
   count: u32,
 }
 
@@ -18,23 +26,42 @@ pub struct Mesh {
   pub vertices: VertexData,
   pub indices: Option<IndexData>,
   pub material: Arc<dyn Material>,
+  pub layers: RenderLayers,
+  /// A sphere enclosing every vertex, for culling. `None` when the vertices aren't known on the CPU, in which case the
+  /// mesh is never culled.
+  pub bounds: Option<Sphere>,
 }
 
 impl Mesh {
   pub fn new(device: &wgpu::Device, vertices: &[Vertex], indices: Option<&[u32]>, material: Arc<dyn Material>) -> Self {
-    let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+    let vertex_buffer = device.create_tracked_buffer_init(&wgpu::util::BufferInitDescriptor {
       label: Some("Vertex Buffer"),
       contents: bytemuck::cast_slice(vertices),
       usage: wgpu::BufferUsages::VERTEX,
     });
 
+    Self {
+      bounds: Sphere::from_points(vertices.iter().map(|vertex| Vec3::from(vertex.position))),
+      ..Self::from_vertex_buffer(device, vertex_buffer, vertices.len() as u32, indices, material)
+    }
+  }
+
+  /// Creates a mesh drawing from an existing buffer of `vertex_count` [`Vertex`]es, e.g. one written by a compute
+  /// pass.
+  pub fn from_vertex_buffer(
+    device: &wgpu::Device,
+    vertex_buffer: Tracked<wgpu::Buffer>,
+    vertex_count: u32,
+    indices: Option<&[u32]>,
+    material: Arc<dyn Material>,
+  ) -> Self {
     let vertices = VertexData {
       buffer: vertex_buffer,
-      count: vertices.len() as u32,
+      count: vertex_count,
     };
 
     let indices = if let Some(indices) = indices {
-      let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      let index_buffer = device.create_tracked_buffer_init(&wgpu::util::BufferInitDescriptor {
         label: Some("Index Buffer"),
         contents: bytemuck::cast_slice(indices),
         usage: wgpu::BufferUsages::INDEX,
@@ -52,18 +79,113 @@ impl Mesh {
       vertices,
       indices,
       material,
+      layers: RenderLayers::default(),
+      bounds: None,
     }
   }
+
+  pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+    self.layers = layers;
+    self
+  }
 }
 
-impl Drawable for Mesh {
-  fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+impl Mesh {
+  /// Draws the `instances` of the mesh, whose data must already be bound to vertex buffer slot 1.
+  pub fn draw_instances<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, instances: Range<u32>) {
     render_pass.set_vertex_buffer(0, self.vertices.buffer.slice(..));
     if let Some(indices) = &self.indices {
       render_pass.set_index_buffer(indices.buffer.slice(..), IndexFormat::Uint32);
-      render_pass.draw_indexed(0..indices.count, 0, 0..1);
+      render_pass.draw_indexed(0..indices.count, 0, instances);
     } else {
-      render_pass.draw(0..self.vertices.count, 0..1);
+      render_pass.draw(0..self.vertices.count, instances);
+    }
+  }
+}
+
+impl Drawable for Mesh {
+  fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+    self.draw_instances(render_pass, 0..1);
+  }
+
+  fn layers(&self) -> RenderLayers {
+    self.layers
+  }
+}
+
+/// A mesh as drawn this frame, once for each instance in its slice of the [`InstanceBuffer`].
+#[derive(Copy, Clone)]
+pub struct MeshDraw<'a> {
+  pub mesh: &'a Mesh,
+  /// Where the draw is in [`RenderData::draws`](super::render_data::RenderData::draws), which is how material
+  /// overrides and highlighting refer to it.
+  pub index: usize,
+  pub instances: wgpu::BufferSlice<'a>,
+  pub instance_count: u32,
+}
+
+impl Drawable for MeshDraw<'_> {
+  fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+    render_pass.set_vertex_buffer(1, self.instances);
+    self.mesh.draw_instances(render_pass, 0..self.instance_count);
+  }
+
+  fn layers(&self) -> RenderLayers {
+    self.mesh.layers()
+  }
+}
+
+/// The instances of every draw in a frame, packed into one vertex buffer. The buffer is only reallocated when it has
+/// to grow.
+#[derive(Default)]
+pub struct InstanceBuffer {
+  buffer: Option<Tracked<wgpu::Buffer>>,
+}
+
+impl InstanceBuffer {
+  /// Uploads the instance transforms of `draws`, given with their index in the frame, and pairs each mesh with its
+  /// instances. Draws without any instances are left out.
+  pub fn upload<'a>(
+    &'a mut self,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    draws: &[(usize, &'a Mesh, &[Mat4])],
+  ) -> Vec<MeshDraw<'a>> {
+    let instances: Vec<Instance> = draws
+      .iter()
+      .flat_map(|(_, _, transforms)| transforms.iter().copied().map(Instance::from))
+      .collect();
+    if instances.is_empty() {
+      return Vec::new();
     }
+
+    write_buffer(
+      device,
+      queue,
+      &mut self.buffer,
+      "Instance Buffer",
+      wgpu::BufferUsages::VERTEX,
+      bytemuck::cast_slice(&instances),
+    );
+    let Some(buffer) = self.buffer.as_ref() else {
+      return Vec::new();
+    };
+
+    let stride = size_of::<Instance>() as u64;
+    let mut offset = 0;
+    draws
+      .iter()
+      .filter(|(_, _, transforms)| !transforms.is_empty())
+      .map(|&(index, mesh, transforms)| {
+        let start = offset;
+        offset += transforms.len() as u64;
+        MeshDraw {
+          mesh,
+          index,
+          instances: buffer.slice(start * stride..offset * stride),
+          instance_count: transforms.len() as u32,
+        }
+      })
+      .collect()
   }
 }
@@ -0,0 +1,93 @@
+use std::sync::{Arc, OnceLock};
+
+use foxy_utils::tracking::Tracked;
+use tracing::*;
+
+use crate::error::{validated, RendererError};
+
+/// Pipelines compiled on a background thread, so that new pipelines don't stall the frame they're needed in. Until
+/// compilation finishes, [`AsyncPipeline::get`] returns the pipelines it had before, which must be compatible with the
+/// same bind groups and vertex buffers.
+///
+/// `T` is usually a single pipeline, but passes that build several pipelines from the same shaders compile them
+/// together so they're always swapped in as a set.
+pub struct AsyncPipeline<T = Tracked<wgpu::RenderPipeline>> {
+  current: Arc<T>,
+  compiled: Arc<OnceLock<Arc<T>>>,
+}
+
+impl<T: Send + Sync + 'static> AsyncPipeline<T> {
+  /// Starts compiling with `create`, using `placeholder` meanwhile.
+  pub fn spawn(
+    label: &str,
+    device: &Arc<wgpu::Device>,
+    placeholder: Arc<T>,
+    create: impl FnOnce(&wgpu::Device) -> T + Send + 'static,
+  ) -> Result<Self, RendererError> {
+    let compiled = Self::compile(label, device, create)?;
+    Ok(Self {
+      current: placeholder,
+      compiled,
+    })
+  }
+
+  /// Pipelines that are already compiled, e.g. the ones a pass needs before it can draw anything at all.
+  pub fn ready(pipeline: T) -> Self {
+    let pipeline = Arc::new(pipeline);
+    Self {
+      current: pipeline.clone(),
+      compiled: Arc::new(OnceLock::from(pipeline)),
+    }
+  }
+
+  /// Recompiles with `create`, e.g. after the shaders were reloaded. The pipelines in use are kept until the new ones
+  /// are ready, and for good if they fail to compile.
+  pub fn rebuild(
+    &mut self,
+    label: &str,
+    device: &Arc<wgpu::Device>,
+    create: impl FnOnce(&wgpu::Device) -> T + Send + 'static,
+  ) -> Result<(), RendererError> {
+    let compiled = Self::compile(label, device, create)?;
+    self.current = self.compiled.get().unwrap_or(&self.current).clone();
+    self.compiled = compiled;
+    Ok(())
+  }
+
+  pub fn is_ready(&self) -> bool {
+    self.compiled.get().is_some()
+  }
+
+  /// The compiled pipelines, or the previous ones while they're still compiling.
+  pub fn get(&self) -> &T {
+    self.compiled.get().unwrap_or(&self.current)
+  }
+}
+
+impl<T: Send + Sync + 'static> AsyncPipeline<T> {
+  fn compile(
+    label: &str,
+    device: &Arc<wgpu::Device>,
+    create: impl FnOnce(&wgpu::Device) -> T + Send + 'static,
+  ) -> Result<Arc<OnceLock<Arc<T>>>, RendererError> {
+    let compiled = Arc::new(OnceLock::new());
+
+    let device = device.clone();
+    let slot = compiled.clone();
+    let name = label.to_string();
+    std::thread::Builder::new()
+      .name(format!("{label} compiler"))
+      .spawn(move || {
+        // wgpu reports invalid pipelines through the device, so they'd be used as if nothing went wrong otherwise
+        match validated(&device, || create(&device)) {
+          Ok(pipeline) => {
+            let _ = slot.set(Arc::new(pipeline));
+            debug!("compiled {name}");
+          }
+          Err(error) => error!("failed to compile {name}, keeping the previous pipeline: {error}"),
+        }
+      })?;
+
+    Ok(compiled)
+  }
+}
@@ -0,0 +1,71 @@
+use std::time::{Duration, Instant};
+
+/// How frames have been reaching the display, as returned by
+/// [`Renderer::present_stats`](super::Renderer::present_stats). Meant for adaptive quality, e.g. lowering the resolution
+/// scale while vsyncs are being missed.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PresentStats {
+  /// The refresh rate of the display the window is on, in hertz. `None` when it isn't known, e.g. when headless.
+  pub refresh_rate: Option<f32>,
+  /// The time between the last two presents.
+  pub present_interval: Duration,
+  /// How long acquiring the last frame waited for the swapchain, which grows when the GPU or the display is what
+  /// holds frames back rather than the CPU.
+  pub acquire_time: Duration,
+  /// How many refreshes of the display passed without a new frame between the last two presents. Frame rate limits
+  /// below the refresh rate count as well.
+  pub missed_vsyncs: u32,
+  /// Every missed vsync since the renderer was created.
+  pub total_missed_vsyncs: u64,
+  /// The time from presenting a frame to it showing on the display. wgpu doesn't report it yet, so it's always `None`
+  /// for now.
+  pub present_latency: Option<Duration>,
+}
+
+impl PresentStats {
+  /// The time a refresh of the display takes, if its refresh rate is known.
+  pub fn refresh_period(&self) -> Option<Duration> {
+    self
+      .refresh_rate
+      .filter(|refresh_rate| *refresh_rate > 0.0)
+      .map(|refresh_rate| Duration::from_secs_f32(1.0 / refresh_rate))
+  }
+}
+
+/// Keeps [`PresentStats`] up to date as frames are presented.
+#[derive(Default)]
+pub(crate) struct PresentTracker {
+  last_present: Option<Instant>,
+  stats: PresentStats,
+}
+
+impl PresentTracker {
+  /// Records that a frame was presented, after waiting `acquire_time` for its texture.
+  pub fn presented(&mut self, acquire_time: Duration, refresh_rate: Option<f32>) {
+    let now = Instant::now();
+    self.stats.refresh_rate = refresh_rate;
+    self.stats.acquire_time = acquire_time;
+    self.stats.missed_vsyncs = 0;
+
+    if let Some(last_present) = self.last_present {
+      self.stats.present_interval = now - last_present;
+      if let Some(refresh_period) = self.stats.refresh_period() {
+        // rounded, since presents jitter around the vsync they land on
+        let refreshes = (self.stats.present_interval.as_secs_f32() / refresh_period.as_secs_f32()).round() as u32;
+        self.stats.missed_vsyncs = refreshes.saturating_sub(1);
+        self.stats.total_missed_vsyncs += self.stats.missed_vsyncs as u64;
+      }
+    }
+    self.last_present = Some(now);
+  }
+
+  /// Forgets the last present, so that the gap of frames skipped on purpose, e.g. while the window is minimized,
+  /// doesn't count as missed vsyncs.
+  pub fn pause(&mut self) {
+    self.last_present = None;
+  }
+
+  pub fn stats(&self) -> &PresentStats {
+    &self.stats
+  }
+}
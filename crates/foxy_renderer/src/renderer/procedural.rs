@@ -0,0 +1,262 @@
+use std::{
+  fmt,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use image::{DynamicImage, RgbaImage};
+use wgpu::{util::BufferInitDescriptor, Device, Queue};
+
+use super::{
+  shader::ShaderStore,
+  texture::{DiffuseTexture, Sampling},
+  tracking::TrackedDevice,
+};
+use crate::error::{validated, RendererError};
+
+type CpuGenerator = Arc<dyn Fn(&[u8], u32, u32) -> [u8; 4] + Send + Sync>;
+
+#[derive(Clone)]
+enum Generator {
+  Cpu { name: String, generate: CpuGenerator },
+  Compute { shader: PathBuf, entry_point: String },
+}
+
+/// What a procedural texture is cached by: the same generator with the same size, sampling and parameters makes the
+/// same texture.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ProceduralKey {
+  generator: String,
+  width: u32,
+  height: u32,
+  sampling: Sampling,
+  parameters: Vec<u8>,
+}
+
+/// A texture generated when it's created rather than loaded from a file, e.g. gradients, noise or lookup tables.
+///
+/// Texels are RGBA8 and sampled as sRGB, like the texels of image files. They're filled either by a function called for
+/// every texel, or by a compute shader with an 8 by 8 workgroup size, which gets the texture as
+/// `@group(0) @binding(0) var output: texture_storage_2d<rgba8unorm, write>` and, if the texture has parameters, them
+/// as `@group(0) @binding(1) var<uniform>`. Compute generated textures have no mipmaps.
+///
+/// Textures are cached by [`TextureStore::get_procedural`](super::texture::TextureStore::get_procedural) for as long as
+/// they're in use, keyed by their generator, size, sampling and parameters.
+#[derive(Clone)]
+pub struct ProceduralTexture {
+  generator: Generator,
+  width: u32,
+  height: u32,
+  sampling: Sampling,
+  parameters: Vec<u8>,
+}
+
+impl ProceduralTexture {
+  /// Fills the texture on the CPU with `generate`, called with the parameters and the coordinates of every texel.
+  /// `name` identifies the generator in the cache, so different generators need different names.
+  pub fn cpu(
+    name: impl Into<String>,
+    width: u32,
+    height: u32,
+    generate: impl Fn(&[u8], u32, u32) -> [u8; 4] + Send + Sync + 'static,
+  ) -> Self {
+    Self::new(
+      Generator::Cpu {
+        name: name.into(),
+        generate: Arc::new(generate),
+      },
+      width,
+      height,
+    )
+  }
+
+  /// Fills the texture by running `entry_point` of the compute shader at `shader`, relative to the shader asset
+  /// directory, once per texel.
+  pub fn compute(shader: impl Into<PathBuf>, entry_point: impl Into<String>, width: u32, height: u32) -> Self {
+    Self::new(
+      Generator::Compute {
+        shader: shader.into(),
+        entry_point: entry_point.into(),
+      },
+      width,
+      height,
+    )
+  }
+
+  /// Passes `parameters` to the generator, e.g. a seed and a scale. Textures with different parameters are cached
+  /// separately.
+  pub fn with_parameters(mut self, parameters: &[u8]) -> Self {
+    self.parameters = parameters.to_vec();
+    self
+  }
+
+  pub fn with_sampling(mut self, sampling: Sampling) -> Self {
+    self.sampling = sampling;
+    self
+  }
+
+  pub fn width(&self) -> u32 {
+    self.width
+  }
+
+  pub fn height(&self) -> u32 {
+    self.height
+  }
+
+  /// Generates the texture, without caching it.
+  pub fn generate(
+    &self,
+    device: &Device,
+    queue: &Queue,
+    shader_store: &mut ShaderStore,
+  ) -> Result<DiffuseTexture, RendererError> {
+    match &self.generator {
+      Generator::Cpu { generate, .. } => {
+        let image = RgbaImage::from_fn(self.width, self.height, |x, y| image::Rgba(generate(&self.parameters, x, y)));
+        Ok(DiffuseTexture::with_sampling(
+          device,
+          queue,
+          &DynamicImage::ImageRgba8(image),
+          self.sampling,
+        ))
+      }
+      Generator::Compute { shader, entry_point } => {
+        self.generate_on_gpu(device, queue, shader_store, shader, entry_point)
+      }
+    }
+  }
+
+  pub(crate) fn key(&self) -> ProceduralKey {
+    let generator = match &self.generator {
+      Generator::Cpu { name, .. } => format!("cpu:{name}"),
+      Generator::Compute { shader, entry_point } => format!("compute:{}:{entry_point}", shader.display()),
+    };
+    ProceduralKey {
+      generator,
+      width: self.width,
+      height: self.height,
+      sampling: self.sampling,
+      parameters: self.parameters.clone(),
+    }
+  }
+}
+
+impl ProceduralTexture {
+  const WORKGROUP_SIZE: u32 = 8;
+
+  fn new(generator: Generator, width: u32, height: u32) -> Self {
+    Self {
+      generator,
+      width: width.max(1),
+      height: height.max(1),
+      sampling: Sampling::default(),
+      parameters: Vec::new(),
+    }
+  }
+
+  fn generate_on_gpu(
+    &self,
+    device: &Device,
+    queue: &Queue,
+    shader_store: &mut ShaderStore,
+    shader: &Path,
+    entry_point: &str,
+  ) -> Result<DiffuseTexture, RendererError> {
+    let shader = shader_store.compute(device, shader)?;
+
+    let texture = validated(device, || {
+      let pipeline = device.create_tracked_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("Procedural Texture Pipeline"),
+        layout: None,
+        module: &shader.module(),
+        entry_point: shader.entry_point(entry_point),
+      });
+
+      // written as plain RGBA8, since sRGB textures can't be storage textures, and sampled as sRGB
+      let texture = device.create_tracked_texture(&wgpu::TextureDescriptor {
+        label: Some("Procedural Texture"),
+        size: wgpu::Extent3d {
+          width: self.width,
+          height: self.height,
+          depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+      });
+      let storage_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+      // uniform buffers are bound in multiples of 16 bytes
+      let parameters_buffer = (!self.parameters.is_empty()).then(|| {
+        let mut contents = self.parameters.clone();
+        contents.resize(contents.len().next_multiple_of(16), 0);
+        device.create_tracked_buffer_init(&BufferInitDescriptor {
+          label: Some("Procedural Texture Parameters"),
+          contents: &contents,
+          usage: wgpu::BufferUsages::UNIFORM,
+        })
+      });
+
+      let mut entries = vec![wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::TextureView(&storage_view),
+      }];
+      if let Some(buffer) = &parameters_buffer {
+        entries.push(wgpu::BindGroupEntry {
+          binding: 1,
+          resource: buffer.as_entire_binding(),
+        });
+      }
+      let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Procedural Texture Bind Group"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &entries,
+      });
+
+      let mut command_encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Procedural Texture Encoder"),
+      });
+      {
+        let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+          label: Some("Procedural Texture Pass"),
+          timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(
+          self.width.div_ceil(Self::WORKGROUP_SIZE),
+          self.height.div_ceil(Self::WORKGROUP_SIZE),
+          1,
+        );
+      }
+      queue.submit(Some(command_encoder.finish()));
+
+      texture
+    })?;
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+      format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+      ..Default::default()
+    });
+    Ok(DiffuseTexture::from_view(
+      device,
+      texture,
+      view,
+      self.sampling.with_mipmaps(false),
+    ))
+  }
+}
+
+impl fmt::Debug for ProceduralTexture {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("ProceduralTexture")
+      .field("generator", &self.key().generator)
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("sampling", &self.sampling)
+      .finish_non_exhaustive()
+  }
+}
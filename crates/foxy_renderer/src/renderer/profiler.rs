@@ -0,0 +1,302 @@
+use std::{
+  collections::VecDeque,
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
+
+use foxy_utils::tracking::Tracked;
+use tracing::*;
+use wgpu::{Buffer, CommandEncoder, Device, QuerySet, Queue};
+
+use super::tracking::TrackedDevice;
+
+/// How long a pass took on the GPU.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PassTiming {
+  pub name: &'static str,
+  pub gpu_time: Duration,
+}
+
+/// What went into drawing a frame, as returned by [`Renderer::frame_stats`](super::Renderer::frame_stats).
+///
+/// GPU timings are only known once the GPU has finished the frame, so the stats are from a frame a few frames back.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FrameStats {
+  /// The passes in the order they ran. Empty if the GPU doesn't support timestamp queries.
+  pub passes: Vec<PassTiming>,
+  /// The time since the frame before it, on the render thread.
+  pub frame_time: Duration,
+  /// The meshes drawn after culling, each of which is one instanced draw call.
+  pub draw_count: usize,
+}
+
+impl FrameStats {
+  /// The time all the passes took on the GPU together.
+  pub fn gpu_time(&self) -> Duration {
+    self.passes.iter().map(|pass| pass.gpu_time).sum()
+  }
+}
+
+/// Measures how long passes take on the GPU by writing a timestamp query before and after each one.
+///
+/// The queries of a frame are copied into one of a few read back buffers, which are read once the GPU has finished
+/// with them, so the CPU never waits on the GPU. Frames are skipped while every buffer is still in use. On GPUs
+/// without [`wgpu::Features::TIMESTAMP_QUERY`] only the CPU side stats are collected.
+pub struct GpuProfiler {
+  queries: Option<Queries>,
+  /// The passes of the frame being recorded.
+  scopes: Vec<&'static str>,
+  /// The read back buffer the frame being recorded is copied into, if one was free.
+  slot: Option<usize>,
+  frame_time: Duration,
+  draw_count: usize,
+  pending: VecDeque<PendingFrame>,
+  stats: FrameStats,
+}
+
+struct Queries {
+  query_set: QuerySet,
+  resolve_buffer: Tracked<Buffer>,
+  read_back_buffers: Vec<Tracked<Buffer>>,
+  free_slots: Vec<usize>,
+  timestamp_period: f32,
+}
+
+struct PendingFrame {
+  slot: usize,
+  scopes: Vec<&'static str>,
+  frame_time: Duration,
+  draw_count: usize,
+  /// Set once mapping the read back buffer finishes, to whether it succeeded.
+  is_mapped: Arc<OnceLock<bool>>,
+}
+
+impl GpuProfiler {
+  /// The most passes timed per frame. Passes after these are still drawn, but not timed.
+  pub const MAX_SCOPES: usize = 32;
+  /// How many frames can be waiting to be read back at once.
+  const FRAMES_IN_FLIGHT: usize = 3;
+  const BUFFER_SIZE: wgpu::BufferAddress = (Self::MAX_SCOPES * 2) as wgpu::BufferAddress * wgpu::QUERY_SIZE as u64;
+
+  pub fn new(device: &Device, queue: &Queue) -> Self {
+    let queries = if device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+      Some(Queries {
+        query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+          label: Some("Profiler Query Set"),
+          ty: wgpu::QueryType::Timestamp,
+          count: (Self::MAX_SCOPES * 2) as u32,
+        }),
+        resolve_buffer: device.create_tracked_buffer(&wgpu::BufferDescriptor {
+          label: Some("Profiler Resolve Buffer"),
+          size: Self::BUFFER_SIZE,
+          usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+          mapped_at_creation: false,
+        }),
+        read_back_buffers: (0..Self::FRAMES_IN_FLIGHT)
+          .map(|_| {
+            device.create_tracked_buffer(&wgpu::BufferDescriptor {
+              label: Some("Profiler Read Back Buffer"),
+              size: Self::BUFFER_SIZE,
+              usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+              mapped_at_creation: false,
+            })
+          })
+          .collect(),
+        free_slots: (0..Self::FRAMES_IN_FLIGHT).collect(),
+        timestamp_period: queue.get_timestamp_period(),
+      })
+    } else {
+      debug!("timestamp queries aren't supported, GPU pass timings won't be collected");
+      None
+    };
+
+    Self {
+      queries,
+      scopes: Vec::new(),
+      slot: None,
+      frame_time: Duration::ZERO,
+      draw_count: 0,
+      pending: VecDeque::new(),
+      stats: FrameStats::default(),
+    }
+  }
+
+  /// The stats of the newest frame the GPU has finished.
+  pub fn stats(&self) -> &FrameStats {
+    &self.stats
+  }
+
+  /// Reads back the frames the GPU has finished since the last call, and starts recording a new one.
+  pub fn begin_frame(&mut self, device: &Device, frame_time: Duration) {
+    device.poll(wgpu::Maintain::Poll);
+    self.read_back_finished();
+
+    self.scopes.clear();
+    self.frame_time = frame_time;
+    self.draw_count = 0;
+    // a frame that failed to draw keeps its slot for the next one
+    if self.slot.is_none() {
+      self.slot = self.queries.as_mut().and_then(|queries| queries.free_slots.pop());
+    }
+  }
+
+  pub fn set_draw_count(&mut self, draw_count: usize) {
+    self.draw_count = draw_count;
+  }
+
+  /// Times everything `record` writes to `command_encoder` as the pass `name`.
+  pub fn scope<R>(
+    &mut self,
+    command_encoder: &mut CommandEncoder,
+    name: &'static str,
+    record: impl FnOnce(&mut CommandEncoder) -> R,
+  ) -> R {
+    let query = match &self.queries {
+      Some(queries) if self.slot.is_some() && self.scopes.len() < Self::MAX_SCOPES => {
+        let query = (self.scopes.len() * 2) as u32;
+        self.scopes.push(name);
+        command_encoder.write_timestamp(&queries.query_set, query);
+        Some((&queries.query_set, query))
+      }
+      _ => None,
+    };
+
+    let result = record(command_encoder);
+
+    if let Some((query_set, query)) = query {
+      command_encoder.write_timestamp(query_set, query + 1);
+    }
+    result
+  }
+
+  /// Copies the frame's queries to its read back buffer. Call after the last pass, before finishing the encoder.
+  pub fn resolve(&mut self, command_encoder: &mut CommandEncoder) {
+    let (Some(queries), Some(slot)) = (&self.queries, self.slot) else {
+      return;
+    };
+    let Some(read_back_buffer) = queries.read_back_buffers.get(slot) else {
+      return;
+    };
+
+    let query_count = (self.scopes.len() * 2) as u32;
+    if query_count > 0 {
+      command_encoder.resolve_query_set(&queries.query_set, 0..query_count, &queries.resolve_buffer, 0);
+      command_encoder.copy_buffer_to_buffer(
+        &queries.resolve_buffer,
+        0,
+        read_back_buffer,
+        0,
+        query_count as wgpu::BufferAddress * wgpu::QUERY_SIZE as u64,
+      );
+    }
+  }
+
+  /// Starts reading back the frame. Call after the encoder was submitted, since buffers can't be submitted while
+  /// they're being mapped.
+  pub fn end_frame(&mut self) {
+    let Some(queries) = &self.queries else {
+      self.stats = FrameStats {
+        passes: Vec::new(),
+        frame_time: self.frame_time,
+        draw_count: self.draw_count,
+      };
+      return;
+    };
+    let Some(slot) = self.slot.take() else {
+      return;
+    };
+
+    let is_mapped = Arc::new(OnceLock::new());
+    if let Some(read_back_buffer) = queries.read_back_buffers.get(slot) {
+      let is_mapped = is_mapped.clone();
+      read_back_buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+          if let Err(error) = &result {
+            error!("failed to read back GPU timings: {error}");
+          }
+          let _ = is_mapped.set(result.is_ok());
+        });
+    }
+
+    self.pending.push_back(PendingFrame {
+      slot,
+      scopes: std::mem::take(&mut self.scopes),
+      frame_time: self.frame_time,
+      draw_count: self.draw_count,
+      is_mapped,
+    });
+  }
+}
+
+impl GpuProfiler {
+  fn read_back_finished(&mut self) {
+    let Some(queries) = &mut self.queries else {
+      return;
+    };
+
+    while self
+      .pending
+      .front()
+      .is_some_and(|frame| frame.is_mapped.get().is_some())
+    {
+      let Some(frame) = self.pending.pop_front() else {
+        break;
+      };
+      queries.free_slots.push(frame.slot);
+      let Some(read_back_buffer) = queries.read_back_buffers.get(frame.slot) else {
+        continue;
+      };
+      if frame.is_mapped.get() != Some(&true) {
+        continue;
+      }
+
+      let timestamps: Vec<u64> = {
+        let range = read_back_buffer.slice(..).get_mapped_range();
+        range
+          .chunks_exact(wgpu::QUERY_SIZE as usize)
+          .take(frame.scopes.len() * 2)
+          .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+          .collect()
+      };
+      read_back_buffer.unmap();
+
+      let passes = frame
+        .scopes
+        .iter()
+        .zip(timestamps.chunks_exact(2))
+        .map(|(&name, pair)| {
+          let ticks = match pair {
+            [start, end] => end.saturating_sub(*start),
+            _ => 0,
+          };
+          PassTiming {
+            name,
+            gpu_time: Duration::from_nanos((ticks as f64 * queries.timestamp_period as f64) as u64),
+          }
+        })
+        .collect();
+
+      self.stats = FrameStats {
+        passes,
+        frame_time: frame.frame_time,
+        draw_count: frame.draw_count,
+      };
+      Self::trace_stats(&self.stats);
+    }
+  }
+
+  fn trace_stats(stats: &FrameStats) {
+    let frame_span = debug_span!(
+      "gpu_frame",
+      gpu_ms = stats.gpu_time().as_secs_f64() * 1000.0,
+      frame_ms = stats.frame_time.as_secs_f64() * 1000.0,
+      draw_count = stats.draw_count,
+    );
+    let _frame = frame_span.enter();
+    for pass in &stats.passes {
+      let gpu_ms = pass.gpu_time.as_secs_f64() * 1000.0;
+      debug_span!("gpu_pass", pass = pass.name, gpu_ms).in_scope(|| trace!("{}: {gpu_ms:.3} ms", pass.name));
+    }
+  }
+}
@@ -0,0 +1,294 @@
+use std::{
+  path::PathBuf,
+  sync::{mpsc, Arc, OnceLock},
+};
+
+use image::DynamicImage;
+use tracing::*;
+
+use super::{
+  material::StandardMaterial,
+  mesh::Mesh,
+  procedural::ProceduralTexture,
+  render_data::RenderLayers,
+  render_pass::skinning::{SkinnedMesh, SkinningPass},
+  shader::ShaderStore,
+  texture::{DiffuseTexture, TextureStore},
+  vertex::{SkinnedVertex, Vertex},
+};
+use crate::{error::RendererError, renderer_error};
+
+/// A resource requested through a [`RendererProxy`]. It's empty until the render thread gets around to creating it,
+/// usually by the next frame.
+///
+/// The resource lives for as long as any clone of its handle does.
+pub struct ResourceHandle<T>(Arc<OnceLock<T>>);
+
+impl<T> Clone for ResourceHandle<T> {
+  fn clone(&self) -> Self {
+    Self(self.0.clone())
+  }
+}
+
+impl<T> ResourceHandle<T> {
+  fn pending() -> Self {
+    Self(Arc::new(OnceLock::new()))
+  }
+
+  pub fn is_ready(&self) -> bool {
+    self.0.get().is_some()
+  }
+
+  pub fn get(&self) -> Option<&T> {
+    self.0.get()
+  }
+
+  /// Whether the command holds the only handle left, in which case nobody is waiting on the resource anymore.
+  fn is_abandoned(&self) -> bool {
+    Arc::strong_count(&self.0) == 1
+  }
+
+  fn resolve(&self, value: T) {
+    let _ = self.0.set(value);
+  }
+}
+
+enum RenderCommand {
+  Material {
+    albedo: Option<DynamicImage>,
+    handle: ResourceHandle<Arc<StandardMaterial>>,
+  },
+  LoadMaterial {
+    path: PathBuf,
+    handle: ResourceHandle<Arc<StandardMaterial>>,
+  },
+  TexturedMaterial {
+    albedo: ResourceHandle<Arc<DiffuseTexture>>,
+    handle: ResourceHandle<Arc<StandardMaterial>>,
+  },
+  ProceduralTexture {
+    texture: ProceduralTexture,
+    handle: ResourceHandle<Arc<DiffuseTexture>>,
+  },
+  Mesh {
+    vertices: Vec<Vertex>,
+    indices: Option<Vec<u32>>,
+    material: ResourceHandle<Arc<StandardMaterial>>,
+    layers: RenderLayers,
+    handle: ResourceHandle<Mesh>,
+  },
+  SkinnedMesh {
+    vertices: Vec<SkinnedVertex>,
+    indices: Option<Vec<u32>>,
+    material: ResourceHandle<Arc<StandardMaterial>>,
+    joint_count: u32,
+    layers: RenderLayers,
+    handle: ResourceHandle<SkinnedMesh>,
+  },
+}
+
+/// Creates renderer resources from threads other than the render thread, e.g. the game thread.
+///
+/// Requests are queued and carried out in order at the start of the next frame, so a mesh can use a material
+/// requested just before it.
+#[derive(Debug, Clone)]
+pub struct RendererProxy {
+  commands: mpsc::Sender<RenderCommand>,
+}
+
+impl RendererProxy {
+  /// Creates a proxy along with the queue its requests end up in, which is handed to the
+  /// [`Renderer`](super::Renderer).
+  pub fn new() -> (Self, RenderCommands) {
+    let (sender, receiver) = mpsc::channel();
+    (Self { commands: sender }, RenderCommands(receiver))
+  }
+
+  /// Creates a material textured with the encoded image in `albedo`, or a plain white one without it.
+  pub fn create_material(&self, albedo: Option<&[u8]>) -> Result<ResourceHandle<Arc<StandardMaterial>>, RendererError> {
+    // decoded here so that bad images are reported to the caller, and decoding doesn't hold up a frame
+    let albedo = albedo.map(image::load_from_memory).transpose()?;
+    let handle = ResourceHandle::pending();
+    self.send(RenderCommand::Material {
+      albedo,
+      handle: handle.clone(),
+    })?;
+    Ok(handle)
+  }
+
+  /// Creates a material textured with the image at `path`, relative to the texture asset directory. Textures are
+  /// cached, so materials loaded from the same image share it on the GPU.
+  pub fn load_material(
+    &self,
+    path: impl Into<PathBuf>,
+  ) -> Result<ResourceHandle<Arc<StandardMaterial>>, RendererError> {
+    let handle = ResourceHandle::pending();
+    self.send(RenderCommand::LoadMaterial {
+      path: path.into(),
+      handle: handle.clone(),
+    })?;
+    Ok(handle)
+  }
+
+  /// Creates a material textured with `albedo`, e.g. a texture from
+  /// [`RendererProxy::create_procedural_texture`].
+  pub fn create_textured_material(
+    &self,
+    albedo: &ResourceHandle<Arc<DiffuseTexture>>,
+  ) -> Result<ResourceHandle<Arc<StandardMaterial>>, RendererError> {
+    let handle = ResourceHandle::pending();
+    self.send(RenderCommand::TexturedMaterial {
+      albedo: albedo.clone(),
+      handle: handle.clone(),
+    })?;
+    Ok(handle)
+  }
+
+  /// Generates `texture`, or shares the one generated with the same parameters if it's still in use. The handle stays
+  /// empty if generating it fails.
+  pub fn create_procedural_texture(
+    &self,
+    texture: ProceduralTexture,
+  ) -> Result<ResourceHandle<Arc<DiffuseTexture>>, RendererError> {
+    let handle = ResourceHandle::pending();
+    self.send(RenderCommand::ProceduralTexture {
+      texture,
+      handle: handle.clone(),
+    })?;
+    Ok(handle)
+  }
+
+  /// Creates a mesh on `layers`, which decide the cameras and passes that draw it.
+  pub fn create_mesh(
+    &self,
+    vertices: Vec<Vertex>,
+    indices: Option<Vec<u32>>,
+    material: &ResourceHandle<Arc<StandardMaterial>>,
+    layers: RenderLayers,
+  ) -> Result<ResourceHandle<Mesh>, RendererError> {
+    let handle = ResourceHandle::pending();
+    self.send(RenderCommand::Mesh {
+      vertices,
+      indices,
+      material: material.clone(),
+      layers,
+      handle: handle.clone(),
+    })?;
+    Ok(handle)
+  }
+
+  /// Creates a mesh skinned with `joint_count` joints, which is posed through
+  /// [`SkinnedDraw`](super::render_data::SkinnedDraw)s.
+  pub fn create_skinned_mesh(
+    &self,
+    vertices: Vec<SkinnedVertex>,
+    indices: Option<Vec<u32>>,
+    material: &ResourceHandle<Arc<StandardMaterial>>,
+    joint_count: u32,
+    layers: RenderLayers,
+  ) -> Result<ResourceHandle<SkinnedMesh>, RendererError> {
+    let handle = ResourceHandle::pending();
+    self.send(RenderCommand::SkinnedMesh {
+      vertices,
+      indices,
+      material: material.clone(),
+      joint_count,
+      layers,
+      handle: handle.clone(),
+    })?;
+    Ok(handle)
+  }
+
+  fn send(&self, command: RenderCommand) -> Result<(), RendererError> {
+    self
+      .commands
+      .send(command)
+      .map_err(|_| renderer_error!("the renderer has shut down"))
+  }
+}
+
+/// The requests made through a [`RendererProxy`], waiting for the render thread.
+pub struct RenderCommands(mpsc::Receiver<RenderCommand>);
+
+impl RenderCommands {
+  /// Carries out every request made since the last call. Requests whose handles were all dropped are skipped.
+  pub(crate) fn process(
+    &self,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    skinning_pass: &SkinningPass,
+    shader_store: &mut ShaderStore,
+    texture_store: &mut TextureStore,
+  ) {
+    for command in self.0.try_iter() {
+      match command {
+        RenderCommand::Material { albedo, handle } => {
+          if handle.is_abandoned() {
+            continue;
+          }
+          let albedo = albedo.map(|image| DiffuseTexture::from_image(device, queue, &image));
+          handle.resolve(StandardMaterial::new(device, queue, albedo));
+        }
+        RenderCommand::LoadMaterial { path, handle } => {
+          if handle.is_abandoned() {
+            continue;
+          }
+          handle.resolve(StandardMaterial::with_albedo(texture_store.get(device, queue, path)));
+        }
+        RenderCommand::TexturedMaterial { albedo, handle } => {
+          if handle.is_abandoned() {
+            continue;
+          }
+          let Some(albedo) = albedo.get().cloned() else {
+            error!("skipped creating a material whose texture doesn't exist");
+            continue;
+          };
+          handle.resolve(StandardMaterial::with_albedo(albedo));
+        }
+        RenderCommand::ProceduralTexture { texture, handle } => {
+          if handle.is_abandoned() {
+            continue;
+          }
+          match texture_store.get_procedural(device, queue, shader_store, &texture) {
+            Ok(generated) => handle.resolve(generated),
+            Err(error) => error!("failed to generate {texture:?}: {error}"),
+          }
+        }
+        RenderCommand::Mesh {
+          vertices,
+          indices,
+          material,
+          layers,
+          handle,
+        } => {
+          if handle.is_abandoned() {
+            continue;
+          }
+          let Some(material) = material.get().cloned() else {
+            error!("skipped creating a mesh whose material doesn't exist");
+            continue;
+          };
+          handle.resolve(Mesh::new(device, &vertices, indices.as_deref(), material).with_layers(layers));
+        }
+        RenderCommand::SkinnedMesh {
+          vertices,
+          indices,
+          material,
+          joint_count,
+          layers,
+          handle,
+        } => {
+          if handle.is_abandoned() {
+            continue;
+          }
+          let Some(material) = material.get().cloned() else {
+            error!("skipped creating a skinned mesh whose material doesn't exist");
+            continue;
+          };
+          let mesh = SkinnedMesh::new(device, skinning_pass, &vertices, indices.as_deref(), material, joint_count);
+          handle.resolve(mesh.with_layers(layers));
+        }
+      }
+    }
+  }
+}
@@ -1,10 +1,214 @@
-use std::fmt::Debug;
+use std::{f32::consts::TAU, fmt::Debug, ops::BitOr};
 
 use egui::FullOutput;
+use foxy_utils::geometry::{Aabb, Frustum, Obb, Sphere};
+use glam::{Mat4, Vec3};
+
+use super::{camera::Camera, mesh::Mesh, proxy::ResourceHandle, render_pass::skinning::SkinnedMesh};
 
 #[derive(Default)]
 pub struct RenderData {
-  pub full_output: FullOutput
+  pub full_output: FullOutput,
+  /// The meshes to draw, in draw order. Draws whose mesh is still being created are skipped, and the built-in quad is
+  /// drawn when there are no draws at all.
+  pub draws: Vec<Draw>,
+  /// The skinned meshes to draw, which are skinned with their pose at the start of the frame and drawn after `draws`.
+  /// They're numbered after `draws` wherever draw order matters, e.g. for `material_overrides`.
+  pub skinned_draws: Vec<SkinnedDraw>,
+  /// Overrides for each draw of the frame, in draw order. Draws without an entry use their material as is.
+  pub material_overrides: Vec<MaterialOverrides>,
+  /// Indices of the draws to outline, in draw order.
+  pub highlighted_draws: Vec<usize>,
+  /// Replaces the camera the scene is rendered with. The previous camera is kept when `None`.
+  pub camera: Option<Camera>,
+  /// Replaces the output adjustments for the user's display. The previous ones are kept when `None`.
+  pub display_calibration: Option<DisplayCalibration>,
+  /// Replaces the color filter for color-blind players. The previous filter is kept when `None`.
+  pub color_filter: Option<ColorFilter>,
+  /// Replaces the scene with a test pattern for tuning the [`DisplayCalibration`].
+  pub show_calibration_pattern: bool,
+  /// Replaces the render scale, see [`Renderer::set_render_scale`](super::Renderer::set_render_scale). The previous
+  /// scale is kept when `None`.
+  pub render_scale: Option<f32>,
+  /// Custom passes not to run this frame, by [`Pass::name`](super::render_pass::Pass::name), e.g. post effects turned
+  /// off to save GPU time.
+  pub disabled_passes: Vec<&'static str>,
+  /// Text placed in the scene, e.g. labels and damage numbers.
+  pub world_text: Vec<WorldText>,
+  /// Lines drawn over the scene, e.g. gizmos and debug visualizations.
+  pub debug_lines: Vec<DebugLine>,
+}
+
+/// A mesh drawn once for each of its instance transforms.
+#[derive(Clone)]
+pub struct Draw {
+  pub mesh: ResourceHandle<Mesh>,
+  pub instances: Vec<Mat4>,
+}
+
+impl Draw {
+  pub fn new(mesh: ResourceHandle<Mesh>, transform: Mat4) -> Self {
+    Self::instanced(mesh, vec![transform])
+  }
+
+  /// Draws `mesh` once per transform in a single draw call, e.g. for foliage or crowds.
+  pub fn instanced(mesh: ResourceHandle<Mesh>, instances: Vec<Mat4>) -> Self {
+    Self { mesh, instances }
+  }
+}
+
+/// A skinned mesh drawn in a pose once for each of its instance transforms.
+#[derive(Clone)]
+pub struct SkinnedDraw {
+  pub mesh: ResourceHandle<SkinnedMesh>,
+  /// The joint matrices of the pose, see [`SkinnedMesh::set_joint_matrices`]. A mesh only holds one pose per frame,
+  /// so drawing it in several poses needs a mesh for each.
+  pub joints: Vec<Mat4>,
+  pub instances: Vec<Mat4>,
+}
+
+impl SkinnedDraw {
+  pub fn new(mesh: ResourceHandle<SkinnedMesh>, joints: Vec<Mat4>, transform: Mat4) -> Self {
+    Self::instanced(mesh, joints, vec![transform])
+  }
+
+  /// Draws `mesh` once per transform in a single draw call, every instance in the same pose.
+  pub fn instanced(mesh: ResourceHandle<SkinnedMesh>, joints: Vec<Mat4>, instances: Vec<Mat4>) -> Self {
+    Self {
+      mesh,
+      joints,
+      instances,
+    }
+  }
+}
+
+/// Material parameters that can be changed per draw without creating a new material, e.g. to animate them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MaterialOverrides {
+  /// Multiplied with the material's color.
+  pub tint: [f32; 4],
+  /// Scales how much the material's color is brightened past its lit value.
+  pub emissive_intensity: f32,
+  /// Added to the texture coordinates of every vertex.
+  pub uv_offset: [f32; 2],
+}
+
+impl Default for MaterialOverrides {
+  fn default() -> Self {
+    Self {
+      tint: [1., 1., 1., 1.],
+      emissive_intensity: 0.,
+      uv_offset: [0., 0.],
+    }
+  }
+}
+
+impl MaterialOverrides {
+  pub fn with_tint(mut self, tint: [f32; 4]) -> Self {
+    self.tint = tint;
+    self
+  }
+
+  pub fn with_emissive_intensity(mut self, emissive_intensity: f32) -> Self {
+    self.emissive_intensity = emissive_intensity;
+    self
+  }
+
+  pub fn with_uv_offset(mut self, uv_offset: [f32; 2]) -> Self {
+    self.uv_offset = uv_offset;
+    self
+  }
+}
+
+/// Text drawn in the scene with signed distance field glyphs, so it stays crisp when scaled or rotated.
+///
+/// The text lies in the XY plane of `transform`, reading along +X with lines stacked down -Y.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldText {
+  pub text: String,
+  pub transform: Mat4,
+  /// Line height in world units.
+  pub size: f32,
+  pub color: [f32; 4],
+  /// Centers each line on the origin instead of starting it there.
+  pub centered: bool,
+}
+
+impl WorldText {
+  pub fn new(text: impl Into<String>, transform: Mat4) -> Self {
+    Self {
+      text: text.into(),
+      transform,
+      size: 1.0,
+      color: [1., 1., 1., 1.],
+      centered: false,
+    }
+  }
+
+  pub fn with_size(mut self, size: f32) -> Self {
+    self.size = size;
+    self
+  }
+
+  pub fn with_color(mut self, color: [f32; 4]) -> Self {
+    self.color = color;
+    self
+  }
+
+  pub fn with_centered(mut self, centered: bool) -> Self {
+    self.centered = centered;
+    self
+  }
+}
+
+/// A line segment in world space, drawn on top of everything else in the scene.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DebugLine {
+  pub start: Vec3,
+  pub end: Vec3,
+  pub color: [f32; 4],
+}
+
+impl DebugLine {
+  pub fn new(start: Vec3, end: Vec3, color: [f32; 4]) -> Self {
+    Self { start, end, color }
+  }
+
+  /// The edges of `aabb`.
+  pub fn aabb(aabb: &Aabb, color: [f32; 4]) -> [Self; 12] {
+    aabb.edges().map(|(start, end)| Self::new(start, end, color))
+  }
+
+  /// The edges of `obb`.
+  pub fn obb(obb: &Obb, color: [f32; 4]) -> [Self; 12] {
+    obb.edges().map(|(start, end)| Self::new(start, end, color))
+  }
+
+  /// The edges of `frustum`, or nothing for infinite ones.
+  pub fn frustum(frustum: &Frustum, color: [f32; 4]) -> Vec<Self> {
+    frustum
+      .edges()
+      .into_iter()
+      .flatten()
+      .map(|(start, end)| Self::new(start, end, color))
+      .collect()
+  }
+
+  /// Circles around `sphere` in its XY, YZ and XZ planes.
+  pub fn sphere(sphere: &Sphere, color: [f32; 4]) -> Vec<Self> {
+    const SEGMENTS: usize = 32;
+
+    [(Vec3::X, Vec3::Y), (Vec3::Y, Vec3::Z), (Vec3::Z, Vec3::X)]
+      .into_iter()
+      .flat_map(|(u, v)| {
+        let point = move |i: usize| {
+          let angle = i as f32 / SEGMENTS as f32 * TAU;
+          sphere.center + (u * angle.cos() + v * angle.sin()) * sphere.radius
+        };
+        (0..SEGMENTS).map(move |i| Self::new(point(i), point(i + 1), color))
+      })
+      .collect()
+  }
 }
 
 impl Debug for RenderData {
@@ -13,6 +217,128 @@ impl Debug for RenderData {
   }
 }
 
+/// Adjustments applied to the final image to suit the user's display, typically set from a calibration screen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DisplayCalibration {
+  /// Values above 1 brighten mid-tones, values below 1 darken them.
+  pub gamma: f32,
+  /// Added to every channel.
+  pub brightness: f32,
+  /// Scales the distance of every channel from middle gray.
+  pub contrast: f32,
+}
+
+impl Default for DisplayCalibration {
+  fn default() -> Self {
+    Self {
+      gamma: 1.0,
+      brightness: 0.0,
+      contrast: 1.0,
+    }
+  }
+}
+
+impl DisplayCalibration {
+  pub fn with_gamma(mut self, gamma: f32) -> Self {
+    self.gamma = gamma;
+    self
+  }
+
+  pub fn with_brightness(mut self, brightness: f32) -> Self {
+    self.brightness = brightness;
+    self
+  }
+
+  pub fn with_contrast(mut self, contrast: f32) -> Self {
+    self.contrast = contrast;
+    self
+  }
+}
+
+/// A kind of dichromatic color blindness.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ColorBlindness {
+  /// Missing red cones.
+  Protanopia,
+  /// Missing green cones, the most common kind.
+  Deuteranopia,
+  /// Missing blue cones.
+  Tritanopia,
+}
+
+/// A filter applied to the final image for color-blind players.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ColorFilter {
+  #[default]
+  None,
+  /// Shows the image as someone with the given color blindness sees it, for checking that nothing relies on colors
+  /// they can't tell apart.
+  Simulate(ColorBlindness),
+  /// Shifts the colors someone with the given color blindness can't tell apart towards ones they can.
+  Correct(ColorBlindness),
+}
+
 pub trait Drawable {
   fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>);
+
+  fn layers(&self) -> RenderLayers {
+    RenderLayers::default()
+  }
+}
+
+/// A set of up to 32 render layers. Passes only draw the drawables that share at least one layer with their mask,
+/// e.g. to keep UI, first-person or minimap geometry out of passes that shouldn't see it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct RenderLayers(u32);
+
+impl Default for RenderLayers {
+  /// Only layer 0.
+  fn default() -> Self {
+    Self::layer(0)
+  }
+}
+
+impl BitOr for RenderLayers {
+  type Output = Self;
+
+  fn bitor(self, rhs: Self) -> Self::Output {
+    Self(self.0 | rhs.0)
+  }
+}
+
+impl RenderLayers {
+  pub const ALL: Self = Self(u32::MAX);
+  pub const COUNT: u32 = u32::BITS;
+  pub const NONE: Self = Self(0);
+
+  /// A set containing only `layer`, which must be below [`RenderLayers::COUNT`].
+  pub const fn layer(layer: u32) -> Self {
+    Self::NONE.with(layer)
+  }
+
+  pub const fn from_bits(bits: u32) -> Self {
+    Self(bits)
+  }
+
+  pub const fn bits(self) -> u32 {
+    self.0
+  }
+
+  pub const fn with(self, layer: u32) -> Self {
+    assert!(layer < Self::COUNT, "render layer out of range");
+    Self(self.0 | 1 << layer)
+  }
+
+  pub const fn without(self, layer: u32) -> Self {
+    assert!(layer < Self::COUNT, "render layer out of range");
+    Self(self.0 & !(1 << layer))
+  }
+
+  pub const fn contains(self, layer: u32) -> bool {
+    layer < Self::COUNT && self.0 & 1 << layer != 0
+  }
+
+  pub const fn intersects(self, other: Self) -> bool {
+    self.0 & other.0 != 0
+  }
 }
\ No newline at end of file
@@ -1,20 +1,278 @@
-use wgpu::{CommandEncoder, PrimitiveTopology};
+use std::{
+  fmt,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
 
-use super::{mesh::Mesh, target::RenderTarget};
+use foxy_utils::tracking::Tracked;
+use wgpu::{Color, CommandEncoder, PrimitiveTopology};
+
+use super::{
+  mesh::MeshDraw,
+  shader::{Fragment, Shader, ShaderStore, Vertex},
+  target::RenderTarget,
+  tracking::TrackedDevice,
+};
 use crate::error::RendererError;
 
+pub mod debug_draw;
+pub mod hi_z;
+pub mod outline;
+pub mod sdf_text;
 pub mod simple;
+pub mod skinning;
 pub mod tonemap;
 
 pub trait Pass {
+  /// What the pass is called in the [`FrameGraph`](super::frame_graph::FrameGraph).
+  fn name(&self) -> &'static str {
+    "Custom Pass"
+  }
+
+  /// Records the pass. `output` is the view the pass writes its final result to, which is either the render
+  /// target's own view or the frame being presented. `draws` are the meshes submitted for the frame.
   fn draw(
     &mut self,
     command_encoder: &mut CommandEncoder,
-    render_target: &wgpu::TextureView,
-    mesh: &Mesh,
+    render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    draws: &[MeshDraw],
   ) -> Result<(), RendererError>;
 
   fn resize(&mut self, device: &wgpu::Device, render_target: &RenderTarget);
+
+  /// Rebuilds the pipelines that use any of the `reloaded` shaders, which were hot reloaded from disk, e.g. with
+  /// [`AsyncPipeline::rebuild`](super::pipeline::AsyncPipeline::rebuild) so the frame doesn't wait on them. Pipelines
+  /// that fail to build are kept as they were.
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError>;
+}
+
+/// Where a custom pass runs in the frame, relative to the built-in passes. Passes with the same order run in the order
+/// they were added.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum PassOrder {
+  /// After the meshes are drawn and before world text, outlines and debug lines. Draws into the HDR render target.
+  AfterOpaque,
+  /// After everything in the scene and before tone mapping, e.g. for post-processing. Draws into the HDR render
+  /// target.
+  BeforeToneMap,
+  /// After tone mapping and before the UI, e.g. for overlays. Draws onto the frame being presented.
+  AfterToneMap,
+}
+
+type CreatePass =
+  dyn Fn(&wgpu::Device, &mut ShaderStore, &RenderTarget) -> Result<Box<dyn Pass>, RendererError> + Send + Sync;
+
+/// A pass of the application's, created along with the renderer since passes need its device to build their
+/// pipelines.
+#[derive(Clone)]
+pub struct CustomPass {
+  pub(crate) order: PassOrder,
+  pub(crate) create: Arc<CreatePass>,
+}
+
+impl CustomPass {
+  pub fn new(
+    order: PassOrder,
+    create: impl Fn(&wgpu::Device, &mut ShaderStore, &RenderTarget) -> Result<Box<dyn Pass>, RendererError>
+      + Send
+      + Sync
+      + 'static,
+  ) -> Self {
+    Self {
+      order,
+      create: Arc::new(create),
+    }
+  }
+}
+
+impl fmt::Debug for CustomPass {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("CustomPass")
+      .field("order", &self.order)
+      .finish_non_exhaustive()
+  }
+}
+
+/// Describes the attachments of a render pass so passes don't have to build raw `wgpu::RenderPassDescriptor`s.
+///
+/// The pass returned by [`RenderingInfo::begin_rendering`] ends when it is dropped.
+#[derive(Default)]
+pub struct RenderingInfo<'a> {
+  label: Option<&'a str>,
+  color_attachments: Vec<Option<wgpu::RenderPassColorAttachment<'a>>>,
+  depth_attachment: Option<wgpu::RenderPassDepthStencilAttachment<'a>>,
+}
+
+impl<'a> RenderingInfo<'a> {
+  pub fn new(label: &'a str) -> Self {
+    Self {
+      label: Some(label),
+      ..Default::default()
+    }
+  }
+
+  pub fn with_color_attachment(mut self, view: &'a wgpu::TextureView, ops: wgpu::Operations<Color>) -> Self {
+    self.color_attachments.push(Some(wgpu::RenderPassColorAttachment {
+      view,
+      resolve_target: None,
+      ops,
+    }));
+    self
+  }
+
+  /// Clears the attachment to `color` before drawing and stores the result.
+  pub fn with_cleared_color(self, view: &'a wgpu::TextureView, color: Color) -> Self {
+    self.with_color_attachment(view, wgpu::Operations {
+      load: wgpu::LoadOp::Clear(color),
+      store: wgpu::StoreOp::Store,
+    })
+  }
+
+  /// Draws on top of the existing contents of the attachment and stores the result.
+  pub fn with_loaded_color(self, view: &'a wgpu::TextureView) -> Self {
+    self.with_color_attachment(view, wgpu::Operations {
+      load: wgpu::LoadOp::Load,
+      store: wgpu::StoreOp::Store,
+    })
+  }
+
+  pub fn with_depth_attachment(mut self, view: &'a wgpu::TextureView, depth_ops: wgpu::Operations<f32>) -> Self {
+    self.depth_attachment = Some(wgpu::RenderPassDepthStencilAttachment {
+      view,
+      depth_ops: Some(depth_ops),
+      stencil_ops: None,
+    });
+    self
+  }
+
+  /// Clears the depth attachment to `depth` before drawing and stores the result.
+  pub fn with_cleared_depth(self, view: &'a wgpu::TextureView, depth: f32) -> Self {
+    self.with_depth_attachment(view, wgpu::Operations {
+      load: wgpu::LoadOp::Clear(depth),
+      store: wgpu::StoreOp::Store,
+    })
+  }
+
+  /// Tests against the existing contents of the depth attachment and stores the result.
+  pub fn with_loaded_depth(self, view: &'a wgpu::TextureView) -> Self {
+    self.with_depth_attachment(view, wgpu::Operations {
+      load: wgpu::LoadOp::Load,
+      store: wgpu::StoreOp::Store,
+    })
+  }
+
+  /// Sets the stencil operations of the depth attachment, which must have been added before and have a stencil
+  /// aspect. Without them the stencil aspect is read-only.
+  pub fn with_stencil_ops(mut self, stencil_ops: wgpu::Operations<u32>) -> Self {
+    if let Some(depth_attachment) = &mut self.depth_attachment {
+      depth_attachment.stencil_ops = Some(stencil_ops);
+    }
+    self
+  }
+
+  /// Clears the stencil aspect to `stencil` before drawing and stores the result.
+  pub fn with_cleared_stencil(self, stencil: u32) -> Self {
+    self.with_stencil_ops(wgpu::Operations {
+      load: wgpu::LoadOp::Clear(stencil),
+      store: wgpu::StoreOp::Store,
+    })
+  }
+
+  /// Tests against and writes to the existing contents of the stencil aspect.
+  pub fn with_loaded_stencil(self) -> Self {
+    self.with_stencil_ops(wgpu::Operations {
+      load: wgpu::LoadOp::Load,
+      store: wgpu::StoreOp::Store,
+    })
+  }
+
+  pub fn begin_rendering<'e>(&self, command_encoder: &'e mut CommandEncoder) -> wgpu::RenderPass<'e>
+  where
+    'a: 'e,
+  {
+    command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+      label: self.label,
+      color_attachments: &self.color_attachments,
+      depth_stencil_attachment: self.depth_attachment.clone(),
+      occlusion_query_set: None,
+      timestamp_writes: None,
+    })
+  }
+}
+
+/// Depth state for pipelines that test against and write their own depth in the render target's depth buffer.
+pub fn depth_write_state(render_target: &RenderTarget) -> wgpu::DepthStencilState {
+  wgpu::DepthStencilState {
+    format: RenderTarget::DEPTH_FORMAT,
+    depth_write_enabled: true,
+    depth_compare: render_target.depth_compare(),
+    stencil: wgpu::StencilState::default(),
+    bias: wgpu::DepthBiasState::default(),
+  }
+}
+
+/// Depth state for pipelines drawn after a depth prepass. Depth is already final, so only the fragments that
+/// survived the prepass are shaded.
+pub fn depth_prepassed_state(render_target: &RenderTarget) -> wgpu::DepthStencilState {
+  wgpu::DepthStencilState {
+    depth_write_enabled: false,
+    depth_compare: if render_target.is_reverse_z() {
+      wgpu::CompareFunction::GreaterEqual
+    } else {
+      wgpu::CompareFunction::LessEqual
+    },
+    ..depth_write_state(render_target)
+  }
+}
+
+/// Stencil state that writes the pass's stencil reference wherever a fragment passes the depth test, e.g. to mark
+/// the pixels covered by a portal, an outlined object or a UI clip region.
+pub fn stencil_write_state() -> wgpu::StencilState {
+  let face = wgpu::StencilFaceState {
+    compare: wgpu::CompareFunction::Always,
+    fail_op: wgpu::StencilOperation::Keep,
+    depth_fail_op: wgpu::StencilOperation::Keep,
+    pass_op: wgpu::StencilOperation::Replace,
+  };
+
+  wgpu::StencilState {
+    front: face,
+    back: face,
+    read_mask: !0,
+    write_mask: !0,
+  }
+}
+
+/// Stencil state that only draws fragments where `compare(reference, stencil)` holds, leaving the stencil untouched.
+/// `Equal` restricts drawing to a marked region and `NotEqual` to everything outside of it.
+pub fn stencil_test_state(compare: wgpu::CompareFunction) -> wgpu::StencilState {
+  let face = wgpu::StencilFaceState {
+    compare,
+    fail_op: wgpu::StencilOperation::Keep,
+    depth_fail_op: wgpu::StencilOperation::Keep,
+    pass_op: wgpu::StencilOperation::Keep,
+  };
+
+  wgpu::StencilState {
+    front: face,
+    back: face,
+    read_mask: !0,
+    write_mask: 0,
+  }
+}
+
+/// The shaders a render pipeline runs, which may come from different files.
+#[derive(Clone, Copy)]
+pub struct ShaderStages<'a> {
+  pub vertex: &'a Shader<Vertex>,
+  pub fragment: &'a Shader<Fragment>,
 }
 
 pub fn create_render_pipeline(
@@ -22,23 +280,24 @@ pub fn create_render_pipeline(
   device: &wgpu::Device,
   layout: &wgpu::PipelineLayout,
   color_format: wgpu::TextureFormat,
-  depth_format: Option<wgpu::TextureFormat>,
+  depth_stencil: Option<wgpu::DepthStencilState>,
   vertex_layouts: &[wgpu::VertexBufferLayout],
-  shader: wgpu::ShaderModuleDescriptor,
-) -> wgpu::RenderPipeline {
-  let shader = device.create_shader_module(shader);
+  shaders: ShaderStages,
+) -> Tracked<wgpu::RenderPipeline> {
+  let vertex_module = shaders.vertex.module();
+  let fragment_module = shaders.fragment.module();
 
-  device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+  device.create_tracked_render_pipeline(&wgpu::RenderPipelineDescriptor {
     label,
     layout: Some(layout),
     vertex: wgpu::VertexState {
-      module: &shader,
-      entry_point: "vs_main",
+      module: &vertex_module,
+      entry_point: shaders.vertex.entry_point("vs_main"),
       buffers: vertex_layouts,
     },
     fragment: Some(wgpu::FragmentState {
-      module: &shader,
-      entry_point: "fs_main",
+      module: &fragment_module,
+      entry_point: shaders.fragment.entry_point("fs_main"),
       targets: &[Some(wgpu::ColorTargetState {
         format: color_format,
         blend: Some(wgpu::BlendState {
@@ -48,25 +307,8 @@ pub fn create_render_pipeline(
         write_mask: wgpu::ColorWrites::ALL,
       })],
     }),
-    primitive: wgpu::PrimitiveState {
-      topology: PrimitiveTopology::TriangleList,
-      strip_index_format: None,
-      front_face: wgpu::FrontFace::Ccw,
-      cull_mode: Some(wgpu::Face::Back),
-      // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-      polygon_mode: wgpu::PolygonMode::Fill,
-      // Requires Features::DEPTH_CLIP_CONTROL
-      unclipped_depth: false,
-      // Requires Features::CONSERVATIVE_RASTERIZATION
-      conservative: false,
-    },
-    depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
-      format,
-      depth_write_enabled: true,
-      depth_compare: wgpu::CompareFunction::Less,
-      stencil: wgpu::StencilState::default(),
-      bias: wgpu::DepthBiasState::default(),
-    }),
+    primitive: primitive_state(),
+    depth_stencil,
     multisample: wgpu::MultisampleState {
       count: 1,
       mask: !0,
@@ -77,3 +319,79 @@ pub fn create_render_pipeline(
     multiview: None,
   })
 }
+
+/// Creates the depth-only counterpart of a pipeline made with [`create_render_pipeline`], for depth prepasses and
+/// stencil masks. The fragment stage is stripped, so the layout only needs the bind groups used by the vertex shader.
+pub fn create_depth_only_pipeline(
+  label: Option<&str>,
+  device: &wgpu::Device,
+  layout: &wgpu::PipelineLayout,
+  depth_stencil: wgpu::DepthStencilState,
+  vertex_layouts: &[wgpu::VertexBufferLayout],
+  vertex_shader: &Shader<Vertex>,
+) -> Tracked<wgpu::RenderPipeline> {
+  let vertex_module = vertex_shader.module();
+
+  device.create_tracked_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label,
+    layout: Some(layout),
+    vertex: wgpu::VertexState {
+      module: &vertex_module,
+      entry_point: vertex_shader.entry_point("vs_main"),
+      buffers: vertex_layouts,
+    },
+    fragment: None,
+    primitive: primitive_state(),
+    depth_stencil: Some(depth_stencil),
+    multisample: wgpu::MultisampleState {
+      count: 1,
+      mask: !0,
+      alpha_to_coverage_enabled: false,
+    },
+    multiview: None,
+  })
+}
+
+fn is_reloaded(reloaded: &[PathBuf], shader: &str) -> bool {
+  reloaded.iter().any(|path| path == Path::new(shader))
+}
+
+/// Uploads `contents` to the start of `buffer` for per-frame geometry. The buffer is only reallocated when it has to
+/// grow.
+pub(crate) fn write_buffer(
+  device: &wgpu::Device,
+  queue: &wgpu::Queue,
+  buffer: &mut Option<Tracked<wgpu::Buffer>>,
+  label: &str,
+  usage: wgpu::BufferUsages,
+  contents: &[u8],
+) {
+  let size = contents.len() as u64;
+  if buffer.as_ref().is_none_or(|buffer| buffer.size() < size) {
+    *buffer = Some(device.create_tracked_buffer(&wgpu::BufferDescriptor {
+      label: Some(label),
+      size: size.next_power_of_two(),
+      usage: usage | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    }));
+  }
+
+  if let Some(buffer) = buffer {
+    queue.write_buffer(buffer, 0, contents);
+  }
+}
+
+fn primitive_state() -> wgpu::PrimitiveState {
+  wgpu::PrimitiveState {
+    topology: PrimitiveTopology::TriangleList,
+    strip_index_format: None,
+    front_face: wgpu::FrontFace::Ccw,
+    cull_mode: Some(wgpu::Face::Back),
+    // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
+    polygon_mode: wgpu::PolygonMode::Fill,
+    // Requires Features::DEPTH_CLIP_CONTROL
+    unclipped_depth: false,
+    // Requires Features::CONSERVATIVE_RASTERIZATION
+    conservative: false,
+  }
+}
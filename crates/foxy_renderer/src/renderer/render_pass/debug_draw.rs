@@ -0,0 +1,181 @@
+use std::{path::PathBuf, sync::Arc};
+
+use foxy_utils::tracking::Tracked;
+use wgpu::{CommandEncoder, PrimitiveTopology};
+
+use super::{is_reloaded, primitive_state, write_buffer, Pass, RenderingInfo};
+use crate::{
+  error::RendererError,
+  renderer::{
+    camera::{Camera, CameraBuffer},
+    mesh::MeshDraw,
+    pipeline::AsyncPipeline,
+    render_data::DebugLine,
+    shader::{Fragment, Shader, ShaderStore, Vertex as VertexStage},
+    target::RenderTarget,
+    tracking::TrackedDevice,
+    vertex::Vertex,
+  },
+};
+
+/// Draws [`DebugLine`]s, e.g. gizmos and debug visualizations.
+///
+/// Lines ignore depth so that they stay visible through whatever covers them.
+pub struct DebugDrawPass {
+  pipeline: AsyncPipeline,
+  pipeline_layout: Arc<wgpu::PipelineLayout>,
+  camera: CameraBuffer,
+  vertex_buffer: Option<Tracked<wgpu::Buffer>>,
+  vertex_count: u32,
+}
+
+impl DebugDrawPass {
+  const SHADER: &'static str = "debug_draw.wgsl";
+
+  pub fn new(device: &wgpu::Device, shader_store: &mut ShaderStore) -> Result<Self, RendererError> {
+    let camera = CameraBuffer::new(device);
+
+    let pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("Debug Draw Pipeline Layout"),
+      bind_group_layouts: &[camera.bind_group_layout()],
+      push_constant_ranges: &[],
+    }));
+    let pipeline = AsyncPipeline::ready(Self::create_pipeline(
+      device,
+      &pipeline_layout,
+      &shader_store.vertex(device, Self::SHADER)?,
+      &shader_store.fragment(device, Self::SHADER)?,
+    ));
+
+    Ok(Self {
+      pipeline,
+      pipeline_layout,
+      camera,
+      vertex_buffer: None,
+      vertex_count: 0,
+    })
+  }
+
+  pub fn set_camera(&mut self, queue: &wgpu::Queue, camera: &Camera, render_target: &RenderTarget) {
+    self.camera.update(queue, camera, render_target);
+  }
+
+  /// Sets the lines to draw this frame.
+  pub fn set_lines(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, lines: &[DebugLine]) {
+    let vertices: Vec<Vertex> = lines
+      .iter()
+      .flat_map(|line| {
+        [line.start, line.end].map(|position| Vertex {
+          position: position.to_array(),
+          color: line.color,
+          ..Default::default()
+        })
+      })
+      .collect();
+
+    self.vertex_count = vertices.len() as u32;
+    if vertices.is_empty() {
+      return;
+    }
+
+    write_buffer(
+      device,
+      queue,
+      &mut self.vertex_buffer,
+      "Debug Draw Vertex Buffer",
+      wgpu::BufferUsages::VERTEX,
+      bytemuck::cast_slice(&vertices),
+    );
+  }
+}
+
+impl DebugDrawPass {
+  fn create_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    vertex_shader: &Shader<VertexStage>,
+    fragment_shader: &Shader<Fragment>,
+  ) -> Tracked<wgpu::RenderPipeline> {
+    let vertex_module = vertex_shader.module();
+    let fragment_module = fragment_shader.module();
+    device.create_tracked_render_pipeline(&wgpu::RenderPipelineDescriptor {
+      label: Some("Debug Draw Pipeline"),
+      layout: Some(pipeline_layout),
+      vertex: wgpu::VertexState {
+        module: &vertex_module,
+        entry_point: vertex_shader.entry_point("vs_main"),
+        buffers: &[Vertex::desc()],
+      },
+      fragment: Some(wgpu::FragmentState {
+        module: &fragment_module,
+        entry_point: fragment_shader.entry_point("fs_main"),
+        targets: &[Some(wgpu::ColorTargetState {
+          format: RenderTarget::RENDER_TARGET_FORMAT,
+          blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+          write_mask: wgpu::ColorWrites::ALL,
+        })],
+      }),
+      primitive: wgpu::PrimitiveState {
+        topology: PrimitiveTopology::LineList,
+        cull_mode: None,
+        ..primitive_state()
+      },
+      depth_stencil: None,
+      multisample: wgpu::MultisampleState {
+        count: 1,
+        mask: !0,
+        alpha_to_coverage_enabled: false,
+      },
+      multiview: None,
+    })
+  }
+}
+
+impl Pass for DebugDrawPass {
+  fn draw(
+    &mut self,
+    command_encoder: &mut CommandEncoder,
+    _render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    _draws: &[MeshDraw],
+  ) -> Result<(), RendererError> {
+    let Some(vertex_buffer) = &self.vertex_buffer else {
+      return Ok(());
+    };
+    if self.vertex_count == 0 {
+      return Ok(());
+    }
+
+    let mut render_pass = RenderingInfo::new("Debug Draw Pass")
+      .with_loaded_color(output)
+      .begin_rendering(command_encoder);
+
+    render_pass.set_pipeline(self.pipeline.get());
+    render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+    render_pass.draw(0..self.vertex_count, 0..1);
+
+    Ok(())
+  }
+
+  fn resize(&mut self, _device: &wgpu::Device, _render_target: &RenderTarget) {}
+
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    _render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    let vertex_shader = shader_store.vertex(device, Self::SHADER)?;
+    let fragment_shader = shader_store.fragment(device, Self::SHADER)?;
+    let layout = self.pipeline_layout.clone();
+    self.pipeline.rebuild("Debug Draw Pipeline", device, move |device| {
+      Self::create_pipeline(device, &layout, &vertex_shader, &fragment_shader)
+    })
+  }
+}
@@ -0,0 +1,259 @@
+use std::{path::PathBuf, sync::Arc};
+
+use foxy_utils::tracking::Tracked;
+use wgpu::CommandEncoder;
+
+use super::{is_reloaded, Pass};
+use crate::{
+  error::RendererError,
+  renderer::{
+    mesh::MeshDraw,
+    pipeline::AsyncPipeline,
+    shader::{Compute, Shader, ShaderStore},
+    target::RenderTarget,
+    tracking::TrackedDevice,
+  },
+};
+
+/// Builds a min/max depth pyramid from the render target's depth buffer, for use by occlusion culling and other
+/// screen-space effects. Mip 0 matches the depth buffer and each following mip halves it.
+pub struct HiZPass {
+  /// The copy pipeline, followed by the downsample pipeline.
+  pipelines: AsyncPipeline<(Tracked<wgpu::ComputePipeline>, Tracked<wgpu::ComputePipeline>)>,
+  copy_layout: Arc<wgpu::BindGroupLayout>,
+  downsample_layout: Arc<wgpu::BindGroupLayout>,
+  pyramid: Tracked<wgpu::Texture>,
+  pyramid_view: wgpu::TextureView,
+  bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl HiZPass {
+  pub const PYRAMID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+  const SHADER: &'static str = "hi_z.wgsl";
+  const WORKGROUP_SIZE: u32 = 8;
+
+  pub fn new(
+    device: &wgpu::Device,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+  ) -> Result<Self, RendererError> {
+    let storage_entry = wgpu::BindGroupLayoutEntry {
+      binding: 2,
+      visibility: wgpu::ShaderStages::COMPUTE,
+      ty: wgpu::BindingType::StorageTexture {
+        access: wgpu::StorageTextureAccess::WriteOnly,
+        format: Self::PYRAMID_FORMAT,
+        view_dimension: wgpu::TextureViewDimension::D2,
+      },
+      count: None,
+    };
+
+    let copy_layout = Arc::new(device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Hi-Z Copy Layout"),
+      entries: &[
+        wgpu::BindGroupLayoutEntry {
+          binding: 0,
+          visibility: wgpu::ShaderStages::COMPUTE,
+          ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Depth,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+          },
+          count: None,
+        },
+        storage_entry,
+      ],
+    }));
+
+    let downsample_layout = Arc::new(device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Hi-Z Downsample Layout"),
+      entries: &[
+        wgpu::BindGroupLayoutEntry {
+          binding: 1,
+          visibility: wgpu::ShaderStages::COMPUTE,
+          ty: wgpu::BindingType::Texture {
+            // 32-bit float formats can't be filtered, but the shader only loads texels
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+          },
+          count: None,
+        },
+        storage_entry,
+      ],
+    }));
+
+    let shader = shader_store.compute(device, Self::SHADER)?;
+    let pipelines = AsyncPipeline::ready(Self::create_pipelines(device, &copy_layout, &downsample_layout, &shader));
+    let (pyramid, pyramid_view, bind_groups) =
+      Self::create_pyramid(device, render_target, &copy_layout, &downsample_layout);
+
+    Ok(Self {
+      pipelines,
+      copy_layout,
+      downsample_layout,
+      pyramid,
+      pyramid_view,
+      bind_groups,
+    })
+  }
+
+  /// A view over every mip of the pyramid. Texels hold the smallest depth in `r` and the largest in `g`, so `r` is
+  /// the nearest unless the render target uses reverse-Z.
+  pub fn pyramid_view(&self) -> &wgpu::TextureView {
+    &self.pyramid_view
+  }
+
+  pub fn mip_count(&self) -> u32 {
+    self.pyramid.mip_level_count()
+  }
+}
+
+impl Pass for HiZPass {
+  fn draw(
+    &mut self,
+    command_encoder: &mut CommandEncoder,
+    _render_target: &RenderTarget,
+    _output: &wgpu::TextureView,
+    _draws: &[MeshDraw],
+  ) -> Result<(), RendererError> {
+    let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+      label: Some("Hi-Z Pass"),
+      timestamp_writes: None,
+    });
+
+    for (level, bind_group) in self.bind_groups.iter().enumerate() {
+      let width = (self.pyramid.width() >> level).max(1);
+      let height = (self.pyramid.height() >> level).max(1);
+
+      let (copy_pipeline, downsample_pipeline) = self.pipelines.get();
+      compute_pass.set_pipeline(if level == 0 { copy_pipeline } else { downsample_pipeline });
+      compute_pass.set_bind_group(0, bind_group, &[]);
+      compute_pass.dispatch_workgroups(
+        width.div_ceil(Self::WORKGROUP_SIZE),
+        height.div_ceil(Self::WORKGROUP_SIZE),
+        1,
+      );
+    }
+
+    Ok(())
+  }
+
+  fn resize(&mut self, device: &wgpu::Device, render_target: &RenderTarget) {
+    (self.pyramid, self.pyramid_view, self.bind_groups) =
+      Self::create_pyramid(device, render_target, &self.copy_layout, &self.downsample_layout);
+  }
+
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    _render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    let shader = shader_store.compute(device, Self::SHADER)?;
+    let (copy_layout, downsample_layout) = (self.copy_layout.clone(), self.downsample_layout.clone());
+    self.pipelines.rebuild("Hi-Z Pipelines", device, move |device| {
+      Self::create_pipelines(device, &copy_layout, &downsample_layout, &shader)
+    })
+  }
+}
+
+impl HiZPass {
+  fn create_pipelines(
+    device: &wgpu::Device,
+    copy_layout: &wgpu::BindGroupLayout,
+    downsample_layout: &wgpu::BindGroupLayout,
+    shader: &Shader<Compute>,
+  ) -> (Tracked<wgpu::ComputePipeline>, Tracked<wgpu::ComputePipeline>) {
+    let module = shader.module();
+    let create_pipeline = |label, layout, entry_point| {
+      device.create_tracked_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some(label),
+        layout: Some(&device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+          label: Some(label),
+          bind_group_layouts: &[layout],
+          push_constant_ranges: &[],
+        })),
+        module: &module,
+        entry_point: shader.entry_point(entry_point),
+      })
+    };
+
+    let copy_pipeline = create_pipeline("Hi-Z Copy Pipeline", copy_layout, "cs_copy_depth");
+    let downsample_pipeline = create_pipeline("Hi-Z Downsample Pipeline", downsample_layout, "cs_downsample");
+
+    (copy_pipeline, downsample_pipeline)
+  }
+
+  fn create_pyramid(
+    device: &wgpu::Device,
+    render_target: &RenderTarget,
+    copy_layout: &wgpu::BindGroupLayout,
+    downsample_layout: &wgpu::BindGroupLayout,
+  ) -> (Tracked<wgpu::Texture>, wgpu::TextureView, Vec<wgpu::BindGroup>) {
+    let width = render_target.width();
+    let height = render_target.height();
+    let mip_level_count = width.max(height).ilog2() + 1;
+
+    let pyramid = device.create_tracked_texture(&wgpu::TextureDescriptor {
+      label: Some("Hi-Z Pyramid"),
+      size: wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+      },
+      mip_level_count,
+      sample_count: 1,
+      dimension: wgpu::TextureDimension::D2,
+      format: Self::PYRAMID_FORMAT,
+      usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+      view_formats: &[],
+    });
+
+    let pyramid_view = pyramid.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mip_views = (0..mip_level_count)
+      .map(|level| {
+        pyramid.create_view(&wgpu::TextureViewDescriptor {
+          label: Some("Hi-Z Mip View"),
+          base_mip_level: level,
+          mip_level_count: Some(1),
+          ..Default::default()
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let bind_groups = mip_views
+      .iter()
+      .enumerate()
+      .map(|(level, view)| {
+        let (layout, source) = match level.checked_sub(1).and_then(|previous| mip_views.get(previous)) {
+          None => (copy_layout, wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(&render_target.depth_only_view),
+          }),
+          Some(previous) => (downsample_layout, wgpu::BindGroupEntry {
+            binding: 1,
+            resource: wgpu::BindingResource::TextureView(previous),
+          }),
+        };
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+          label: Some("Hi-Z Bind Group"),
+          layout,
+          entries: &[source, wgpu::BindGroupEntry {
+            binding: 2,
+            resource: wgpu::BindingResource::TextureView(view),
+          }],
+        })
+      })
+      .collect();
+
+    (pyramid, pyramid_view, bind_groups)
+  }
+}
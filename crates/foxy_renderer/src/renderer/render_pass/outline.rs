@@ -0,0 +1,308 @@
+use std::{mem::size_of, num::NonZeroU64, path::PathBuf, sync::Arc};
+
+use bytemuck::{Pod, Zeroable};
+use foxy_utils::tracking::Tracked;
+use wgpu::CommandEncoder;
+
+use super::{
+  create_depth_only_pipeline,
+  create_render_pipeline,
+  is_reloaded,
+  stencil_test_state,
+  stencil_write_state,
+  Pass,
+  RenderingInfo,
+  ShaderStages,
+};
+use crate::{
+  error::RendererError,
+  renderer::{
+    camera::{Camera, CameraBuffer},
+    mesh::MeshDraw,
+    pipeline::AsyncPipeline,
+    render_data::Drawable,
+    shader::{Fragment, Shader, ShaderStore, Vertex as VertexStage},
+    target::RenderTarget,
+    tracking::TrackedDevice,
+    vertex::{Instance, Vertex},
+  },
+};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct OutlineUniforms {
+  color: [f32; 4],
+  offset: [f32; 2],
+  _padding: [f32; 2],
+}
+
+/// Outlines the highlighted draws, e.g. for selection feedback in editors and gameplay.
+///
+/// Each highlighted mesh is first marked in the stencil buffer, then drawn again shifted in every direction by the
+/// outline thickness wherever the stencil isn't marked. Outlines are drawn on top of everything else.
+pub struct OutlinePass {
+  /// The mark pipeline, followed by the outline pipeline.
+  pipelines: AsyncPipeline<(Tracked<wgpu::RenderPipeline>, Tracked<wgpu::RenderPipeline>)>,
+  pipeline_layout: Arc<wgpu::PipelineLayout>,
+  layout: wgpu::BindGroupLayout,
+  uniform_buffer: Tracked<wgpu::Buffer>,
+  bind_group: wgpu::BindGroup,
+  stride: u32,
+  camera: CameraBuffer,
+  color: [f32; 4],
+  thickness: f32,
+  highlighted: Vec<usize>,
+}
+
+impl OutlinePass {
+  pub const DEFAULT_COLOR: [f32; 4] = [1.0, 0.6, 0.1, 1.0];
+  pub const DEFAULT_THICKNESS: f32 = 2.0;
+  const DIRECTIONS: [[f32; 2]; 8] = [
+    [1.0, 0.0],
+    [-1.0, 0.0],
+    [0.0, 1.0],
+    [0.0, -1.0],
+    [std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2],
+    [std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2],
+    [-std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2],
+    [-std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2],
+  ];
+  const SHADER: &'static str = "outline.wgsl";
+  const STENCIL_REFERENCE: u32 = 1;
+
+  pub fn new(
+    device: &wgpu::Device,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+  ) -> Result<Self, RendererError> {
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Outline Layout"),
+      entries: &[wgpu::BindGroupLayoutEntry {
+        binding: 0,
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+          ty: wgpu::BufferBindingType::Uniform,
+          has_dynamic_offset: true,
+          min_binding_size: NonZeroU64::new(size_of::<OutlineUniforms>() as u64),
+        },
+        count: None,
+      }],
+    });
+
+    let camera = CameraBuffer::new(device);
+
+    let pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("Outline Pipeline Layout"),
+      bind_group_layouts: &[&layout, camera.bind_group_layout()],
+      push_constant_ranges: &[],
+    }));
+
+    let pipelines = AsyncPipeline::ready(Self::create_pipelines(
+      device,
+      &pipeline_layout,
+      &shader_store.vertex(device, Self::SHADER)?,
+      &shader_store.fragment(device, Self::SHADER)?,
+    ));
+
+    let (uniform_buffer, bind_group, stride) = Self::create_uniforms(
+      device,
+      &layout,
+      render_target,
+      Self::DEFAULT_COLOR,
+      Self::DEFAULT_THICKNESS,
+    );
+
+    Ok(Self {
+      pipelines,
+      pipeline_layout,
+      layout,
+      uniform_buffer,
+      bind_group,
+      stride,
+      camera,
+      color: Self::DEFAULT_COLOR,
+      thickness: Self::DEFAULT_THICKNESS,
+      highlighted: Vec::new(),
+    })
+  }
+
+  pub fn set_camera(&mut self, queue: &wgpu::Queue, camera: &Camera, render_target: &RenderTarget) {
+    self.camera.update(queue, camera, render_target);
+  }
+
+  pub fn highlighted(&self) -> &[usize] {
+    &self.highlighted
+  }
+
+  /// Sets which draws of the frame to outline, by their index in draw order.
+  pub fn set_highlighted(&mut self, draws: &[usize]) {
+    self.highlighted.clear();
+    self.highlighted.extend_from_slice(draws);
+  }
+
+  /// Sets the outline color and its thickness in pixels.
+  pub fn set_style(&mut self, device: &wgpu::Device, render_target: &RenderTarget, color: [f32; 4], thickness: f32) {
+    self.color = color;
+    self.thickness = thickness;
+    (self.uniform_buffer, self.bind_group, self.stride) =
+      Self::create_uniforms(device, &self.layout, render_target, color, thickness);
+  }
+}
+
+impl Pass for OutlinePass {
+  fn draw(
+    &mut self,
+    command_encoder: &mut CommandEncoder,
+    render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    draws: &[MeshDraw],
+  ) -> Result<(), RendererError> {
+    let highlighted: Vec<&MeshDraw> = draws
+      .iter()
+      .filter(|draw| self.highlighted.contains(&draw.index))
+      .collect();
+    if highlighted.is_empty() {
+      return Ok(());
+    }
+
+    let mut render_pass = RenderingInfo::new("Outline Pass")
+      .with_loaded_color(output)
+      .with_loaded_depth(&render_target.depth_view)
+      .with_cleared_stencil(0)
+      .begin_rendering(command_encoder);
+
+    render_pass.set_stencil_reference(Self::STENCIL_REFERENCE);
+    render_pass.set_bind_group(1, self.camera.bind_group(), &[]);
+
+    let (mark_pipeline, outline_pipeline) = self.pipelines.get();
+    render_pass.set_pipeline(mark_pipeline);
+    render_pass.set_bind_group(0, &self.bind_group, &[0]);
+    for draw in &highlighted {
+      draw.draw(&mut render_pass);
+    }
+
+    render_pass.set_pipeline(outline_pipeline);
+    for slot in 1..=Self::DIRECTIONS.len() as u32 {
+      render_pass.set_bind_group(0, &self.bind_group, &[slot * self.stride]);
+      for draw in &highlighted {
+        draw.draw(&mut render_pass);
+      }
+    }
+
+    Ok(())
+  }
+
+  fn resize(&mut self, device: &wgpu::Device, render_target: &RenderTarget) {
+    // offsets are in clip space, so they have to follow the size of the target
+    self.set_style(device, render_target, self.color, self.thickness);
+  }
+
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    _render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    let vertex_shader = shader_store.vertex(device, Self::SHADER)?;
+    let fragment_shader = shader_store.fragment(device, Self::SHADER)?;
+    let layout = self.pipeline_layout.clone();
+    self.pipelines.rebuild("Outline Pipelines", device, move |device| {
+      Self::create_pipelines(device, &layout, &vertex_shader, &fragment_shader)
+    })
+  }
+}
+
+impl OutlinePass {
+  fn create_pipelines(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    vertex_shader: &Shader<VertexStage>,
+    fragment_shader: &Shader<Fragment>,
+  ) -> (Tracked<wgpu::RenderPipeline>, Tracked<wgpu::RenderPipeline>) {
+    // outlines ignore depth so that they stay visible through whatever covers the highlighted mesh
+    let depth_stencil = |stencil| wgpu::DepthStencilState {
+      format: RenderTarget::DEPTH_FORMAT,
+      depth_write_enabled: false,
+      depth_compare: wgpu::CompareFunction::Always,
+      stencil,
+      bias: wgpu::DepthBiasState::default(),
+    };
+
+    let mark_pipeline = create_depth_only_pipeline(
+      Some("Outline Mark Pipeline"),
+      device,
+      pipeline_layout,
+      depth_stencil(stencil_write_state()),
+      &[Vertex::desc(), Instance::desc()],
+      vertex_shader,
+    );
+
+    let outline_pipeline = create_render_pipeline(
+      Some("Outline Pipeline"),
+      device,
+      pipeline_layout,
+      RenderTarget::RENDER_TARGET_FORMAT,
+      Some(depth_stencil(stencil_test_state(wgpu::CompareFunction::NotEqual))),
+      &[Vertex::desc(), Instance::desc()],
+      ShaderStages {
+        vertex: vertex_shader,
+        fragment: fragment_shader,
+      },
+    );
+
+    (mark_pipeline, outline_pipeline)
+  }
+
+  /// Creates a uniform buffer with the unshifted mark at slot 0, followed by one slot per outline direction.
+  fn create_uniforms(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    render_target: &RenderTarget,
+    color: [f32; 4],
+    thickness: f32,
+  ) -> (Tracked<wgpu::Buffer>, wgpu::BindGroup, u32) {
+    let size = size_of::<OutlineUniforms>();
+    let stride = (size as u32).next_multiple_of(device.limits().min_uniform_buffer_offset_alignment);
+    let pixel_size = [2.0 / render_target.width() as f32, 2.0 / render_target.height() as f32];
+
+    let mut contents = vec![0u8; stride as usize * (Self::DIRECTIONS.len() + 1)];
+    let offsets = std::iter::once([0.0, 0.0]).chain(
+      Self::DIRECTIONS
+        .iter()
+        .map(|[x, y]| [x * thickness * pixel_size[0], y * thickness * pixel_size[1]]),
+    );
+    for (slot, offset) in contents.chunks_exact_mut(stride as usize).zip(offsets) {
+      slot.split_at_mut(size).0.copy_from_slice(bytemuck::bytes_of(&OutlineUniforms {
+        color,
+        offset,
+        _padding: [0.0; 2],
+      }));
+    }
+
+    let uniform_buffer = device.create_tracked_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Outline Uniform Buffer"),
+      contents: &contents,
+      usage: wgpu::BufferUsages::UNIFORM,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("Outline Bind Group"),
+      layout,
+      entries: &[wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+          buffer: &uniform_buffer,
+          offset: 0,
+          size: NonZeroU64::new(size as u64),
+        }),
+      }],
+    });
+
+    (uniform_buffer, bind_group, stride)
+  }
+}
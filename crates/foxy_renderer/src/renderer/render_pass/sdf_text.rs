@@ -0,0 +1,333 @@
+use std::{path::PathBuf, sync::Arc};
+
+use foxy_utils::tracking::Tracked;
+use glam::{Vec2, Vec3};
+use wgpu::CommandEncoder;
+
+use super::{depth_prepassed_state, is_reloaded, primitive_state, write_buffer, Pass, RenderingInfo};
+use crate::{
+  error::RendererError,
+  renderer::{
+    camera::{Camera, CameraBuffer},
+    mesh::MeshDraw,
+    pipeline::AsyncPipeline,
+    render_data::WorldText,
+    shader::{Fragment, Shader, ShaderStore, Vertex as VertexStage},
+    target::RenderTarget,
+    text::{GlyphQuad, SdfFont},
+    tracking::TrackedDevice,
+    vertex::Vertex,
+  },
+};
+
+/// Draws [`WorldText`] in the scene from a signed distance field font.
+///
+/// Text is depth tested against the scene but doesn't write depth, and is visible from both sides.
+pub struct SdfTextPass {
+  pipeline: AsyncPipeline,
+  pipeline_layout: Arc<wgpu::PipelineLayout>,
+  bind_group: wgpu::BindGroup,
+  camera: CameraBuffer,
+  font: SdfFont,
+  vertex_buffer: Option<Tracked<wgpu::Buffer>>,
+  index_buffer: Option<Tracked<wgpu::Buffer>>,
+  index_count: u32,
+}
+
+impl SdfTextPass {
+  const SHADER: &'static str = "sdf_text.wgsl";
+
+  pub fn new(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+    font: SdfFont,
+  ) -> Result<Self, RendererError> {
+    let (width, height) = font.atlas_size();
+    let size = wgpu::Extent3d {
+      width,
+      height,
+      depth_or_array_layers: 1,
+    };
+    let atlas = device.create_tracked_texture(&wgpu::TextureDescriptor {
+      label: Some("SDF Font Atlas"),
+      size,
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: wgpu::TextureDimension::D2,
+      format: wgpu::TextureFormat::R8Unorm,
+      usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+      view_formats: &[],
+    });
+    queue.write_texture(
+      atlas.as_image_copy(),
+      font.atlas(),
+      wgpu::ImageDataLayout {
+        offset: 0,
+        bytes_per_row: Some(width),
+        rows_per_image: Some(height),
+      },
+      size,
+    );
+
+    let atlas_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+      label: Some("SDF Font Sampler"),
+      address_mode_u: wgpu::AddressMode::ClampToEdge,
+      address_mode_v: wgpu::AddressMode::ClampToEdge,
+      address_mode_w: wgpu::AddressMode::ClampToEdge,
+      mag_filter: wgpu::FilterMode::Linear,
+      min_filter: wgpu::FilterMode::Linear,
+      mipmap_filter: wgpu::FilterMode::Nearest,
+      ..Default::default()
+    });
+
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("SDF Text Layout"),
+      entries: &[
+        wgpu::BindGroupLayoutEntry {
+          binding: 0,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+          },
+          count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+          binding: 1,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+          count: None,
+        },
+      ],
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("SDF Text Bind Group"),
+      layout: &layout,
+      entries: &[
+        wgpu::BindGroupEntry {
+          binding: 0,
+          resource: wgpu::BindingResource::TextureView(&atlas_view),
+        },
+        wgpu::BindGroupEntry {
+          binding: 1,
+          resource: wgpu::BindingResource::Sampler(&sampler),
+        },
+      ],
+    });
+
+    let camera = CameraBuffer::new(device);
+
+    let pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("SDF Text Pipeline Layout"),
+      bind_group_layouts: &[&layout, camera.bind_group_layout()],
+      push_constant_ranges: &[],
+    }));
+
+    let pipeline = AsyncPipeline::ready(Self::create_pipeline(
+      device,
+      &pipeline_layout,
+      depth_prepassed_state(render_target),
+      &shader_store.vertex(device, Self::SHADER)?,
+      &shader_store.fragment(device, Self::SHADER)?,
+    ));
+
+    Ok(Self {
+      pipeline,
+      pipeline_layout,
+      bind_group,
+      camera,
+      font,
+      vertex_buffer: None,
+      index_buffer: None,
+      index_count: 0,
+    })
+  }
+
+  pub fn font(&self) -> &SdfFont {
+    &self.font
+  }
+
+  pub fn set_camera(&mut self, queue: &wgpu::Queue, camera: &Camera, render_target: &RenderTarget) {
+    self.camera.update(queue, camera, render_target);
+  }
+
+  /// Lays out the text to draw this frame. Buffers are only reallocated when they have to grow.
+  pub fn set_text(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, text: &[WorldText]) {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for world_text in text {
+      let quads = self.font.layout(&world_text.text);
+      let line_offsets = if world_text.centered {
+        Self::line_centering(&quads)
+      } else {
+        Vec::new()
+      };
+
+      for quad in quads {
+        let offset = line_offsets.get(quad.line).copied().unwrap_or_default();
+
+        let corners = [
+          (Vec2::new(quad.plane_min.x, quad.plane_min.y), Vec2::new(quad.uv_min.x, quad.uv_min.y)),
+          (Vec2::new(quad.plane_max.x, quad.plane_min.y), Vec2::new(quad.uv_max.x, quad.uv_min.y)),
+          (Vec2::new(quad.plane_max.x, quad.plane_max.y), Vec2::new(quad.uv_max.x, quad.uv_max.y)),
+          (Vec2::new(quad.plane_min.x, quad.plane_max.y), Vec2::new(quad.uv_min.x, quad.uv_max.y)),
+        ];
+
+        let base = vertices.len() as u32;
+        for (position, uv) in corners {
+          let local = Vec3::new(position.x + offset, position.y, 0.0) * world_text.size;
+          vertices.push(Vertex {
+            position: world_text.transform.transform_point3(local).to_array(),
+            color: world_text.color,
+            uv: uv.to_array(),
+          });
+        }
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+      }
+    }
+
+    self.index_count = indices.len() as u32;
+    if indices.is_empty() {
+      return;
+    }
+
+    write_buffer(
+      device,
+      queue,
+      &mut self.vertex_buffer,
+      "SDF Text Vertex Buffer",
+      wgpu::BufferUsages::VERTEX,
+      bytemuck::cast_slice(&vertices),
+    );
+    write_buffer(
+      device,
+      queue,
+      &mut self.index_buffer,
+      "SDF Text Index Buffer",
+      wgpu::BufferUsages::INDEX,
+      bytemuck::cast_slice(&indices),
+    );
+  }
+}
+
+impl Pass for SdfTextPass {
+  fn draw(
+    &mut self,
+    command_encoder: &mut CommandEncoder,
+    render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    _draws: &[MeshDraw],
+  ) -> Result<(), RendererError> {
+    let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer) else {
+      return Ok(());
+    };
+    if self.index_count == 0 {
+      return Ok(());
+    }
+
+    let mut render_pass = RenderingInfo::new("SDF Text Pass")
+      .with_loaded_color(output)
+      .with_loaded_depth(&render_target.depth_view)
+      .begin_rendering(command_encoder);
+
+    render_pass.set_pipeline(self.pipeline.get());
+    render_pass.set_bind_group(0, &self.bind_group, &[]);
+    render_pass.set_bind_group(1, self.camera.bind_group(), &[]);
+    render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+    render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+    render_pass.draw_indexed(0..self.index_count, 0, 0..1);
+
+    Ok(())
+  }
+
+  fn resize(&mut self, _device: &wgpu::Device, _render_target: &RenderTarget) {}
+
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    let vertex_shader = shader_store.vertex(device, Self::SHADER)?;
+    let fragment_shader = shader_store.fragment(device, Self::SHADER)?;
+    let depth_stencil = depth_prepassed_state(render_target);
+    let layout = self.pipeline_layout.clone();
+    self.pipeline.rebuild("SDF Text Pipeline", device, move |device| {
+      Self::create_pipeline(device, &layout, depth_stencil, &vertex_shader, &fragment_shader)
+    })
+  }
+}
+
+impl SdfTextPass {
+  /// `depth_stencil` should be a [`depth_prepassed_state`], so that text is tested like geometry drawn after a
+  /// prepass and overlapping glyphs blend instead of hiding each other.
+  fn create_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    depth_stencil: wgpu::DepthStencilState,
+    vertex_shader: &Shader<VertexStage>,
+    fragment_shader: &Shader<Fragment>,
+  ) -> Tracked<wgpu::RenderPipeline> {
+    let vertex_module = vertex_shader.module();
+    let fragment_module = fragment_shader.module();
+    device.create_tracked_render_pipeline(&wgpu::RenderPipelineDescriptor {
+      label: Some("SDF Text Pipeline"),
+      layout: Some(pipeline_layout),
+      vertex: wgpu::VertexState {
+        module: &vertex_module,
+        entry_point: vertex_shader.entry_point("vs_main"),
+        buffers: &[Vertex::desc()],
+      },
+      fragment: Some(wgpu::FragmentState {
+        module: &fragment_module,
+        entry_point: fragment_shader.entry_point("fs_main"),
+        targets: &[Some(wgpu::ColorTargetState {
+          format: RenderTarget::RENDER_TARGET_FORMAT,
+          blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+          write_mask: wgpu::ColorWrites::ALL,
+        })],
+      }),
+      primitive: wgpu::PrimitiveState {
+        cull_mode: None,
+        ..primitive_state()
+      },
+      depth_stencil: Some(depth_stencil),
+      multisample: wgpu::MultisampleState {
+        count: 1,
+        mask: !0,
+        alpha_to_coverage_enabled: false,
+      },
+      multiview: None,
+    })
+  }
+
+  /// How far to shift each line so that it's centered on the origin.
+  fn line_centering(quads: &[GlyphQuad]) -> Vec<f32> {
+    let mut extents: Vec<(f32, f32)> = Vec::new();
+    for quad in quads {
+      if extents.len() <= quad.line {
+        extents.resize(quad.line + 1, (f32::INFINITY, f32::NEG_INFINITY));
+      }
+      if let Some((min, max)) = extents.get_mut(quad.line) {
+        *min = min.min(quad.plane_min.x);
+        *max = max.max(quad.plane_max.x);
+      }
+    }
+
+    extents
+      .into_iter()
+      .map(|(min, max)| if min <= max { -(min + max) / 2.0 } else { 0.0 })
+      .collect()
+  }
+}
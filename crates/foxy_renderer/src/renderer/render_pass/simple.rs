@@ -1,35 +1,207 @@
+use std::{path::PathBuf, sync::Arc};
+
+use foxy_utils::tracking::Tracked;
 use wgpu::{Color, CommandEncoder};
 
-use super::{create_render_pipeline, Pass};
-use crate::renderer::{
-  context::GraphicsContext, mesh::Mesh, render_data::Drawable, target::RenderTarget, texture::DiffuseTexture, vertex::Vertex, Renderer
+use super::{
+  create_depth_only_pipeline,
+  create_render_pipeline,
+  depth_prepassed_state,
+  depth_write_state,
+  is_reloaded,
+  Pass,
+  RenderingInfo,
+  ShaderStages,
+};
+use crate::{
+  error::RendererError,
+  renderer::{
+    camera::{Camera, CameraBuffer},
+    context::GraphicsContext,
+    material::MaterialOverrideRing,
+    mesh::MeshDraw,
+    pipeline::AsyncPipeline,
+    render_data::{Drawable, MaterialOverrides, RenderLayers},
+    shader::{Fragment, Shader, ShaderStore, Vertex as VertexStage},
+    target::RenderTarget,
+    texture::DiffuseTexture,
+    vertex::{Instance, Vertex},
+    Renderer,
+  },
 };
 
 pub struct SimplePass {
-  pipeline: wgpu::RenderPipeline,
+  pipeline: AsyncPipeline,
+  depth_prepass_pipeline: Option<AsyncPipeline>,
+  pipeline_layout: Arc<wgpu::PipelineLayout>,
+  material_overrides: MaterialOverrideRing,
+  camera: CameraBuffer,
+  override_offsets: Vec<u32>,
+  layer_mask: RenderLayers,
 }
 
 impl SimplePass {
-  pub fn new(device: &wgpu::Device) -> Self {
-    let shader = wgpu::include_wgsl!("../../../assets/shaders/texture.wgsl");
+  const SHADER: &'static str = "texture.wgsl";
+  const PLACEHOLDER_SHADER: &'static str = "placeholder.wgsl";
+
+  /// With `depth_prepass`, depth is laid down by a depth-only pass first so the main pass only shades visible
+  /// fragments.
+  ///
+  /// The main pipeline is compiled in the background, and meshes are drawn in flat gray until it's ready.
+  pub fn new(
+    device: &Arc<wgpu::Device>,
+    queue: &wgpu::Queue,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+    depth_prepass: bool,
+  ) -> Result<Self, RendererError> {
+    let material_overrides = MaterialOverrideRing::new(device, queue, MaterialOverrideRing::DEFAULT_CAPACITY);
+    let camera = CameraBuffer::new(device);
 
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+    let pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
       label: Some("Simple Pipeline Layout"),
-      bind_group_layouts: &[DiffuseTexture::bind_group_layout(device)],
+      bind_group_layouts: &[
+        DiffuseTexture::bind_group_layout(device),
+        material_overrides.bind_group_layout(),
+        camera.bind_group_layout(),
+      ],
       push_constant_ranges: &[],
-    });
+    }));
+
+    let (placeholder, depth_prepass_pipeline) =
+      Self::create_placeholder_pipelines(device, shader_store, &pipeline_layout, render_target, depth_prepass)?;
+
+    let vertex_shader = shader_store.vertex(device, Self::SHADER)?;
+    let fragment_shader = shader_store.fragment(device, Self::SHADER)?;
+    let depth_stencil = Self::depth_stencil(render_target, depth_prepass);
+    let layout = pipeline_layout.clone();
+    let pipeline = AsyncPipeline::spawn("Simple Pipeline", device, Arc::new(placeholder), move |device| {
+      Self::create_pipeline(device, &layout, depth_stencil, &vertex_shader, &fragment_shader)
+    })?;
+
+    Ok(Self {
+      pipeline,
+      depth_prepass_pipeline: depth_prepass_pipeline.map(AsyncPipeline::ready),
+      pipeline_layout,
+      material_overrides,
+      camera,
+      override_offsets: Vec::new(),
+      layer_mask: RenderLayers::ALL,
+    })
+  }
+
+  pub fn has_depth_prepass(&self) -> bool {
+    self.depth_prepass_pipeline.is_some()
+  }
+
+  /// Only drawables on at least one of these layers are drawn. Follows the layers of the camera.
+  pub fn layer_mask(&self) -> RenderLayers {
+    self.layer_mask
+  }
+
+  pub fn set_camera(&mut self, queue: &wgpu::Queue, camera: &Camera, render_target: &RenderTarget) {
+    self.camera.update(queue, camera, render_target);
+    self.layer_mask = camera.layers;
+  }
+
+  /// Uploads the material overrides for this frame's draws, in draw order.
+  pub fn set_material_overrides(&mut self, queue: &wgpu::Queue, overrides: &[MaterialOverrides]) {
+    self.material_overrides.reset();
+    self.override_offsets.clear();
+    self
+      .override_offsets
+      .extend(overrides.iter().map(|overrides| self.material_overrides.push(queue, overrides)));
+  }
 
-    let pipeline = create_render_pipeline(
+  fn override_offset(&self, draw_index: usize) -> u32 {
+    self
+      .override_offsets
+      .get(draw_index)
+      .copied()
+      .unwrap_or(MaterialOverrideRing::DEFAULT_OFFSET)
+  }
+}
+
+impl SimplePass {
+  fn depth_stencil(render_target: &RenderTarget, depth_prepass: bool) -> wgpu::DepthStencilState {
+    if depth_prepass {
+      depth_prepassed_state(render_target)
+    } else {
+      depth_write_state(render_target)
+    }
+  }
+
+  fn create_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    depth_stencil: wgpu::DepthStencilState,
+    vertex_shader: &Shader<VertexStage>,
+    fragment_shader: &Shader<Fragment>,
+  ) -> Tracked<wgpu::RenderPipeline> {
+    create_render_pipeline(
       Some("Simple Pipeline"),
       device,
-      &pipeline_layout,
+      pipeline_layout,
+      RenderTarget::RENDER_TARGET_FORMAT,
+      Some(depth_stencil),
+      &[Vertex::desc(), Instance::desc()],
+      ShaderStages {
+        vertex: vertex_shader,
+        fragment: fragment_shader,
+      },
+    )
+  }
+
+  fn create_depth_prepass_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    depth_stencil: wgpu::DepthStencilState,
+    vertex_shader: &Shader<VertexStage>,
+  ) -> Tracked<wgpu::RenderPipeline> {
+    create_depth_only_pipeline(
+      Some("Simple Depth Prepass Pipeline"),
+      device,
+      pipeline_layout,
+      depth_stencil,
+      &[Vertex::desc(), Instance::desc()],
+      vertex_shader,
+    )
+  }
+
+  /// Creates the pipelines that don't have to wait on the main one: the placeholder, and the depth prepass if there
+  /// is one.
+  fn create_placeholder_pipelines(
+    device: &wgpu::Device,
+    shader_store: &mut ShaderStore,
+    pipeline_layout: &wgpu::PipelineLayout,
+    render_target: &RenderTarget,
+    depth_prepass: bool,
+  ) -> Result<(Tracked<wgpu::RenderPipeline>, Option<Tracked<wgpu::RenderPipeline>>), RendererError> {
+    let depth_prepass_pipeline = if depth_prepass {
+      Some(Self::create_depth_prepass_pipeline(
+        device,
+        pipeline_layout,
+        depth_write_state(render_target),
+        &shader_store.vertex(device, Self::SHADER)?,
+      ))
+    } else {
+      None
+    };
+
+    let placeholder = create_render_pipeline(
+      Some("Simple Placeholder Pipeline"),
+      device,
+      pipeline_layout,
       RenderTarget::RENDER_TARGET_FORMAT,
-      None,
-      &[Vertex::desc()],
-      shader,
+      Some(Self::depth_stencil(render_target, depth_prepass)),
+      &[Vertex::desc(), Instance::desc()],
+      ShaderStages {
+        vertex: &shader_store.vertex(device, Self::PLACEHOLDER_SHADER)?,
+        fragment: &shader_store.fragment(device, Self::PLACEHOLDER_SHADER)?,
+      },
     );
 
-    Self { pipeline }
+    Ok((placeholder, depth_prepass_pipeline))
   }
 }
 
@@ -37,30 +209,84 @@ impl Pass for SimplePass {
   fn draw(
     &mut self,
     command_encoder: &mut CommandEncoder,
-    render_target: &wgpu::TextureView,
-    mesh: &Mesh,
+    render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    draws: &[MeshDraw],
   ) -> Result<(), crate::error::RendererError> {
-    let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-      label: Some("Simple Pass"),
-      color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-        view: render_target,
-        resolve_target: None,
-        ops: wgpu::Operations {
-          load: wgpu::LoadOp::Load,
-          store: wgpu::StoreOp::Store,
-        },
-      })],
-      depth_stencil_attachment: None,
-      occlusion_query_set: None,
-      timestamp_writes: None,
-    });
-
-    render_pass.set_pipeline(&self.pipeline);
-    render_pass.set_bind_group(0, &mesh.material.albedo().bind_group, &[]);
-    mesh.draw(&mut render_pass);
+    let visible: Vec<&MeshDraw> = draws
+      .iter()
+      .filter(|draw| self.layer_mask.intersects(draw.layers()))
+      .collect();
+    let rendering_info = RenderingInfo::new("Simple Pass").with_loaded_color(output);
+
+    let rendering_info = match &self.depth_prepass_pipeline {
+      Some(depth_prepass_pipeline) => {
+        {
+          let mut depth_prepass = RenderingInfo::new("Simple Depth Prepass")
+            .with_cleared_depth(&render_target.depth_view, render_target.depth_clear_value())
+            .with_cleared_stencil(0)
+            .begin_rendering(command_encoder);
+
+          depth_prepass.set_pipeline(depth_prepass_pipeline.get());
+          depth_prepass.set_bind_group(2, self.camera.bind_group(), &[]);
+          for draw in &visible {
+            depth_prepass.set_bind_group(0, &draw.mesh.material.albedo().bind_group, &[]);
+            depth_prepass.set_bind_group(1, self.material_overrides.bind_group(), &[self.override_offset(draw.index)]);
+            draw.draw(&mut depth_prepass);
+          }
+        }
+
+        rendering_info
+          .with_loaded_depth(&render_target.depth_view)
+          .with_loaded_stencil()
+      }
+      None => rendering_info
+        .with_cleared_depth(&render_target.depth_view, render_target.depth_clear_value())
+        .with_cleared_stencil(0),
+    };
+
+    let mut render_pass = rendering_info.begin_rendering(command_encoder);
+
+    render_pass.set_pipeline(self.pipeline.get());
+    render_pass.set_bind_group(2, self.camera.bind_group(), &[]);
+    for draw in &visible {
+      render_pass.set_bind_group(0, &draw.mesh.material.albedo().bind_group, &[]);
+      render_pass.set_bind_group(1, self.material_overrides.bind_group(), &[self.override_offset(draw.index)]);
+      draw.draw(&mut render_pass);
+    }
 
     Ok(())
   }
 
   fn resize(&mut self, device: &wgpu::Device, render_target: &RenderTarget) {}
+
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    // the placeholder only shows until the first compile finishes, so there's nothing to rebuild for it
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    // the current pipelines keep drawing until the new ones are compiled
+    let vertex_shader = shader_store.vertex(device, Self::SHADER)?;
+    let fragment_shader = shader_store.fragment(device, Self::SHADER)?;
+    if let Some(depth_prepass_pipeline) = &mut self.depth_prepass_pipeline {
+      let (layout, depth_stencil, vertex_shader) =
+        (self.pipeline_layout.clone(), depth_write_state(render_target), vertex_shader.clone());
+      depth_prepass_pipeline.rebuild("Simple Depth Prepass Pipeline", device, move |device| {
+        Self::create_depth_prepass_pipeline(device, &layout, depth_stencil, &vertex_shader)
+      })?;
+    }
+
+    let layout = self.pipeline_layout.clone();
+    let depth_stencil = Self::depth_stencil(render_target, self.has_depth_prepass());
+    self.pipeline.rebuild("Simple Pipeline", device, move |device| {
+      Self::create_pipeline(device, &layout, depth_stencil, &vertex_shader, &fragment_shader)
+    })
+  }
 }
@@ -0,0 +1,220 @@
+use std::{mem::size_of, path::PathBuf, sync::Arc};
+
+use foxy_utils::tracking::Tracked;
+use glam::Mat4;
+use wgpu::CommandEncoder;
+
+use super::is_reloaded;
+use crate::{
+  error::RendererError,
+  renderer::{
+    material::Material,
+    mesh::Mesh,
+    pipeline::AsyncPipeline,
+    render_data::RenderLayers,
+    shader::{Compute, Shader, ShaderStore},
+    tracking::TrackedDevice,
+    vertex::{SkinnedVertex, Vertex},
+  },
+};
+
+/// Skins meshes on the GPU. Each [`SkinnedMesh`] keeps its bind pose and joint matrices in storage buffers, and the
+/// pass writes the skinned vertices into a vertex buffer that is drawn like any other mesh, so neither the CPU nor
+/// the vertex stage of every pass has to blend joints.
+pub struct SkinningPass {
+  pipeline: AsyncPipeline<Tracked<wgpu::ComputePipeline>>,
+  pipeline_layout: Arc<wgpu::PipelineLayout>,
+  layout: wgpu::BindGroupLayout,
+}
+
+impl SkinningPass {
+  const SHADER: &'static str = "skinning.wgsl";
+  const WORKGROUP_SIZE: u32 = 64;
+
+  pub fn new(device: &wgpu::Device, shader_store: &mut ShaderStore) -> Result<Self, RendererError> {
+    let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+      binding,
+      visibility: wgpu::ShaderStages::COMPUTE,
+      ty: wgpu::BindingType::Buffer {
+        ty: wgpu::BufferBindingType::Storage { read_only },
+        has_dynamic_offset: false,
+        min_binding_size: None,
+      },
+      count: None,
+    };
+
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("Skinning Layout"),
+      entries: &[storage_entry(0, true), storage_entry(1, true), storage_entry(2, false)],
+    });
+
+    let pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("Skinning Pipeline Layout"),
+      bind_group_layouts: &[&layout],
+      push_constant_ranges: &[],
+    }));
+    let shader = shader_store.compute(device, Self::SHADER)?;
+    let pipeline = AsyncPipeline::ready(Self::create_pipeline(device, &pipeline_layout, &shader));
+
+    Ok(Self {
+      pipeline,
+      pipeline_layout,
+      layout,
+    })
+  }
+
+  pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+    &self.layout
+  }
+
+  /// Skins every mesh with its current joint matrices. Must be recorded before the meshes are drawn, which the
+  /// [`Renderer`](crate::renderer::Renderer) does for the skinned draws of each frame.
+  pub fn dispatch(&self, command_encoder: &mut CommandEncoder, meshes: &[&SkinnedMesh]) {
+    if meshes.is_empty() {
+      return;
+    }
+
+    let mut compute_pass = command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+      label: Some("Skinning Pass"),
+      timestamp_writes: None,
+    });
+
+    compute_pass.set_pipeline(self.pipeline.get());
+    for mesh in meshes {
+      compute_pass.set_bind_group(0, &mesh.bind_group, &[]);
+      compute_pass.dispatch_workgroups(mesh.vertex_count.div_ceil(Self::WORKGROUP_SIZE), 1, 1);
+    }
+  }
+
+  /// Rebuilds the pipeline if its shader is among the `reloaded` ones, like
+  /// [`Pass::reload_shaders`](super::Pass::reload_shaders).
+  pub fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    let shader = shader_store.compute(device, Self::SHADER)?;
+    let layout = self.pipeline_layout.clone();
+    self.pipeline.rebuild("Skinning Pipeline", device, move |device| {
+      Self::create_pipeline(device, &layout, &shader)
+    })
+  }
+}
+
+impl SkinningPass {
+  fn create_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    shader: &Shader<Compute>,
+  ) -> Tracked<wgpu::ComputePipeline> {
+    let module = shader.module();
+
+    device.create_tracked_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+      label: Some("Skinning Pipeline"),
+      layout: Some(pipeline_layout),
+      module: &module,
+      entry_point: shader.entry_point("cs_skin"),
+    })
+  }
+}
+
+/// A mesh deformed by a skeleton of up to `joint_count` joints.
+pub struct SkinnedMesh {
+  mesh: Mesh,
+  joint_buffer: Tracked<wgpu::Buffer>,
+  joint_count: u32,
+  vertex_count: u32,
+  bind_group: wgpu::BindGroup,
+  _source_buffer: Tracked<wgpu::Buffer>,
+}
+
+impl SkinnedMesh {
+  pub fn new(
+    device: &wgpu::Device,
+    skinning_pass: &SkinningPass,
+    vertices: &[SkinnedVertex],
+    indices: Option<&[u32]>,
+    material: Arc<dyn Material>,
+    joint_count: u32,
+  ) -> Self {
+    let source_buffer = device.create_tracked_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Skinning Source Buffer"),
+      contents: bytemuck::cast_slice(vertices),
+      usage: wgpu::BufferUsages::STORAGE,
+    });
+
+    // every joint starts out at the bind pose
+    let joint_buffer = device.create_tracked_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Skinning Joint Buffer"),
+      contents: bytemuck::cast_slice(&vec![Mat4::IDENTITY.to_cols_array(); joint_count.max(1) as usize]),
+      usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+    });
+
+    let skinned_buffer = device.create_tracked_buffer(&wgpu::BufferDescriptor {
+      label: Some("Skinned Vertex Buffer"),
+      size: (vertices.len() * size_of::<Vertex>()) as wgpu::BufferAddress,
+      usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+      mapped_at_creation: false,
+    });
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("Skinning Bind Group"),
+      layout: skinning_pass.bind_group_layout(),
+      entries: &[
+        wgpu::BindGroupEntry {
+          binding: 0,
+          resource: source_buffer.as_entire_binding(),
+        },
+        wgpu::BindGroupEntry {
+          binding: 1,
+          resource: joint_buffer.as_entire_binding(),
+        },
+        wgpu::BindGroupEntry {
+          binding: 2,
+          resource: skinned_buffer.as_entire_binding(),
+        },
+      ],
+    });
+
+    let vertex_count = vertices.len() as u32;
+
+    Self {
+      mesh: Mesh::from_vertex_buffer(device, skinned_buffer, vertex_count, indices, material),
+      joint_buffer,
+      joint_count,
+      vertex_count,
+      bind_group,
+      _source_buffer: source_buffer,
+    }
+  }
+
+  pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+    self.mesh = self.mesh.with_layers(layers);
+    self
+  }
+
+  /// The skinned mesh, as of the last [`SkinningPass::dispatch`].
+  pub fn mesh(&self) -> &Mesh {
+    &self.mesh
+  }
+
+  pub fn joint_count(&self) -> u32 {
+    self.joint_count
+  }
+
+  /// Uploads the joint matrices of the current pose, each mapping from bind pose to model space. Extra matrices
+  /// beyond the mesh's joint count are ignored.
+  pub fn set_joint_matrices(&self, queue: &wgpu::Queue, joints: &[Mat4]) {
+    let joints = joints
+      .iter()
+      .take(self.joint_count as usize)
+      .map(Mat4::to_cols_array)
+      .collect::<Vec<_>>();
+    queue.write_buffer(&self.joint_buffer, 0, bytemuck::cast_slice(&joints));
+  }
+}
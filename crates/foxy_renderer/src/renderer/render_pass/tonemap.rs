@@ -1,16 +1,59 @@
+use std::{path::PathBuf, sync::Arc};
+
+use bytemuck::{Pod, Zeroable};
+use foxy_utils::tracking::Tracked;
 use wgpu::{Color, CommandEncoder};
 
-use super::{create_render_pipeline, Pass};
-use crate::renderer::{context::GraphicsContext, render_data::Drawable, target::RenderTarget, Renderer};
+use super::{create_render_pipeline, is_reloaded, Pass, RenderingInfo, ShaderStages};
+use crate::{
+  error::RendererError,
+  renderer::{
+    context::GraphicsContext,
+    mesh::MeshDraw,
+    pipeline::AsyncPipeline,
+    render_data::{ColorBlindness, ColorFilter, DisplayCalibration, Drawable},
+    shader::{Fragment, Shader, ShaderStore, Vertex},
+    target::RenderTarget,
+    tracking::TrackedDevice,
+    Renderer,
+  },
+};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct OutputUniforms {
+  encode_srgb: u32,
+  gamma: f32,
+  brightness: f32,
+  contrast: f32,
+  show_test_pattern: u32,
+  color_filter: u32,
+  color_blindness: u32,
+  _padding: u32,
+}
 
 pub struct ToneMapPass {
-  pipeline: wgpu::RenderPipeline,
+  pipeline: AsyncPipeline,
+  pipeline_layout: Arc<wgpu::PipelineLayout>,
+  output_format: wgpu::TextureFormat,
   bind_group: wgpu::BindGroup,
   layout: wgpu::BindGroupLayout,
+  output_buffer: Tracked<wgpu::Buffer>,
+  encode_srgb: bool,
+  calibration: DisplayCalibration,
+  color_filter: ColorFilter,
+  show_test_pattern: bool,
 }
 
 impl ToneMapPass {
-  pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, render_target: &RenderTarget) -> Self {
+  const SHADER: &'static str = "hdr.wgsl";
+
+  pub fn new(
+    device: &wgpu::Device,
+    shader_store: &mut ShaderStore,
+    output_format: wgpu::TextureFormat,
+    render_target: &RenderTarget,
+  ) -> Result<Self, RendererError> {
     let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
       label: Some("HDR Layout"),
       entries: &[
@@ -31,9 +74,33 @@ impl ToneMapPass {
           ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
           count: None,
         },
+        wgpu::BindGroupLayoutEntry {
+          binding: 2,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+          },
+          count: None,
+        },
       ],
     });
 
+    // Outputs without an sRGB view still expect gamma encoded values, so the shader has to do it
+    let encode_srgb = GraphicsContext::needs_srgb_encode(output_format);
+    let calibration = DisplayCalibration::default();
+    let output_buffer = device.create_tracked_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("HDR Output Buffer"),
+      contents: bytemuck::bytes_of(&Self::output_uniforms(
+        encode_srgb,
+        &calibration,
+        ColorFilter::None,
+        false,
+      )),
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
       label: Some("HDR Bind Group"),
       layout: &layout,
@@ -46,64 +113,151 @@ impl ToneMapPass {
           binding: 1,
           resource: wgpu::BindingResource::Sampler(&render_target.sampler),
         },
+        wgpu::BindGroupEntry {
+          binding: 2,
+          resource: output_buffer.as_entire_binding(),
+        },
       ],
     });
 
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+    let pipeline_layout = Arc::new(device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
       label: Some("HDR Pipeline Layout"),
       bind_group_layouts: &[&layout],
       push_constant_ranges: &[],
-    });
+    }));
 
-    let shader = wgpu::include_wgsl!("../../../assets/shaders/hdr.wgsl");
-
-    let pipeline = create_render_pipeline(
-      Some("HDR Pipeline"),
+    let pipeline = AsyncPipeline::ready(Self::create_pipeline(
       device,
       &pipeline_layout,
-      config.format,
-      None,
-      &[],
-      shader,
-    );
+      output_format,
+      &shader_store.vertex(device, Self::SHADER)?,
+      &shader_store.fragment(device, Self::SHADER)?,
+    ));
 
-    Self {
+    Ok(Self {
       pipeline,
+      pipeline_layout,
+      output_format,
       bind_group,
       layout,
+      output_buffer,
+      encode_srgb,
+      calibration,
+      color_filter: ColorFilter::None,
+      show_test_pattern: false,
+    })
+  }
+
+  pub fn calibration(&self) -> &DisplayCalibration {
+    &self.calibration
+  }
+
+  pub fn set_calibration(&mut self, queue: &wgpu::Queue, calibration: DisplayCalibration) {
+    if self.calibration != calibration {
+      self.calibration = calibration;
+      self.write_output_uniforms(queue);
+    }
+  }
+
+  pub fn color_filter(&self) -> ColorFilter {
+    self.color_filter
+  }
+
+  pub fn set_color_filter(&mut self, queue: &wgpu::Queue, color_filter: ColorFilter) {
+    if self.color_filter != color_filter {
+      self.color_filter = color_filter;
+      self.write_output_uniforms(queue);
     }
   }
+
+  /// Replaces the scene with a calibration pattern: a gray ramp above rows of near-black and near-white patches.
+  /// With a good calibration, every patch is just barely distinguishable from its background.
+  pub fn set_test_pattern(&mut self, queue: &wgpu::Queue, show_test_pattern: bool) {
+    if self.show_test_pattern != show_test_pattern {
+      self.show_test_pattern = show_test_pattern;
+      self.write_output_uniforms(queue);
+    }
+  }
+}
+
+impl ToneMapPass {
+  fn create_pipeline(
+    device: &wgpu::Device,
+    pipeline_layout: &wgpu::PipelineLayout,
+    output_format: wgpu::TextureFormat,
+    vertex_shader: &Shader<Vertex>,
+    fragment_shader: &Shader<Fragment>,
+  ) -> Tracked<wgpu::RenderPipeline> {
+    create_render_pipeline(
+      Some("HDR Pipeline"),
+      device,
+      pipeline_layout,
+      output_format,
+      None,
+      &[],
+      ShaderStages {
+        vertex: vertex_shader,
+        fragment: fragment_shader,
+      },
+    )
+  }
+
+  fn output_uniforms(
+    encode_srgb: bool,
+    calibration: &DisplayCalibration,
+    color_filter: ColorFilter,
+    show_test_pattern: bool,
+  ) -> OutputUniforms {
+    let (color_filter, color_blindness) = match color_filter {
+      ColorFilter::None => (0, None),
+      ColorFilter::Simulate(color_blindness) => (1, Some(color_blindness)),
+      ColorFilter::Correct(color_blindness) => (2, Some(color_blindness)),
+    };
+
+    OutputUniforms {
+      encode_srgb: encode_srgb as u32,
+      gamma: calibration.gamma,
+      brightness: calibration.brightness,
+      contrast: calibration.contrast,
+      show_test_pattern: show_test_pattern as u32,
+      color_filter,
+      color_blindness: match color_blindness {
+        None | Some(ColorBlindness::Protanopia) => 0,
+        Some(ColorBlindness::Deuteranopia) => 1,
+        Some(ColorBlindness::Tritanopia) => 2,
+      },
+      _padding: 0,
+    }
+  }
+
+  fn write_output_uniforms(&self, queue: &wgpu::Queue) {
+    let uniforms = Self::output_uniforms(
+      self.encode_srgb,
+      &self.calibration,
+      self.color_filter,
+      self.show_test_pattern,
+    );
+    queue.write_buffer(&self.output_buffer, 0, bytemuck::bytes_of(&uniforms));
+  }
 }
 
 impl Pass for ToneMapPass {
   fn draw(
     &mut self,
     command_encoder: &mut CommandEncoder,
-    render_target: &wgpu::TextureView,
-    mesh: &crate::renderer::mesh::Mesh,
+    _render_target: &RenderTarget,
+    output: &wgpu::TextureView,
+    _draws: &[MeshDraw],
   ) -> Result<(), crate::error::RendererError> {
-    let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-      label: Some("HDR Pass"),
-      color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-        view: render_target,
-        resolve_target: None,
-        ops: wgpu::Operations {
-          load: wgpu::LoadOp::Clear(Renderer::CLEAR_VALUE),
-          store: wgpu::StoreOp::Store,
-        },
-      })],
-      depth_stencil_attachment: None,
-      occlusion_query_set: None,
-      timestamp_writes: None,
-    });
+    let mut render_pass = RenderingInfo::new("HDR Pass")
+      .with_cleared_color(output, Renderer::CLEAR_VALUE)
+      .begin_rendering(command_encoder);
 
-    render_pass.set_pipeline(&self.pipeline);
+    render_pass.set_pipeline(self.pipeline.get());
     render_pass.set_bind_group(0, &self.bind_group, &[]);
 
     render_pass.draw(0..3, 0..1);
 
-    // mesh.draw(&mut render_pass);
-
     Ok(())
   }
 
@@ -120,7 +274,30 @@ impl Pass for ToneMapPass {
           binding: 1,
           resource: wgpu::BindingResource::Sampler(&render_target.sampler),
         },
+        wgpu::BindGroupEntry {
+          binding: 2,
+          resource: self.output_buffer.as_entire_binding(),
+        },
       ],
     });
   }
+
+  fn reload_shaders(
+    &mut self,
+    device: &Arc<wgpu::Device>,
+    shader_store: &mut ShaderStore,
+    _render_target: &RenderTarget,
+    reloaded: &[PathBuf],
+  ) -> Result<(), RendererError> {
+    if !is_reloaded(reloaded, Self::SHADER) {
+      return Ok(());
+    }
+
+    let vertex_shader = shader_store.vertex(device, Self::SHADER)?;
+    let fragment_shader = shader_store.fragment(device, Self::SHADER)?;
+    let (layout, output_format) = (self.pipeline_layout.clone(), self.output_format);
+    self.pipeline.rebuild("HDR Pipeline", device, move |device| {
+      Self::create_pipeline(device, &layout, output_format, &vertex_shader, &fragment_shader)
+    })
+  }
 }
@@ -0,0 +1,556 @@
+use std::{
+  collections::HashMap,
+  marker::PhantomData,
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc,
+    Arc,
+    RwLockReadGuard,
+  },
+};
+
+use foxy_utils::{tracking::Tracked, types::handle::Handle};
+use notify::{RecursiveMode, Watcher};
+use strum::EnumIter;
+use tracing::*;
+use wgpu::naga;
+
+use super::tracking::TrackedDevice;
+use crate::{
+  error::{validated, RendererError},
+  renderer_error,
+};
+
+#[derive(EnumIter, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ShaderKind {
+  Vertex,
+  Fragment,
+  Compute,
+}
+
+impl From<ShaderKind> for naga::ShaderStage {
+  fn from(value: ShaderKind) -> Self {
+    match value {
+      ShaderKind::Vertex => naga::ShaderStage::Vertex,
+      ShaderKind::Fragment => naga::ShaderStage::Fragment,
+      ShaderKind::Compute => naga::ShaderStage::Compute,
+    }
+  }
+}
+
+pub trait Stage {
+  const KIND: ShaderKind;
+}
+
+pub struct Vertex;
+
+impl Stage for Vertex {
+  const KIND: ShaderKind = ShaderKind::Vertex;
+}
+
+pub struct Fragment;
+
+impl Stage for Fragment {
+  const KIND: ShaderKind = ShaderKind::Fragment;
+}
+
+pub struct Compute;
+
+impl Stage for Compute {
+  const KIND: ShaderKind = ShaderKind::Compute;
+}
+
+/// A shader module known to contain an entry point for stage `S`.
+pub struct Shader<S: Stage> {
+  module: Handle<Tracked<wgpu::ShaderModule>>,
+  /// Whether the magenta error shader stands in for the module, until a hot reload fixes it.
+  is_error_shader: Arc<AtomicBool>,
+  error_entry_point: &'static str,
+  _stage: PhantomData<S>,
+}
+
+impl<S: Stage> Clone for Shader<S> {
+  fn clone(&self) -> Self {
+    Self {
+      module: self.module.clone(),
+      is_error_shader: self.is_error_shader.clone(),
+      error_entry_point: self.error_entry_point,
+      _stage: PhantomData,
+    }
+  }
+}
+
+impl<S: Stage> Shader<S> {
+  pub fn kind(&self) -> ShaderKind {
+    S::KIND
+  }
+
+  pub fn module(&self) -> RwLockReadGuard<'_, Tracked<wgpu::ShaderModule>> {
+    self.module.get()
+  }
+
+  pub fn handle(&self) -> &Handle<Tracked<wgpu::ShaderModule>> {
+    &self.module
+  }
+
+  /// The entry point to build pipelines of the shader with: `entry_point`, unless the shader failed to load and the
+  /// error shader stands in for it, whose entry points are `vs_main`, `fs_main` and `cs_main` in WGSL, and only ever
+  /// `main` in GLSL.
+  pub fn entry_point<'a>(&self, entry_point: &'a str) -> &'a str {
+    if self.is_error_shader.load(Ordering::Relaxed) {
+      self.error_entry_point
+    } else {
+      entry_point
+    }
+  }
+}
+
+struct CachedShader {
+  module: Handle<Tracked<wgpu::ShaderModule>>,
+  is_error_shader: Arc<AtomicBool>,
+  last_used: u64,
+  is_stale: bool,
+}
+
+struct ShaderWatcher {
+  // watching stops when the watcher is dropped
+  _watcher: notify::RecommendedWatcher,
+  dir: PathBuf,
+  changes: mpsc::Receiver<PathBuf>,
+}
+
+/// Loads shader modules from the asset directory and caches them by path and stage.
+///
+/// WGSL files may hold several stages, so every stage requested from the same `.wgsl` file shares one module. GLSL
+/// files (`.vert`, `.frag`, `.comp`, `.glsl`) are compiled for the requested stage only.
+///
+/// WGSL files can pull in the engine's shader snippets with a `#include "<snippet>"` line, e.g. `#include
+/// "noise.wgsl"` for [`ShaderStore::NOISE_SNIPPET`].
+///
+/// Handles returned by the store stay valid after an entry is unloaded or evicted; the store only drops its own
+/// reference. Entries that are still referenced elsewhere are never evicted.
+///
+/// With [`ShaderStore::enable_hot_reload`], shaders edited on disk are reloaded by
+/// [`ShaderStore::reload_changed`].
+pub struct ShaderStore {
+  asset_dir: PathBuf,
+  shaders: HashMap<(PathBuf, ShaderKind), CachedShader>,
+  budget: usize,
+  use_counter: u64,
+  watcher: Option<ShaderWatcher>,
+}
+
+impl Default for ShaderStore {
+  fn default() -> Self {
+    let asset_dir = std::env::current_exe()
+      .ok()
+      .and_then(|exe| exe.parent().map(Path::to_path_buf))
+      .unwrap_or_default()
+      .join(Self::SHADER_ASSET_DIR);
+
+    Self::new(asset_dir)
+  }
+}
+
+impl ShaderStore {
+  /// Built-in shaders compiled into the binary, used when their asset files are missing or broken.
+  const BUILTIN_SHADERS: &'static [(&'static str, &'static str)] = &[
+    ("debug_draw.wgsl", include_str!("../../assets/shaders/debug_draw.wgsl")),
+    ("hdr.wgsl", include_str!("../../assets/shaders/hdr.wgsl")),
+    ("hi_z.wgsl", include_str!("../../assets/shaders/hi_z.wgsl")),
+    ("outline.wgsl", include_str!("../../assets/shaders/outline.wgsl")),
+    ("placeholder.wgsl", include_str!("../../assets/shaders/placeholder.wgsl")),
+    ("sdf_text.wgsl", include_str!("../../assets/shaders/sdf_text.wgsl")),
+    ("shader.wgsl", include_str!("../../assets/shaders/shader.wgsl")),
+    ("skinning.wgsl", include_str!("../../assets/shaders/skinning.wgsl")),
+    ("texture.wgsl", include_str!("../../assets/shaders/texture.wgsl")),
+  ];
+  /// Snippets WGSL shaders can `#include` by name.
+  const SNIPPETS: &'static [(&'static str, &'static str)] = &[("noise.wgsl", Self::NOISE_SNIPPET)];
+  pub const DEFAULT_BUDGET: usize = 64;
+  /// Seedable Perlin, simplex and Worley noise, matching `foxy::math::noise` on the CPU.
+  pub const NOISE_SNIPPET: &'static str = include_str!("../../assets/shaders/noise.wgsl");
+  pub const SHADER_ASSET_DIR: &'static str = "assets/shaders";
+
+  pub fn new(asset_dir: impl Into<PathBuf>) -> Self {
+    Self {
+      asset_dir: asset_dir.into(),
+      shaders: HashMap::new(),
+      budget: Self::DEFAULT_BUDGET,
+      use_counter: 0,
+      watcher: None,
+    }
+  }
+
+  /// The number of cached shaders above which the least recently used, unreferenced ones are evicted.
+  pub fn with_budget(mut self, budget: usize) -> Self {
+    self.budget = budget;
+    self
+  }
+
+  pub fn asset_dir(&self) -> &Path {
+    &self.asset_dir
+  }
+
+  pub fn len(&self) -> usize {
+    self.shaders.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.shaders.is_empty()
+  }
+
+  pub fn contains(&self, path: impl AsRef<Path>, kind: ShaderKind) -> bool {
+    self.shaders.contains_key(&(path.as_ref().to_path_buf(), kind))
+  }
+
+  /// Every cached shader, e.g. for checking which ones need to be reloaded.
+  pub fn iter(&self) -> impl Iterator<Item = (&Path, ShaderKind, &Handle<Tracked<wgpu::ShaderModule>>)> {
+    self
+      .shaders
+      .iter()
+      .map(|((path, kind), shader)| (path.as_path(), *kind, &shader.module))
+  }
+
+  pub fn vertex(&mut self, device: &wgpu::Device, path: impl AsRef<Path>) -> Result<Shader<Vertex>, RendererError> {
+    self.get::<Vertex>(device, path)
+  }
+
+  pub fn fragment(&mut self, device: &wgpu::Device, path: impl AsRef<Path>) -> Result<Shader<Fragment>, RendererError> {
+    self.get::<Fragment>(device, path)
+  }
+
+  pub fn compute(&mut self, device: &wgpu::Device, path: impl AsRef<Path>) -> Result<Shader<Compute>, RendererError> {
+    self.get::<Compute>(device, path)
+  }
+
+  /// Returns the shader at `path` (relative to the asset directory), loading it on first use.
+  ///
+  /// If the shader can't be loaded, the embedded copy of the built-in shader with the same file name is used, or
+  /// the magenta error shader when there is none. Pipelines pick their entry points with [`Shader::entry_point`], so
+  /// that they still build with the error shader.
+  pub fn get<S: Stage>(&mut self, device: &wgpu::Device, path: impl AsRef<Path>) -> Result<Shader<S>, RendererError> {
+    let path = path.as_ref();
+    let module = self.get_module(device, path, S::KIND)?;
+    let is_error_shader = self
+      .shaders
+      .get(&(path.to_path_buf(), S::KIND))
+      .map(|shader| shader.is_error_shader.clone())
+      .unwrap_or_default();
+
+    Ok(Shader {
+      module,
+      is_error_shader,
+      error_entry_point: Self::error_entry_point(path, S::KIND),
+      _stage: PhantomData,
+    })
+  }
+
+  /// Like [`ShaderStore::get`], for when the stage is only known at runtime.
+  pub fn get_module(
+    &mut self,
+    device: &wgpu::Device,
+    path: impl AsRef<Path>,
+    kind: ShaderKind,
+  ) -> Result<Handle<Tracked<wgpu::ShaderModule>>, RendererError> {
+    let path = path.as_ref();
+    let key = (path.to_path_buf(), kind);
+    self.use_counter += 1;
+
+    if let Some(shader) = self.shaders.get_mut(&key) {
+      shader.last_used = self.use_counter;
+      if !shader.is_stale {
+        return Ok(shader.module.clone());
+      }
+
+      let module = Self::load(device, &self.asset_dir, path, kind)?;
+      let mut handle = shader.module.clone();
+      handle.replace(module);
+      // stages of the same WGSL file share this flag too
+      shader.is_error_shader.store(false, Ordering::Relaxed);
+      // stages of the same WGSL file share the module that was just reloaded
+      if Self::is_wgsl(path) {
+        for ((_, _), shader) in self.shaders.iter_mut().filter(|((p, _), _)| p == path) {
+          shader.is_stale = false;
+        }
+      } else if let Some(shader) = self.shaders.get_mut(&key) {
+        shader.is_stale = false;
+      }
+      return Ok(handle);
+    }
+
+    let shared = Self::is_wgsl(path)
+      .then(|| {
+        self
+          .shaders
+          .iter()
+          .find(|((p, _), shader)| p == path && !shader.is_stale)
+          .map(|(_, shader)| (shader.module.clone(), shader.is_error_shader.clone()))
+      })
+      .flatten();
+
+    let (module, is_error_shader) = match shared {
+      Some(shared) => shared,
+      None => {
+        let (module, is_error_shader) = match Self::load(device, &self.asset_dir, path, kind) {
+          Ok(module) => (module, false),
+          Err(error) => {
+            warn!("failed to load {kind:?} shader {path:?}, using fallback: {error}");
+            Self::load_fallback(device, path, kind)
+          }
+        };
+        (Handle::new(module), Arc::new(AtomicBool::new(is_error_shader)))
+      }
+    };
+
+    self.shaders.insert(key, CachedShader {
+      module: module.clone(),
+      is_error_shader,
+      last_used: self.use_counter,
+      is_stale: false,
+    });
+    self.evict();
+
+    Ok(module)
+  }
+
+  /// Drops the store's references to every stage loaded from `path`. Returns whether any were cached.
+  pub fn unload(&mut self, path: impl AsRef<Path>) -> bool {
+    let count = self.shaders.len();
+    self.shaders.retain(|(p, _), _| p != path.as_ref());
+    count != self.shaders.len()
+  }
+
+  /// Marks every stage loaded from `path` as out of date. The next `get` reloads it from disk and swaps the new
+  /// module into every outstanding handle. If reloading fails, the old module is kept.
+  pub fn invalidate(&mut self, path: impl AsRef<Path>) {
+    for ((_, _), shader) in self.shaders.iter_mut().filter(|((p, _), _)| p == path.as_ref()) {
+      shader.is_stale = true;
+    }
+  }
+
+  pub fn clear(&mut self) {
+    self.shaders.clear();
+  }
+
+  /// Watches the asset directory for changes, so that [`ShaderStore::reload_changed`] can reload edited shaders.
+  pub fn enable_hot_reload(&mut self) -> Result<(), RendererError> {
+    if self.watcher.is_some() {
+      return Ok(());
+    }
+
+    // events carry absolute paths, which have to be made relative to the directory again
+    let dir = self.asset_dir.canonicalize()?;
+    let (sender, changes) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+      Ok(event) if event.kind.is_create() || event.kind.is_modify() => {
+        for path in event.paths {
+          let _ = sender.send(path);
+        }
+      }
+      Ok(_) => (),
+      Err(error) => warn!("failed to watch shaders: {error}"),
+    })?;
+    watcher.watch(&dir, RecursiveMode::Recursive)?;
+
+    info!("hot reloading shaders in {dir:?}");
+    self.watcher = Some(ShaderWatcher {
+      _watcher: watcher,
+      dir,
+      changes,
+    });
+    Ok(())
+  }
+
+  pub fn is_hot_reloading(&self) -> bool {
+    self.watcher.is_some()
+  }
+
+  /// Reloads every cached shader whose file changed since the last call, swapping the new modules into their
+  /// handles. Shaders that fail to compile keep their old module. Returns the paths of the reloaded shaders, whose
+  /// pipelines have to be rebuilt to use them.
+  ///
+  /// Does nothing unless hot reloading is enabled.
+  pub fn reload_changed(&mut self, device: &wgpu::Device) -> Vec<PathBuf> {
+    let Some(watcher) = &self.watcher else {
+      return Vec::new();
+    };
+
+    // saving a file usually takes several events
+    let mut changed: Vec<PathBuf> = watcher
+      .changes
+      .try_iter()
+      .filter_map(|path| path.strip_prefix(&watcher.dir).ok().map(Path::to_path_buf))
+      .collect();
+    changed.sort();
+    changed.dedup();
+
+    let mut reloaded = Vec::new();
+    for path in changed {
+      let kinds: Vec<ShaderKind> = self
+        .shaders
+        .keys()
+        .filter(|(p, _)| *p == path)
+        .map(|(_, kind)| *kind)
+        .collect();
+      if kinds.is_empty() {
+        continue;
+      }
+
+      self.invalidate(&path);
+      let mut is_reloaded = false;
+      for kind in kinds {
+        match self.get_module(device, &path, kind) {
+          Ok(_) => is_reloaded = true,
+          Err(error) => {
+            error!("failed to reload {kind:?} shader {path:?}, keeping the old one: {error}");
+            // the stages of a WGSL file share one module, so the others would fail the same way
+            if Self::is_wgsl(&path) {
+              break;
+            }
+          }
+        }
+      }
+      // shaders that failed have nothing to retry until the file changes again
+      for ((_, _), shader) in self.shaders.iter_mut().filter(|((p, _), _)| *p == path) {
+        shader.is_stale = false;
+      }
+
+      if is_reloaded {
+        info!("reloaded shader {path:?}");
+        reloaded.push(path);
+      }
+    }
+
+    reloaded
+  }
+
+  /// Evicts least recently used shaders that nothing else references until the store fits its budget.
+  pub fn evict(&mut self) {
+    while self.shaders.len() > self.budget {
+      let lru = self
+        .shaders
+        .iter()
+        .filter(|((path, _), shader)| shader.module.strong_count() <= self.references_to(path))
+        .min_by_key(|(_, shader)| shader.last_used)
+        .map(|(key, _)| key.clone());
+
+      let Some((path, kind)) = lru else {
+        break;
+      };
+
+      trace!("evicting {kind:?} shader {path:?}");
+      if Self::is_wgsl(&path) {
+        self.unload(path);
+      } else {
+        self.shaders.remove(&(path, kind));
+      }
+    }
+  }
+}
+
+impl ShaderStore {
+  /// How many handles to the module of `path` are held by the store itself.
+  fn references_to(&self, path: &Path) -> usize {
+    if Self::is_wgsl(path) {
+      self.shaders.keys().filter(|(p, _)| p == path).count()
+    } else {
+      1
+    }
+  }
+
+  fn is_wgsl(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "wgsl")
+  }
+
+  /// The embedded copy of the built-in shader named like `path`, or the error shader, and whether it's the latter. The
+  /// GLSL error shaders only have a `main` entry point.
+  fn load_fallback(device: &wgpu::Device, path: &Path, kind: ShaderKind) -> (Tracked<wgpu::ShaderModule>, bool) {
+    let builtin = path.file_name().and_then(|name| {
+      Self::BUILTIN_SHADERS
+        .iter()
+        .find(|(builtin_name, _)| name == *builtin_name)
+        .map(|(_, source)| *source)
+    });
+
+    let source = match (builtin, Self::is_wgsl(path), kind) {
+      (Some(source), ..) => wgpu::ShaderSource::Wgsl(source.into()),
+      (None, true, _) => wgpu::ShaderSource::Wgsl(include_str!("../../assets/shaders/error.wgsl").into()),
+      (None, false, kind) => wgpu::ShaderSource::Glsl {
+        shader: match kind {
+          ShaderKind::Vertex => include_str!("../../assets/shaders/error.vert"),
+          ShaderKind::Fragment => include_str!("../../assets/shaders/error.frag"),
+          ShaderKind::Compute => include_str!("../../assets/shaders/error.comp"),
+        }
+        .into(),
+        stage: kind.into(),
+        defines: Default::default(),
+      },
+    };
+
+    let module = device.create_tracked_shader_module(wgpu::ShaderModuleDescriptor {
+      label: Some("Fallback Shader"),
+      source,
+    });
+    (module, builtin.is_none())
+  }
+
+  /// The entry point of the error shader standing in for a `kind` shader at `path`.
+  fn error_entry_point(path: &Path, kind: ShaderKind) -> &'static str {
+    match (Self::is_wgsl(path), kind) {
+      (true, ShaderKind::Vertex) => "vs_main",
+      (true, ShaderKind::Fragment) => "fs_main",
+      (true, ShaderKind::Compute) => "cs_main",
+      (false, _) => "main",
+    }
+  }
+
+  /// Replaces every `#include "<snippet>"` line with the snippet of that name.
+  fn resolve_includes(source: &str) -> Result<String, RendererError> {
+    let lines = source
+      .lines()
+      .map(|line| {
+        let Some(name) = line.trim().strip_prefix("#include") else {
+          return Ok(line);
+        };
+        let name = name.trim().trim_matches('"');
+        Self::SNIPPETS
+          .iter()
+          .find(|(snippet, _)| *snippet == name)
+          .map(|(_, snippet)| *snippet)
+          .ok_or_else(|| renderer_error!("no shader snippet named {name:?}"))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(lines.join("\n"))
+  }
+
+  fn load(
+    device: &wgpu::Device,
+    asset_dir: &Path,
+    path: &Path,
+    kind: ShaderKind,
+  ) -> Result<Tracked<wgpu::ShaderModule>, RendererError> {
+    let source = std::fs::read_to_string(asset_dir.join(path))?;
+
+    let source = match path.extension().and_then(|extension| extension.to_str()) {
+      Some("wgsl") => wgpu::ShaderSource::Wgsl(Self::resolve_includes(&source)?.into()),
+      Some("vert" | "frag" | "comp" | "glsl") => wgpu::ShaderSource::Glsl {
+        shader: source.into(),
+        stage: kind.into(),
+        defines: Default::default(),
+      },
+      _ => return Err(renderer_error!("unrecognized shader format: {path:?}")),
+    };
+
+    let module = validated(device, || {
+      device.create_tracked_shader_module(wgpu::ShaderModuleDescriptor {
+        label: path.to_str(),
+        source,
+      })
+    })?;
+
+    debug!("loaded {kind:?} shader {path:?}");
+    Ok(module)
+  }
+}
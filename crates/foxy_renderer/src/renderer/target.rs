@@ -1,26 +1,30 @@
-use std::sync::Arc;
-
+use foxy_utils::tracking::Tracked;
 use wgpu::{Device, TextureFormat};
-use winit::window::Window;
 
-use super::Renderer;
+use super::{tracking::TrackedDevice, Renderer};
 
 pub struct RenderTarget {
-  window: Arc<Window>,
-  pub texture: wgpu::Texture,
+  pub texture: Tracked<wgpu::Texture>,
   pub view: wgpu::TextureView,
   pub sampler: wgpu::Sampler,
+  pub depth_texture: Tracked<wgpu::Texture>,
+  /// The depth-stencil attachment.
+  pub depth_view: wgpu::TextureView,
+  /// The depth aspect alone, for binding the depth buffer to shaders.
+  pub depth_only_view: wgpu::TextureView,
+  reverse_z: bool,
 }
 
 impl RenderTarget {
+  pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth24PlusStencil8;
   pub const RENDER_TARGET_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
 
-  pub fn new(window: Arc<Window>, device: &Device) -> Self {
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
+  pub fn new(device: &Device, width: u32, height: u32, reverse_z: bool) -> Self {
+    let texture = device.create_tracked_texture(&wgpu::TextureDescriptor {
       label: Some("HDR Render Texture"),
       size: wgpu::Extent3d {
-        width: window.inner_size().width,
-        height: window.inner_size().height,
+        width: width.max(1),
+        height: height.max(1),
         depth_or_array_layers: 1,
       },
       mip_level_count: 1,
@@ -44,20 +48,25 @@ impl RenderTarget {
       ..Default::default()
     });
 
+    let (depth_texture, depth_view, depth_only_view) = Self::create_depth_texture(device, width, height);
+
     Self {
-      window,
       texture,
       view,
       sampler,
+      depth_texture,
+      depth_view,
+      depth_only_view,
+      reverse_z,
     }
   }
 
-  pub fn resize(&mut self, device: &Device) {
-    self.texture = device.create_texture(&wgpu::TextureDescriptor {
+  pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+    self.texture = device.create_tracked_texture(&wgpu::TextureDescriptor {
       label: Some("HDR Render Texture"),
       size: wgpu::Extent3d {
-        width: self.window.inner_size().width.max(1),
-        height: self.window.inner_size().height.max(1),
+        width: width.max(1),
+        height: height.max(1),
         depth_or_array_layers: 1,
       },
       mip_level_count: 1,
@@ -80,5 +89,67 @@ impl RenderTarget {
       mipmap_filter: wgpu::FilterMode::Nearest,
       ..Default::default()
     });
+
+    (self.depth_texture, self.depth_view, self.depth_only_view) = Self::create_depth_texture(device, width, height);
+  }
+
+  pub fn width(&self) -> u32 {
+    self.texture.width()
+  }
+
+  pub fn height(&self) -> u32 {
+    self.texture.height()
+  }
+
+  /// Whether depth is reversed, with the near plane at 1 and the far plane at 0. Spreads floating point precision
+  /// much more evenly across the view distance. Projections must map depth to the same range.
+  pub fn is_reverse_z(&self) -> bool {
+    self.reverse_z
+  }
+
+  /// The depth of the far plane, which the depth buffer is cleared to.
+  pub fn depth_clear_value(&self) -> f32 {
+    if self.reverse_z { 0.0 } else { 1.0 }
+  }
+
+  /// The comparison that passes for fragments nearer than the stored depth.
+  pub fn depth_compare(&self) -> wgpu::CompareFunction {
+    if self.reverse_z {
+      wgpu::CompareFunction::Greater
+    } else {
+      wgpu::CompareFunction::Less
+    }
+  }
+}
+
+impl RenderTarget {
+  fn create_depth_texture(
+    device: &Device,
+    width: u32,
+    height: u32,
+  ) -> (Tracked<wgpu::Texture>, wgpu::TextureView, wgpu::TextureView) {
+    let texture = device.create_tracked_texture(&wgpu::TextureDescriptor {
+      label: Some("Depth Texture"),
+      size: wgpu::Extent3d {
+        width: width.max(1),
+        height: height.max(1),
+        depth_or_array_layers: 1,
+      },
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: wgpu::TextureDimension::D2,
+      format: Self::DEPTH_FORMAT,
+      usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+      view_formats: &[],
+    });
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let depth_only_view = texture.create_view(&wgpu::TextureViewDescriptor {
+      label: Some("Depth Only View"),
+      aspect: wgpu::TextureAspect::DepthOnly,
+      ..Default::default()
+    });
+
+    (texture, view, depth_only_view)
   }
 }
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontVec, GlyphId, PxScale, ScaleFont};
+use glam::Vec2;
+
+use crate::{error::RendererError, renderer_error};
+
+/// Where a glyph sits in the atlas and how large it is relative to the font's line height.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SdfGlyph {
+  pub uv_min: Vec2,
+  pub uv_max: Vec2,
+  /// Bounds of the glyph's quad relative to its origin on the baseline, with y pointing up.
+  pub plane_min: Vec2,
+  pub plane_max: Vec2,
+}
+
+/// A quad of laid out text, in line heights from the origin of the first line.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GlyphQuad {
+  /// The line the glyph is on, counting from 0.
+  pub line: usize,
+  pub plane_min: Vec2,
+  pub plane_max: Vec2,
+  pub uv_min: Vec2,
+  pub uv_max: Vec2,
+}
+
+/// A font rasterized into a signed distance field atlas, so its glyphs stay sharp at any size, scale or rotation.
+///
+/// Each texel holds the distance to the glyph's outline, mapped so that 0.5 lies on the outline and values above it
+/// are inside. Distances are clamped to [`SdfFont::SPREAD`] texels, which also pads every glyph.
+pub struct SdfFont {
+  font: FontVec,
+  glyphs: HashMap<char, SdfGlyph>,
+  atlas: Vec<u8>,
+  atlas_width: u32,
+  atlas_height: u32,
+}
+
+impl SdfFont {
+  /// Printable ASCII.
+  pub const DEFAULT_CHARACTERS: std::ops::RangeInclusive<char> = ' '..='~';
+  /// The line height glyphs are rasterized at, in texels.
+  pub const GLYPH_SIZE: f32 = 48.0;
+  pub const SPREAD: u32 = 6;
+  const ATLAS_WIDTH: u32 = 512;
+
+  /// Rasterizes `characters` of the TrueType or OpenType font in `data`.
+  pub fn new(data: Vec<u8>, characters: impl IntoIterator<Item = char>) -> Result<Self, RendererError> {
+    let font = FontVec::try_from_vec(data).map_err(|error| renderer_error!("failed to parse font: {error}"))?;
+    let scaled = font.as_scaled(PxScale::from(Self::GLYPH_SIZE));
+    let spread = Self::SPREAD as f32;
+
+    let mut fields = Vec::new();
+    for character in characters {
+      let glyph = scaled.scaled_glyph(character);
+      let Some(outlined) = font.outline_glyph(glyph) else {
+        // whitespace has an advance but nothing to draw
+        continue;
+      };
+
+      let bounds = outlined.px_bounds();
+      let width = bounds.width().ceil() as u32 + 2 * Self::SPREAD;
+      let height = bounds.height().ceil() as u32 + 2 * Self::SPREAD;
+      let mut coverage = vec![0.0; (width * height) as usize];
+      outlined.draw(|x, y, value| {
+        let index = ((y + Self::SPREAD) * width + x + Self::SPREAD) as usize;
+        if let Some(texel) = coverage.get_mut(index) {
+          *texel = value;
+        }
+      });
+
+      let plane_min = Vec2::new(bounds.min.x - spread, -bounds.max.y - spread) / Self::GLYPH_SIZE;
+      let plane_max = Vec2::new(bounds.min.x + width as f32 - spread, -bounds.min.y + spread) / Self::GLYPH_SIZE;
+      fields.push((character, width, height, signed_distance_field(&coverage, width, height), plane_min, plane_max));
+    }
+
+    // pack glyphs into rows, then size the atlas to fit them
+    let mut cursor = (0, 0);
+    let mut row_height = 0;
+    let mut placements = Vec::with_capacity(fields.len());
+    for (_, width, height, ..) in &fields {
+      if cursor.0 + width > Self::ATLAS_WIDTH {
+        cursor = (0, cursor.1 + row_height);
+        row_height = 0;
+      }
+      placements.push(cursor);
+      cursor.0 += width;
+      row_height = row_height.max(*height);
+    }
+    let atlas_width = Self::ATLAS_WIDTH;
+    let atlas_height = (cursor.1 + row_height).max(1).next_power_of_two();
+
+    let mut atlas = vec![0; (atlas_width * atlas_height) as usize];
+    let mut glyphs = HashMap::with_capacity(fields.len());
+    let atlas_size = Vec2::new(atlas_width as f32, atlas_height as f32);
+    for ((character, width, height, field, plane_min, plane_max), (x, y)) in fields.into_iter().zip(placements) {
+      for (row, texels) in field.chunks_exact(width as usize).enumerate() {
+        let start = ((y + row as u32) * atlas_width + x) as usize;
+        if let Some(destination) = atlas.get_mut(start..start + texels.len()) {
+          destination.copy_from_slice(texels);
+        }
+      }
+
+      glyphs.insert(character, SdfGlyph {
+        // v is flipped so that the top of the glyph maps to plane_max.y
+        uv_min: Vec2::new(x as f32, (y + height) as f32) / atlas_size,
+        uv_max: Vec2::new((x + width) as f32, y as f32) / atlas_size,
+        plane_min,
+        plane_max,
+      });
+    }
+
+    Ok(Self {
+      font,
+      glyphs,
+      atlas,
+      atlas_width,
+      atlas_height,
+    })
+  }
+
+  /// The atlas as single channel texels, row by row.
+  pub fn atlas(&self) -> &[u8] {
+    &self.atlas
+  }
+
+  pub fn atlas_size(&self) -> (u32, u32) {
+    (self.atlas_width, self.atlas_height)
+  }
+
+  pub fn glyph(&self, character: char) -> Option<&SdfGlyph> {
+    self.glyphs.get(&character)
+  }
+
+  /// Lays out `text` in line heights, starting on the baseline of the first line and moving down for each `\n`.
+  /// Characters that weren't rasterized are skipped.
+  pub fn layout(&self, text: &str) -> Vec<GlyphQuad> {
+    let scaled = self.font.as_scaled(PxScale::from(Self::GLYPH_SIZE));
+    let line_height = (scaled.height() + scaled.line_gap()) / Self::GLYPH_SIZE;
+
+    let mut quads = Vec::with_capacity(text.len());
+    let mut pen = Vec2::ZERO;
+    let mut line = 0;
+    let mut previous: Option<GlyphId> = None;
+    for character in text.chars() {
+      if character == '\n' {
+        pen = Vec2::new(0.0, pen.y - line_height);
+        line += 1;
+        previous = None;
+        continue;
+      }
+
+      let id = scaled.glyph_id(character);
+      if let Some(previous) = previous {
+        pen.x += scaled.kern(previous, id) / Self::GLYPH_SIZE;
+      }
+      previous = Some(id);
+
+      if let Some(glyph) = self.glyphs.get(&character) {
+        quads.push(GlyphQuad {
+          line,
+          plane_min: pen + glyph.plane_min,
+          plane_max: pen + glyph.plane_max,
+          uv_min: glyph.uv_min,
+          uv_max: glyph.uv_max,
+        });
+      }
+      pen.x += scaled.h_advance(id) / Self::GLYPH_SIZE;
+    }
+
+    quads
+  }
+}
+
+/// Turns glyph coverage into distances to its outline, encoded as described on [`SdfFont`].
+fn signed_distance_field(coverage: &[f32], width: u32, height: u32) -> Vec<u8> {
+  let inside = coverage.iter().map(|value| *value >= 0.5).collect::<Vec<_>>();
+  let to_inside = distance_transform(&inside, width, height);
+  let outside = inside.iter().map(|inside| !inside).collect::<Vec<_>>();
+  let to_outside = distance_transform(&outside, width, height);
+
+  to_inside
+    .iter()
+    .zip(&to_outside)
+    .map(|(to_inside, to_outside)| {
+      let distance = to_outside.sqrt() - to_inside.sqrt();
+      let value = 0.5 + distance / (2.0 * SdfFont::SPREAD as f32);
+      (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    })
+    .collect()
+}
+
+/// Squared distance from every texel to the nearest texel where `mask` is set, using the separable algorithm by
+/// Felzenszwalb and Huttenlocher.
+fn distance_transform(mask: &[bool], width: u32, height: u32) -> Vec<f32> {
+  let (width, height) = (width as usize, height as usize);
+  let mut grid = mask
+    .iter()
+    .map(|set| if *set { 0.0 } else { f32::INFINITY })
+    .collect::<Vec<_>>();
+
+  let mut line = vec![0.0; width.max(height)];
+  let mut output = vec![0.0; width.max(height)];
+  for x in 0..width {
+    for (y, value) in line.iter_mut().take(height).enumerate() {
+      *value = grid.get(y * width + x).copied().unwrap_or(f32::INFINITY);
+    }
+    distance_transform_1d(line.get(..height).unwrap_or_default(), &mut output);
+    for (y, value) in output.iter().take(height).enumerate() {
+      if let Some(texel) = grid.get_mut(y * width + x) {
+        *texel = *value;
+      }
+    }
+  }
+
+  for row in grid.chunks_exact_mut(width) {
+    distance_transform_1d(row, &mut output);
+    row.copy_from_slice(output.get(..width).unwrap_or_default());
+  }
+
+  grid
+}
+
+#[allow(clippy::indexing_slicing)] // every index is below `f.len()`, which `output` is at least as long as
+fn distance_transform_1d(f: &[f32], output: &mut [f32]) {
+  let n = f.len();
+  if n == 0 {
+    return;
+  }
+
+  // parabolas of the lower envelope, and where each one starts
+  let mut vertices = vec![0; n];
+  let mut boundaries = vec![0.0; n + 1];
+  let mut k = 0;
+  boundaries[0] = f32::NEG_INFINITY;
+  boundaries[1] = f32::INFINITY;
+
+  let intersection = |f: &[f32], q: usize, p: usize| {
+    ((f[q] + (q * q) as f32) - (f[p] + (p * p) as f32)) / (2.0 * q as f32 - 2.0 * p as f32)
+  };
+
+  for q in 1..n {
+    if f[q].is_infinite() {
+      continue;
+    }
+    if f[vertices[k]].is_infinite() {
+      vertices[k] = q;
+      continue;
+    }
+
+    let mut s = intersection(f, q, vertices[k]);
+    while s <= boundaries[k] && k > 0 {
+      k -= 1;
+      s = intersection(f, q, vertices[k]);
+    }
+    k += 1;
+    vertices[k] = q;
+    boundaries[k] = s;
+    boundaries[k + 1] = f32::INFINITY;
+  }
+
+  k = 0;
+  for (q, value) in output.iter_mut().enumerate().take(n) {
+    while boundaries[k + 1] < q as f32 {
+      k += 1;
+    }
+    let p = vertices[k];
+    let offset = q as f32 - p as f32;
+    *value = offset * offset + f[p];
+  }
+}
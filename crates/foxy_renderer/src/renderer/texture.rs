@@ -1,53 +1,99 @@
 use std::{
-  cell::OnceCell,
-  mem::size_of,
-  sync::{Arc, OnceLock},
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::{Arc, OnceLock, Weak},
 };
 
-use image::{EncodableLayout, GenericImageView, Pixel};
-use itertools::Itertools;
-use wgpu::{Device, Extent3d, Queue, Texture};
-use winit::window::Window;
+use foxy_utils::tracking::Tracked;
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use tracing::*;
+use wgpu::{Device, Queue, Texture};
 
-use super::target::RenderTarget;
+use super::{
+  procedural::{ProceduralKey, ProceduralTexture},
+  shader::ShaderStore,
+  tracking::TrackedDevice,
+};
+use crate::error::RendererError;
+
+/// How a texture is filtered and wrapped when it's sampled.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Sampling {
+  pub filter: wgpu::FilterMode,
+  pub address_mode: wgpu::AddressMode,
+  /// Generates the full chain of mipmaps on upload, so textures don't shimmer when they're drawn small.
+  pub mipmaps: bool,
+}
+
+impl Default for Sampling {
+  fn default() -> Self {
+    Self {
+      filter: wgpu::FilterMode::Linear,
+      address_mode: wgpu::AddressMode::Repeat,
+      mipmaps: true,
+    }
+  }
+}
+
+impl Sampling {
+  /// Sharp, unfiltered texels without mipmaps, e.g. for pixel art.
+  pub const PIXELATED: Self = Self {
+    filter: wgpu::FilterMode::Nearest,
+    address_mode: wgpu::AddressMode::ClampToEdge,
+    mipmaps: false,
+  };
+
+  pub fn with_filter(mut self, filter: wgpu::FilterMode) -> Self {
+    self.filter = filter;
+    self
+  }
+
+  pub fn with_address_mode(mut self, address_mode: wgpu::AddressMode) -> Self {
+    self.address_mode = address_mode;
+    self
+  }
+
+  pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+    self.mipmaps = mipmaps;
+    self
+  }
+}
 
 pub struct DiffuseTexture {
-  pub texture: Texture,
+  pub texture: Tracked<Texture>,
   pub view: wgpu::TextureView,
   pub sampler: wgpu::Sampler,
   pub bind_group: wgpu::BindGroup,
 }
 
 impl DiffuseTexture {
+  /// The plain white texture of materials without an albedo, also used in place of textures that fail to load.
+  pub const DEFAULT: &'static [u8] = include_bytes!("../../assets/textures/default.png");
+
   pub fn new(device: &Device, queue: &Queue, bytes: &[u8]) -> Self {
     let diffuse_image = image::load_from_memory(bytes).unwrap();
-    let dimensions = diffuse_image.dimensions();
-    let data = diffuse_image.to_rgba8();
+    Self::from_image(device, queue, &diffuse_image)
+  }
 
-    let size = wgpu::Extent3d {
-      width: dimensions.0,
-      height: dimensions.1,
-      depth_or_array_layers: 1,
-    };
+  pub fn from_image(device: &Device, queue: &Queue, diffuse_image: &DynamicImage) -> Self {
+    Self::with_sampling(device, queue, diffuse_image, Sampling::PIXELATED)
+  }
 
-    let unpadded_bytes_per_row = size_of::<[u8; 4]>() * size.width as usize;
-    let padding = (256 - (unpadded_bytes_per_row % 256)) % 256;
-    let mut padded_data = Vec::with_capacity((unpadded_bytes_per_row + padding) * size.height as usize);
-    for (_, row) in data.enumerate_rows() {
-      padded_data.extend_from_slice(
-        &row
-          .flat_map(|(_, _, value)| {
-            let bytes = value.channels();
-            bytes.as_bytes().to_vec()
-          })
-          .collect_vec(),
-      );
-      padded_data.resize(padded_data.len() + padding, 0);
-    }
+  pub fn with_sampling(device: &Device, queue: &Queue, diffuse_image: &DynamicImage, sampling: Sampling) -> Self {
+    let (width, height) = diffuse_image.dimensions();
+    let mip_level_count = if sampling.mipmaps {
+      u32::BITS - width.max(height).max(1).leading_zeros()
+    } else {
+      1
+    };
 
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-      size,
-      mip_level_count: 1,
+    let texture = device.create_tracked_texture(&wgpu::TextureDescriptor {
+      size: wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+      },
+      mip_level_count,
       sample_count: 1,
       dimension: wgpu::TextureDimension::D2,
       format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -56,30 +102,61 @@ impl DiffuseTexture {
       view_formats: &[],
     });
 
+    // mipmaps are downsampled from the full image rather than from each other, which keeps them sharper
+    for mip_level in 0..mip_level_count {
+      let mip_width = (width >> mip_level).max(1);
+      let mip_height = (height >> mip_level).max(1);
+      let data = if mip_level == 0 {
+        diffuse_image.to_rgba8()
+      } else {
+        diffuse_image
+          .resize_exact(mip_width, mip_height, FilterType::Triangle)
+          .to_rgba8()
+      };
+
+      queue.write_texture(
+        wgpu::ImageCopyTexture {
+          texture: &texture,
+          mip_level,
+          origin: wgpu::Origin3d::ZERO,
+          aspect: wgpu::TextureAspect::All,
+        },
+        &data,
+        wgpu::ImageDataLayout {
+          offset: 0,
+          bytes_per_row: Some(4 * mip_width),
+          rows_per_image: Some(mip_height),
+        },
+        wgpu::Extent3d {
+          width: mip_width,
+          height: mip_height,
+          depth_or_array_layers: 1,
+        },
+      );
+    }
+
     let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    Self::from_view(device, texture, view, sampling)
+  }
 
+  /// Wraps a texture that's already been filled, sampled through `view`.
+  pub(crate) fn from_view(
+    device: &Device,
+    texture: Tracked<Texture>,
+    view: wgpu::TextureView,
+    sampling: Sampling,
+  ) -> Self {
     let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
       label: Some("Diffuse Sampler"),
-      address_mode_u: wgpu::AddressMode::ClampToEdge,
-      address_mode_v: wgpu::AddressMode::ClampToEdge,
-      address_mode_w: wgpu::AddressMode::ClampToEdge,
-      mag_filter: wgpu::FilterMode::Nearest,
-      min_filter: wgpu::FilterMode::Nearest,
-      mipmap_filter: wgpu::FilterMode::Nearest,
+      address_mode_u: sampling.address_mode,
+      address_mode_v: sampling.address_mode,
+      address_mode_w: sampling.address_mode,
+      mag_filter: sampling.filter,
+      min_filter: sampling.filter,
+      mipmap_filter: sampling.filter,
       ..Default::default()
     });
 
-    queue.write_texture(
-      texture.as_image_copy(),
-      &padded_data,
-      wgpu::ImageDataLayout {
-        offset: 0,
-        bytes_per_row: Some((unpadded_bytes_per_row + padding) as u32),
-        rows_per_image: Some(size.height),
-      },
-      size,
-    );
-
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
       label: Some("Diffuse Bind Group"),
       layout: Self::bind_group_layout(device),
@@ -133,3 +210,185 @@ impl DiffuseTexture {
     })
   }
 }
+
+struct CachedTexture {
+  texture: Arc<DiffuseTexture>,
+  last_used: u64,
+}
+
+/// Loads PNG and JPEG textures from the asset directory and caches them by path and sampling.
+///
+/// Textures that can't be loaded are replaced by [`DiffuseTexture::DEFAULT`], so a missing file shows up as an
+/// untextured mesh rather than stopping the app.
+///
+/// Textures returned by the store stay valid after an entry is unloaded or evicted; the store only drops its own
+/// reference. Entries that are still referenced elsewhere are never evicted.
+///
+/// [`ProceduralTexture`]s are cached apart from the budget, for only as long as they're referenced elsewhere.
+pub struct TextureStore {
+  asset_dir: PathBuf,
+  textures: HashMap<(PathBuf, Sampling), CachedTexture>,
+  procedural: HashMap<ProceduralKey, Weak<DiffuseTexture>>,
+  budget: usize,
+  use_counter: u64,
+}
+
+impl Default for TextureStore {
+  fn default() -> Self {
+    let asset_dir = std::env::current_exe()
+      .ok()
+      .and_then(|exe| exe.parent().map(Path::to_path_buf))
+      .unwrap_or_default()
+      .join(Self::TEXTURE_ASSET_DIR);
+
+    Self::new(asset_dir)
+  }
+}
+
+impl TextureStore {
+  pub const DEFAULT_BUDGET: usize = 256;
+  pub const TEXTURE_ASSET_DIR: &'static str = "assets/textures";
+
+  pub fn new(asset_dir: impl Into<PathBuf>) -> Self {
+    Self {
+      asset_dir: asset_dir.into(),
+      textures: HashMap::new(),
+      procedural: HashMap::new(),
+      budget: Self::DEFAULT_BUDGET,
+      use_counter: 0,
+    }
+  }
+
+  /// The number of cached textures above which the least recently used, unreferenced ones are evicted.
+  pub fn with_budget(mut self, budget: usize) -> Self {
+    self.budget = budget;
+    self
+  }
+
+  pub fn asset_dir(&self) -> &Path {
+    &self.asset_dir
+  }
+
+  pub fn len(&self) -> usize {
+    self.textures.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.textures.is_empty()
+  }
+
+  pub fn contains(&self, path: impl AsRef<Path>, sampling: Sampling) -> bool {
+    self.textures.contains_key(&(path.as_ref().to_path_buf(), sampling))
+  }
+
+  /// Returns the texture at `path` (relative to the asset directory) with the default [`Sampling`], loading it on
+  /// first use.
+  pub fn get(&mut self, device: &Device, queue: &Queue, path: impl AsRef<Path>) -> Arc<DiffuseTexture> {
+    self.get_with_sampling(device, queue, path, Sampling::default())
+  }
+
+  /// Like [`TextureStore::get`]. The same file sampled in different ways is cached once per sampling.
+  pub fn get_with_sampling(
+    &mut self,
+    device: &Device,
+    queue: &Queue,
+    path: impl AsRef<Path>,
+    sampling: Sampling,
+  ) -> Arc<DiffuseTexture> {
+    let key = (path.as_ref().to_path_buf(), sampling);
+    self.use_counter += 1;
+
+    if let Some(cached) = self.textures.get_mut(&key) {
+      cached.last_used = self.use_counter;
+      return cached.texture.clone();
+    }
+
+    let texture = Arc::new(Self::load_or_fallback(device, queue, &self.asset_dir, &key.0, sampling));
+    self.textures.insert(key, CachedTexture {
+      texture: texture.clone(),
+      last_used: self.use_counter,
+    });
+    self.evict();
+
+    texture
+  }
+
+  /// Returns the texture `procedural` generates, generating it unless the same texture is still in use.
+  pub fn get_procedural(
+    &mut self,
+    device: &Device,
+    queue: &Queue,
+    shader_store: &mut ShaderStore,
+    procedural: &ProceduralTexture,
+  ) -> Result<Arc<DiffuseTexture>, RendererError> {
+    let key = procedural.key();
+    if let Some(texture) = self.procedural.get(&key).and_then(Weak::upgrade) {
+      return Ok(texture);
+    }
+
+    let texture = Arc::new(procedural.generate(device, queue, shader_store)?);
+    self.procedural.retain(|_, texture| texture.strong_count() > 0);
+    self.procedural.insert(key, Arc::downgrade(&texture));
+    Ok(texture)
+  }
+
+  /// Drops the store's references to `path` in every sampling. Returns whether any were cached.
+  pub fn unload(&mut self, path: impl AsRef<Path>) -> bool {
+    let count = self.textures.len();
+    self.textures.retain(|(p, _), _| p != path.as_ref());
+    count != self.textures.len()
+  }
+
+  pub fn clear(&mut self) {
+    self.textures.clear();
+    self.procedural.clear();
+  }
+
+  /// Evicts least recently used textures that nothing else references until the store fits its budget.
+  pub fn evict(&mut self) {
+    while self.textures.len() > self.budget {
+      let lru = self
+        .textures
+        .iter()
+        .filter(|(_, cached)| Arc::strong_count(&cached.texture) == 1)
+        .min_by_key(|(_, cached)| cached.last_used)
+        .map(|(key, _)| key.clone());
+
+      let Some(key) = lru else {
+        break;
+      };
+
+      trace!("evicting texture {:?}", key.0);
+      self.textures.remove(&key);
+    }
+  }
+}
+
+impl TextureStore {
+  fn load_or_fallback(
+    device: &Device,
+    queue: &Queue,
+    asset_dir: &Path,
+    path: &Path,
+    sampling: Sampling,
+  ) -> DiffuseTexture {
+    Self::load(device, queue, asset_dir, path, sampling).unwrap_or_else(|error| {
+      warn!("failed to load texture {path:?}, using the default texture: {error}");
+      let image = image::load_from_memory(DiffuseTexture::DEFAULT).expect("the default texture is a valid image");
+      DiffuseTexture::with_sampling(device, queue, &image, sampling)
+    })
+  }
+
+  fn load(
+    device: &Device,
+    queue: &Queue,
+    asset_dir: &Path,
+    path: &Path,
+    sampling: Sampling,
+  ) -> Result<DiffuseTexture, RendererError> {
+    let bytes = std::fs::read(asset_dir.join(path))?;
+    let image = image::load_from_memory(&bytes)?;
+    debug!("loaded texture {path:?} ({}x{})", image.width(), image.height());
+    Ok(DiffuseTexture::with_sampling(device, queue, &image, sampling))
+  }
+}
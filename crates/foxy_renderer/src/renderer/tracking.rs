@@ -0,0 +1,88 @@
+use foxy_utils::tracking::{ResourceKind, Tracked};
+use wgpu::util::DeviceExt;
+
+/// Creates GPU resources registered with the resource registry, so that any still alive at shutdown are reported as
+/// leaks. Every resource the renderer owns should be created through these.
+pub trait TrackedDevice {
+  fn create_tracked_buffer(&self, descriptor: &wgpu::BufferDescriptor) -> Tracked<wgpu::Buffer>;
+
+  fn create_tracked_buffer_init(&self, descriptor: &wgpu::util::BufferInitDescriptor) -> Tracked<wgpu::Buffer>;
+
+  fn create_tracked_texture(&self, descriptor: &wgpu::TextureDescriptor) -> Tracked<wgpu::Texture>;
+
+  fn create_tracked_render_pipeline(
+    &self,
+    descriptor: &wgpu::RenderPipelineDescriptor,
+  ) -> Tracked<wgpu::RenderPipeline>;
+
+  fn create_tracked_compute_pipeline(
+    &self,
+    descriptor: &wgpu::ComputePipelineDescriptor,
+  ) -> Tracked<wgpu::ComputePipeline>;
+
+  fn create_tracked_shader_module(&self, descriptor: wgpu::ShaderModuleDescriptor) -> Tracked<wgpu::ShaderModule>;
+}
+
+impl TrackedDevice for wgpu::Device {
+  fn create_tracked_buffer(&self, descriptor: &wgpu::BufferDescriptor) -> Tracked<wgpu::Buffer> {
+    let buffer = self.create_buffer(descriptor);
+    Tracked::new(ResourceKind::Buffer, label(descriptor.label), buffer.size(), buffer)
+  }
+
+  fn create_tracked_buffer_init(&self, descriptor: &wgpu::util::BufferInitDescriptor) -> Tracked<wgpu::Buffer> {
+    let buffer = self.create_buffer_init(descriptor);
+    Tracked::new(ResourceKind::Buffer, label(descriptor.label), buffer.size(), buffer)
+  }
+
+  fn create_tracked_texture(&self, descriptor: &wgpu::TextureDescriptor) -> Tracked<wgpu::Texture> {
+    let texture = self.create_texture(descriptor);
+    let bytes = texture_size(descriptor);
+    Tracked::new(ResourceKind::Texture, label(descriptor.label), bytes, texture)
+  }
+
+  fn create_tracked_render_pipeline(
+    &self,
+    descriptor: &wgpu::RenderPipelineDescriptor,
+  ) -> Tracked<wgpu::RenderPipeline> {
+    let pipeline = self.create_render_pipeline(descriptor);
+    Tracked::new(ResourceKind::Pipeline, label(descriptor.label), 0, pipeline)
+  }
+
+  fn create_tracked_compute_pipeline(
+    &self,
+    descriptor: &wgpu::ComputePipelineDescriptor,
+  ) -> Tracked<wgpu::ComputePipeline> {
+    let pipeline = self.create_compute_pipeline(descriptor);
+    Tracked::new(ResourceKind::Pipeline, label(descriptor.label), 0, pipeline)
+  }
+
+  fn create_tracked_shader_module(&self, descriptor: wgpu::ShaderModuleDescriptor) -> Tracked<wgpu::ShaderModule> {
+    let label = label(descriptor.label);
+    let module = self.create_shader_module(descriptor);
+    Tracked::new(ResourceKind::ShaderModule, label, 0, module)
+  }
+}
+
+fn label(label: wgpu::Label) -> String {
+  label.unwrap_or("unlabeled").to_string()
+}
+
+/// The memory a texture takes with all of its mips, layers and samples.
+fn texture_size(descriptor: &wgpu::TextureDescriptor) -> u64 {
+  let format = descriptor.format;
+  let (block_width, block_height) = format.block_dimensions();
+  // depth formats have no fixed size; most are 4 bytes per texel
+  let block_size = format
+    .block_copy_size(None)
+    .or_else(|| format.block_copy_size(Some(wgpu::TextureAspect::DepthOnly)))
+    .unwrap_or(4);
+
+  (0..descriptor.mip_level_count)
+    .filter_map(|level| descriptor.mip_level_size(level))
+    .map(|size| {
+      let size = size.physical_size(format);
+      let blocks = (size.width / block_width) as u64 * (size.height / block_height) as u64;
+      blocks * size.depth_or_array_layers as u64 * block_size as u64 * descriptor.sample_count as u64
+    })
+    .sum()
+}
@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use bytemuck::{Pod, Zeroable};
+use glam::Mat4;
 
 // pub trait Vertex {
 //   fn desc() -> wgpu::VertexBufferLayout<'static>;
@@ -50,3 +51,69 @@ impl Vertex {
     self
   }
 }
+
+/// The data of one instance of a mesh, read from a second vertex buffer that steps once per instance.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct Instance {
+  pub model: [[f32; 4]; 4],
+}
+
+impl From<Mat4> for Instance {
+  fn from(model: Mat4) -> Self {
+    Self {
+      model: model.to_cols_array_2d(),
+    }
+  }
+}
+
+impl Instance {
+  pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+    // the model matrix takes up one location per column
+    const ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+      wgpu::vertex_attr_array![3 => Float32x4, 4 => Float32x4, 5 => Float32x4, 6 => Float32x4];
+    wgpu::VertexBufferLayout {
+      array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+      step_mode: wgpu::VertexStepMode::Instance,
+      attributes: &ATTRIBUTES,
+    }
+  }
+}
+
+/// A vertex influenced by up to four joints, for GPU skinning. Weights should sum to one, and unused influences
+/// should have a weight of zero.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+pub struct SkinnedVertex {
+  pub position: [f32; 3],
+  pub color: [f32; 4],
+  pub uv: [f32; 2],
+  pub joints: [u32; 4],
+  pub weights: [f32; 4],
+}
+
+impl Default for SkinnedVertex {
+  fn default() -> Self {
+    Self::from(Vertex::default())
+  }
+}
+
+impl From<Vertex> for SkinnedVertex {
+  fn from(vertex: Vertex) -> Self {
+    Self {
+      position: vertex.position,
+      color: vertex.color,
+      uv: vertex.uv,
+      joints: [0; 4],
+      weights: [1.0, 0.0, 0.0, 0.0],
+    }
+  }
+}
+
+impl SkinnedVertex {
+  pub fn with_joints(mut self, joints: [u32; 4], weights: [f32; 4]) -> Self {
+    self.joints = joints;
+    self.weights = weights;
+    self
+  }
+}
@@ -0,0 +1,26 @@
+use std::{env, path::PathBuf};
+
+fn main() {
+  println!("cargo:rerun-if-env-changed=STEAMWORKS_SDK");
+  if env::var_os("CARGO_FEATURE_STEAMWORKS").is_none() {
+    return;
+  }
+
+  let Some(sdk) = env::var_os("STEAMWORKS_SDK") else {
+    panic!("the `steamworks` feature needs `STEAMWORKS_SDK` set to the Steamworks SDK's directory");
+  };
+
+  let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+  let pointer_width = env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap_or_default();
+  // the 32-bit Windows library sits at the root of `redistributable_bin`
+  let platform = match (target_os.as_str(), pointer_width.as_str()) {
+    ("windows", "64") => "win64",
+    ("windows", _) => "",
+    ("macos", _) => "osx",
+    (_, "64") => "linux64",
+    _ => "linux32",
+  };
+
+  let library_dir = PathBuf::from(sdk).join("redistributable_bin").join(platform);
+  println!("cargo:rustc-link-search=native={}", library_dir.display());
+}
@@ -0,0 +1,207 @@
+//! Steamworks for Foxy: achievements, rich presence and the Steam overlay.
+//!
+//! Add [`SteamPlugin`] with [`FoxyCreateInfo::with_plugin`] and keep its [`Steam`] handle to talk to Steam from the
+//! app. The plugin pumps Steam's callbacks once per frame on the game thread.
+//!
+//! The overlay draws itself by hooking the graphics API's present call, which works with the renderer's swapchain on
+//! every wgpu backend it supports, as long as the game was launched through Steam (Vulkan relies on Steam's implicit
+//! overlay layer). It only redraws when the game presents, so the plugin keeps frames coming while it's open, even if
+//! the window would otherwise sit idle.
+//!
+//! [`FoxyCreateInfo::with_plugin`]: foxy::core::builder::FoxyCreateInfo::with_plugin
+
+#![deny(unsafe_op_in_unsafe_fn)]
+
+mod sys;
+
+use std::{
+  ffi::{c_void, CStr, CString},
+  mem,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+    Mutex,
+  },
+};
+
+use foxy::core::{event::FoxyEvent, foxy_state::Foxy, plugin::Plugin, FoxyError, FoxyResult};
+use tracing::*;
+
+/// Something Steam told the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SteamEvent {
+  /// The overlay opened (`true`) or closed. Games usually pause while it's open.
+  OverlayActivated(bool),
+}
+
+/// A handle to the Steam client, shared between the app and the plugin.
+#[derive(Clone)]
+pub struct Steam(Arc<Client>);
+
+struct Client {
+  pipe: sys::HSteamPipe,
+  user_stats: *mut c_void,
+  friends: *mut c_void,
+  utils: *mut c_void,
+  overlay_active: AtomicBool,
+  events: Mutex<Vec<SteamEvent>>,
+}
+
+// The Steamworks interfaces may be called from any thread.
+unsafe impl Send for Client {}
+unsafe impl Sync for Client {}
+
+impl Drop for Client {
+  fn drop(&mut self) {
+    unsafe { sys::SteamAPI_Shutdown() };
+  }
+}
+
+impl Steam {
+  /// Unlocks an achievement by its API name. It's only saved once [`Steam::store_stats`] is called.
+  pub fn set_achievement(&self, name: &str) -> FoxyResult<()> {
+    let name = Self::c_string(name)?;
+    Self::check(
+      unsafe { sys::SteamAPI_ISteamUserStats_SetAchievement(self.0.user_stats, name.as_ptr()) },
+      "set achievement",
+    )
+  }
+
+  pub fn clear_achievement(&self, name: &str) -> FoxyResult<()> {
+    let name = Self::c_string(name)?;
+    Self::check(
+      unsafe { sys::SteamAPI_ISteamUserStats_ClearAchievement(self.0.user_stats, name.as_ptr()) },
+      "clear achievement",
+    )
+  }
+
+  /// Uploads changed stats and achievements, showing the unlock popup for new achievements.
+  pub fn store_stats(&self) -> FoxyResult<()> {
+    Self::check(
+      unsafe { sys::SteamAPI_ISteamUserStats_StoreStats(self.0.user_stats) },
+      "store stats",
+    )
+  }
+
+  /// Sets a rich presence key, e.g. `steam_display` to a localization token from the Steamworks partner site.
+  pub fn set_rich_presence(&self, key: &str, value: &str) -> FoxyResult<()> {
+    let (key, value) = (Self::c_string(key)?, Self::c_string(value)?);
+    Self::check(
+      unsafe { sys::SteamAPI_ISteamFriends_SetRichPresence(self.0.friends, key.as_ptr(), value.as_ptr()) },
+      "set rich presence",
+    )
+  }
+
+  pub fn clear_rich_presence(&self) {
+    unsafe { sys::SteamAPI_ISteamFriends_ClearRichPresence(self.0.friends) };
+  }
+
+  pub fn is_overlay_enabled(&self) -> bool {
+    unsafe { sys::SteamAPI_ISteamUtils_IsOverlayEnabled(self.0.utils) }
+  }
+
+  pub fn is_overlay_active(&self) -> bool {
+    self.0.overlay_active.load(Ordering::Relaxed)
+  }
+
+  /// What Steam told the game since the last call, oldest first.
+  pub fn take_events(&self) -> Vec<SteamEvent> {
+    mem::take(&mut *self.0.events.lock().expect("steam events poisoned"))
+  }
+
+  fn c_string(text: &str) -> FoxyResult<CString> {
+    CString::new(text).map_err(|_| FoxyError::Error(format!("`{text}` contains a nul byte")))
+  }
+
+  fn check(succeeded: bool, action: &str) -> FoxyResult<()> {
+    if succeeded {
+      Ok(())
+    } else {
+      Err(FoxyError::Error(format!("steam failed to {action}")))
+    }
+  }
+}
+
+/// Runs Steam's callbacks every frame and keeps the overlay drawn while it's open.
+pub struct SteamPlugin {
+  steam: Steam,
+}
+
+impl SteamPlugin {
+  /// Connects to the running Steam client. Fails if Steam isn't running, the game isn't owned, or the crate was built
+  /// without the `steamworks` feature.
+  pub fn new() -> FoxyResult<Self> {
+    let mut error: sys::SteamErrMsg = [0; 1024];
+    let client = unsafe {
+      // must come before initializing, so callbacks wait for the plugin instead of going to C++ listeners
+      sys::SteamAPI_ManualDispatch_Init();
+      if sys::SteamAPI_InitFlat(&mut error) != sys::STEAM_API_INIT_RESULT_OK {
+        let error = CStr::from_ptr(error.as_ptr()).to_string_lossy();
+        return Err(FoxyError::Error(format!("failed to initialize steam: {error}")));
+      }
+
+      let user_stats = sys::SteamAPI_SteamUserStats_v012();
+      sys::SteamAPI_ISteamUserStats_RequestCurrentStats(user_stats);
+
+      Client {
+        pipe: sys::SteamAPI_GetHSteamPipe(),
+        user_stats,
+        friends: sys::SteamAPI_SteamFriends_v017(),
+        utils: sys::SteamAPI_SteamUtils_v010(),
+        overlay_active: AtomicBool::new(false),
+        events: Mutex::new(Vec::new()),
+      }
+    };
+
+    Ok(Self {
+      steam: Steam(Arc::new(client)),
+    })
+  }
+
+  pub fn steam(&self) -> Steam {
+    self.steam.clone()
+  }
+
+  fn handle_callback(&self, message: &sys::CallbackMsg) {
+    if message.callback == sys::GameOverlayActivated::CALLBACK {
+      let activated = unsafe { &*(message.param as *const sys::GameOverlayActivated) };
+      let active = activated.active != 0;
+      self.steam.0.overlay_active.store(active, Ordering::Relaxed);
+      self
+        .steam
+        .0
+        .events
+        .lock()
+        .expect("steam events poisoned")
+        .push(SteamEvent::OverlayActivated(active));
+    }
+  }
+}
+
+impl Plugin for SteamPlugin {
+  fn name(&self) -> &str {
+    "steam"
+  }
+
+  fn start(&mut self, _foxy: &Foxy) {
+    if !self.steam.is_overlay_enabled() {
+      info!("Steam overlay is disabled");
+    }
+  }
+
+  fn update(&mut self, foxy: &Foxy, _event: &FoxyEvent) {
+    let pipe = self.steam.0.pipe;
+    let mut message = sys::CallbackMsg::default();
+    unsafe {
+      sys::SteamAPI_ManualDispatch_RunFrame(pipe);
+      while sys::SteamAPI_ManualDispatch_GetNextCallback(pipe, &mut message) {
+        self.handle_callback(&message);
+        sys::SteamAPI_ManualDispatch_FreeLastCallback(pipe);
+      }
+    }
+
+    if unsafe { sys::SteamAPI_ISteamUtils_BOverlayNeedsPresent(self.steam.0.utils) } {
+      foxy.read().window().request_redraw();
+    }
+  }
+}
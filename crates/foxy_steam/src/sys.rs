@@ -0,0 +1,139 @@
+//! The parts of the Steamworks SDK 1.58 flat API the plugin uses.
+
+#![allow(non_snake_case, dead_code)]
+
+use std::ffi::{c_char, c_void};
+
+pub type HSteamPipe = i32;
+pub type SteamErrMsg = [c_char; 1024];
+
+pub const STEAM_API_INIT_RESULT_OK: i32 = 0;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct CallbackMsg {
+  pub steam_user: i32,
+  pub callback: i32,
+  pub param: *mut u8,
+  pub param_size: i32,
+}
+
+impl Default for CallbackMsg {
+  fn default() -> Self {
+    Self {
+      steam_user: 0,
+      callback: 0,
+      param: std::ptr::null_mut(),
+      param_size: 0,
+    }
+  }
+}
+
+/// `GameOverlayActivated_t`, sent when the overlay opens or closes.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct GameOverlayActivated {
+  pub active: u8,
+  pub user_initiated: bool,
+  pub app_id: u32,
+  pub overlay_pid: u32,
+}
+
+impl GameOverlayActivated {
+  pub const CALLBACK: i32 = 331;
+}
+
+#[cfg(feature = "steamworks")]
+#[cfg_attr(all(windows, target_pointer_width = "64"), link(name = "steam_api64"))]
+#[cfg_attr(not(all(windows, target_pointer_width = "64")), link(name = "steam_api"))]
+extern "C" {
+  pub fn SteamAPI_InitFlat(error: *mut SteamErrMsg) -> i32;
+  pub fn SteamAPI_Shutdown();
+  pub fn SteamAPI_GetHSteamPipe() -> HSteamPipe;
+
+  pub fn SteamAPI_ManualDispatch_Init();
+  pub fn SteamAPI_ManualDispatch_RunFrame(pipe: HSteamPipe);
+  pub fn SteamAPI_ManualDispatch_GetNextCallback(pipe: HSteamPipe, message: *mut CallbackMsg) -> bool;
+  pub fn SteamAPI_ManualDispatch_FreeLastCallback(pipe: HSteamPipe);
+
+  pub fn SteamAPI_SteamUserStats_v012() -> *mut c_void;
+  pub fn SteamAPI_ISteamUserStats_RequestCurrentStats(stats: *mut c_void) -> bool;
+  pub fn SteamAPI_ISteamUserStats_SetAchievement(stats: *mut c_void, name: *const c_char) -> bool;
+  pub fn SteamAPI_ISteamUserStats_ClearAchievement(stats: *mut c_void, name: *const c_char) -> bool;
+  pub fn SteamAPI_ISteamUserStats_StoreStats(stats: *mut c_void) -> bool;
+
+  pub fn SteamAPI_SteamFriends_v017() -> *mut c_void;
+  pub fn SteamAPI_ISteamFriends_SetRichPresence(
+    friends: *mut c_void,
+    key: *const c_char,
+    value: *const c_char,
+  ) -> bool;
+  pub fn SteamAPI_ISteamFriends_ClearRichPresence(friends: *mut c_void);
+
+  pub fn SteamAPI_SteamUtils_v010() -> *mut c_void;
+  pub fn SteamAPI_ISteamUtils_IsOverlayEnabled(utils: *mut c_void) -> bool;
+  pub fn SteamAPI_ISteamUtils_BOverlayNeedsPresent(utils: *mut c_void) -> bool;
+}
+
+/// Stand-ins for builds without the `steamworks` feature, where initializing always fails and nothing else is ever
+/// called.
+#[cfg(not(feature = "steamworks"))]
+mod stub {
+  use super::*;
+
+  pub unsafe fn SteamAPI_InitFlat(_error: *mut SteamErrMsg) -> i32 {
+    1
+  }
+  pub unsafe fn SteamAPI_Shutdown() {}
+  pub unsafe fn SteamAPI_GetHSteamPipe() -> HSteamPipe {
+    0
+  }
+
+  pub unsafe fn SteamAPI_ManualDispatch_Init() {}
+  pub unsafe fn SteamAPI_ManualDispatch_RunFrame(_pipe: HSteamPipe) {}
+  pub unsafe fn SteamAPI_ManualDispatch_GetNextCallback(_pipe: HSteamPipe, _message: *mut CallbackMsg) -> bool {
+    false
+  }
+  pub unsafe fn SteamAPI_ManualDispatch_FreeLastCallback(_pipe: HSteamPipe) {}
+
+  pub unsafe fn SteamAPI_SteamUserStats_v012() -> *mut c_void {
+    std::ptr::null_mut()
+  }
+  pub unsafe fn SteamAPI_ISteamUserStats_RequestCurrentStats(_stats: *mut c_void) -> bool {
+    false
+  }
+  pub unsafe fn SteamAPI_ISteamUserStats_SetAchievement(_stats: *mut c_void, _name: *const c_char) -> bool {
+    false
+  }
+  pub unsafe fn SteamAPI_ISteamUserStats_ClearAchievement(_stats: *mut c_void, _name: *const c_char) -> bool {
+    false
+  }
+  pub unsafe fn SteamAPI_ISteamUserStats_StoreStats(_stats: *mut c_void) -> bool {
+    false
+  }
+
+  pub unsafe fn SteamAPI_SteamFriends_v017() -> *mut c_void {
+    std::ptr::null_mut()
+  }
+  pub unsafe fn SteamAPI_ISteamFriends_SetRichPresence(
+    _friends: *mut c_void,
+    _key: *const c_char,
+    _value: *const c_char,
+  ) -> bool {
+    false
+  }
+  pub unsafe fn SteamAPI_ISteamFriends_ClearRichPresence(_friends: *mut c_void) {}
+
+  pub unsafe fn SteamAPI_SteamUtils_v010() -> *mut c_void {
+    std::ptr::null_mut()
+  }
+  pub unsafe fn SteamAPI_ISteamUtils_IsOverlayEnabled(_utils: *mut c_void) -> bool {
+    false
+  }
+  pub unsafe fn SteamAPI_ISteamUtils_BOverlayNeedsPresent(_utils: *mut c_void) -> bool {
+    false
+  }
+}
+
+#[cfg(not(feature = "steamworks"))]
+pub use stub::*;
@@ -0,0 +1,159 @@
+pub mod hash;
+pub mod manifest;
+pub mod mesh;
+pub mod sound;
+pub mod texture;
+
+use thiserror::Error;
+
+/// The first bytes of every baked asset.
+pub const MAGIC: [u8; 4] = *b"FOXB";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BakedError {
+  #[error("not a baked asset")]
+  NotBaked,
+  #[error("expected a baked {expected}, found a baked {found}")]
+  WrongKind { expected: String, found: String },
+  #[error("baked {kind} version {version} is not supported")]
+  UnsupportedVersion { kind: String, version: u32 },
+  #[error("baked asset ends unexpectedly")]
+  Truncated,
+  #[error("{0}")]
+  Invalid(String),
+}
+
+/// Writes the little-endian layout shared by the baked formats.
+#[derive(Default)]
+pub(crate) struct Writer {
+  bytes: Vec<u8>,
+}
+
+impl Writer {
+  pub(crate) fn new(kind: [u8; 4], version: u32) -> Self {
+    let mut writer = Self::default();
+    writer.bytes.extend_from_slice(&MAGIC);
+    writer.bytes.extend_from_slice(&kind);
+    writer.u32(version);
+    writer
+  }
+
+  pub(crate) fn u8(&mut self, value: u8) {
+    self.bytes.push(value);
+  }
+
+  pub(crate) fn u16(&mut self, value: u16) {
+    self.bytes.extend_from_slice(&value.to_le_bytes());
+  }
+
+  pub(crate) fn u32(&mut self, value: u32) {
+    self.bytes.extend_from_slice(&value.to_le_bytes());
+  }
+
+  pub(crate) fn f32(&mut self, value: f32) {
+    self.bytes.extend_from_slice(&value.to_le_bytes());
+  }
+
+  pub(crate) fn len(&mut self, len: usize) {
+    self.u32(len as u32);
+  }
+
+  /// Writes `bytes` prefixed by their length.
+  pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+    self.len(bytes.len());
+    self.bytes.extend_from_slice(bytes);
+  }
+
+  pub(crate) fn str(&mut self, value: &str) {
+    self.bytes(value.as_bytes());
+  }
+
+  pub(crate) fn finish(self) -> Vec<u8> {
+    self.bytes
+  }
+}
+
+/// Reads what a [`Writer`] wrote.
+pub(crate) struct Reader<'a> {
+  bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  /// Checks the header, returning a reader for the rest and the version the asset was baked with.
+  pub(crate) fn new(bytes: &'a [u8], kind: [u8; 4], max_version: u32) -> Result<(Self, u32), BakedError> {
+    let mut reader = Self { bytes };
+    if reader.take(4).ok() != Some(MAGIC.as_slice()) {
+      return Err(BakedError::NotBaked);
+    }
+    let found = reader.take(4)?;
+    if found != kind {
+      return Err(BakedError::WrongKind {
+        expected: kind_name(&kind),
+        found: kind_name(found),
+      });
+    }
+    let version = reader.u32()?;
+    if version == 0 || version > max_version {
+      return Err(BakedError::UnsupportedVersion {
+        kind: kind_name(&kind),
+        version,
+      });
+    }
+
+    Ok((reader, version))
+  }
+
+  pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], BakedError> {
+    if self.bytes.len() < len {
+      return Err(BakedError::Truncated);
+    }
+    let (taken, rest) = self.bytes.split_at(len);
+    self.bytes = rest;
+    Ok(taken)
+  }
+
+  fn array<const N: usize>(&mut self) -> Result<[u8; N], BakedError> {
+    let mut array = [0; N];
+    array.copy_from_slice(self.take(N)?);
+    Ok(array)
+  }
+
+  pub(crate) fn u8(&mut self) -> Result<u8, BakedError> {
+    Ok(self.array::<1>()?[0])
+  }
+
+  pub(crate) fn u16(&mut self) -> Result<u16, BakedError> {
+    Ok(u16::from_le_bytes(self.array()?))
+  }
+
+  pub(crate) fn u32(&mut self) -> Result<u32, BakedError> {
+    Ok(u32::from_le_bytes(self.array()?))
+  }
+
+  pub(crate) fn f32(&mut self) -> Result<f32, BakedError> {
+    Ok(f32::from_le_bytes(self.array()?))
+  }
+
+  /// Reads a length, checking that at least `len * min_size` bytes follow so that a corrupt length can't cause a huge
+  /// allocation.
+  pub(crate) fn len(&mut self, min_size: usize) -> Result<usize, BakedError> {
+    let len = self.u32()? as usize;
+    if len.saturating_mul(min_size) > self.bytes.len() {
+      return Err(BakedError::Truncated);
+    }
+    Ok(len)
+  }
+
+  pub(crate) fn bytes(&mut self) -> Result<&'a [u8], BakedError> {
+    let len = self.len(1)?;
+    self.take(len)
+  }
+
+  pub(crate) fn str(&mut self) -> Result<&'a str, BakedError> {
+    std::str::from_utf8(self.bytes()?).map_err(|error| BakedError::Invalid(error.to_string()))
+  }
+}
+
+fn kind_name(kind: &[u8]) -> String {
+  String::from_utf8_lossy(kind).trim_end_matches('\0').to_lowercase()
+}
@@ -0,0 +1,69 @@
+use std::{fmt, str::FromStr};
+
+use super::BakedError;
+
+/// A 128-bit FNV-1a hash of some content, stable across platforms and builds, for telling whether an asset changed.
+/// It isn't cryptographic.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(pub u128);
+
+impl ContentHash {
+  pub fn of(bytes: &[u8]) -> Self {
+    ContentHasher::new().update(bytes).finish()
+  }
+}
+
+impl fmt::Display for ContentHash {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{:032x}", self.0)
+  }
+}
+
+impl FromStr for ContentHash {
+  type Err = BakedError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    u128::from_str_radix(s, 16)
+      .map(Self)
+      .map_err(|_| BakedError::Invalid(format!("{s} is not a content hash")))
+  }
+}
+
+/// Hashes content fed to it in pieces.
+#[derive(Debug, Clone)]
+pub struct ContentHasher {
+  state: u128,
+}
+
+impl Default for ContentHasher {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl ContentHasher {
+  const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+  const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+  pub fn new() -> Self {
+    Self {
+      state: Self::OFFSET_BASIS,
+    }
+  }
+
+  pub fn update(mut self, bytes: &[u8]) -> Self {
+    for byte in bytes {
+      self.state = (self.state ^ *byte as u128).wrapping_mul(Self::PRIME);
+    }
+    self
+  }
+
+  /// Hashes `bytes` prefixed by their length, so that where one piece ends and the next starts matters.
+  pub fn update_piece(self, bytes: &[u8]) -> Self {
+    self.update(&(bytes.len() as u64).to_le_bytes()).update(bytes)
+  }
+
+  pub fn finish(self) -> ContentHash {
+    ContentHash(self.state)
+  }
+}
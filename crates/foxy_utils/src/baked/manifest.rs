@@ -0,0 +1,123 @@
+use std::{
+  collections::BTreeMap,
+  fmt,
+  fs,
+  io,
+  path::{Path, PathBuf},
+};
+
+use super::{hash::ContentHash, BakedError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+  artifact: PathBuf,
+  hash: Option<ContentHash>,
+}
+
+/// Maps source assets to the artifacts baked from them, both relative to their directories, along with the hash of
+/// what each artifact was baked from.
+///
+/// It's stored as text, one `source<TAB>artifact<TAB>hash` line per asset, with `/` separated paths. The hash is
+/// optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetManifest {
+  entries: BTreeMap<PathBuf, Entry>,
+}
+
+impl AssetManifest {
+  /// The name of the manifest file in the baked asset directory.
+  pub const FILE_NAME: &'static str = "manifest.txt";
+
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn insert(&mut self, source: impl Into<PathBuf>, artifact: impl Into<PathBuf>) {
+    self.insert_entry(source.into(), artifact.into(), None);
+  }
+
+  /// Inserts an artifact along with the hash of the content it was baked from.
+  pub fn insert_hashed(&mut self, source: impl Into<PathBuf>, artifact: impl Into<PathBuf>, hash: ContentHash) {
+    self.insert_entry(source.into(), artifact.into(), Some(hash));
+  }
+
+  pub fn remove(&mut self, source: impl AsRef<Path>) -> Option<PathBuf> {
+    self.entries.remove(source.as_ref()).map(|entry| entry.artifact)
+  }
+
+  /// The artifact baked from `source`, if any.
+  pub fn artifact(&self, source: impl AsRef<Path>) -> Option<&Path> {
+    self.entries.get(source.as_ref()).map(|entry| entry.artifact.as_path())
+  }
+
+  /// The hash of the content the artifact of `source` was baked from, if it was recorded.
+  pub fn hash(&self, source: impl AsRef<Path>) -> Option<ContentHash> {
+    self.entries.get(source.as_ref())?.hash
+  }
+
+  /// Every source and its artifact, ordered by source.
+  pub fn iter(&self) -> impl Iterator<Item = (&Path, &Path)> {
+    self
+      .entries
+      .iter()
+      .map(|(source, entry)| (source.as_path(), entry.artifact.as_path()))
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  fn insert_entry(&mut self, source: PathBuf, artifact: PathBuf, hash: Option<ContentHash>) {
+    self.entries.insert(source, Entry { artifact, hash });
+  }
+
+  pub fn parse(text: &str) -> Result<Self, BakedError> {
+    let mut manifest = Self::new();
+    for (number, line) in text.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let mut columns = line.split('\t');
+      let (Some(source), Some(artifact)) = (columns.next(), columns.next()) else {
+        return Err(BakedError::Invalid(format!("manifest line {} has no artifact", number + 1)));
+      };
+      let hash = columns.next().map(str::parse).transpose()?;
+      manifest.insert_entry(source.into(), artifact.into(), hash);
+    }
+
+    Ok(manifest)
+  }
+
+  /// Reads the manifest in the baked asset directory `dir`.
+  pub fn read(dir: impl AsRef<Path>) -> io::Result<Self> {
+    let text = fs::read_to_string(dir.as_ref().join(Self::FILE_NAME))?;
+    Self::parse(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+  }
+
+  /// Writes the manifest into the baked asset directory `dir`.
+  pub fn write(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+    fs::write(dir.as_ref().join(Self::FILE_NAME), self.to_string())
+  }
+}
+
+impl fmt::Display for AssetManifest {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (source, entry) in &self.entries {
+      write!(f, "{}\t{}", portable(source), portable(&entry.artifact))?;
+      if let Some(hash) = entry.hash {
+        write!(f, "\t{hash}")?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+fn portable(path: &Path) -> String {
+  path.to_string_lossy().replace('\\', "/")
+}
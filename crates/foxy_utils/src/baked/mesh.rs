@@ -0,0 +1,105 @@
+use super::{BakedError, Reader, Writer};
+
+/// A vertex laid out like the renderer's.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BakedVertex {
+  pub position: [f32; 3],
+  pub color: [f32; 4],
+  pub uv: [f32; 2],
+}
+
+impl BakedVertex {
+  const FLOATS: usize = 9;
+}
+
+impl Default for BakedVertex {
+  fn default() -> Self {
+    Self {
+      position: [0.0; 3],
+      color: [1.0; 4],
+      uv: [0.0; 2],
+    }
+  }
+}
+
+/// An indexed triangle list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BakedMesh {
+  pub name: String,
+  pub vertices: Vec<BakedVertex>,
+  pub indices: Vec<u32>,
+}
+
+/// The meshes of a model file, e.g. each primitive of a glTF file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BakedModel {
+  pub meshes: Vec<BakedMesh>,
+}
+
+impl BakedModel {
+  /// The extension of baked model files.
+  pub const EXTENSION: &'static str = "fxmesh";
+  const KIND: [u8; 4] = *b"MESH";
+  const VERSION: u32 = 1;
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut writer = Writer::new(Self::KIND, Self::VERSION);
+    writer.len(self.meshes.len());
+    for mesh in &self.meshes {
+      writer.str(&mesh.name);
+      writer.len(mesh.vertices.len());
+      for vertex in &mesh.vertices {
+        for value in vertex.position.iter().chain(&vertex.color).chain(&vertex.uv) {
+          writer.f32(*value);
+        }
+      }
+      writer.len(mesh.indices.len());
+      for index in &mesh.indices {
+        writer.u32(*index);
+      }
+    }
+    writer.finish()
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, BakedError> {
+    let (mut reader, _) = Reader::new(bytes, Self::KIND, Self::VERSION)?;
+    let mesh_count = reader.len(12)?;
+
+    let mut meshes = Vec::with_capacity(mesh_count);
+    for _ in 0..mesh_count {
+      let name = reader.str()?.to_owned();
+
+      let vertex_count = reader.len(BakedVertex::FLOATS * 4)?;
+      let mut vertices = Vec::with_capacity(vertex_count);
+      for _ in 0..vertex_count {
+        let mut floats = [0.0; BakedVertex::FLOATS];
+        for float in &mut floats {
+          *float = reader.f32()?;
+        }
+        vertices.push(BakedVertex {
+          position: [floats[0], floats[1], floats[2]],
+          color: [floats[3], floats[4], floats[5], floats[6]],
+          uv: [floats[7], floats[8]],
+        });
+      }
+
+      let index_count = reader.len(4)?;
+      let mut indices = Vec::with_capacity(index_count);
+      for _ in 0..index_count {
+        let index = reader.u32()?;
+        if index as usize >= vertex_count {
+          return Err(BakedError::Invalid(format!("mesh {name} has an out of range index {index}")));
+        }
+        indices.push(index);
+      }
+
+      meshes.push(BakedMesh {
+        name,
+        vertices,
+        indices,
+      });
+    }
+
+    Ok(Self { meshes })
+  }
+}
@@ -0,0 +1,55 @@
+use super::{BakedError, Reader, Writer};
+
+/// Decoded audio, ready to mix.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BakedSound {
+  pub channels: u16,
+  pub sample_rate: u32,
+  /// Samples from -1 to 1, interleaved by channel.
+  pub samples: Vec<f32>,
+}
+
+impl BakedSound {
+  /// The extension of baked sound files.
+  pub const EXTENSION: &'static str = "fxsnd";
+  const KIND: [u8; 4] = *b"SND\0";
+  const VERSION: u32 = 1;
+
+  /// The number of samples per channel.
+  pub fn frames(&self) -> usize {
+    self.samples.len() / usize::from(self.channels.max(1))
+  }
+
+  pub fn duration_secs(&self) -> f64 {
+    self.frames() as f64 / f64::from(self.sample_rate.max(1))
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut writer = Writer::new(Self::KIND, Self::VERSION);
+    writer.u16(self.channels);
+    writer.u32(self.sample_rate);
+    writer.len(self.samples.len());
+    for sample in &self.samples {
+      writer.f32(*sample);
+    }
+    writer.finish()
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, BakedError> {
+    let (mut reader, _) = Reader::new(bytes, Self::KIND, Self::VERSION)?;
+    let channels = reader.u16()?;
+    let sample_rate = reader.u32()?;
+    if channels == 0 || sample_rate == 0 {
+      return Err(BakedError::Invalid("sound has no channels or sample rate".into()));
+    }
+
+    let sample_count = reader.len(4)?;
+    let samples = (0..sample_count).map(|_| reader.f32()).collect::<Result<_, _>>()?;
+
+    Ok(Self {
+      channels,
+      sample_rate,
+      samples,
+    })
+  }
+}
@@ -0,0 +1,96 @@
+use super::{BakedError, Reader, Writer};
+
+/// How the texels of a [`BakedTexture`] are stored. Both are sRGB.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BakedTextureFormat {
+  /// 4 bytes per texel.
+  Rgba8,
+  /// BC3 (DXT5) blocks, 16 bytes per 4x4 texels, uploaded as-is on GPUs that support BC compression.
+  Bc3,
+}
+
+impl BakedTextureFormat {
+  fn tag(self) -> u8 {
+    match self {
+      BakedTextureFormat::Rgba8 => 0,
+      BakedTextureFormat::Bc3 => 1,
+    }
+  }
+
+  fn from_tag(tag: u8) -> Result<Self, BakedError> {
+    match tag {
+      0 => Ok(BakedTextureFormat::Rgba8),
+      1 => Ok(BakedTextureFormat::Bc3),
+      _ => Err(BakedError::Invalid(format!("unknown texture format {tag}"))),
+    }
+  }
+
+  /// The number of bytes a `width` by `height` image takes in this format.
+  pub fn data_len(self, width: u32, height: u32) -> usize {
+    let (width, height) = (width as usize, height as usize);
+    match self {
+      BakedTextureFormat::Rgba8 => width * height * 4,
+      BakedTextureFormat::Bc3 => width.div_ceil(4) * height.div_ceil(4) * 16,
+    }
+  }
+}
+
+/// A texture ready to upload, with its whole mip chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedTexture {
+  pub format: BakedTextureFormat,
+  pub width: u32,
+  pub height: u32,
+  /// The data of each mip level, the full size image first.
+  pub mips: Vec<Vec<u8>>,
+}
+
+impl BakedTexture {
+  /// The extension of baked texture files.
+  pub const EXTENSION: &'static str = "fxtex";
+  const KIND: [u8; 4] = *b"TEX\0";
+  const VERSION: u32 = 1;
+
+  /// The size of mip `level`, which halves with each level down to 1x1.
+  pub fn mip_size(&self, level: usize) -> (u32, u32) {
+    let level = level.min(31) as u32;
+    ((self.width >> level).max(1), (self.height >> level).max(1))
+  }
+
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let mut writer = Writer::new(Self::KIND, Self::VERSION);
+    writer.u8(self.format.tag());
+    writer.u32(self.width);
+    writer.u32(self.height);
+    writer.len(self.mips.len());
+    for mip in &self.mips {
+      writer.bytes(mip);
+    }
+    writer.finish()
+  }
+
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, BakedError> {
+    let (mut reader, _) = Reader::new(bytes, Self::KIND, Self::VERSION)?;
+    let format = BakedTextureFormat::from_tag(reader.u8()?)?;
+    let width = reader.u32()?;
+    let height = reader.u32()?;
+    let mip_count = reader.len(4)?;
+
+    let mut texture = Self {
+      format,
+      width,
+      height,
+      mips: Vec::with_capacity(mip_count),
+    };
+    for level in 0..mip_count {
+      let mip = reader.bytes()?;
+      let (mip_width, mip_height) = texture.mip_size(level);
+      if mip.len() != format.data_len(mip_width, mip_height) {
+        return Err(BakedError::Invalid(format!("mip {level} has the wrong size")));
+      }
+      texture.mips.push(mip.to_vec());
+    }
+
+    Ok(texture)
+  }
+}
@@ -0,0 +1,255 @@
+use std::{collections::VecDeque, fmt, mem};
+
+type Action<T> = Box<dyn FnMut(&mut T) + Send>;
+
+/// A reversible edit of a `T`, e.g. an editor's document.
+pub struct Command<T> {
+  name: String,
+  apply: Action<T>,
+  revert: Action<T>,
+  size: usize,
+}
+
+impl<T> Command<T> {
+  /// `apply` makes the edit and `revert` undoes it. Each may be called many times, alternately, starting with `apply`.
+  pub fn new(
+    name: impl Into<String>,
+    apply: impl FnMut(&mut T) + Send + 'static,
+    revert: impl FnMut(&mut T) + Send + 'static,
+  ) -> Self {
+    let size = mem::size_of_val(&apply) + mem::size_of_val(&revert);
+    Self {
+      name: name.into(),
+      apply: Box::new(apply),
+      revert: Box::new(revert),
+      size,
+    }
+  }
+
+  /// How many bytes the command holds on to, counted against the stack's memory limit. Defaults to the size of the
+  /// closures, which doesn't include anything they own on the heap, so commands holding buffers or strings should set
+  /// it.
+  pub fn with_size(mut self, size: usize) -> Self {
+    self.size = size;
+    self
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn size(&self) -> usize {
+    self.size
+  }
+}
+
+impl<T> fmt::Debug for Command<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Command")
+      .field("name", &self.name)
+      .field("size", &self.size)
+      .finish_non_exhaustive()
+  }
+}
+
+/// Commands undone and redone together.
+#[derive(Debug)]
+struct Entry<T> {
+  name: String,
+  commands: Vec<Command<T>>,
+}
+
+impl<T> Entry<T> {
+  fn size(&self) -> usize {
+    self.commands.iter().map(Command::size).sum()
+  }
+
+  fn apply(&mut self, target: &mut T) {
+    for command in &mut self.commands {
+      (command.apply)(target);
+    }
+  }
+
+  fn revert(&mut self, target: &mut T) {
+    for command in self.commands.iter_mut().rev() {
+      (command.revert)(target);
+    }
+  }
+}
+
+/// An undo/redo history of [`Command`]s, for editor-style apps.
+///
+/// Commands can be grouped, so that e.g. every change made while dragging a slider is undone at once. The history can
+/// be capped by number of entries and by memory; the oldest entries are forgotten first.
+#[derive(Debug)]
+pub struct CommandStack<T> {
+  undo: VecDeque<Entry<T>>,
+  redo: Vec<Entry<T>>,
+  group: Option<Entry<T>>,
+  group_depth: usize,
+  max_entries: Option<usize>,
+  memory_limit: Option<usize>,
+  memory_usage: usize,
+}
+
+impl<T> Default for CommandStack<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<T> CommandStack<T> {
+  pub fn new() -> Self {
+    Self {
+      undo: VecDeque::new(),
+      redo: Vec::new(),
+      group: None,
+      group_depth: 0,
+      max_entries: None,
+      memory_limit: None,
+      memory_usage: 0,
+    }
+  }
+
+  /// Keeps at most `max_entries` entries to undo.
+  pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+    self.max_entries = Some(max_entries.max(1));
+    self
+  }
+
+  /// Keeps the undo and redo history within `bytes`, as measured by [`Command::size`]. The latest entry is always
+  /// kept, even if it's bigger on its own.
+  pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+    self.memory_limit = Some(bytes);
+    self
+  }
+
+  /// Applies `command` to `target` and records it, discarding anything that could be redone.
+  pub fn execute(&mut self, target: &mut T, mut command: Command<T>) {
+    (command.apply)(target);
+    self.push(command);
+  }
+
+  /// Records a command whose edit was already made to the target, e.g. by a widget editing it in place.
+  pub fn push(&mut self, command: Command<T>) {
+    self.clear_redo();
+    self.memory_usage += command.size;
+
+    match &mut self.group {
+      Some(group) => group.commands.push(command),
+      None => {
+        let entry = Entry {
+          name: command.name.clone(),
+          commands: vec![command],
+        };
+        self.push_entry(entry);
+      }
+    }
+  }
+
+  /// Groups the commands recorded until the matching [`end_group`](Self::end_group) into one entry named `name`.
+  /// Groups can be nested; the outermost one wins.
+  pub fn begin_group(&mut self, name: impl Into<String>) {
+    self.group_depth += 1;
+    if self.group.is_none() {
+      self.group = Some(Entry {
+        name: name.into(),
+        commands: Vec::new(),
+      });
+    }
+  }
+
+  pub fn end_group(&mut self) {
+    self.group_depth = self.group_depth.saturating_sub(1);
+    if self.group_depth == 0 {
+      self.close_group();
+    }
+  }
+
+  pub fn is_grouping(&self) -> bool {
+    self.group.is_some()
+  }
+
+  /// Reverts the latest entry. Closes any open group first. Returns whether there was anything to undo.
+  pub fn undo(&mut self, target: &mut T) -> bool {
+    self.group_depth = 0;
+    self.close_group();
+
+    let Some(mut entry) = self.undo.pop_back() else {
+      return false;
+    };
+    entry.revert(target);
+    self.redo.push(entry);
+    true
+  }
+
+  /// Reapplies the latest undone entry. Returns whether there was anything to redo.
+  pub fn redo(&mut self, target: &mut T) -> bool {
+    let Some(mut entry) = self.redo.pop() else {
+      return false;
+    };
+    entry.apply(target);
+    self.undo.push_back(entry);
+    true
+  }
+
+  pub fn can_undo(&self) -> bool {
+    !self.undo.is_empty() || self.group.as_ref().is_some_and(|group| !group.commands.is_empty())
+  }
+
+  pub fn can_redo(&self) -> bool {
+    !self.redo.is_empty()
+  }
+
+  /// The name of the entry [`undo`](Self::undo) would revert, e.g. for an "Undo Move" menu item.
+  pub fn undo_name(&self) -> Option<&str> {
+    match &self.group {
+      Some(group) if !group.commands.is_empty() => Some(&group.name),
+      _ => self.undo.back().map(|entry| entry.name.as_str()),
+    }
+  }
+
+  pub fn redo_name(&self) -> Option<&str> {
+    self.redo.last().map(|entry| entry.name.as_str())
+  }
+
+  /// The number of bytes held by the history, as measured by [`Command::size`].
+  pub fn memory_usage(&self) -> usize {
+    self.memory_usage
+  }
+
+  /// Forgets the whole history, including any open group.
+  pub fn clear(&mut self) {
+    self.undo.clear();
+    self.redo.clear();
+    self.group = None;
+    self.group_depth = 0;
+    self.memory_usage = 0;
+  }
+
+  fn close_group(&mut self) {
+    if let Some(group) = self.group.take() {
+      if !group.commands.is_empty() {
+        self.push_entry(group);
+      }
+    }
+  }
+
+  fn clear_redo(&mut self) {
+    for entry in self.redo.drain(..) {
+      self.memory_usage -= entry.size();
+    }
+  }
+
+  fn push_entry(&mut self, entry: Entry<T>) {
+    self.undo.push_back(entry);
+
+    let over_max_entries = |stack: &Self| stack.max_entries.is_some_and(|max| stack.undo.len() > max);
+    let over_memory_limit = |stack: &Self| stack.memory_limit.is_some_and(|limit| stack.memory_usage > limit);
+    while self.undo.len() > 1 && (over_max_entries(self) || over_memory_limit(self)) {
+      if let Some(oldest) = self.undo.pop_front() {
+        self.memory_usage -= oldest.size();
+      }
+    }
+  }
+}
@@ -0,0 +1,649 @@
+//! Geometric primitives and the intersection tests between them, shared by culling, picking and debug drawing.
+//!
+//! Everything is in whatever space the caller works in; the `transformed` methods move a primitive between spaces.
+
+use glam::{Mat4, Quat, Vec3, Vec4, Vec4Swizzles};
+
+/// Below this, directions and determinants are treated as zero.
+const EPSILON: f32 = 1e-6;
+
+/// The pairs of corners joined by the 12 edges of a box, with corners ordered like [`Aabb::corners`].
+const BOX_EDGES: [(usize, usize); 12] = [
+  (0, 1),
+  (2, 3),
+  (4, 5),
+  (6, 7),
+  (0, 2),
+  (1, 3),
+  (4, 6),
+  (5, 7),
+  (0, 4),
+  (1, 5),
+  (2, 6),
+  (3, 7),
+];
+
+/// A half line, starting at `origin`. The direction is kept normalized, so distances along the ray are world
+/// distances.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ray {
+  pub origin: Vec3,
+  pub direction: Vec3,
+}
+
+impl Ray {
+  pub fn new(origin: Vec3, direction: Vec3) -> Self {
+    Self {
+      origin,
+      direction: direction.normalize_or_zero(),
+    }
+  }
+
+  /// The ray from `from` through `to`.
+  pub fn between(from: Vec3, to: Vec3) -> Self {
+    Self::new(from, to - from)
+  }
+
+  pub fn at(&self, distance: f32) -> Vec3 {
+    self.origin + self.direction * distance
+  }
+
+  /// The ray moved by `transform`, e.g. into the local space of a mesh by the inverse of its transform. Distances along
+  /// the moved ray are in the new space.
+  pub fn transformed(&self, transform: &Mat4) -> Self {
+    Self::new(
+      transform.transform_point3(self.origin),
+      transform.transform_vector3(self.direction),
+    )
+  }
+
+  /// How far along the ray the point on it closest to `point` is.
+  pub fn closest_distance(&self, point: Vec3) -> f32 {
+    self.direction.dot(point - self.origin).max(0.0)
+  }
+
+  pub fn closest_point(&self, point: Vec3) -> Vec3 {
+    self.at(self.closest_distance(point))
+  }
+
+  pub fn distance_to_point(&self, point: Vec3) -> f32 {
+    self.closest_point(point).distance(point)
+  }
+
+  /// The distance along the ray and along the line through `origin` in the normalized `direction` of the closest
+  /// points between them, or `None` if they're parallel. The distance along the ray may be negative.
+  pub fn closest_to_line(&self, origin: Vec3, direction: Vec3) -> Option<(f32, f32)> {
+    let cos = self.direction.dot(direction);
+    let denominator = 1.0 - cos * cos;
+    if denominator < EPSILON {
+      return None;
+    }
+
+    let offset = self.origin - origin;
+    let along_ray = self.direction.dot(offset);
+    let along_line = direction.dot(offset);
+    Some((
+      (cos * along_line - along_ray) / denominator,
+      (along_line - cos * along_ray) / denominator,
+    ))
+  }
+
+  /// How far along the ray it crosses `plane`, from either side, if it does.
+  pub fn intersect_plane(&self, plane: &Plane) -> Option<f32> {
+    let facing = self.direction.dot(plane.normal);
+    if facing.abs() < EPSILON {
+      return None;
+    }
+
+    let distance = -plane.signed_distance(self.origin) / facing;
+    (distance >= 0.0).then_some(distance)
+  }
+
+  /// How far along the ray it enters `sphere`, or 0 if it starts inside it.
+  pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<f32> {
+    let offset = self.origin - sphere.center;
+    let along = offset.dot(self.direction);
+    let outside = offset.length_squared() - sphere.radius * sphere.radius;
+    if outside > 0.0 && along > 0.0 {
+      return None;
+    }
+
+    let discriminant = along * along - outside;
+    (discriminant >= 0.0).then(|| (-along - discriminant.sqrt()).max(0.0))
+  }
+
+  /// How far along the ray it enters `aabb`, or 0 if it starts inside it.
+  pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+    let mut near = 0.0f32;
+    let mut far = f32::INFINITY;
+    for axis in 0..3 {
+      let (origin, direction) = (self.origin[axis], self.direction[axis]);
+      let (min, max) = (aabb.min[axis], aabb.max[axis]);
+
+      // parallel to the slab, so either always inside it or never
+      if direction.abs() < EPSILON {
+        if origin < min || origin > max {
+          return None;
+        }
+        continue;
+      }
+
+      let (a, b) = ((min - origin) / direction, (max - origin) / direction);
+      near = near.max(a.min(b));
+      far = far.min(a.max(b));
+      if near > far {
+        return None;
+      }
+    }
+    Some(near)
+  }
+
+  /// How far along the ray it enters `obb`, or 0 if it starts inside it.
+  pub fn intersect_obb(&self, obb: &Obb) -> Option<f32> {
+    // the box's rotation keeps lengths, so distances in its space are the same as outside it
+    let inverse = obb.rotation.inverse();
+    let local = Self {
+      origin: inverse * (self.origin - obb.center),
+      direction: inverse * self.direction,
+    };
+    local.intersect_aabb(&Aabb::from_center_half_extents(Vec3::ZERO, obb.half_extents))
+  }
+
+  /// How far along the ray it hits the triangle `a`, `b`, `c`, from either side.
+  pub fn intersect_triangle(&self, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+    let (edge1, edge2) = (b - a, c - a);
+    let p = self.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < EPSILON {
+      return None;
+    }
+
+    let inverse = 1.0 / determinant;
+    let offset = self.origin - a;
+    let u = offset.dot(p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+      return None;
+    }
+    let q = offset.cross(edge1);
+    let v = self.direction.dot(q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+      return None;
+    }
+
+    let distance = edge2.dot(q) * inverse;
+    (distance >= 0.0).then_some(distance)
+  }
+}
+
+/// The points where `normal.dot(point) + distance` is 0. The normal is kept normalized, so that is the signed distance
+/// of a point from the plane, positive on the side the normal points to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Plane {
+  pub normal: Vec3,
+  pub distance: f32,
+}
+
+impl Plane {
+  pub fn new(normal: Vec3, distance: f32) -> Self {
+    Self::from_vec4(normal.extend(distance))
+  }
+
+  /// The plane through `point` facing `normal`.
+  pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+    let normal = normal.normalize_or_zero();
+    Self {
+      normal,
+      distance: -normal.dot(point),
+    }
+  }
+
+  /// The plane through the three points, facing the side they wind counter-clockwise from, or `None` if they're on a
+  /// line.
+  pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Option<Self> {
+    let normal = (b - a).cross(c - a);
+    (normal.length_squared() > EPSILON * EPSILON).then(|| Self::from_point_normal(a, normal))
+  }
+
+  /// The plane packed as `(normal, distance)`, with a normal of any length.
+  pub fn from_vec4(plane: Vec4) -> Self {
+    let length = plane.xyz().length();
+    let plane = if length > EPSILON { plane / length } else { plane };
+    Self {
+      normal: plane.xyz(),
+      distance: plane.w,
+    }
+  }
+
+  pub fn to_vec4(&self) -> Vec4 {
+    self.normal.extend(self.distance)
+  }
+
+  /// How far `point` is in front of the plane, negative behind it.
+  pub fn signed_distance(&self, point: Vec3) -> f32 {
+    self.normal.dot(point) + self.distance
+  }
+
+  /// The point on the plane closest to `point`.
+  pub fn project_point(&self, point: Vec3) -> Vec3 {
+    point - self.normal * self.signed_distance(point)
+  }
+
+  /// The same plane, facing the other way.
+  pub fn flipped(&self) -> Self {
+    Self {
+      normal: -self.normal,
+      distance: -self.distance,
+    }
+  }
+
+  pub fn transformed(&self, transform: &Mat4) -> Self {
+    // normals move by the inverse transpose, so that they stay perpendicular under non-uniform scales
+    let normal = transform.inverse().transpose().transform_vector3(self.normal);
+    Self::from_point_normal(transform.transform_point3(self.normal * -self.distance), normal)
+  }
+
+  /// The point where the three planes cross, or `None` if two of them are parallel.
+  fn intersection(a: &Plane, b: &Plane, c: &Plane) -> Option<Vec3> {
+    let bc = b.normal.cross(c.normal);
+    let determinant = a.normal.dot(bc);
+    if determinant.abs() < EPSILON {
+      return None;
+    }
+
+    let point = bc * a.distance + c.normal.cross(a.normal) * b.distance + a.normal.cross(b.normal) * c.distance;
+    Some(-point / determinant)
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sphere {
+  pub center: Vec3,
+  pub radius: f32,
+}
+
+impl Sphere {
+  pub fn new(center: Vec3, radius: f32) -> Self {
+    Self { center, radius }
+  }
+
+  /// A sphere enclosing every point, or `None` if there are none. Centered on the bounding box of the points, which
+  /// isn't the tightest sphere but is close and cheap.
+  pub fn from_points<I>(points: I) -> Option<Self>
+  where
+    I: IntoIterator<Item = Vec3>,
+    I::IntoIter: Clone,
+  {
+    let points = points.into_iter();
+    let center = Aabb::from_points(points.clone())?.center();
+    let radius = points.map(|point| center.distance(point)).fold(0.0, f32::max);
+    Some(Self { center, radius })
+  }
+
+  /// The sphere moved by `transform`, grown to enclose the original under its largest scale.
+  pub fn transformed(&self, transform: &Mat4) -> Self {
+    let scale = [transform.x_axis, transform.y_axis, transform.z_axis]
+      .map(|axis| axis.truncate().length())
+      .into_iter()
+      .fold(0.0, f32::max);
+    Self {
+      center: transform.transform_point3(self.center),
+      radius: self.radius * scale,
+    }
+  }
+
+  pub fn aabb(&self) -> Aabb {
+    Aabb::from_center_half_extents(self.center, Vec3::splat(self.radius))
+  }
+
+  pub fn contains_point(&self, point: Vec3) -> bool {
+    self.center.distance_squared(point) <= self.radius * self.radius
+  }
+
+  pub fn contains_sphere(&self, other: &Sphere) -> bool {
+    self.center.distance(other.center) + other.radius <= self.radius
+  }
+
+  pub fn intersects_sphere(&self, other: &Sphere) -> bool {
+    let radius = self.radius + other.radius;
+    self.center.distance_squared(other.center) <= radius * radius
+  }
+
+  pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+    aabb.intersects_sphere(self)
+  }
+}
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Aabb {
+  pub min: Vec3,
+  pub max: Vec3,
+}
+
+impl Aabb {
+  /// The box between the two corners, in either order.
+  pub fn new(a: Vec3, b: Vec3) -> Self {
+    Self {
+      min: a.min(b),
+      max: a.max(b),
+    }
+  }
+
+  pub fn from_center_half_extents(center: Vec3, half_extents: Vec3) -> Self {
+    Self::new(center - half_extents, center + half_extents)
+  }
+
+  /// The smallest box enclosing every point, or `None` if there are none.
+  pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+    let mut points = points.into_iter();
+    let first = points.next()?;
+    Some(points.fold(Self { min: first, max: first }, |aabb, point| aabb.grown_to(point)))
+  }
+
+  pub fn center(&self) -> Vec3 {
+    (self.min + self.max) / 2.0
+  }
+
+  pub fn half_extents(&self) -> Vec3 {
+    (self.max - self.min) / 2.0
+  }
+
+  pub fn size(&self) -> Vec3 {
+    self.max - self.min
+  }
+
+  /// The corners, with bits 0, 1 and 2 of the index picking the max rather than the min on X, Y and Z.
+  pub fn corners(&self) -> [Vec3; 8] {
+    std::array::from_fn(|i| {
+      Vec3::new(
+        if i & 1 == 0 { self.min.x } else { self.max.x },
+        if i & 2 == 0 { self.min.y } else { self.max.y },
+        if i & 4 == 0 { self.min.z } else { self.max.z },
+      )
+    })
+  }
+
+  /// The 12 edges, as pairs of corners.
+  pub fn edges(&self) -> [(Vec3, Vec3); 12] {
+    box_edges(&self.corners())
+  }
+
+  /// The box grown to enclose `point`.
+  pub fn grown_to(&self, point: Vec3) -> Self {
+    Self {
+      min: self.min.min(point),
+      max: self.max.max(point),
+    }
+  }
+
+  /// The smallest box enclosing both boxes.
+  pub fn union(&self, other: &Aabb) -> Self {
+    Self {
+      min: self.min.min(other.min),
+      max: self.max.max(other.max),
+    }
+  }
+
+  /// The box where both boxes overlap, or `None` if they don't.
+  pub fn intersection(&self, other: &Aabb) -> Option<Self> {
+    let min = self.min.max(other.min);
+    let max = self.max.min(other.max);
+    min.cmple(max).all().then_some(Self { min, max })
+  }
+
+  /// The smallest axis-aligned box enclosing this one moved by `transform`.
+  pub fn transformed(&self, transform: &Mat4) -> Self {
+    let half_extents = self.half_extents();
+    let half_extents = [transform.x_axis, transform.y_axis, transform.z_axis]
+      .into_iter()
+      .enumerate()
+      .map(|(i, axis)| axis.truncate().abs() * half_extents[i])
+      .sum();
+    Self::from_center_half_extents(transform.transform_point3(self.center()), half_extents)
+  }
+
+  pub fn bounding_sphere(&self) -> Sphere {
+    Sphere::new(self.center(), self.half_extents().length())
+  }
+
+  /// The point in the box closest to `point`, which is `point` itself if it's inside.
+  pub fn closest_point(&self, point: Vec3) -> Vec3 {
+    point.clamp(self.min, self.max)
+  }
+
+  pub fn contains_point(&self, point: Vec3) -> bool {
+    point.cmpge(self.min).all() && point.cmple(self.max).all()
+  }
+
+  pub fn contains_aabb(&self, other: &Aabb) -> bool {
+    other.min.cmpge(self.min).all() && other.max.cmple(self.max).all()
+  }
+
+  pub fn intersects_aabb(&self, other: &Aabb) -> bool {
+    self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+  }
+
+  pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+    self.closest_point(sphere.center).distance_squared(sphere.center) <= sphere.radius * sphere.radius
+  }
+}
+
+/// An oriented bounding box: a box rotated by `rotation` around its center.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Obb {
+  pub center: Vec3,
+  pub half_extents: Vec3,
+  pub rotation: Quat,
+}
+
+impl Obb {
+  pub fn new(center: Vec3, half_extents: Vec3, rotation: Quat) -> Self {
+    Self {
+      center,
+      half_extents: half_extents.abs(),
+      rotation,
+    }
+  }
+
+  /// `aabb` moved by `transform`, which keeps it a box as long as the transform has no shear.
+  pub fn from_aabb(aabb: &Aabb, transform: &Mat4) -> Self {
+    let (scale, rotation, _) = transform.to_scale_rotation_translation();
+    Self::new(transform.transform_point3(aabb.center()), aabb.half_extents() * scale, rotation)
+  }
+
+  /// The box moved by `transform`, which keeps it a box as long as the transform only scales along the box's axes.
+  pub fn transformed(&self, transform: &Mat4) -> Self {
+    let local = Aabb::from_center_half_extents(Vec3::ZERO, self.half_extents);
+    Self::from_aabb(
+      &local,
+      &(*transform * Mat4::from_rotation_translation(self.rotation, self.center)),
+    )
+  }
+
+  /// The box's local X, Y and Z axes.
+  pub fn axes(&self) -> [Vec3; 3] {
+    [Vec3::X, Vec3::Y, Vec3::Z].map(|axis| self.rotation * axis)
+  }
+
+  /// The corners, with bits 0, 1 and 2 of the index picking the positive side of the local X, Y and Z axes.
+  pub fn corners(&self) -> [Vec3; 8] {
+    let local = Aabb::from_center_half_extents(Vec3::ZERO, self.half_extents);
+    local.corners().map(|corner| self.center + self.rotation * corner)
+  }
+
+  /// The 12 edges, as pairs of corners.
+  pub fn edges(&self) -> [(Vec3, Vec3); 12] {
+    box_edges(&self.corners())
+  }
+
+  /// The smallest axis-aligned box enclosing this one.
+  pub fn aabb(&self) -> Aabb {
+    let half_extents = self
+      .axes()
+      .into_iter()
+      .enumerate()
+      .map(|(i, axis)| axis.abs() * self.half_extents[i])
+      .sum();
+    Aabb::from_center_half_extents(self.center, half_extents)
+  }
+
+  pub fn bounding_sphere(&self) -> Sphere {
+    Sphere::new(self.center, self.half_extents.length())
+  }
+
+  /// The point in the box closest to `point`, which is `point` itself if it's inside.
+  pub fn closest_point(&self, point: Vec3) -> Vec3 {
+    let local = self.rotation.inverse() * (point - self.center);
+    self.center + self.rotation * local.clamp(-self.half_extents, self.half_extents)
+  }
+
+  pub fn contains_point(&self, point: Vec3) -> bool {
+    let local = self.rotation.inverse() * (point - self.center);
+    local.abs().cmple(self.half_extents).all()
+  }
+
+  pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+    self.closest_point(sphere.center).distance_squared(sphere.center) <= sphere.radius * sphere.radius
+  }
+
+  pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+    self.intersects_obb(&Obb::new(aabb.center(), aabb.half_extents(), Quat::IDENTITY))
+  }
+
+  /// Whether the boxes overlap, by looking for an axis separating them among the 15 that can.
+  pub fn intersects_obb(&self, other: &Obb) -> bool {
+    let (a, b) = (self.axes(), other.axes());
+    let (ea, eb) = (self.half_extents, other.half_extents);
+
+    // `other`'s axes and the offset between the boxes, in `self`'s space
+    let r: [[f32; 3]; 3] = std::array::from_fn(|i| std::array::from_fn(|j| a[i].dot(b[j])));
+    // padded, so that nearly parallel edges don't make a cross product of almost zero pass as a separating axis
+    let abs_r = r.map(|row| row.map(|value| value.abs() + EPSILON));
+    let offset = other.center - self.center;
+    let t = a.map(|axis| offset.dot(axis));
+
+    for i in 0..3 {
+      let rb = eb[0] * abs_r[i][0] + eb[1] * abs_r[i][1] + eb[2] * abs_r[i][2];
+      if t[i].abs() > ea[i] + rb {
+        return false;
+      }
+    }
+
+    for j in 0..3 {
+      let ra = ea[0] * abs_r[0][j] + ea[1] * abs_r[1][j] + ea[2] * abs_r[2][j];
+      let distance = t[0] * r[0][j] + t[1] * r[1][j] + t[2] * r[2][j];
+      if distance.abs() > ra + eb[j] {
+        return false;
+      }
+    }
+
+    for i in 0..3 {
+      let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+      for j in 0..3 {
+        let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+        let ra = ea[i1] * abs_r[i2][j] + ea[i2] * abs_r[i1][j];
+        let rb = eb[j1] * abs_r[i][j2] + eb[j2] * abs_r[i][j1];
+        let distance = t[i2] * r[i1][j] - t[i1] * r[i2][j];
+        if distance.abs() > ra + rb {
+          return false;
+        }
+      }
+    }
+
+    true
+  }
+}
+
+/// The volume a camera sees, as planes with normals pointing inwards.
+///
+/// The tests are conservative: something near a corner of the frustum can pass while being just outside it, which
+/// only costs drawing something unseen.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frustum {
+  /// Left, right, bottom, top and near planes.
+  pub planes: [Plane; 5],
+  /// `None` for infinite projections.
+  pub far: Option<Plane>,
+}
+
+impl Frustum {
+  /// The frustum of a view projection with a 0 to 1 depth range and the near plane at 0, i.e. not reversed.
+  pub fn from_view_projection(view_projection: &Mat4) -> Self {
+    let [x, y, z, w] = [0, 1, 2, 3].map(|i| view_projection.row(i));
+    let far = w - z;
+    Self {
+      planes: [w + x, w - x, w + y, w - y, z].map(Plane::from_vec4),
+      // an infinite projection's far plane has no normal
+      far: (far.xyz().length_squared() > EPSILON * EPSILON).then(|| Plane::from_vec4(far)),
+    }
+  }
+
+  pub fn planes(&self) -> impl Iterator<Item = &Plane> {
+    self.planes.iter().chain(self.far.iter())
+  }
+
+  /// The corners, ordered like [`Aabb::corners`] from left, bottom and near to right, top and far, or `None` for
+  /// infinite projections.
+  pub fn corners(&self) -> Option<[Vec3; 8]> {
+    let [left, right, bottom, top, near] = &self.planes;
+    let far = self.far.as_ref()?;
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, corner) in corners.iter_mut().enumerate() {
+      let x = if i & 1 == 0 { left } else { right };
+      let y = if i & 2 == 0 { bottom } else { top };
+      let z = if i & 4 == 0 { near } else { far };
+      *corner = Plane::intersection(x, y, z)?;
+    }
+    Some(corners)
+  }
+
+  /// The 12 edges, as pairs of corners, or `None` for infinite projections.
+  pub fn edges(&self) -> Option<[(Vec3, Vec3); 12]> {
+    self.corners().map(|corners| box_edges(&corners))
+  }
+
+  pub fn contains_point(&self, point: Vec3) -> bool {
+    self.planes().all(|plane| plane.signed_distance(point) >= 0.0)
+  }
+
+  pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+    self
+      .planes()
+      .all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+  }
+
+  pub fn contains_sphere(&self, sphere: &Sphere) -> bool {
+    self
+      .planes()
+      .all(|plane| plane.signed_distance(sphere.center) >= sphere.radius)
+  }
+
+  pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+    // the corner furthest along each plane's normal is the last to leave it
+    self.planes().all(|plane| {
+      let corner = Vec3::select(plane.normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+      plane.signed_distance(corner) >= 0.0
+    })
+  }
+
+  pub fn contains_aabb(&self, aabb: &Aabb) -> bool {
+    self.planes().all(|plane| {
+      let corner = Vec3::select(plane.normal.cmpge(Vec3::ZERO), aabb.min, aabb.max);
+      plane.signed_distance(corner) >= 0.0
+    })
+  }
+
+  pub fn intersects_obb(&self, obb: &Obb) -> bool {
+    let axes = obb.axes();
+    self.planes().all(|plane| {
+      let radius = (0..3)
+        .map(|i| plane.normal.dot(axes[i]).abs() * obb.half_extents[i])
+        .sum::<f32>();
+      plane.signed_distance(obb.center) >= -radius
+    })
+  }
+}
+
+fn box_edges(corners: &[Vec3; 8]) -> [(Vec3, Vec3); 12] {
+  BOX_EDGES.map(|(start, end)| (corners[start], corners[end]))
+}
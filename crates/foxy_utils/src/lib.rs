@@ -1,7 +1,11 @@
 #![deny(unsafe_op_in_unsafe_fn)]
 
+pub mod baked;
+pub mod command;
+pub mod geometry;
 pub mod log;
 pub mod mailbox;
 pub mod thread;
 pub mod time;
+pub mod tracking;
 pub mod types;
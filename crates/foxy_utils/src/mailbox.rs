@@ -33,13 +33,20 @@ impl<SenderMessage: Send + Sync, ReceiverMessage: Send + Sync> Mailbox<SenderMes
     self.receiver.try_recv().map_err(MessagingError::from)
   }
 
+  /// Waits for a message for at most `timeout`. A timeout of [`Duration::MAX`] waits indefinitely.
+  pub fn recv_timeout(&self, timeout: Duration) -> Result<ReceiverMessage, MessagingError<SenderMessage>> {
+    self.receiver.recv_timeout(timeout).map_err(MessagingError::from)
+  }
+
   pub fn send_and_recv(&self, message: SenderMessage) -> Result<ReceiverMessage, MessagingError<SenderMessage>> {
     self.send(message)?;
     self.recv()
   }
 }
 
-use crossbeam::channel::{Receiver, RecvError, SendError, Sender, TryRecvError};
+use std::time::Duration;
+
+use crossbeam::channel::{Receiver, RecvError, RecvTimeoutError, SendError, Sender, TryRecvError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -59,4 +66,9 @@ pub enum MessagingError<SenderMessage> {
     #[from]
     error: RecvError,
   },
+  #[error("{error}")]
+  RecvTimeoutError {
+    #[from]
+    error: RecvTimeoutError,
+  },
 }
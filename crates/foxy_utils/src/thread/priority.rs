@@ -0,0 +1,120 @@
+use super::error::ThreadError;
+
+/// How eagerly the OS schedules a thread over the others of the system.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ThreadPriority {
+  Lowest,
+  BelowNormal,
+  #[default]
+  Normal,
+  AboveNormal,
+  Highest,
+  /// Runs ahead of nearly everything else on the system, e.g. for the thread that has to hit every frame. Starves
+  /// other threads if it never waits, so it's best kept to threads that sleep or block between short bursts of work.
+  TimeCritical,
+}
+
+/// The priority and affinity to give a thread. Whatever is left `None` stays as the OS set it.
+///
+/// Only applied on Windows for now. Elsewhere, applying any setting fails and the thread keeps running as it was.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ThreadSettings {
+  pub priority: Option<ThreadPriority>,
+  /// The logical cores the thread may run on, counting from 0.
+  pub affinity: Option<Vec<usize>>,
+}
+
+impl ThreadSettings {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn with_priority(mut self, priority: ThreadPriority) -> Self {
+    self.priority = Some(priority);
+    self
+  }
+
+  /// Pins the thread to the logical cores in `cores`, e.g. to keep it from being moved around mid-frame.
+  pub fn with_affinity(mut self, cores: impl IntoIterator<Item = usize>) -> Self {
+    self.affinity = Some(cores.into_iter().collect());
+    self
+  }
+
+  /// Applies the settings to the thread this is called from.
+  pub fn apply(&self) -> Result<(), ThreadError> {
+    if let Some(priority) = self.priority {
+      os::set_priority(priority)?;
+    }
+    if let Some(cores) = &self.affinity {
+      os::set_affinity(cores)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(windows)]
+mod os {
+  use std::io;
+
+  use windows_sys::Win32::System::Threading::{
+    GetCurrentThread,
+    SetThreadAffinityMask,
+    SetThreadPriority,
+    THREAD_PRIORITY_ABOVE_NORMAL,
+    THREAD_PRIORITY_BELOW_NORMAL,
+    THREAD_PRIORITY_HIGHEST,
+    THREAD_PRIORITY_LOWEST,
+    THREAD_PRIORITY_NORMAL,
+    THREAD_PRIORITY_TIME_CRITICAL,
+  };
+
+  use super::ThreadPriority;
+  use crate::{thread::error::ThreadError, thread_err};
+
+  pub fn set_priority(priority: ThreadPriority) -> Result<(), ThreadError> {
+    let priority = match priority {
+      ThreadPriority::Lowest => THREAD_PRIORITY_LOWEST,
+      ThreadPriority::BelowNormal => THREAD_PRIORITY_BELOW_NORMAL,
+      ThreadPriority::Normal => THREAD_PRIORITY_NORMAL,
+      ThreadPriority::AboveNormal => THREAD_PRIORITY_ABOVE_NORMAL,
+      ThreadPriority::Highest => THREAD_PRIORITY_HIGHEST,
+      ThreadPriority::TimeCritical => THREAD_PRIORITY_TIME_CRITICAL,
+    };
+
+    // SAFETY: the pseudo handle of the current thread is always valid, and doesn't need closing
+    if unsafe { SetThreadPriority(GetCurrentThread(), priority) } == 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+  }
+
+  pub fn set_affinity(cores: &[usize]) -> Result<(), ThreadError> {
+    let mask = cores
+      .iter()
+      .filter(|core| **core < usize::BITS as usize)
+      .fold(0, |mask, core| mask | 1usize << core);
+    if mask == 0 {
+      return thread_err!("no core in the affinity {cores:?} exists");
+    }
+
+    // SAFETY: the pseudo handle of the current thread is always valid, and doesn't need closing
+    if unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } == 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+  }
+}
+
+#[cfg(not(windows))]
+mod os {
+  use super::ThreadPriority;
+  use crate::{thread::error::ThreadError, thread_err};
+
+  pub fn set_priority(_priority: ThreadPriority) -> Result<(), ThreadError> {
+    thread_err!("thread priorities are only supported on Windows")
+  }
+
+  pub fn set_affinity(_cores: &[usize]) -> Result<(), ThreadError> {
+    thread_err!("thread affinities are only supported on Windows")
+  }
+}
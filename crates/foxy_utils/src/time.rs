@@ -1,4 +1,5 @@
 pub mod game_loop;
+pub mod sleep;
 pub mod stopwatch;
 pub mod timer;
 
@@ -16,6 +17,34 @@ pub struct Time {
   delta_time: Duration,
   tick_delta_time: Duration,
   average_delta_time: Duration,
+  real: Clock,
+  virtual_: Clock,
+}
+
+/// How much time has passed on one of the clocks of [`Time`], as of the start of the frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Clock {
+  elapsed: Duration,
+  delta: Duration,
+}
+
+impl Clock {
+  pub fn elapsed(&self) -> Duration {
+    self.elapsed
+  }
+
+  pub fn elapsed_secs(&self) -> f64 {
+    self.elapsed.as_secs_f64()
+  }
+
+  /// The time that passed since the last frame.
+  pub fn delta(&self) -> Duration {
+    self.delta
+  }
+
+  pub fn delta_secs(&self) -> f64 {
+    self.delta.as_secs_f64()
+  }
 }
 
 #[allow(unused)]
@@ -51,12 +80,28 @@ impl Time {
   pub fn now(&self) -> Instant {
     Instant::now()
   }
+
+  /// Wall clock time, which keeps running while the simulation is paused or slowed down, e.g. for UI animations and
+  /// network timeouts.
+  pub fn real(&self) -> Clock {
+    self.real
+  }
+
+  /// Simulation time, which follows the time scale, stops while paused and moves forward when stepping, e.g. for
+  /// gameplay.
+  pub fn virtual_(&self) -> Clock {
+    self.virtual_
+  }
 }
 
 #[derive(Debug)]
 pub struct TimeCreateInfo {
+  /// Fixed updates per second.
   pub tick_rate: f64,
+  /// The most fixed updates run in a single frame. Once a frame hits it, the simulation falls behind real time
+  /// rather than taking ever longer to catch up.
   pub bail_threshold: u32,
+  /// How many frames the average frame time is taken over.
   pub max_samples: usize,
 }
 
@@ -82,6 +127,7 @@ pub struct EngineTime {
   lag_time: Duration,
   step_count: u32,
   bail_threshold: u32,
+  time_scale: f64,
 
   start_time: Instant,
 
@@ -93,6 +139,9 @@ pub struct EngineTime {
   tick_current_frame: Instant,
   tick_delta_time: Duration,
 
+  virtual_elapsed: Duration,
+  virtual_delta: Duration,
+
   frame_times: RingBuffer<Duration>,
 }
 
@@ -107,6 +156,7 @@ impl Default for EngineTime {
       lag_time: Default::default(),
       step_count: 0,
       bail_threshold: BAIL_THRESHOLD,
+      time_scale: 1.0,
       start_time: Instant::now(),
       previous_frame: Instant::now(),
       current_frame: Instant::now(),
@@ -114,6 +164,8 @@ impl Default for EngineTime {
       tick_previous_frame: Instant::now(),
       tick_current_frame: Instant::now(),
       tick_delta_time: Default::default(),
+      virtual_elapsed: Default::default(),
+      virtual_delta: Default::default(),
       frame_times: RingBuffer::new(100),
     }
   }
@@ -122,15 +174,16 @@ impl Default for EngineTime {
 impl EngineTime {
   pub fn new(tick_rate: f64, bail_threshold: u32, max_samples: usize) -> Self {
     Self {
-      tick_rate,
       bail_threshold,
       frame_times: RingBuffer::new(max_samples),
       ..Default::default()
     }
+    .with_tick_rate(tick_rate)
   }
 
   pub fn with_tick_rate(mut self, tick_rate: f64) -> Self {
     self.tick_rate = tick_rate;
+    self.tick_time = Duration::from_secs_f64(1. / tick_rate);
     self
   }
 
@@ -139,12 +192,41 @@ impl EngineTime {
     self
   }
 
+  pub fn time_scale(&self) -> f64 {
+    self.time_scale
+  }
+
+  /// Scales how fast simulation time passes relative to real time, e.g. `0.25` for slow motion or `0.0` to stop
+  /// ticking. Frame deltas stay in real time, and only the [virtual clock](Time::virtual_) is scaled.
+  pub fn set_time_scale(&mut self, time_scale: f64) {
+    self.time_scale = time_scale.max(0.0);
+  }
+
+  pub fn tick_duration(&self) -> Duration {
+    self.tick_time
+  }
+
+  /// Adds `duration` of simulation time to be ticked through, regardless of the time scale.
+  pub fn advance(&mut self, duration: Duration) {
+    self.lag_time += duration;
+    self.virtual_delta += duration;
+    self.virtual_elapsed += duration;
+  }
+
   pub fn time(&self) -> Time {
     Time {
       start_time: self.start_time,
       delta_time: self.delta_time,
       tick_delta_time: self.tick_delta_time,
       average_delta_time: self.average_delta(),
+      real: Clock {
+        elapsed: self.current_frame - self.start_time,
+        delta: self.delta_time,
+      },
+      virtual_: Clock {
+        elapsed: self.virtual_elapsed,
+        delta: self.virtual_delta,
+      },
     }
   }
 
@@ -169,7 +251,9 @@ impl EngineTime {
     self.current_frame = Instant::now();
     self.delta_time = self.current_frame - self.previous_frame;
     self.previous_frame = self.current_frame;
-    self.lag_time += self.delta_time;
+    self.virtual_delta = self.delta_time.mul_f64(self.time_scale);
+    self.virtual_elapsed += self.virtual_delta;
+    self.lag_time += self.virtual_delta;
     self.step_count = 0;
 
     self.frame_times.push(self.delta_time);
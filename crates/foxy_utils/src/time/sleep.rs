@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+/// How long before the deadline sleeping stops and spinning takes over, to cover how late the OS may wake the thread.
+#[cfg(windows)]
+const SPIN_THRESHOLD: Duration = Duration::from_millis(1);
+#[cfg(not(windows))]
+const SPIN_THRESHOLD: Duration = Duration::from_micros(250);
+
+/// Sleeps for `duration`, far more precisely than [`std::thread::sleep`], which can overshoot by a whole scheduler
+/// tick (around 15 ms on Windows). See [`sleep_until`].
+pub fn sleep(duration: Duration) {
+  sleep_until(Instant::now() + duration);
+}
+
+/// Sleeps until `deadline`, waking within a few microseconds of it. The thread sleeps through most of the wait and
+/// spins through the last fraction of a millisecond, so it costs a little CPU time in exchange for less jitter, e.g.
+/// when pacing frames.
+pub fn sleep_until(deadline: Instant) {
+  let remaining = deadline.saturating_duration_since(Instant::now());
+  if let Some(coarse) = remaining.checked_sub(SPIN_THRESHOLD) {
+    os::sleep(coarse);
+  }
+
+  while Instant::now() < deadline {
+    std::hint::spin_loop();
+  }
+}
+
+#[cfg(windows)]
+mod os {
+  use std::{ptr, time::Duration};
+
+  use windows_sys::Win32::{
+    Foundation::CloseHandle,
+    Media::{timeBeginPeriod, timeEndPeriod},
+    System::Threading::{
+      CreateWaitableTimerExW,
+      SetWaitableTimer,
+      WaitForSingleObject,
+      CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+      INFINITE,
+      TIMER_ALL_ACCESS,
+    },
+  };
+
+  pub fn sleep(duration: Duration) {
+    // SAFETY: the timer is only used within this call, and closed before returning
+    let slept = unsafe {
+      // high resolution timers need Windows 10 1803 or later
+      let timer = CreateWaitableTimerExW(
+        ptr::null(),
+        ptr::null(),
+        CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+        TIMER_ALL_ACCESS,
+      );
+      if timer.is_null() {
+        false
+      } else {
+        // negative due times are relative, in 100 ns intervals
+        let due_time = -((duration.as_nanos() / 100) as i64);
+        let is_set = SetWaitableTimer(timer, &due_time, 0, None, ptr::null(), 0) != 0;
+        if is_set {
+          WaitForSingleObject(timer, INFINITE);
+        }
+        CloseHandle(timer);
+        is_set
+      }
+    };
+
+    if !slept {
+      // SAFETY: every call to `timeBeginPeriod` is matched by a call to `timeEndPeriod` with the same period
+      unsafe {
+        timeBeginPeriod(1);
+        std::thread::sleep(duration);
+        timeEndPeriod(1);
+      }
+    }
+  }
+}
+
+#[cfg(not(windows))]
+mod os {
+  use std::time::Duration;
+
+  /// `nanosleep` on Unix, which already wakes within tens of microseconds.
+  pub fn sleep(duration: Duration) {
+    std::thread::sleep(duration);
+  }
+}
@@ -0,0 +1,148 @@
+use std::{
+  backtrace::{Backtrace, BacktraceStatus},
+  collections::HashMap,
+  fmt,
+  ops::{Deref, DerefMut},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+    MutexGuard,
+    OnceLock,
+  },
+};
+
+use strum::{Display, EnumIter, IntoEnumIterator};
+use tracing::*;
+
+/// The kinds of resources the engine tracks.
+#[derive(Display, EnumIter, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+  Buffer,
+  Texture,
+  Pipeline,
+  ShaderModule,
+  AudioBuffer,
+}
+
+/// How many resources of a kind are alive, and how much memory they hold.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ResourceStats {
+  pub kind: ResourceKind,
+  pub count: usize,
+  pub bytes: u64,
+}
+
+struct Record {
+  kind: ResourceKind,
+  label: String,
+  bytes: u64,
+  backtrace: Backtrace,
+}
+
+fn registry() -> MutexGuard<'static, HashMap<u64, Record>> {
+  static REGISTRY: OnceLock<Mutex<HashMap<u64, Record>>> = OnceLock::new();
+  REGISTRY
+    .get_or_init(Default::default)
+    .lock()
+    .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// A resource registered with the engine-wide resource registry for as long as it's alive.
+///
+/// Whatever is still registered at shutdown was leaked, and is logged by [`report_leaks`] along with where it was
+/// created. Backtraces are only captured when `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` is set, since they're slow.
+pub struct Tracked<T> {
+  value: T,
+  id: u64,
+}
+
+impl<T> Tracked<T> {
+  /// Registers `value`, a resource holding `bytes` of memory.
+  pub fn new(kind: ResourceKind, label: impl Into<String>, bytes: u64, value: T) -> Self {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    registry().insert(
+      id,
+      Record {
+        kind,
+        label: label.into(),
+        bytes,
+        backtrace: Backtrace::capture(),
+      },
+    );
+
+    Self { value, id }
+  }
+}
+
+impl<T> Deref for Tracked<T> {
+  type Target = T;
+
+  fn deref(&self) -> &Self::Target {
+    &self.value
+  }
+}
+
+impl<T> DerefMut for Tracked<T> {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    &mut self.value
+  }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracked<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.value.fmt(f)
+  }
+}
+
+impl<T> Drop for Tracked<T> {
+  fn drop(&mut self) {
+    registry().remove(&self.id);
+  }
+}
+
+/// The live resources and memory of every kind, e.g. for a debug overlay.
+pub fn resource_stats() -> Vec<ResourceStats> {
+  let registry = registry();
+  ResourceKind::iter()
+    .map(|kind| {
+      let records = registry.values().filter(|record| record.kind == kind);
+      ResourceStats {
+        kind,
+        count: records.clone().count(),
+        bytes: records.map(|record| record.bytes).sum(),
+      }
+    })
+    .collect()
+}
+
+/// Logs every resource still alive, with where it was created. Call at shutdown, once everything owning resources
+/// has been dropped. Returns the number of leaked resources.
+pub fn report_leaks() -> usize {
+  let registry = registry();
+  let mut leaks: Vec<(&u64, &Record)> = registry.iter().collect();
+  leaks.sort_by_key(|(id, _)| **id);
+
+  for (_, record) in &leaks {
+    match record.backtrace.status() {
+      BacktraceStatus::Captured => warn!(
+        "leaked {} \"{}\" ({} bytes), created at:\n{}",
+        record.kind, record.label, record.bytes, record.backtrace
+      ),
+      _ => warn!("leaked {} \"{}\" ({} bytes)", record.kind, record.label, record.bytes),
+    }
+  }
+  if !leaks.is_empty() {
+    let bytes: u64 = leaks.iter().map(|(_, record)| record.bytes).sum();
+    warn!("{} resources leaked, holding {bytes} bytes", leaks.len());
+    if leaks
+      .iter()
+      .any(|(_, record)| record.backtrace.status() != BacktraceStatus::Captured)
+    {
+      warn!("set RUST_BACKTRACE=1 to see where leaked resources were created");
+    }
+  }
+
+  leaks.len()
+}
@@ -28,4 +28,14 @@ impl<T> Handle<T> {
   pub fn get_mut(&mut self) -> RwLockWriteGuard<'_, T> {
     self.0.write().expect("lock was poisoned")
   }
+
+  /// Number of handles currently pointing at the same value, including this one.
+  pub fn strong_count(&self) -> usize {
+    Arc::strong_count(&self.0)
+  }
+
+  /// Swaps the value seen by every clone of this handle, returning the old one.
+  pub fn replace(&mut self, t: T) -> T {
+    std::mem::replace(&mut *self.get_mut(), t)
+  }
 }
@@ -5,6 +5,9 @@ use tracing::{debug, warn};
 
 pub struct App {
   x: u32,
+  show_color_test: bool,
+  show_calibration: bool,
+  calibration: DisplayCalibration,
 }
 
 impl Runnable for App {
@@ -16,7 +19,12 @@ impl Runnable for App {
   }
 
   fn new(_foxy: &Foxy) -> Self {
-    Self { x: 0 }
+    Self {
+      x: 0,
+      show_color_test: false,
+      show_calibration: false,
+      calibration: DisplayCalibration::default(),
+    }
   }
 
   fn input(&mut self, foxy: &Foxy, event: &InputEvent) {
@@ -45,7 +53,54 @@ impl Runnable for App {
         if slider.changed() {
           debug!("x: {}", self.x);
         }
+
+        ui.checkbox(&mut self.show_color_test, "Color Test");
+        ui.checkbox(&mut self.show_calibration, "Display Calibration");
+      });
+
+    // The test pattern replaces the scene while this window is open
+    egui::Window::new("Display Calibration")
+      .open(&mut self.show_calibration)
+      .resizable(false)
+      .anchor(Align2::RIGHT_TOP, (-5.0, 5.0))
+      .show(egui, |ui| {
+        ui.add(egui::Slider::new(&mut self.calibration.gamma, 0.5..=2.0).text("Gamma"));
+        ui.add(egui::Slider::new(&mut self.calibration.brightness, -0.25..=0.25).text("Brightness"));
+        ui.add(egui::Slider::new(&mut self.calibration.contrast, 0.5..=1.5).text("Contrast"));
+        if ui.button("Reset").clicked() {
+          self.calibration = DisplayCalibration::default();
+        }
       });
+
+    // A gray ramp should step evenly from black to white, and the swatches should match their sRGB hex codes on any
+    // surface format.
+    egui::Window::new("Color Test")
+      .open(&mut self.show_color_test)
+      .resizable(false)
+      .show(egui, |ui| {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(256.0, 32.0), egui::Sense::hover());
+        for step in 0..16u8 {
+          let x = rect.left() + step as f32 * 16.0;
+          let cell = egui::Rect::from_min_size(egui::pos2(x, rect.top()), egui::vec2(16.0, 32.0));
+          ui.painter().rect_filled(cell, 0.0, egui::Color32::from_gray(step * 17));
+        }
+
+        ui.horizontal(|ui| {
+          for (name, color) in [
+            ("#FF0000", egui::Color32::from_rgb(255, 0, 0)),
+            ("#00FF00", egui::Color32::from_rgb(0, 255, 0)),
+            ("#0000FF", egui::Color32::from_rgb(0, 0, 255)),
+            ("#808080", egui::Color32::from_rgb(128, 128, 128)),
+          ] {
+            ui.colored_label(color, name);
+          }
+        });
+      });
+  }
+
+  fn render(&mut self, _foxy: &Foxy, render_data: &mut RenderData) {
+    render_data.display_calibration = Some(self.calibration);
+    render_data.show_calibration_pattern = self.show_calibration;
   }
 }
 